@@ -1,6 +1,7 @@
 pub mod command;
 pub mod config_loader;
 pub mod filesystem;
+pub mod install_state;
 pub mod package_repo;
 pub mod progress;
 pub mod user_interface;
@@ -2,7 +2,9 @@
 
 use crate::adapters::progress::ProgressManager;
 use crate::ports::command::OutputChunk;
+use std::collections::VecDeque;
 use std::fmt::Write;
+use std::sync::{Arc, Mutex};
 
 /// Buffer that both displays and captures command output in real-time
 #[derive(Clone)]
@@ -21,6 +23,14 @@ pub struct CommandOutputBuffer {
 
     /// Whether to display output (verbose mode)
     display_output: bool,
+
+    /// The latest lines of stdout and stderr, in the order they came. Shared
+    /// between clones, so it can be read back after running a command with
+    /// a callback made by `into_callback`.
+    tail: Arc<Mutex<VecDeque<String>>>,
+
+    /// How many lines `tail` keeps
+    tail_lines: usize,
 }
 
 impl CommandOutputBuffer {
@@ -36,6 +46,42 @@ impl CommandOutputBuffer {
             progress_manager,
             indent: " ".repeat(indent_level),
             display_output,
+            tail: Arc::default(),
+            tail_lines: 0,
+        }
+    }
+
+    /// Keep the last `lines` lines of output, to show if the command fails
+    pub fn with_tail_lines(mut self, lines: usize) -> Self {
+        self.tail_lines = lines;
+        self
+    }
+
+    /// Whether output is being displayed as it comes
+    pub fn displays_output(&self) -> bool {
+        self.display_output
+    }
+
+    /// The last lines of output captured, oldest first
+    pub fn tail(&self) -> Vec<String> {
+        self.tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push_tail(&self, output: &str) {
+        if self.tail_lines == 0 {
+            return;
+        }
+
+        if let Ok(mut tail) = self.tail.lock() {
+            for line in output.lines() {
+                tail.push_back(line.trim_end().to_string());
+                if tail.len() > self.tail_lines {
+                    tail.pop_front();
+                }
+            }
         }
     }
 
@@ -45,6 +91,7 @@ impl CommandOutputBuffer {
             OutputChunk::Stdout(line) => {
                 // Capture the output
                 let _ = writeln!(self.stdout_buffer, "{}", line.trim_end());
+                self.push_tail(&line);
 
                 // Display if in verbose mode
                 if self.display_output {
@@ -58,6 +105,7 @@ impl CommandOutputBuffer {
             OutputChunk::Stderr(line) => {
                 // Capture the output
                 let _ = writeln!(self.stderr_buffer, "{}", line.trim_end());
+                self.push_tail(&line);
 
                 // Display if in verbose mode
                 if self.display_output {
@@ -82,7 +130,30 @@ impl CommandOutputBuffer {
     }
 
     /// Convert to a callback closure that can be passed to execute_streaming
-    pub fn into_callback(mut self) -> impl FnMut(OutputChunk) + Send + 'static {
+    pub fn into_callback(mut self) -> impl FnMut(OutputChunk) + Clone + Send + 'static {
         move |chunk| self.process_chunk(chunk)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_keeps_last_lines_across_clones() {
+        let buffer =
+            CommandOutputBuffer::new(ProgressManager::default(), 2, false).with_tail_lines(3);
+
+        let mut callback = buffer.clone().into_callback();
+        callback(OutputChunk::Stdout("one".to_string()));
+        callback(OutputChunk::Stderr("two\nthree".to_string()));
+        callback(OutputChunk::Stdout("four\n".to_string()));
+
+        assert_eq!(buffer.tail(), vec!["two", "three", "four"]);
+
+        // Without tail lines, nothing is kept
+        let buffer = CommandOutputBuffer::new(ProgressManager::default(), 2, false);
+        buffer.clone().into_callback()(OutputChunk::Stdout("one".to_string()));
+        assert!(buffer.tail().is_empty());
+    }
+}
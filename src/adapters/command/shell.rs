@@ -3,14 +3,19 @@
 
 use std::collections::HashMap;
 use std::process::{Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use futures::TryFutureExt;
-use tokio::io::AsyncBufReadExt;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
 use tokio::process::Command;
 
-use crate::ports::command::{CommandError, CommandOutput, CommandRunner, OutputChunk};
+use crate::{
+    domain::shell::Shell,
+    ports::command::{CommandError, CommandOutput, CommandRunner, OutputChunk, PROBE_TIMEOUT},
+};
 
 /// Shell command runner implementation
 #[derive(Clone)]
@@ -18,11 +23,20 @@ pub struct ShellCommandRunner {
     /// Path to the shell executable
     shell: String,
 
+    /// Interpreter to run commands under instead of `shell`
+    interpreter: Option<Shell>,
+
     /// Default timeout for commands
     default_timeout: Duration,
 
     /// Environment variables to set for commands
     environment: HashMap<String, String>,
+
+    /// Let Ctrl-C reach running commands instead of shielding them from it
+    kill_on_interrupt: bool,
+
+    /// Set once selfie has been interrupted; shared between clones
+    interrupted: Arc<AtomicBool>,
 }
 
 impl ShellCommandRunner {
@@ -30,9 +44,69 @@ impl ShellCommandRunner {
     pub fn new(shell: &str, default_timeout: Duration) -> Self {
         Self {
             shell: shell.to_string(),
+            interpreter: None,
             default_timeout,
             environment: HashMap::new(),
+            kill_on_interrupt: false,
+            interrupted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether commands should be killed when selfie is interrupted. By
+    /// default they ignore Ctrl-C and are left to finish.
+    pub fn with_kill_on_interrupt(mut self, kill_on_interrupt: bool) -> Self {
+        self.kill_on_interrupt = kill_on_interrupt;
+        self
+    }
+
+    /// Extra environment variables to set for every command, on top of
+    /// selfie's own environment
+    pub fn with_environment(mut self, environment: HashMap<String, String>) -> Self {
+        self.environment.extend(environment);
+        self
+    }
+
+    /// Stop starting new commands. Commands that are already running carry on.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    /// Build the shell invocation for `command`, with piped output and no input
+    fn command(&self, command: &str) -> Result<Command, CommandError> {
+        if self.interrupted.load(Ordering::SeqCst) {
+            return Err(CommandError::Interrupted);
+        }
+
+        let (program, args) = match self.interpreter {
+            Some(shell) => (shell.program(), shell.command_args()),
+            None => (self.shell.as_str(), &["-c"][..]),
+        };
+        let posix = self.interpreter.is_none_or(|shell| shell.is_posix());
+
+        // Signals ignored by the shell stay ignored in everything it runs, so
+        // the terminal's SIGINT never reaches the command
+        let script = if self.kill_on_interrupt || !posix {
+            command.to_string()
+        } else {
+            format!("trap '' INT; {}", command)
+        };
+
+        let mut cmd = Command::new(program);
+
+        cmd.args(args)
+            .arg(script)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // Commands abandoned along with selfie's work shouldn't outlive it
+            .kill_on_drop(true);
+
+        // Add environment variables
+        for (key, value) in &self.environment {
+            cmd.env(key, value);
         }
+
+        Ok(cmd)
     }
 
     /// Process command output into a CommandOutput
@@ -66,18 +140,7 @@ impl CommandRunner for ShellCommandRunner {
     ) -> Result<CommandOutput, CommandError> {
         let start_time = Instant::now();
 
-        let mut cmd = Command::new(&self.shell);
-
-        cmd.arg("-c")
-            .arg(command)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        // Add environment variables
-        for (key, value) in &self.environment {
-            cmd.env(key, value);
-        }
+        let mut cmd = self.command(command)?;
 
         let duration = start_time.elapsed();
 
@@ -86,15 +149,82 @@ impl CommandRunner for ShellCommandRunner {
             .await
             .map_err(|_| CommandError::Timeout(timeout))??;
 
-        Ok(self.process_output(output, duration))
+        exit_result(command, self.process_output(output, duration))
     }
 
-    async fn is_command_available(&self, command: &str) -> bool {
+    async fn execute_with_stdin(
+        &self,
+        command: &str,
+        stdin: &[u8],
+        timeout: Duration,
+    ) -> Result<CommandOutput, CommandError> {
+        let start_time = Instant::now();
+
+        let mut cmd = self.command(command)?;
+        cmd.stdin(Stdio::piped());
+
+        let mut child = cmd.spawn().map_err(CommandError::from)?;
+
+        // Write the input from a separate task so a child that fills its
+        // output pipes before reading all of its input can't deadlock us.
+        // Dropping the pipe afterwards closes the child's stdin.
+        let mut child_stdin = child.stdin.take().unwrap();
+        let input = stdin.to_vec();
+        let writer = tokio::spawn(async move {
+            // The child may exit without reading everything; that's its call
+            let _ = child_stdin.write_all(&input).await;
+        });
+
+        let output = tokio::time::timeout(
+            timeout,
+            child.wait_with_output().map_err(CommandError::from),
+        )
+        .await
+        .map_err(|_| CommandError::Timeout(timeout))??;
+
+        let _ = writer.await;
+
+        exit_result(command, self.process_output(output, start_time.elapsed()))
+    }
+
+    async fn execute_interactive(&self, command: &str) -> Result<CommandOutput, CommandError> {
+        let start_time = Instant::now();
+
+        let mut cmd = self.command(command)?;
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        let status = cmd.status().await.map_err(CommandError::from)?;
+
+        exit_result(
+            command,
+            CommandOutput {
+                status: status.code().unwrap_or(-1),
+                success: status.success(),
+                duration: start_time.elapsed(),
+                ..CommandOutput::default()
+            },
+        )
+    }
+
+    async fn is_command_available(&self, command: &str) -> Result<bool, CommandError> {
         // Shell-agnostic way to check if a command exists
         let check_cmd = format!("command -v {} >/dev/null 2>&1", command);
-        match self.execute(&check_cmd).await {
-            Ok(output) => output.success,
-            Err(_) => false,
+        // A lookup never gets longer than any other command would
+        let timeout = PROBE_TIMEOUT.min(self.default_timeout);
+        let output = self
+            .execute_with_timeout(&check_cmd, timeout)
+            .await
+            .or_else(CommandError::into_output)?;
+
+        Ok(output.succeeded())
+    }
+
+    fn with_shell(&self, shell: Shell) -> Self {
+        Self {
+            interpreter: Some(shell),
+            ..self.clone()
         }
     }
 
@@ -108,17 +238,7 @@ impl CommandRunner for ShellCommandRunner {
         F: FnMut(OutputChunk) + Send + 'static,
     {
         let start_time = Instant::now();
-        let mut cmd = Command::new(&self.shell);
-        cmd.arg("-c")
-            .arg(command)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        // Add environment variables
-        for (key, value) in &self.environment {
-            cmd.env(key, value);
-        }
+        let mut cmd = self.command(command)?;
 
         let mut child = cmd.spawn().map_err(CommandError::from)?;
 
@@ -150,7 +270,7 @@ impl CommandRunner for ShellCommandRunner {
                     let status = status.map_err(CommandError::from)?;
                     let duration = start_time.elapsed();
 
-                    return Ok(CommandOutput {
+                    return exit_result(command, CommandOutput {
                         stdout: full_stdout,
                         stderr: full_stderr,
                         status: status.code().unwrap_or(-1),
@@ -163,6 +283,19 @@ impl CommandRunner for ShellCommandRunner {
     }
 }
 
+/// The output of `command` if it succeeded, otherwise a
+/// [`CommandError::NonZeroExit`] carrying it
+fn exit_result(command: &str, output: CommandOutput) -> Result<CommandOutput, CommandError> {
+    if output.succeeded() {
+        Ok(output)
+    } else {
+        Err(CommandError::NonZeroExit {
+            command: command.to_string(),
+            output,
+        })
+    }
+}
+
 fn handle_read_result<F>(
     result: Result<usize, tokio::io::Error>,
     full_output: &mut String,
@@ -186,6 +319,23 @@ where
     Ok(())
 }
 
+/// Write a shell to `dir` that hangs on `command -v` lookups and runs
+/// everything else with `/bin/sh`, for runners whose lookups time out
+#[cfg(test)]
+pub(crate) fn hanging_lookup_shell(dir: &std::path::Path) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("hanging-lookup-sh");
+    std::fs::write(
+        &path,
+        "#!/bin/sh\ncase \"$2\" in\n  *'command -v'*) exec sleep 10 ;;\nesac\nexec /bin/sh \"$@\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    path.to_string_lossy().into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,11 +354,44 @@ mod tests {
         assert!(output.success);
 
         // Test command failure
-        let result = runner.execute("exit 1").await;
-        assert!(result.is_ok());
-        let output = result.unwrap();
-        assert!(!output.success);
-        assert_eq!(output.status, 1);
+        let result = runner.execute("echo oops >&2; exit 1").await;
+        match result {
+            Err(CommandError::NonZeroExit { command, output }) => {
+                assert_eq!(command, "echo oops >&2; exit 1");
+                assert!(!output.success);
+                assert_eq!(output.status, 1);
+                assert_eq!(output.stderr, "oops\n");
+            }
+            other => panic!("Expected NonZeroExit, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_environment() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10)).with_environment(
+            HashMap::from([("SELFIE_TEST_VAR".to_string(), "hello world".to_string())]),
+        );
+
+        let output = runner.execute("echo \"$SELFIE_TEST_VAR\"").await.unwrap();
+        assert_eq!(output.stdout.trim(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_with_shell() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10))
+            .with_environment(HashMap::from([(
+                "SELFIE_TEST_VAR".to_string(),
+                "kept".to_string(),
+            )]))
+            .with_shell(Shell::Bash);
+
+        // Only bash sets BASH_VERSION; the environment carries over
+        let output = runner
+            .execute("[ -n \"$BASH_VERSION\" ] && echo \"$SELFIE_TEST_VAR\"")
+            .await
+            .unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout.trim(), "kept");
     }
 
     #[tokio::test]
@@ -216,11 +399,74 @@ mod tests {
         let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10));
 
         // "echo" should be available in most environments
-        assert!(runner.is_command_available("echo").await);
+        assert!(runner.is_command_available("echo").await.unwrap());
 
         // A random string should not be a valid command
         let random_cmd = "xyzabc123notarealcommand";
-        assert!(!runner.is_command_available(random_cmd).await);
+        assert!(!runner.is_command_available(random_cmd).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_stdin() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10));
+
+        let output = runner
+            .execute_with_stdin("tr a-z A-Z", b"hello\n", Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout, "HELLO\n");
+
+        // A command that never reads its input still completes
+        let output = runner
+            .execute_with_stdin("echo done", b"ignored", Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert_eq!(output.stdout_trimmed(), "done");
+    }
+
+    #[tokio::test]
+    async fn test_failed_command_is_an_error() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10));
+
+        let result = runner
+            .execute_streaming("echo partial; exit 3", Duration::from_secs(10), |_| {})
+            .await;
+        assert!(matches!(
+            result,
+            Err(CommandError::NonZeroExit { ref output, .. })
+                if output.status == 3 && output.stdout == "partial\n"
+        ));
+
+        let result = runner
+            .execute_with_stdin("cat >/dev/null; exit 2", b"input", Duration::from_secs(10))
+            .await;
+        assert!(matches!(
+            result.or_else(CommandError::into_output),
+            Ok(output) if output.status == 2
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_interrupt() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10));
+
+        // Commands ignore SIGINT unless they should be killed on interrupt
+        let output = runner.execute("kill -INT $$; echo survived").await.unwrap();
+        assert_eq!(output.stdout_trimmed(), "survived");
+
+        let killable = runner.clone().with_kill_on_interrupt(true);
+        assert!(matches!(
+            killable.execute("kill -INT $$; echo survived").await,
+            Err(CommandError::NonZeroExit { .. })
+        ));
+
+        // Once interrupted, no more commands start, including from clones
+        killable.interrupt();
+        assert!(matches!(
+            runner.execute("echo hello").await,
+            Err(CommandError::Interrupted)
+        ));
     }
 
     // This test relies on timing and could be flaky
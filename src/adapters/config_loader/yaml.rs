@@ -1,10 +1,10 @@
 // src/adapters/config/yaml_config_loader.rs
-use std::path::PathBuf;
+use std::{env, path::PathBuf};
 
 use config::FileFormat;
 
 use crate::{
-    domain::config::AppConfig,
+    domain::config::{AppConfig, ProgressFormat},
     ports::{
         application::ApplicationArguments,
         config_loader::{ConfigLoadError, ConfigLoader},
@@ -14,22 +14,93 @@ use crate::{
 
 pub struct Yaml<'a> {
     fs: &'a dyn FileSystem,
+
+    /// Directories to search for the config file instead of the standard ones
+    search_dirs: Option<Vec<PathBuf>>,
 }
 
 impl<'a> Yaml<'a> {
     pub fn new(fs: &'a dyn FileSystem) -> Self {
-        Self { fs }
+        Self {
+            fs,
+            search_dirs: None,
+        }
+    }
+
+    /// Search these directories for the config file, in order, instead of the
+    /// standard ones
+    #[cfg(test)]
+    pub(crate) fn with_search_dirs(mut self, search_dirs: Vec<PathBuf>) -> Self {
+        self.search_dirs = Some(search_dirs);
+        self
+    }
+
+    /// Directories to search for the config file, in order: the standard
+    /// ones, then the platform's config directory (e.g. under
+    /// `~/Library/Application Support` on macOS) when it's different
+    fn search_dirs(&self) -> Vec<PathBuf> {
+        if let Some(search_dirs) = &self.search_dirs {
+            return search_dirs.clone();
+        }
+
+        let mut search_dirs = standard_config_dirs(
+            env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+            dirs::home_dir(),
+        );
+        if let Ok(config_dir) = self.fs.config_dir() {
+            if !search_dirs.contains(&config_dir) {
+                search_dirs.push(config_dir);
+            }
+        }
+
+        search_dirs
+    }
+
+    /// Every file the config could be read from, in search order
+    fn candidate_paths(&self) -> Vec<PathBuf> {
+        self.search_dirs()
+            .into_iter()
+            .flat_map(|dir| [dir.join("config.yaml"), dir.join("config.yml")])
+            .collect()
     }
 }
 
-impl ConfigLoader for Yaml<'_> {
-    fn load_config(&self, app_args: &ApplicationArguments) -> Result<AppConfig, ConfigLoadError> {
-        let config_paths = self.find_config_paths();
+/// The standard directories holding selfie's config, in search order:
+/// `$XDG_CONFIG_HOME/selfie`, `~/.config/selfie`, then `~/.selfie`. A
+/// relative `XDG_CONFIG_HOME` is ignored, as the XDG spec says to.
+fn standard_config_dirs(
+    xdg_config_home: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(xdg_config_home) = xdg_config_home.filter(|dir| dir.is_absolute()) {
+        dirs.push(xdg_config_home.join("selfie"));
+    }
 
-        if config_paths.is_empty() {
-            // No config file found, return error
-            return Err(ConfigLoadError::NotFound);
+    if let Some(home_dir) = home_dir {
+        let config_dir = home_dir.join(".config").join("selfie");
+        if !dirs.contains(&config_dir) {
+            dirs.push(config_dir);
         }
+        dirs.push(home_dir.join(".selfie"));
+    }
+
+    dirs
+}
+
+impl ConfigLoader for Yaml<'_> {
+    fn load_config(&self, app_args: &ApplicationArguments) -> Result<AppConfig, ConfigLoadError> {
+        // --config takes the place of the search
+        let config_path = match &app_args.config_path {
+            Some(path) if self.fs.path_exists(path) => path.clone(),
+            Some(path) => return Err(ConfigLoadError::NotFound(vec![path.clone()])),
+            None => self
+                .find_config_paths()
+                .into_iter()
+                .next()
+                .ok_or_else(|| ConfigLoadError::NotFound(self.candidate_paths()))?,
+        };
 
         // Start with default configuration
         let mut builder = config::Config::builder();
@@ -37,11 +108,9 @@ impl ConfigLoader for Yaml<'_> {
         // Add default values
         builder = builder.set_default("verbose", false).unwrap();
 
-        // builder = builder.add_source(config::File::from(config_paths[0].as_path()));
-        let config_path = &config_paths[0];
         let file_contents = self
             .fs
-            .read_file(config_path)
+            .read_file(&config_path)
             .map_err(|e| ConfigLoadError::ReadError(e.to_string()))?;
         builder = builder.add_source(config::File::from_str(&file_contents, FileFormat::Yaml));
 
@@ -57,8 +126,51 @@ impl ConfigLoader for Yaml<'_> {
             )?;
         }
 
-        builder = builder.set_override("verbose", app_args.verbose)?;
-        builder = builder.set_override("use_colors", !app_args.no_color)?;
+        // Flags left off keep the file's settings, as in `AppConfig::merge`
+        if app_args.verbose {
+            builder = builder.set_override("verbose", true)?;
+        }
+
+        if app_args.no_color {
+            builder = builder.set_override("use_colors", false)?;
+        }
+
+        if let Some(progress_format) = app_args.progress_format {
+            let progress_format = match progress_format {
+                ProgressFormat::Human => "human",
+                ProgressFormat::Json => "json",
+                ProgressFormat::Compact => "compact",
+            };
+            builder = builder.set_override("progress_format", progress_format)?;
+        }
+
+        if app_args.assume_yes {
+            builder = builder.set_override("assume_yes", true)?;
+        }
+
+        if app_args.kill_on_interrupt {
+            builder = builder.set_override("kill_on_interrupt", true)?;
+        }
+
+        if app_args.strict {
+            builder = builder.set_override("strict", true)?;
+        }
+
+        if app_args.no_cache {
+            builder = builder.set_override("use_cache", false)?;
+        }
+
+        if let Some(wrapper) = app_args.command_wrapper.as_ref() {
+            builder = builder.set_override("command_wrapper", wrapper.clone())?;
+        }
+
+        if let Some(env_file) = app_args.env_file.as_ref() {
+            builder = builder.set_override("env_file", env_file.to_string_lossy().into_owned())?;
+        }
+
+        if let Some(check_timeout) = app_args.check_timeout {
+            builder = builder.set_override("check_timeout", check_timeout.get())?;
+        }
 
         // Build the config
         let config = builder.build()?;
@@ -82,21 +194,10 @@ impl ConfigLoader for Yaml<'_> {
     }
 
     fn find_config_paths(&self) -> Vec<PathBuf> {
-        let mut paths = Vec::new();
-
-        if let Ok(config_dir) = self.fs.config_dir() {
-            let config_yaml = config_dir.join("config.yaml");
-            let config_yml = config_dir.join("config.yml");
-
-            if self.fs.path_exists(&config_yaml) {
-                paths.push(config_yaml);
-            }
-            if self.fs.path_exists(&config_yml) {
-                paths.push(config_yml);
-            }
-        }
-
-        paths
+        self.candidate_paths()
+            .into_iter()
+            .filter(|path| self.fs.path_exists(path))
+            .collect()
     }
 
     fn default_config(&self) -> AppConfig {
@@ -134,36 +235,81 @@ mod tests {
     }
 
     #[test]
-    fn test_find_config_paths() {
-        let (mut fs, home_dir) = setup_test_fs();
-        let config_dir = home_dir.join(".config").join("selfie");
-        fs.mock_config_dir(&config_dir);
-        fs.mock_path_exists(config_dir.join("selfie").join("config.yaml"), true);
-
-        let loader = Yaml::new(&fs);
-
-        let paths = loader.find_config_paths();
+    fn test_standard_config_dirs() {
+        let home_dir = Some(PathBuf::from("/home/test"));
+
+        assert_eq!(
+            standard_config_dirs(Some(PathBuf::from("/xdg")), home_dir.clone()),
+            vec![
+                PathBuf::from("/xdg/selfie"),
+                PathBuf::from("/home/test/.config/selfie"),
+                PathBuf::from("/home/test/.selfie"),
+            ]
+        );
+
+        // The usual XDG_CONFIG_HOME is only searched once
+        assert_eq!(
+            standard_config_dirs(Some(PathBuf::from("/home/test/.config")), home_dir.clone()),
+            vec![
+                PathBuf::from("/home/test/.config/selfie"),
+                PathBuf::from("/home/test/.selfie"),
+            ]
+        );
+
+        // A relative XDG_CONFIG_HOME is ignored
+        assert_eq!(
+            standard_config_dirs(Some(PathBuf::from("xdg")), home_dir),
+            vec![
+                PathBuf::from("/home/test/.config/selfie"),
+                PathBuf::from("/home/test/.selfie"),
+            ]
+        );
+
+        assert_eq!(
+            standard_config_dirs(Some(PathBuf::from("/xdg")), None),
+            vec![PathBuf::from("/xdg/selfie")]
+        );
+    }
 
-        // Should find at least the one we set up
-        assert!(!paths.is_empty());
-        assert!(paths.iter().any(|p| p.ends_with("config.yaml")));
+    #[test]
+    fn test_find_config_paths() {
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists("/xdg/selfie/config.yaml", false);
+        fs.mock_path_exists("/xdg/selfie/config.yml", false);
+        fs.mock_path_exists("/home/test/.config/selfie/config.yaml", false);
+        fs.mock_path_exists("/home/test/.config/selfie/config.yml", true);
+        fs.mock_path_exists("/home/test/.selfie/config.yaml", true);
+        fs.mock_path_exists("/home/test/.selfie/config.yml", false);
+
+        let loader = Yaml::new(&fs).with_search_dirs(standard_config_dirs(
+            Some(PathBuf::from("/xdg")),
+            Some(PathBuf::from("/home/test")),
+        ));
+
+        // Found files come in search order, so the first one is used
+        assert_eq!(
+            loader.find_config_paths(),
+            vec![
+                PathBuf::from("/home/test/.config/selfie/config.yml"),
+                PathBuf::from("/home/test/.selfie/config.yaml"),
+            ]
+        );
     }
 
     #[test]
     fn test_load_config() {
         let (mut fs, home_dir) = setup_test_fs();
         let config_dir = home_dir.join(".config").join("selfie");
-        fs.mock_config_dir(&config_dir);
-        fs.mock_path_exists(config_dir.join("config.yaml"), true);
 
         let package_dir = Path::new("/test/packages");
         fs.mock_path_exists(&package_dir, true);
         fs.mock_expand_path(&package_dir, &package_dir);
 
-        let loader = Yaml::new(&fs);
+        let loader = Yaml::new(&fs).with_search_dirs(vec![config_dir]);
         let args = ApplicationArgumentsBuilder::default()
             .environment("test-env")
             .package_directory(package_dir)
+            .assume_yes(true)
             .build();
 
         let config = loader.load_config(&args).unwrap();
@@ -171,22 +317,62 @@ mod tests {
         // Check the loaded values
         assert_eq!(config.environment, "test-env");
         assert_eq!(config.package_directory, package_dir);
+        assert!(config.assume_yes());
     }
 
     #[test]
-    fn test_load_config_not_found() {
-        let mut fs = MockFileSystem::default(); // Empty file system
-        let config_dir = Path::new("/home/test/.config/selfie");
-        fs.mock_config_dir(&config_dir);
-        fs.mock_path_exists(config_dir, true);
-        fs.mock_path_exists(config_dir.join("config.yaml"), false);
-        fs.mock_path_exists(config_dir.join("config.yml"), false);
+    fn test_load_config_from_config_path() {
+        let mut fs = MockFileSystem::default();
+        let config_path = Path::new("/elsewhere/selfie.yaml");
+        fs.mock_path_exists(config_path, true);
+        fs.mock_read_file(
+            config_path,
+            "environment: other-env\npackage_directory: /other/packages\n",
+        );
+        fs.mock_expand_path("/other/packages", "/other/packages");
+
+        // The search directories aren't looked at
+        let loader = Yaml::new(&fs).with_search_dirs(vec![PathBuf::from("/home/test/.selfie")]);
+        let args = ApplicationArgumentsBuilder::default()
+            .config_path(config_path)
+            .build();
+
+        let config = loader.load_config(&args).unwrap();
+        assert_eq!(config.environment, "other-env");
 
-        let loader = Yaml::new(&fs);
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists(config_path, false);
+        let result = Yaml::new(&fs).load_config(&args);
+        assert!(
+            matches!(result, Err(ConfigLoadError::NotFound(paths)) if paths == vec![config_path])
+        );
+    }
 
-        // Should return error
-        let result = loader.load_config(&ApplicationArguments::default());
-        assert!(matches!(result, Err(ConfigLoadError::NotFound)));
+    #[test]
+    fn test_load_config_not_found() {
+        let mut fs = MockFileSystem::default(); // Empty file system
+        fs.mock_path_exists("/xdg/selfie/config.yaml", false);
+        fs.mock_path_exists("/xdg/selfie/config.yml", false);
+        fs.mock_path_exists("/home/test/.selfie/config.yaml", false);
+        fs.mock_path_exists("/home/test/.selfie/config.yml", false);
+
+        let loader = Yaml::new(&fs).with_search_dirs(vec![
+            PathBuf::from("/xdg/selfie"),
+            PathBuf::from("/home/test/.selfie"),
+        ]);
+
+        // Should return an error naming every path searched
+        let err = loader
+            .load_config(&ApplicationArguments::default())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No configuration file found. Looked for:
+  /xdg/selfie/config.yaml
+  /xdg/selfie/config.yml
+  /home/test/.selfie/config.yaml
+  /home/test/.selfie/config.yml"
+        );
     }
 
     #[test]
@@ -209,14 +395,13 @@ mod tests {
         "#;
 
         let config_path = config_dir.join("config.yaml");
-        fs.mock_config_dir(&config_dir);
         fs.mock_path_exists(&config_path, true);
         fs.mock_path_exists(&config_dir.join("config.yml"), false);
         fs.mock_read_file(&config_path, config_yaml);
 
         fs.mock_expand_path("/test/packages", "/test/packages");
 
-        let loader = Yaml::new(&fs);
+        let loader = Yaml::new(&fs).with_search_dirs(vec![config_dir.to_path_buf()]);
         let config = loader
             .load_config(&ApplicationArguments::default())
             .unwrap();
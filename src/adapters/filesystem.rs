@@ -2,8 +2,10 @@
 // Real file system adapter implementation
 
 use std::{
-    fs, io,
+    fs,
+    io::{self, Write},
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 use etcetera::{choose_app_strategy, AppStrategy, AppStrategyArgs};
@@ -20,7 +22,57 @@ impl FileSystem for RealFileSystem {
                 FileSystemError::PathNotFound(path.to_string_lossy().to_string())
             }
             io::ErrorKind::PermissionDenied => {
-                FileSystemError::PermissionDenied(path.to_string_lossy().to_string())
+                FileSystemError::PermissionDenied(path.to_path_buf())
+            }
+            _ => FileSystemError::IoError(e),
+        })
+    }
+
+    /// Writes to a temp file next to the file, then renames it into place, so
+    /// an interrupted write never leaves a truncated file behind. A symlink is
+    /// written through: the file it points to is replaced, not the link.
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), FileSystemError> {
+        let target = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        write_atomically(&target, content).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                FileSystemError::PathNotFound(path.to_string_lossy().to_string())
+            }
+            io::ErrorKind::PermissionDenied => {
+                FileSystemError::PermissionDenied(path.to_path_buf())
+            }
+            _ => FileSystemError::IoError(e),
+        })
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), FileSystemError> {
+        fs::create_dir_all(path).map_err(|e| match e.kind() {
+            io::ErrorKind::PermissionDenied => {
+                FileSystemError::PermissionDenied(path.to_path_buf())
+            }
+            _ => FileSystemError::IoError(e),
+        })
+    }
+
+    fn rename_file(&self, from: &Path, to: &Path) -> Result<(), FileSystemError> {
+        fs::rename(from, to).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                FileSystemError::PathNotFound(from.to_string_lossy().to_string())
+            }
+            io::ErrorKind::PermissionDenied => {
+                FileSystemError::PermissionDenied(from.to_path_buf())
+            }
+            _ => FileSystemError::IoError(e),
+        })
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), FileSystemError> {
+        fs::remove_file(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                FileSystemError::PathNotFound(path.to_string_lossy().to_string())
+            }
+            io::ErrorKind::PermissionDenied => {
+                FileSystemError::PermissionDenied(path.to_path_buf())
             }
             _ => FileSystemError::IoError(e),
         })
@@ -35,12 +87,14 @@ impl FileSystem for RealFileSystem {
         let expanded = shellexpand::tilde(&binding);
         PathBuf::from(expanded.as_ref())
             .canonicalize()
-            .map_err(|e| {
-                if e.kind() == io::ErrorKind::NotFound {
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => {
                     FileSystemError::PathNotFound(path.to_string_lossy().to_string())
-                } else {
-                    FileSystemError::IoError(e)
                 }
+                io::ErrorKind::PermissionDenied => {
+                    FileSystemError::PermissionDenied(path.to_path_buf())
+                }
+                _ => FileSystemError::IoError(e),
             })
     }
 
@@ -49,7 +103,7 @@ impl FileSystem for RealFileSystem {
             if e.kind() == io::ErrorKind::NotFound {
                 FileSystemError::PathNotFound(path.to_string_lossy().to_string())
             } else if e.kind() == io::ErrorKind::PermissionDenied {
-                FileSystemError::PermissionDenied(path.to_string_lossy().to_string())
+                FileSystemError::PermissionDenied(path.to_path_buf())
             } else {
                 FileSystemError::IoError(e)
             }
@@ -57,36 +111,95 @@ impl FileSystem for RealFileSystem {
 
         let mut paths = Vec::new();
         for entry in entries {
-            let entry = entry.map_err(FileSystemError::IoError)?;
+            let entry = entry.map_err(|e| match e.kind() {
+                io::ErrorKind::PermissionDenied => {
+                    FileSystemError::PermissionDenied(path.to_path_buf())
+                }
+                _ => FileSystemError::IoError(e),
+            })?;
             paths.push(entry.path());
         }
 
         Ok(paths)
     }
 
+    fn modified(&self, path: &Path) -> Result<SystemTime, FileSystemError> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => {
+                    FileSystemError::PathNotFound(path.to_string_lossy().to_string())
+                }
+                io::ErrorKind::PermissionDenied => {
+                    FileSystemError::PermissionDenied(path.to_path_buf())
+                }
+                _ => FileSystemError::IoError(e),
+            })
+    }
+
     fn canonicalize(&self, path: &Path) -> Result<PathBuf, FileSystemError> {
         path.canonicalize().map_err(|e| match e.kind() {
             io::ErrorKind::NotFound => {
                 FileSystemError::PathNotFound(path.to_string_lossy().to_string())
             }
             io::ErrorKind::PermissionDenied => {
-                FileSystemError::PermissionDenied(path.to_string_lossy().to_string())
+                FileSystemError::PermissionDenied(path.to_path_buf())
             }
             _ => FileSystemError::IoError(e),
         })
     }
 
     fn config_dir(&self) -> Result<PathBuf, FileSystemError> {
-        choose_app_strategy(AppStrategyArgs {
-            top_level_domain: "net".to_string(),
-            author: "turboladen".to_string(),
-            app_name: "selfie".to_string(),
-        })
-        .map(|xdg| xdg.config_dir())
-        .map_err(|_| FileSystemError::PathNotFound("Unable to find home directory".to_string()))
+        app_strategy().map(|xdg| xdg.config_dir())
+    }
+
+    fn cache_dir(&self) -> Result<PathBuf, FileSystemError> {
+        app_strategy().map(|xdg| xdg.cache_dir())
     }
 }
 
+/// Where selfie's directories go on this platform, e.g. the XDG ones on Linux
+fn app_strategy() -> Result<impl AppStrategy, FileSystemError> {
+    choose_app_strategy(AppStrategyArgs {
+        top_level_domain: "net".to_string(),
+        author: "turboladen".to_string(),
+        app_name: "selfie".to_string(),
+    })
+    .map_err(|_| FileSystemError::PathNotFound("Unable to find home directory".to_string()))
+}
+
+/// Replace `target` with `content` by writing it to a new file in the same
+/// directory, flushing it to disk, and renaming it into place, keeping the
+/// permissions of the file it replaces. Each write gets a temporary file of
+/// its own, so concurrent writes never share one; it's removed if the write
+/// fails.
+fn write_atomically(target: &Path, content: &str) -> io::Result<()> {
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut file_name = target.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    let mut temp_file = tempfile::Builder::new()
+        .prefix(&file_name)
+        .suffix(".tmp")
+        .tempfile_in(dir)?;
+
+    temp_file.write_all(content.as_bytes())?;
+    temp_file.as_file().sync_all()?;
+
+    if let Ok(metadata) = fs::metadata(target) {
+        temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?;
+    }
+
+    temp_file.persist(target).map_err(|err| err.error)?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,4 +246,91 @@ mod tests {
         assert!(paths.contains(&file1));
         assert!(paths.contains(&file2));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_canonicalize_resolves_symlinks() {
+        let fs = RealFileSystem;
+
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.yaml");
+        let link = dir.path().join("link.yaml");
+        File::create(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert_eq!(
+            fs.canonicalize(&link).unwrap(),
+            target.canonicalize().unwrap()
+        );
+        assert!(matches!(
+            fs.canonicalize(&dir.path().join("missing.yaml")),
+            Err(FileSystemError::PathNotFound(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_through_symlink() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let fs = RealFileSystem;
+
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.yaml");
+        let link = dir.path().join("link.yaml");
+        std::fs::write(&target, "old").unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        fs.write_file(&link, "new").unwrap();
+
+        // The file behind the link is replaced, keeping its permissions, and
+        // the link itself is left alone
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert!(std::fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(
+            std::fs::metadata(&target).unwrap().permissions().mode() & 0o777,
+            0o640
+        );
+        assert_eq!(fs.list_directory(dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_write_file_leaves_other_temp_files_alone() {
+        let fs = RealFileSystem;
+
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.yaml");
+        std::fs::write(&target, "old").unwrap();
+
+        // Where another write might be happening under a fixed temporary name
+        let other_temp = dir.path().join("target.yaml.tmp");
+        std::fs::write(&other_temp, "someone else's").unwrap();
+
+        fs.write_file(&target, "new").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert_eq!(
+            std::fs::read_to_string(&other_temp).unwrap(),
+            "someone else's"
+        );
+        assert_eq!(fs.list_directory(dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_create_dir_all() {
+        let fs = RealFileSystem;
+
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+
+        fs.create_dir_all(&nested).unwrap();
+        assert!(nested.is_dir());
+
+        // Creating it again is fine
+        fs.create_dir_all(&nested).unwrap();
+    }
 }
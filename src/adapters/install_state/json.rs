@@ -0,0 +1,94 @@
+// src/adapters/install_state/json.rs
+use std::path::{Path, PathBuf};
+
+use crate::{
+    domain::install_state::InstallState,
+    ports::{
+        filesystem::FileSystem,
+        install_state::{InstallStateError, InstallStateStore},
+    },
+};
+
+/// File in the package directory recording what selfie has installed
+pub(crate) const STATE_FILE: &str = ".selfie-state.json";
+
+/// Keeps the install state in a JSON file
+pub(crate) struct JsonInstallStateStore<'a, F: FileSystem> {
+    fs: &'a F,
+    path: PathBuf,
+}
+
+impl<'a, F: FileSystem> JsonInstallStateStore<'a, F> {
+    pub(crate) fn new(fs: &'a F, path: PathBuf) -> Self {
+        Self { fs, path }
+    }
+
+    /// Store the state in the default state file within `package_dir`
+    pub(crate) fn in_package_directory(fs: &'a F, package_dir: &Path) -> Self {
+        Self::new(fs, package_dir.join(STATE_FILE))
+    }
+}
+
+impl<F: FileSystem> InstallStateStore for JsonInstallStateStore<'_, F> {
+    fn load(&self) -> Result<InstallState, InstallStateError> {
+        if !self.fs.path_exists(&self.path) {
+            return Ok(InstallState::default());
+        }
+
+        let content = self.fs.read_file(&self.path)?;
+
+        serde_json::from_str(&content).map_err(|source| InstallStateError::Parse {
+            path: self.path.clone(),
+            source,
+        })
+    }
+
+    fn save(&self, state: &InstallState) -> Result<(), InstallStateError> {
+        let content = serde_json::to_string_pretty(state).map_err(InstallStateError::Serialize)?;
+
+        // Written in full or not at all, so an interrupted write can never
+        // leave a truncated state file behind
+        self.fs.write_file(&self.path, &content)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::adapters::filesystem::RealFileSystem;
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JsonInstallStateStore::in_package_directory(&RealFileSystem, dir.path());
+
+        assert!(store.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JsonInstallStateStore::in_package_directory(&RealFileSystem, dir.path());
+
+        let mut state = InstallState::default();
+        state.record("ripgrep", "1.0.0", "test-env");
+        store.save(&state).unwrap();
+
+        assert_eq!(store.load().unwrap(), state);
+        assert!(dir.path().join(STATE_FILE).exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(STATE_FILE), "not json").unwrap();
+        let store = JsonInstallStateStore::in_package_directory(&RealFileSystem, dir.path());
+
+        assert!(matches!(store.load(), Err(InstallStateError::Parse { .. })));
+    }
+}
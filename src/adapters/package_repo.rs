@@ -1 +1,3 @@
+pub mod cache;
+pub mod overlay;
 pub mod yaml;
@@ -0,0 +1,288 @@
+// src/adapters/package_repo/cache.rs
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::ports::filesystem::{FileSystem, FileSystemError};
+
+/// Directory within selfie's cache directory that parsed packages are kept in
+pub(crate) const PACKAGE_CACHE_DIR: &str = "packages";
+
+/// Version of the layout of cache entries; bump it when [`CacheEntry`] or
+/// the types cached in it change shape
+const CACHE_FORMAT: u32 = 1;
+
+/// What a package file parsed to, which selfie parsed it, and a hash of what
+/// the file held then
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    format: u32,
+    selfie_version: String,
+    path: PathBuf,
+    content_hash: u64,
+    value: T,
+}
+
+/// Keeps what package files parse to, so a file is only parsed again once
+/// its content changes, or a different version of selfie reads it.
+///
+/// Each file's entry is a JSON file of its own, written atomically, so runs at
+/// the same time never see half an entry. An entry that can't be read or
+/// doesn't match the file is a miss rather than an error; the worst a race or
+/// a corrupt cache can do is make selfie parse the file again.
+#[derive(Clone)]
+pub(crate) struct PackageCache<'a, F: FileSystem> {
+    fs: &'a F,
+    dir: PathBuf,
+}
+
+impl<'a, F: FileSystem> PackageCache<'a, F> {
+    pub(crate) fn new(fs: &'a F, dir: PathBuf) -> Self {
+        Self { fs, dir }
+    }
+
+    /// Keep the cache in the default directory within selfie's cache directory
+    pub(crate) fn in_cache_dir(fs: &'a F) -> Result<Self, FileSystemError> {
+        Ok(Self::new(fs, fs.cache_dir()?.join(PACKAGE_CACHE_DIR)))
+    }
+
+    /// What `path` parses to: the cached value if the file holds what it did
+    /// when it was cached, else the result of `parse`, which is then cached
+    pub(crate) fn get_or_parse<T, E>(
+        &self,
+        path: &Path,
+        parse: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        // A modification time can stay the same across an edit, e.g. one
+        // within the file system's timestamp resolution, so the content is
+        // what's compared
+        let Ok(content) = self.fs.read_file(path) else {
+            return parse();
+        };
+        let content_hash = hash(&content);
+
+        let entry_path = self.entry_path(path);
+        if let Some(value) = self.load(&entry_path, path, content_hash) {
+            return Ok(value);
+        }
+
+        let value = parse()?;
+
+        // Failing to cache only means parsing the file again next time
+        let _ = self.store(
+            &entry_path,
+            &CacheEntry {
+                format: CACHE_FORMAT,
+                selfie_version: env!("CARGO_PKG_VERSION").to_string(),
+                path: path.to_path_buf(),
+                content_hash,
+                value: &value,
+            },
+        );
+
+        Ok(value)
+    }
+
+    /// Delete every cached entry, returning how many there were
+    pub(crate) fn clear(&self) -> Result<usize, FileSystemError> {
+        if !self.fs.path_exists(&self.dir) {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in self.fs.list_directory(&self.dir)? {
+            if entry.extension().is_some_and(|ext| ext == "json") {
+                self.fs.remove_file(&entry)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// File the entry for the package file at `path` is kept in
+    fn entry_path(&self, path: &Path) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", hash(path)))
+    }
+
+    /// The cached value for `path`, if there's an entry for it, written by
+    /// this version of selfie, from when its content hashed to `content_hash`
+    fn load<T: DeserializeOwned>(
+        &self,
+        entry_path: &Path,
+        path: &Path,
+        content_hash: u64,
+    ) -> Option<T> {
+        if !self.fs.path_exists(entry_path) {
+            return None;
+        }
+
+        let content = self.fs.read_file(entry_path).ok()?;
+        let entry: CacheEntry<T> = serde_json::from_str(&content).ok()?;
+
+        // Two paths can hash the same, so the entry's own path is checked too
+        (entry.format == CACHE_FORMAT
+            && entry.selfie_version == env!("CARGO_PKG_VERSION")
+            && entry.path == path
+            && entry.content_hash == content_hash)
+            .then_some(entry.value)
+    }
+
+    fn store<T: Serialize>(
+        &self,
+        entry_path: &Path,
+        entry: &CacheEntry<&T>,
+    ) -> Result<(), FileSystemError> {
+        let content =
+            serde_json::to_string(entry).map_err(|e| FileSystemError::IoError(e.into()))?;
+
+        self.fs.create_dir_all(&self.dir)?;
+        self.fs.write_file(entry_path, &content)
+    }
+}
+
+/// Hash of `value`; only compared with hashes from the same version of
+/// selfie, so the hasher needn't be stable across Rust releases
+fn hash(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{adapters::filesystem::RealFileSystem, domain::package::Package};
+
+    const YAML: &str = r#"
+name: ripgrep
+version: 1.0.0
+environments:
+  macos:
+    install: brew install ripgrep
+    check: which rg
+"#;
+
+    #[test]
+    fn test_cache_reuses_unchanged_file() {
+        let dir = tempdir().unwrap();
+        let package_file = dir.path().join("ripgrep.yaml");
+        fs::write(&package_file, YAML).unwrap();
+
+        let fs = RealFileSystem;
+        let cache = PackageCache::new(&fs, dir.path().join("cache"));
+
+        let parsed = cache
+            .get_or_parse(&package_file, || Package::from_file(&fs, &package_file))
+            .unwrap();
+        let cached: Package = cache
+            .get_or_parse(&package_file, || -> Result<Package, ()> {
+                panic!("an unchanged file shouldn't be parsed again")
+            })
+            .unwrap();
+
+        assert_eq!(cached.name, parsed.name);
+        assert_eq!(cached.environments, parsed.environments);
+    }
+
+    #[test]
+    fn test_cache_parses_modified_file() {
+        let dir = tempdir().unwrap();
+        let package_file = dir.path().join("ripgrep.yaml");
+        fs::write(&package_file, YAML).unwrap();
+        let modified = fs::metadata(&package_file).unwrap().modified().unwrap();
+
+        let fs = RealFileSystem;
+        let cache = PackageCache::new(&fs, dir.path().join("cache"));
+        cache
+            .get_or_parse(&package_file, || Package::from_file(&fs, &package_file))
+            .unwrap();
+
+        // An edit that keeps the size and modification time is still seen
+        fs::write(&package_file, YAML.replace("1.0.0", "2.0.0")).unwrap();
+        let file = fs::File::options().write(true).open(&package_file).unwrap();
+        file.set_modified(modified).unwrap();
+
+        let package = cache
+            .get_or_parse(&package_file, || Package::from_file(&fs, &package_file))
+            .unwrap();
+
+        assert_eq!(package.version, "2.0.0");
+    }
+
+    #[test]
+    fn test_cache_ignores_entry_from_other_version() {
+        let dir = tempdir().unwrap();
+        let package_file = dir.path().join("ripgrep.yaml");
+        fs::write(&package_file, YAML).unwrap();
+
+        let fs = RealFileSystem;
+        let cache = PackageCache::new(&fs, dir.path().join("cache"));
+        cache
+            .get_or_parse(&package_file, || Package::from_file(&fs, &package_file))
+            .unwrap();
+
+        let entry_path = cache.entry_path(&package_file);
+        let entry = fs::read_to_string(&entry_path).unwrap().replace(
+            &format!("\"selfie_version\":\"{}\"", env!("CARGO_PKG_VERSION")),
+            "\"selfie_version\":\"0.0.0-old\"",
+        );
+        fs::write(&entry_path, entry).unwrap();
+
+        let mut parsed = false;
+        cache
+            .get_or_parse(&package_file, || {
+                parsed = true;
+                Package::from_file(&fs, &package_file)
+            })
+            .unwrap();
+
+        assert!(parsed);
+    }
+
+    #[test]
+    fn test_cache_ignores_corrupt_entry() {
+        let dir = tempdir().unwrap();
+        let package_file = dir.path().join("ripgrep.yaml");
+        fs::write(&package_file, YAML).unwrap();
+
+        let fs = RealFileSystem;
+        let cache = PackageCache::new(&fs, dir.path().join("cache"));
+        fs::create_dir_all(dir.path().join("cache")).unwrap();
+        fs::write(cache.entry_path(&package_file), "{ not json").unwrap();
+
+        let package = cache
+            .get_or_parse(&package_file, || Package::from_file(&fs, &package_file))
+            .unwrap();
+
+        assert_eq!(package.name, "ripgrep");
+    }
+
+    #[test]
+    fn test_cache_clear() {
+        let dir = tempdir().unwrap();
+        let package_file = dir.path().join("ripgrep.yaml");
+        fs::write(&package_file, YAML).unwrap();
+
+        let fs = RealFileSystem;
+        let cache = PackageCache::new(&fs, dir.path().join("cache"));
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        cache
+            .get_or_parse(&package_file, || Package::from_file(&fs, &package_file))
+            .unwrap();
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(!cache.entry_path(&package_file).exists());
+    }
+}
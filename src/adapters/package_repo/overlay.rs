@@ -0,0 +1,87 @@
+// src/adapters/package_repo/overlay.rs
+use std::path::PathBuf;
+
+use crate::domain::package::Package;
+use crate::ports::package_repo::{PackageRepoError, PackageRepository};
+
+/// Serves one package loaded from outside the package directory (e.g. via
+/// `--package-file`) in front of another repository, which still provides
+/// everything else, like its dependencies
+pub(crate) struct OverlayPackageRepository<'a, PR: PackageRepository> {
+    package: Package,
+    inner: &'a PR,
+}
+
+impl<'a, PR: PackageRepository> OverlayPackageRepository<'a, PR> {
+    pub(crate) fn new(package: Package, inner: &'a PR) -> Self {
+        Self { package, inner }
+    }
+}
+
+impl<PR: PackageRepository> PackageRepository for OverlayPackageRepository<'_, PR> {
+    fn get_package(&self, name: &str) -> Result<Package, PackageRepoError> {
+        if self.package.is_named(name) {
+            Ok(self.package.clone())
+        } else {
+            self.inner.get_package(name)
+        }
+    }
+
+    fn list_packages(&self) -> Result<Vec<Package>, PackageRepoError> {
+        let mut packages = self.inner.list_packages()?;
+        packages.retain(|package| package.name != self.package.name);
+        packages.push(self.package.clone());
+
+        Ok(packages)
+    }
+
+    fn find_package_files(&self, name: &str) -> Result<Vec<PathBuf>, PackageRepoError> {
+        if name == self.package.name {
+            Ok(vec![self.package.path.clone()])
+        } else {
+            self.inner.find_package_files(name)
+        }
+    }
+
+    fn package_exists(&self, name: &str) -> Result<bool, PackageRepoError> {
+        Ok(self.package.is_named(name) || self.inner.package_exists(name)?)
+    }
+
+    fn find_similar(&self, name: &str) -> Result<Vec<String>, PackageRepoError> {
+        self.inner.find_similar(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{domain::package::PackageBuilder, ports::package_repo::MockPackageRepository};
+
+    #[test]
+    fn test_overlay_package_shadows_inner() {
+        let mut inner = MockPackageRepository::new();
+        inner.mock_get_package_ok(
+            "rust",
+            PackageBuilder::default()
+                .name("rust")
+                .version("1.0.0")
+                .environment("test-env", "rustup install")
+                .build(),
+        );
+
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("2.0.0")
+            .environment_with_dependencies("test-env", "cargo install ripgrep", vec!["rust"])
+            .path("/drafts/ripgrep.yaml")
+            .build();
+        let repo = OverlayPackageRepository::new(package, &inner);
+
+        assert_eq!(repo.get_package("ripgrep").unwrap().version, "2.0.0");
+        assert_eq!(repo.get_package("rust").unwrap().version, "1.0.0");
+        assert_eq!(
+            repo.find_package_files("ripgrep").unwrap(),
+            vec![PathBuf::from("/drafts/ripgrep.yaml")]
+        );
+    }
+}
@@ -1,16 +1,45 @@
 // src/adapters/package_repo/yaml.rs
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
+use crate::adapters::package_repo::cache::PackageCache;
 use crate::adapters::progress::ProgressManager;
-use crate::domain::package::Package;
-use crate::ports::filesystem::FileSystem;
+use crate::domain::config::{NameSource, DEFAULT_PACKAGE_FILE_EXTENSIONS};
+use crate::domain::package::{Package, PackageParseError};
+use crate::ports::filesystem::{FileSystem, FileSystemError};
 use crate::ports::package_repo::{PackageRepoError, PackageRepository};
 
+/// Optional file in the package directory holding many packages, keyed by name
+pub(crate) const COMBINED_PACKAGES_FILE: &str = "packages.yaml";
+
+/// Largest edit distance at which a package name still counts as similar
+const MAX_SIMILAR_DISTANCE: usize = 2;
+
 #[derive(Clone)]
 pub(crate) struct YamlPackageRepository<'a, F: FileSystem> {
     fs: &'a F,
     package_dir: PathBuf,
     progress_manager: ProgressManager,
+
+    /// Extensions package files may have, without the leading dot
+    extensions: Vec<String>,
+
+    /// Where parsed package files are kept between runs, if anywhere
+    cache: Option<PackageCache<'a, F>>,
+
+    /// Where a package file's package gets its name
+    name_source: NameSource,
+
+    /// The packages in the combined file, parsed the first time they're
+    /// needed
+    combined: OnceLock<Vec<Package>>,
+
+    /// Every declared alias and what it resolves to, built the first time an
+    /// alias is looked up
+    aliases: OnceLock<HashMap<String, AliasTarget>>,
 }
 
 impl<'a, F: FileSystem> YamlPackageRepository<'a, F> {
@@ -19,38 +48,218 @@ impl<'a, F: FileSystem> YamlPackageRepository<'a, F> {
             fs,
             package_dir,
             progress_manager,
+            extensions: DEFAULT_PACKAGE_FILE_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            cache: None,
+            name_source: NameSource::default(),
+            combined: OnceLock::new(),
+            aliases: OnceLock::new(),
         }
     }
 
-    /// List all YAML files in a directory
-    fn list_yaml_files(&self, dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
-        let entries = self
-            .fs
-            .list_directory(dir)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    /// Use these package file extensions instead of `yaml` and `yml`. They're
+    /// tried in order when looking a package up by name.
+    pub(crate) fn with_extensions(mut self, extensions: &[String]) -> Self {
+        self.extensions = extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_string())
+            .collect();
+        self
+    }
 
-        let yaml_files: Vec<PathBuf> = entries
+    /// Reuse what package files parsed to on earlier runs, as long as they
+    /// haven't been modified since
+    pub(crate) fn with_cache(mut self, cache: PackageCache<'a, F>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Name packages after their file instead of their `name` field. Packages
+    /// in the combined file are keyed by name, so keep theirs.
+    pub(crate) fn with_name_source(mut self, name_source: NameSource) -> Self {
+        self.name_source = name_source;
+        self
+    }
+
+    /// Parse the file holding a single package, from the cache if it has it
+    fn parse_package_file(&self, path: &Path) -> Result<Package, PackageParseError> {
+        let parse = || Package::from_file(self.fs, path);
+
+        let mut package = match &self.cache {
+            Some(cache) => cache.get_or_parse(path, parse)?,
+            None => parse()?,
+        };
+        package.path = path.to_path_buf();
+
+        if self.name_source == NameSource::Filename {
+            if let Some(name) = self.package_name_of(path) {
+                package.name = name.to_string();
+            }
+        }
+
+        Ok(package)
+    }
+
+    /// Name of the package a file defines, going by its file name; see
+    /// [`package_name_of`]
+    fn package_name_of<'p>(&self, path: &'p Path) -> Option<&'p str> {
+        package_name_of(path, &self.extensions)
+    }
+
+    /// List all package files in a directory
+    fn list_package_files(&self, dir: &Path) -> Result<Vec<PathBuf>, FileSystemError> {
+        let entries = self.fs.list_directory(dir)?;
+
+        Ok(entries
             .into_iter()
-            .filter(|path| {
-                if let Some(ext) = path.extension() {
-                    let ext_str = ext.to_string_lossy().to_lowercase();
-                    ext_str == "yaml" || ext_str == "yml"
-                } else {
-                    false
-                }
-            })
+            .filter(|path| self.package_name_of(path).is_some())
+            .collect())
+    }
+
+    /// Files in the package directory holding one package each (everything
+    /// but the combined file), sorted, whether or not they parse
+    pub(crate) fn package_files(&self) -> Result<Vec<PathBuf>, PackageRepoError> {
+        if !self.fs.path_exists(&self.package_dir) {
+            return Err(PackageRepoError::DirectoryNotFound(
+                self.package_dir.to_string_lossy().into_owned(),
+            ));
+        }
+
+        let combined_path = self.combined_file_path();
+        let mut package_files: Vec<PathBuf> = self
+            .list_package_files(&self.package_dir)?
+            .into_iter()
+            .filter(|path| *path != combined_path)
             .collect();
+        package_files.sort();
+
+        Ok(package_files)
+    }
 
-        Ok(yaml_files)
+    /// Path of the combined package file within the package directory
+    fn combined_file_path(&self) -> PathBuf {
+        self.package_dir.join(COMBINED_PACKAGES_FILE)
+    }
+
+    /// The packages defined in the combined package file, if there is one.
+    /// It's only read once; if it doesn't parse, that's a warning and it
+    /// defines no packages, so packages in their own files still work.
+    fn list_combined_packages(&self) -> &[Package] {
+        self.combined.get_or_init(|| {
+            self.parse_combined_file().unwrap_or_else(|err| {
+                self.progress_manager.print_error_detail(format!(
+                    "Warning: Failed to parse package file '{}': {}",
+                    COMBINED_PACKAGES_FILE, err
+                ));
+                Vec::new()
+            })
+        })
+    }
+
+    /// Parse the combined package file, from the cache if it has it
+    fn parse_combined_file(&self) -> Result<Vec<Package>, PackageRepoError> {
+        let path = self.combined_file_path();
+        if !self.fs.path_exists(&path) {
+            return Ok(Vec::new());
+        }
+
+        let parse = || {
+            let content = self.fs.read_file(&path)?;
+
+            Package::from_combined_yaml(&content)
+        };
+
+        let mut packages = match &self.cache {
+            Some(cache) => cache.get_or_parse(&path, parse)?,
+            None => parse()?,
+        };
+        for package in &mut packages {
+            package.path = path.clone();
+        }
+
+        Ok(packages)
+    }
+
+    /// Map of every declared alias to the package declaring it, built from
+    /// the listed packages the first time an alias is looked up. An alias
+    /// declared by two packages could mean either, so it maps to the
+    /// collision instead.
+    fn alias_index(&self) -> Result<&HashMap<String, AliasTarget>, PackageRepoError> {
+        if let Some(index) = self.aliases.get() {
+            return Ok(index);
+        }
+
+        let mut index: HashMap<String, AliasTarget> = HashMap::new();
+        for package in self.list_packages()? {
+            for alias in &package.aliases {
+                // A package aliased to its own name is a validation error;
+                // looking it up by name already finds it
+                if *alias == package.name {
+                    continue;
+                }
+
+                match index.get_mut(alias) {
+                    Some(AliasTarget::Package(first)) if first.name != package.name => {
+                        let (first, second) = if first.name < package.name {
+                            (first.name.clone(), package.name.clone())
+                        } else {
+                            (package.name.clone(), first.name.clone())
+                        };
+                        index.insert(alias.clone(), AliasTarget::Collision { first, second });
+                    }
+                    Some(_) => {}
+                    None => {
+                        index.insert(
+                            alias.clone(),
+                            AliasTarget::Package(Box::new(package.clone())),
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(self.aliases.get_or_init(|| index))
+    }
+
+    /// The package `name` is an alias of, if it's one. An alias declared by
+    /// two packages is an error.
+    fn resolve_alias(&self, name: &str) -> Result<Option<Package>, PackageRepoError> {
+        match self.alias_index()?.get(name) {
+            Some(AliasTarget::Package(package)) => Ok(Some(Package::clone(package))),
+            Some(AliasTarget::Collision { first, second }) => {
+                Err(PackageRepoError::AliasCollision {
+                    alias: name.to_string(),
+                    first: first.clone(),
+                    second: second.clone(),
+                })
+            }
+            None => Ok(None),
+        }
     }
 }
 
+/// What an alias resolves to
+#[derive(Clone, Debug)]
+enum AliasTarget {
+    /// The one package declaring it
+    Package(Box<Package>),
+
+    /// The first two packages declaring it, by name
+    Collision { first: String, second: String },
+}
+
 impl<F: FileSystem> PackageRepository for YamlPackageRepository<'_, F> {
     fn get_package(&self, name: &str) -> Result<Package, PackageRepoError> {
         let package_files = self.find_package_files(name)?;
 
         if package_files.is_empty() {
-            return Err(PackageRepoError::PackageNotFound(name.to_string()));
+            // A package's own name wins over another's alias, so aliases are
+            // only looked at once there's no package by that name
+            return self
+                .resolve_alias(name)?
+                .ok_or_else(|| PackageRepoError::PackageNotFound(name.to_string()));
         }
 
         if package_files.len() > 1 {
@@ -58,7 +267,16 @@ impl<F: FileSystem> PackageRepository for YamlPackageRepository<'_, F> {
         }
 
         let package_file = &package_files[0];
-        let package = Package::from_file(self.fs, package_file)?;
+        if *package_file == self.combined_file_path() {
+            return self
+                .list_combined_packages()
+                .iter()
+                .find(|package| package.name == name)
+                .cloned()
+                .ok_or_else(|| PackageRepoError::PackageNotFound(name.to_string()));
+        }
+
+        let package = self.parse_package_file(package_file)?;
 
         Ok(package)
     }
@@ -70,18 +288,22 @@ impl<F: FileSystem> PackageRepository for YamlPackageRepository<'_, F> {
             ));
         }
 
-        // Get all YAML files in the directory
-        let yaml_files = self.list_yaml_files(&self.package_dir)?;
+        // Get all package files in the directory, except the combined file
+        let combined_path = self.combined_file_path();
+        let package_files = self.list_package_files(&self.package_dir)?;
 
         // Parse each file into a Package
-        let mut packages = Vec::new();
-        for path in yaml_files {
-            match Package::from_file(self.fs, &path) {
+        let mut packages: Vec<Package> = Vec::new();
+        for path in package_files
+            .into_iter()
+            .filter(|path| *path != combined_path)
+        {
+            match self.parse_package_file(&path) {
                 Ok(package) => packages.push(package),
                 Err(err) => {
                     // Skip invalid files but log them if we had a proper logging system
                     if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                        self.progress_manager.print_error(format!(
+                        self.progress_manager.print_error_detail(format!(
                             "Warning: Failed to parse package file '{}': {}",
                             file_name, err
                         ));
@@ -90,6 +312,18 @@ impl<F: FileSystem> PackageRepository for YamlPackageRepository<'_, F> {
             }
         }
 
+        // Merge in the combined file, skipping names already defined by their own file
+        for package in self.list_combined_packages() {
+            if packages.iter().any(|p| p.name == package.name) {
+                self.progress_manager.print_error_detail(format!(
+                    "Warning: Package '{}' is defined in both its own file and '{}'",
+                    package.name, COMBINED_PACKAGES_FILE
+                ));
+            } else {
+                packages.push(package.clone());
+            }
+        }
+
         Ok(packages)
     }
 
@@ -100,20 +334,124 @@ impl<F: FileSystem> PackageRepository for YamlPackageRepository<'_, F> {
             ));
         }
 
-        // Look for both name.yaml and name.yml
-        let yaml_path = self.package_dir.join(format!("{}.yaml", name));
-        let yml_path = self.package_dir.join(format!("{}.yml", name));
+        // Look for the name with each extension, e.g. name.yaml and name.yml;
+        // finding more than one is reported as a duplicate by the callers
+        let combined_path = self.combined_file_path();
 
         let mut result = Vec::new();
-        if self.fs.path_exists(&yaml_path) {
-            result.push(yaml_path);
+        for ext in &self.extensions {
+            let path = self.package_dir.join(format!("{}.{}", name, ext));
+
+            if path != combined_path && !result.contains(&path) && self.fs.path_exists(&path) {
+                result.push(path);
+            }
         }
-        if self.fs.path_exists(&yml_path) {
-            result.push(yml_path);
+
+        // Names that are symlinks to the same file are one package file, not
+        // duplicates
+        if result.len() > 1 {
+            let mut real_paths = Vec::new();
+            result.retain(|path| {
+                let real_path = self.fs.canonicalize(path).unwrap_or_else(|_| path.clone());
+                let is_new = !real_paths.contains(&real_path);
+                real_paths.push(real_path);
+                is_new
+            });
+        }
+
+        // The combined file counts as a match if it defines the package
+        if self
+            .list_combined_packages()
+            .iter()
+            .any(|package| package.name == name)
+        {
+            result.push(combined_path);
         }
 
         Ok(result)
     }
+
+    fn package_exists(&self, name: &str) -> Result<bool, PackageRepoError> {
+        match self.find_package_files(name)?.len() {
+            0 => Ok(self.resolve_alias(name)?.is_some()),
+            1 => Ok(true),
+            _ => Err(PackageRepoError::MultiplePackagesFound(name.to_string())),
+        }
+    }
+
+    fn find_similar(&self, name: &str) -> Result<Vec<String>, PackageRepoError> {
+        if !self.fs.path_exists(&self.package_dir) {
+            return Err(PackageRepoError::DirectoryNotFound(
+                self.package_dir.to_string_lossy().into_owned(),
+            ));
+        }
+
+        // Package files are named after their package, so there's no need to
+        // parse them
+        let combined_path = self.combined_file_path();
+        let mut names: Vec<String> = self
+            .list_package_files(&self.package_dir)?
+            .iter()
+            .filter(|path| **path != combined_path)
+            .filter_map(|path| self.package_name_of(path).map(String::from))
+            .collect();
+
+        names.extend(
+            self.list_combined_packages()
+                .iter()
+                .map(|package| package.name.clone()),
+        );
+
+        Ok(similar_names(name, names))
+    }
+}
+
+/// The candidates within a small edit distance of `name`, or sharing a prefix
+/// with it, closest first
+/// Name of the package a file defines, going by its file name, or `None` if it
+/// doesn't have one of `extensions`. The longest matching extension wins, so
+/// `fd.pkg.yaml` is `fd` when `pkg.yaml` is configured.
+pub(crate) fn package_name_of<'p>(path: &'p Path, extensions: &[String]) -> Option<&'p str> {
+    let file_name = path.file_name()?.to_str()?;
+
+    extensions
+        .iter()
+        .map(|ext| ext.trim_start_matches('.'))
+        .filter_map(|ext| {
+            let split = file_name.len().checked_sub(ext.len() + 1)?;
+            let (stem, suffix) = (file_name.get(..split)?, file_name.get(split..)?);
+
+            (!stem.is_empty() && suffix.strip_prefix('.')?.eq_ignore_ascii_case(ext))
+                .then_some(stem)
+        })
+        .min_by_key(|stem| stem.len())
+}
+
+fn similar_names(name: &str, candidates: Vec<String>) -> Vec<String> {
+    let name_lower = name.to_lowercase();
+
+    let mut similar: Vec<(usize, String)> = candidates
+        .into_iter()
+        .filter(|candidate| !candidate.is_empty() && candidate != name)
+        .filter_map(|candidate| {
+            let candidate_lower = candidate.to_lowercase();
+            let distance = strsim::levenshtein(&name_lower, &candidate_lower);
+
+            let shares_prefix = !name_lower.is_empty()
+                && (candidate_lower.starts_with(&name_lower)
+                    || name_lower.starts_with(&candidate_lower));
+
+            (distance <= MAX_SIMILAR_DISTANCE || shares_prefix).then_some((distance, candidate))
+        })
+        .collect();
+
+    similar.sort();
+    similar.dedup_by(|a, b| a.1 == b.1);
+
+    similar
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
 }
 
 #[cfg(test)]
@@ -129,6 +467,7 @@ mod tests {
         fs.expect_path_exists()
             .with(mockall::predicate::eq(package_dir.clone()))
             .returning(|_| true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
 
         // Create mock package file
         let package_path = package_dir.join("ripgrep.yaml");
@@ -157,6 +496,33 @@ mod tests {
         assert_eq!(package.environments.len(), 1);
     }
 
+    #[test]
+    fn test_get_package_named_by_filename() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+        fs.mock_path_exists(package_dir.join("ripgrep.yaml"), true);
+        fs.mock_path_exists(package_dir.join("ripgrep.yml"), false);
+        fs.mock_read_file(
+            package_dir.join("ripgrep.yaml"),
+            r#"
+            name: rg
+            version: 0.1.0
+            environments:
+              mac:
+                install: brew install ripgrep
+        "#,
+        );
+
+        let repo = YamlPackageRepository::new(&fs, package_dir.clone(), ProgressManager::default());
+        assert_eq!(repo.get_package("ripgrep").unwrap().name, "rg");
+
+        let repo = repo.with_name_source(NameSource::Filename);
+        assert_eq!(repo.get_package("ripgrep").unwrap().name, "ripgrep");
+    }
+
     #[test]
     fn test_get_package_not_found() {
         let mut fs = MockFileSystem::default();
@@ -165,12 +531,14 @@ mod tests {
         fs.expect_path_exists()
             .with(mockall::predicate::eq(package_dir.clone()))
             .returning(|_| true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
         fs.expect_path_exists()
             .with(mockall::predicate::eq(package_dir.join("nonexistent.yaml")))
             .returning(|_| false);
         fs.expect_path_exists()
             .with(mockall::predicate::eq(package_dir.join("nonexistent.yml")))
             .returning(|_| false);
+        fs.mock_list_directory(package_dir.clone(), &[]);
 
         let progress_manager = ProgressManager::default();
         let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
@@ -208,17 +576,31 @@ mod tests {
         let yml_path = package_dir.join("ripgrep.yml");
 
         fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
         fs.mock_path_exists(&yaml_path, true);
         fs.mock_path_exists(&yml_path, true);
+        fs.mock_real_paths();
 
         let progress_manager = ProgressManager::default();
-        let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
+        let repo = YamlPackageRepository::new(&fs, package_dir.clone(), progress_manager);
         let result = repo.get_package("ripgrep");
 
         assert!(matches!(
             result,
             Err(PackageRepoError::MultiplePackagesFound(_))
         ));
+
+        // Names that link to the same file are that one file
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+        fs.mock_path_exists(&yaml_path, true);
+        fs.mock_path_exists(&yml_path, true);
+        fs.mock_symlink(&yaml_path, &PathBuf::from("/dotfiles/ripgrep.yaml"));
+        fs.mock_symlink(&yml_path, &PathBuf::from("/dotfiles/ripgrep.yaml"));
+
+        let repo = YamlPackageRepository::new(&fs, package_dir, ProgressManager::default());
+        assert_eq!(repo.find_package_files("ripgrep").unwrap(), vec![yaml_path]);
     }
 
     #[test]
@@ -233,6 +615,7 @@ mod tests {
         fs.expect_path_exists()
             .with(mockall::predicate::eq(package_dir.clone()))
             .returning(|_| true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
         fs.expect_path_exists()
             .with(mockall::predicate::eq(yaml_path.clone()))
             .returning(|_| true);
@@ -270,6 +653,152 @@ mod tests {
         assert_eq!(files.len(), 0);
     }
 
+    #[test]
+    fn test_package_exists() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+        fs.mock_path_exists(package_dir.join("ripgrep.yaml"), true);
+        fs.mock_path_exists(package_dir.join("ripgrep.yml"), false);
+        fs.mock_path_exists(package_dir.join("fzf.yaml"), true);
+        fs.mock_path_exists(package_dir.join("fzf.yml"), true);
+        fs.mock_path_exists(package_dir.join("nonexistent.yaml"), false);
+        fs.mock_path_exists(package_dir.join("nonexistent.yml"), false);
+        fs.mock_real_paths();
+
+        // Only a name no file has is looked for among the aliases, which
+        // means parsing the packages
+        fs.mock_list_directory(package_dir.clone(), &[package_dir.join("ripgrep.yaml")]);
+        fs.mock_read_file(
+            package_dir.join("ripgrep.yaml"),
+            "name: ripgrep\nversion: 1.0.0\naliases: [rg]\nenvironments: {}\n",
+        );
+        fs.mock_path_exists(package_dir.join("rg.yaml"), false);
+        fs.mock_path_exists(package_dir.join("rg.yml"), false);
+
+        let progress_manager = ProgressManager::default();
+        let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
+
+        assert!(repo.package_exists("ripgrep").unwrap());
+        assert!(repo.package_exists("rg").unwrap());
+        assert!(!repo.package_exists("nonexistent").unwrap());
+        assert!(matches!(
+            repo.package_exists("fzf"),
+            Err(PackageRepoError::MultiplePackagesFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_package_by_alias() {
+        let package_dir = PathBuf::from("/test/packages");
+        let create_fs = |fd_aliases: &str| {
+            let mut fs = MockFileSystem::default();
+
+            fs.mock_path_exists(&package_dir, true);
+            fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+            for name in ["ripgrep", "fd"] {
+                fs.mock_path_exists(package_dir.join(format!("{}.yaml", name)), true);
+                fs.mock_path_exists(package_dir.join(format!("{}.yml", name)), false);
+            }
+            for name in ["rg", "fdfind"] {
+                fs.mock_path_exists(package_dir.join(format!("{}.yaml", name)), false);
+                fs.mock_path_exists(package_dir.join(format!("{}.yml", name)), false);
+            }
+
+            fs.mock_list_directory(
+                package_dir.clone(),
+                &[
+                    package_dir.join("ripgrep.yaml"),
+                    package_dir.join("fd.yaml"),
+                ],
+            );
+            fs.mock_read_file(
+                package_dir.join("ripgrep.yaml"),
+                "name: ripgrep\nversion: 1.0.0\naliases: [rg]\nenvironments: {}\n",
+            );
+            fs.mock_read_file(
+                package_dir.join("fd.yaml"),
+                format!(
+                    "name: fd\nversion: 1.0.0\naliases: [{}]\nenvironments: {{}}\n",
+                    fd_aliases
+                ),
+            );
+
+            fs
+        };
+
+        let fs = create_fs("fdfind");
+        let repo = YamlPackageRepository::new(&fs, package_dir.clone(), ProgressManager::default());
+        assert_eq!(repo.get_package("rg").unwrap().name, "ripgrep");
+        assert_eq!(repo.get_package("fdfind").unwrap().name, "fd");
+
+        // An alias two packages declare can't be resolved, but that doesn't
+        // stop other names from being looked up
+        let fs = create_fs("rg");
+        let repo = YamlPackageRepository::new(&fs, package_dir.clone(), ProgressManager::default());
+        match repo.get_package("rg") {
+            Err(PackageRepoError::AliasCollision {
+                alias,
+                first,
+                second,
+            }) => {
+                assert_eq!(alias, "rg");
+                assert_eq!((first.as_str(), second.as_str()), ("fd", "ripgrep"));
+            }
+            other => panic!("expected an alias collision, got {:?}", other),
+        }
+        assert!(matches!(
+            repo.get_package("fdfind"),
+            Err(PackageRepoError::PackageNotFound(name)) if name == "fdfind"
+        ));
+        assert!(!repo.package_exists("fdfind").unwrap());
+        assert_eq!(repo.get_package("ripgrep").unwrap().name, "ripgrep");
+    }
+
+    #[test]
+    fn test_get_package_by_alias_parses_packages_once() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+        for name in ["rg", "ripgrep", "missing"] {
+            fs.mock_path_exists(package_dir.join(format!("{}.yaml", name)), false);
+            fs.mock_path_exists(package_dir.join(format!("{}.yml", name)), false);
+        }
+
+        // The file isn't named after the package, which also lists its own
+        // name as an alias
+        let path = package_dir.join("search-tools.yaml");
+        fs.mock_list_directory(package_dir.clone(), std::slice::from_ref(&path));
+        fs.expect_read_file()
+            .with(mockall::predicate::eq(path.clone()))
+            .times(1)
+            .returning(|_| {
+                Ok(
+                    "name: ripgrep\nversion: 1.0.0\naliases: [rg, ripgrep]\nenvironments: {}\n"
+                        .to_string(),
+                )
+            });
+
+        let repo = YamlPackageRepository::new(&fs, package_dir, ProgressManager::default());
+
+        let package = repo.get_package("rg").unwrap();
+        assert_eq!(package.name, "ripgrep");
+        assert_eq!(package.path, path);
+        assert!(matches!(
+            repo.get_package("missing"),
+            Err(PackageRepoError::PackageNotFound(_))
+        ));
+        assert!(matches!(
+            repo.get_package("ripgrep"),
+            Err(PackageRepoError::PackageNotFound(_))
+        ));
+        assert!(!repo.package_exists("missing").unwrap());
+    }
+
     #[test]
     fn test_list_packages() {
         let mut fs = MockFileSystem::default();
@@ -278,6 +807,7 @@ mod tests {
         fs.expect_path_exists()
             .with(mockall::predicate::eq(package_dir.clone()))
             .returning(|_| true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
 
         // Add valid package files
         let package1 = r#"
@@ -302,6 +832,9 @@ mod tests {
                 package_dir.join("ripgrep.yaml"),
                 package_dir.join("fzf.yml"),
                 package_dir.join("invalid.yaml"),
+                // Only .yaml and .yml files are packages by default
+                package_dir.join("bat.selfie"),
+                package_dir.join("README.md"),
             ],
         );
 
@@ -328,7 +861,222 @@ mod tests {
     }
 
     #[test]
-    fn test_list_yaml_files() {
+    fn test_custom_package_file_extensions() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+        fs.mock_path_exists(package_dir.join("ripgrep.selfie"), true);
+        fs.mock_path_exists(package_dir.join("ripgrep.pkg.yaml"), false);
+        fs.mock_path_exists(package_dir.join("fzf.selfie"), true);
+        fs.mock_path_exists(package_dir.join("fzf.pkg.yaml"), true);
+        fs.mock_real_paths();
+
+        fs.mock_list_directory(
+            package_dir.clone(),
+            &[
+                package_dir.join("ripgrep.selfie"),
+                package_dir.join("fd.pkg.yaml"),
+                package_dir.join("bat.yaml"),
+            ],
+        );
+        fs.mock_read_file(
+            package_dir.join("ripgrep.selfie"),
+            "name: ripgrep\nversion: 1.0.0\nenvironments:\n  test-env:\n    install: brew install ripgrep\n",
+        );
+        fs.mock_read_file(
+            package_dir.join("fd.pkg.yaml"),
+            "name: fd\nversion: 1.0.0\nenvironments:\n  test-env:\n    install: brew install fd\n",
+        );
+
+        let repo = YamlPackageRepository::new(&fs, package_dir.clone(), ProgressManager::default())
+            .with_extensions(&[".selfie".to_string(), "pkg.yaml".to_string()]);
+
+        assert_eq!(
+            repo.find_package_files("ripgrep").unwrap(),
+            vec![package_dir.join("ripgrep.selfie")]
+        );
+
+        // Files with different configured extensions are still duplicates
+        assert!(matches!(
+            repo.package_exists("fzf"),
+            Err(PackageRepoError::MultiplePackagesFound(_))
+        ));
+
+        let mut names: Vec<String> = repo
+            .list_packages()
+            .unwrap()
+            .into_iter()
+            .map(|package| package.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["fd", "ripgrep"]);
+
+        assert_eq!(repo.find_similar("f").unwrap(), vec!["fd"]);
+    }
+
+    #[test]
+    fn test_get_package_from_combined_file() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+        let combined_path = package_dir.join(COMBINED_PACKAGES_FILE);
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join("fzf.yaml"), false);
+        fs.mock_path_exists(package_dir.join("fzf.yml"), false);
+        fs.mock_path_exists(&combined_path, true);
+        fs.mock_read_file(
+            &combined_path,
+            r#"
+            ripgrep:
+              version: 1.0.0
+              environments:
+                test-env:
+                  install: brew install ripgrep
+            fzf:
+              version: 0.2.0
+              environments:
+                test-env:
+                  install: brew install fzf
+            "#,
+        );
+
+        let progress_manager = ProgressManager::default();
+        let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
+
+        assert_eq!(
+            repo.find_package_files("fzf").unwrap(),
+            vec![combined_path.clone()]
+        );
+
+        let package = repo.get_package("fzf").unwrap();
+        assert_eq!(package.name, "fzf");
+        assert_eq!(package.version, "0.2.0");
+        assert_eq!(package.path, combined_path);
+    }
+
+    #[test]
+    fn test_get_package_in_both_sources() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+        let combined_path = package_dir.join(COMBINED_PACKAGES_FILE);
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join("ripgrep.yaml"), true);
+        fs.mock_path_exists(package_dir.join("ripgrep.yml"), false);
+        fs.mock_path_exists(&combined_path, true);
+        fs.mock_read_file(
+            &combined_path,
+            r#"
+            ripgrep:
+              version: 1.0.0
+              environments:
+                test-env:
+                  install: brew install ripgrep
+            "#,
+        );
+
+        let progress_manager = ProgressManager::default();
+        let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
+
+        assert!(matches!(
+            repo.get_package("ripgrep"),
+            Err(PackageRepoError::MultiplePackagesFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_broken_combined_file() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+        let combined_path = package_dir.join(COMBINED_PACKAGES_FILE);
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join("ripgrep.yaml"), true);
+        fs.mock_path_exists(package_dir.join("ripgrep.yml"), false);
+        fs.mock_path_exists(package_dir.join("fzf.yaml"), false);
+        fs.mock_path_exists(package_dir.join("fzf.yml"), false);
+        fs.mock_path_exists(&combined_path, true);
+        fs.mock_list_directory(package_dir.clone(), &[package_dir.join("ripgrep.yaml")]);
+        fs.mock_read_file(
+            package_dir.join("ripgrep.yaml"),
+            "name: ripgrep\nversion: 1.0.0\nenvironments: {}\n",
+        );
+
+        // Read once, however many lookups there are
+        let combined = combined_path.clone();
+        fs.expect_read_file()
+            .withf(move |path| path == combined)
+            .times(1)
+            .returning(|_| Ok("fzf: [not, a, package".to_string()));
+
+        let repo = YamlPackageRepository::new(&fs, package_dir, ProgressManager::default());
+
+        // Packages in their own files are unaffected
+        assert_eq!(repo.get_package("ripgrep").unwrap().name, "ripgrep");
+        assert!(repo.package_exists("ripgrep").unwrap());
+        assert!(!repo.package_exists("fzf").unwrap());
+        assert!(matches!(
+            repo.get_package("fzf"),
+            Err(PackageRepoError::PackageNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_packages_with_combined_file() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+        let combined_path = package_dir.join(COMBINED_PACKAGES_FILE);
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(&combined_path, true);
+        fs.mock_list_directory(
+            package_dir.clone(),
+            &[package_dir.join("ripgrep.yaml"), combined_path.clone()],
+        );
+        fs.mock_read_file(
+            package_dir.join("ripgrep.yaml"),
+            r#"
+            name: ripgrep
+            version: 1.0.0
+            environments:
+              test-env:
+                install: brew install ripgrep
+            "#,
+        );
+        fs.mock_read_file(
+            &combined_path,
+            r#"
+            ripgrep:
+              version: 9.9.9
+              environments:
+                test-env:
+                  install: cargo install ripgrep
+            fzf:
+              version: 0.2.0
+              environments:
+                test-env:
+                  install: brew install fzf
+            "#,
+        );
+
+        let progress_manager = ProgressManager::default();
+        let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
+        let packages = repo.list_packages().unwrap();
+
+        // The duplicate in the combined file is skipped in favour of the package's own file
+        assert_eq!(packages.len(), 2);
+
+        let ripgrep = packages.iter().find(|p| p.name == "ripgrep").unwrap();
+        let fzf = packages.iter().find(|p| p.name == "fzf").unwrap();
+
+        assert_eq!(ripgrep.version, "1.0.0");
+        assert_eq!(fzf.path, combined_path);
+    }
+
+    #[test]
+    fn test_list_package_files() {
         let mut fs = MockFileSystem::default();
         let dir = PathBuf::from("/test/dir");
         let cloned = dir.clone();
@@ -347,7 +1095,7 @@ mod tests {
 
         let progress_manager = ProgressManager::default();
         let repo = YamlPackageRepository::new(&fs, PathBuf::from("/dummy"), progress_manager); // Path doesn't matter here
-        let yaml_files = repo.list_yaml_files(&dir).unwrap();
+        let yaml_files = repo.list_package_files(&dir).unwrap();
 
         // Should find all yaml/yml files regardless of case
         assert_eq!(yaml_files.len(), 4);
@@ -361,4 +1109,34 @@ mod tests {
         // Check that non-yaml file is not included
         assert!(!yaml_files.contains(&dir.join("file3.txt")));
     }
+
+    #[test]
+    fn test_find_similar() {
+        let mut fs = MockFileSystem::default();
+        let package_dir = PathBuf::from("/test/packages");
+
+        fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join(COMBINED_PACKAGES_FILE), false);
+        fs.mock_list_directory(
+            package_dir.clone(),
+            &[
+                package_dir.join("ripgrep.yaml"),
+                package_dir.join("ripgrep-all.yaml"),
+                package_dir.join("fzf.yaml"),
+                package_dir.join("bat.yml"),
+                package_dir.join("README.md"),
+            ],
+        );
+
+        let progress_manager = ProgressManager::default();
+        let repo = YamlPackageRepository::new(&fs, package_dir, progress_manager);
+
+        assert_eq!(repo.find_similar("rigrep").unwrap(), vec!["ripgrep"]);
+        assert_eq!(
+            repo.find_similar("rip").unwrap(),
+            vec!["ripgrep", "ripgrep-all"]
+        );
+        assert_eq!(repo.find_similar("Bat").unwrap(), vec!["bat"]);
+        assert!(repo.find_similar("something-else").unwrap().is_empty());
+    }
 }
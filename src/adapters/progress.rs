@@ -1,11 +1,54 @@
 // src/adapters/progress.rs
-// Simplified progress reporting without indicatif dependency
+// Progress reporting, safe to share between concurrent installs
 
-use std::{fmt, time::Duration};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::IsTerminal,
+    path::Path,
+    sync::{LazyLock, Mutex, PoisonError},
+    time::Duration,
+};
 
-use console::{style, Emoji};
+use console::{measure_text_width, style, Emoji, Term};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::{Serialize, Serializer};
+use similar::TextDiff;
 
-use crate::domain::config::AppConfig;
+use crate::domain::{
+    config::{AppConfig, ProgressFormat},
+    errors::{ErrorObject, ErrorReport},
+    installation::InstallationStatus,
+};
+
+#[cfg(test)]
+thread_local! {
+    /// Events emitted on this thread, as JSON, for tests to read back
+    static EMITTED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+
+    /// Questions asked on this thread, which tests always answer no
+    static ASKED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Take the events emitted on this thread so far, in the order they were
+/// emitted
+#[cfg(test)]
+pub(crate) fn take_emitted() -> Vec<serde_json::Value> {
+    EMITTED.with(|emitted| {
+        emitted
+            .take()
+            .iter()
+            .filter_map(|json| serde_json::from_str(json).ok())
+            .collect()
+    })
+}
+
+/// Take the questions asked on this thread so far, in the order they were
+/// asked
+#[cfg(test)]
+pub(crate) fn take_asked() -> Vec<String> {
+    ASKED.with(|asked| asked.take())
+}
 
 // Define emojis with fallbacks
 static INFO_EMOJI: Emoji<'_, '_> = Emoji("ℹ️ ", "[i] ");
@@ -13,6 +56,28 @@ static SUCCESS_EMOJI: Emoji<'_, '_> = Emoji("✅ ", "[√] ");
 static ERROR_EMOJI: Emoji<'_, '_> = Emoji("❌ ", "[x] ");
 static WARNING_EMOJI: Emoji<'_, '_> = Emoji("⚠️ ", "[!] ");
 
+/// Output shared by every copy of a [`ProgressManager`], so packages
+/// reported on at the same time don't garble each other's lines
+static OUTPUT: LazyLock<SharedOutput> = LazyLock::new(|| SharedOutput {
+    bars: MultiProgress::with_draw_target(ProgressDrawTarget::stdout()),
+    package_lines: Mutex::default(),
+    overall: Mutex::default(),
+});
+
+struct SharedOutput {
+    /// Lines of packages still in progress, drawn beneath the other output.
+    /// They're only drawn on a terminal, where they can be replaced.
+    bars: MultiProgress,
+
+    /// Each in-progress package's line. The lock is held while writing, so
+    /// writes from different tasks don't interleave.
+    package_lines: Mutex<HashMap<String, ProgressBar>>,
+
+    /// How many of the packages in an install of many are done, kept beneath
+    /// the package lines. Only locked after `package_lines`.
+    overall: Mutex<Option<ProgressBar>>,
+}
+
 /// Types of status messages
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum MessageType {
@@ -22,11 +87,130 @@ pub(crate) enum MessageType {
     Warning,
 }
 
-/// Streamlined progress manager
+/// Structured progress events, rendered either as styled text or as JSON
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum ProgressEvent<'a> {
+    /// A package is planned to be installed and waits its turn
+    Pending { package: &'a str, dependency: bool },
+
+    /// A package (or one of its dependencies) is about to be installed
+    InstallStart {
+        package: &'a str,
+        version: &'a str,
+        path: &'a Path,
+        dependency: bool,
+    },
+
+    /// The package's check command finished
+    Check {
+        package: &'a str,
+        installed: bool,
+        #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+        duration: Duration,
+        dependency: bool,
+    },
+
+    /// The package's install command finished successfully
+    InstallComplete {
+        package: &'a str,
+        #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+        duration: Duration,
+        dependency: bool,
+    },
+
+    /// The package was left out of the install
+    Skip {
+        package: &'a str,
+        reason: &'a str,
+        dependency: bool,
+    },
+
+    /// Checking or installing the package failed
+    Error {
+        package: &'a str,
+        message: &'a str,
+        dependency: bool,
+    },
+
+    /// Final summary of an install run
+    Summary {
+        package: &'a str,
+        #[serde(serialize_with = "serialize_status")]
+        status: &'a InstallationStatus,
+        dependencies: usize,
+        /// Elapsed time for the whole install
+        #[serde(rename = "wall_clock_ms", serialize_with = "serialize_millis")]
+        wall_clock: Duration,
+        /// Sum of the time each step took, which can differ from the wall
+        /// clock time when steps overlap
+        #[serde(rename = "total_duration_ms", serialize_with = "serialize_millis")]
+        total_duration: Duration,
+        #[serde(rename = "dependency_duration_ms", serialize_with = "serialize_millis")]
+        dependency_duration: Duration,
+        #[serde(rename = "package_duration_ms", serialize_with = "serialize_millis")]
+        package_duration: Duration,
+        /// How many times installing the package was tried
+        attempts: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        last_error: Option<&'a str>,
+    },
+}
+
+impl ProgressEvent<'_> {
+    /// The package the event is about
+    fn package(&self) -> &str {
+        match *self {
+            ProgressEvent::Pending { package, .. }
+            | ProgressEvent::InstallStart { package, .. }
+            | ProgressEvent::Check { package, .. }
+            | ProgressEvent::InstallComplete { package, .. }
+            | ProgressEvent::Skip { package, .. }
+            | ProgressEvent::Error { package, .. }
+            | ProgressEvent::Summary { package, .. } => package,
+        }
+    }
+}
+
+/// "failed", or "failed after 3 attempts" when the install was retried
+fn failed_outcome(attempts: u32) -> String {
+    if attempts > 1 {
+        format!("failed after {} attempts", attempts)
+    } else {
+        "failed".to_string()
+    }
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+fn serialize_status<S: Serializer>(
+    status: &&InstallationStatus,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let status = match status {
+        InstallationStatus::Pending => "pending",
+        InstallationStatus::NotStarted => "not_started",
+        InstallationStatus::Checking => "checking",
+        InstallationStatus::NotInstalled => "not_installed",
+        InstallationStatus::AlreadyInstalled => "already_installed",
+        InstallationStatus::Installing => "installing",
+        InstallationStatus::Complete => "complete",
+        InstallationStatus::Failed(_) => "failed",
+        InstallationStatus::Skipped(_) => "skipped",
+        InstallationStatus::CheckTimedOut(_) => "check_timed_out",
+    };
+    serializer.serialize_str(status)
+}
+
+/// Streamlined progress manager. Copies share their output, so they can
+/// report from concurrent tasks.
 #[derive(Default, Clone, Copy)]
 pub struct ProgressManager {
     use_colors: bool,
     verbose: bool,
+    format: ProgressFormat,
 }
 
 impl ProgressManager {
@@ -35,9 +219,16 @@ impl ProgressManager {
         Self {
             use_colors,
             verbose,
+            format: ProgressFormat::Human,
         }
     }
 
+    /// Set the format used to report progress events
+    pub fn with_format(mut self, format: ProgressFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     /// Format a status message
     pub(crate) fn status_line(
         &self,
@@ -75,7 +266,7 @@ impl ProgressManager {
         self.status_line(MessageType::Success, message)
     }
 
-    /// Formeprintln!at a message with an info indicator
+    /// Format a message with an info indicator
     pub(crate) fn info(&self, message: impl fmt::Display) -> String {
         self.status_line(MessageType::Info, message)
     }
@@ -125,35 +316,606 @@ impl ProgressManager {
         self.verbose
     }
 
+    /// Whether each package is reported on a single line, leaving out the
+    /// step-by-step output
+    pub(crate) fn compact(&self) -> bool {
+        self.format == ProgressFormat::Compact
+    }
+
+    /// Report a progress event in the configured format
+    pub(crate) fn emit(&self, event: ProgressEvent<'_>) {
+        #[cfg(test)]
+        EMITTED.with(|emitted| emitted.borrow_mut().push(Self::render_json(&event)));
+
+        match self.format {
+            ProgressFormat::Human => self.render_human(&event),
+            ProgressFormat::Json => self.write(|| println!("{}", Self::render_json(&event))),
+            ProgressFormat::Compact => self.render_compact(&event),
+        }
+    }
+
+    /// Render an event as the package's single line. Each package still in
+    /// progress has a line of its own, so packages installing at the same
+    /// time each update theirs.
+    fn render_compact(&self, event: &ProgressEvent<'_>) {
+        let Some((line, done)) = self.compact_line(event) else {
+            return;
+        };
+
+        let mut package_lines = OUTPUT
+            .package_lines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if done {
+            if let Some(bar) = package_lines.remove(event.package()) {
+                bar.finish_and_clear();
+                OUTPUT.bars.remove(&bar);
+            }
+            OUTPUT.bars.suspend(|| println!("{}", line));
+        } else {
+            package_lines
+                .entry(event.package().to_string())
+                .or_insert_with(|| {
+                    let style = ProgressStyle::with_template("{msg}")
+                        .unwrap_or_else(|_| ProgressStyle::default_spinner());
+                    let bar = ProgressBar::new_spinner().with_style(style);
+                    match &*OUTPUT
+                        .overall
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                    {
+                        Some(overall) => OUTPUT.bars.insert_before(overall, bar),
+                        None => OUTPUT.bars.add(bar),
+                    }
+                })
+                .set_message(line);
+        }
+    }
+
+    /// Start a bar showing how many of `total` packages are done, beneath any
+    /// package lines. Like them, it's only drawn on a terminal, and never
+    /// with JSON output.
+    pub(crate) fn start_overall(&self, total: usize) {
+        if self.format == ProgressFormat::Json {
+            return;
+        }
+
+        let style = ProgressStyle::with_template("[{bar:30}] {pos}/{len} complete")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> ");
+        let bar = OUTPUT
+            .bars
+            .add(ProgressBar::new(total as u64).with_style(style));
+
+        let previous = OUTPUT
+            .overall
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(bar);
+        if let Some(previous) = previous {
+            previous.finish_and_clear();
+            OUTPUT.bars.remove(&previous);
+        }
+    }
+
+    /// Count one more package as done on the bar from
+    /// [`Self::start_overall`], whatever became of it
+    pub(crate) fn advance_overall(&self) {
+        if let Some(overall) = &*OUTPUT
+            .overall
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            overall.inc(1);
+        }
+    }
+
+    /// Clear the bar from [`Self::start_overall`], before the install's
+    /// summary is written
+    pub(crate) fn finish_overall(&self) {
+        let overall = OUTPUT
+            .overall
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(overall) = overall {
+            overall.finish_and_clear();
+            OUTPUT.bars.remove(&overall);
+        }
+    }
+
+    /// Write output with the shared output lock held, above any in-progress
+    /// package lines
+    fn write<R>(&self, write: impl FnOnce() -> R) -> R {
+        let _package_lines = OUTPUT
+            .package_lines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        OUTPUT.bars.suspend(write)
+    }
+
+    /// The line for a package after `event`, and whether it's the package's
+    /// final line
+    fn compact_line(&self, event: &ProgressEvent<'_>) -> Option<(String, bool)> {
+        let mark = |mark: &str, package: &str| {
+            if self.use_colors {
+                format!("{} {}", mark, style(package).bold())
+            } else {
+                format!("{} {}", mark, package)
+            }
+        };
+
+        let line = match *event {
+            ProgressEvent::Pending { package, .. } => {
+                (format!("{} queued", mark(&self.dim("·"), package)), false)
+            }
+            ProgressEvent::InstallStart { package, .. } => {
+                (format!("{}...", mark("⌛", package)), false)
+            }
+            ProgressEvent::Check {
+                package,
+                installed: false,
+                ..
+            } => (format!("{} installing...", mark("⌛", package)), false),
+            ProgressEvent::Check {
+                package,
+                installed: true,
+                ..
+            } => (
+                format!("{} already installed", mark(&self.dim("="), package)),
+                true,
+            ),
+            ProgressEvent::InstallComplete {
+                package, duration, ..
+            } => (
+                self.with_duration(mark(&self.green("✓"), package), Some(duration)),
+                true,
+            ),
+            ProgressEvent::Skip {
+                package, reason, ..
+            } => (
+                format!("{} skipped: {}", mark(&self.dim("-"), package), reason),
+                true,
+            ),
+            ProgressEvent::Error {
+                package, message, ..
+            } => (
+                format!("{}: {}", mark(&self.red("✗"), package), message),
+                true,
+            ),
+            ProgressEvent::Summary {
+                package,
+                status,
+                dependencies,
+                wall_clock,
+                attempts,
+                ..
+            } => {
+                let outcome = match status {
+                    InstallationStatus::AlreadyInstalled => "already installed".to_string(),
+                    InstallationStatus::Skipped(_) => "dependencies installed".to_string(),
+                    InstallationStatus::Failed(_) => failed_outcome(attempts),
+                    InstallationStatus::CheckTimedOut(_) => "check timed out".to_string(),
+                    _ => "installed".to_string(),
+                };
+                let dependencies = match dependencies {
+                    0 => String::new(),
+                    1 => ", with 1 dependency".to_string(),
+                    n => format!(", with {} dependencies", n),
+                };
+
+                (
+                    self.with_duration(
+                        format!("{}: {}{}", package, outcome, dependencies),
+                        Some(wall_clock),
+                    ),
+                    true,
+                )
+            }
+        };
+
+        Some(line)
+    }
+
+    fn green(&self, text: &str) -> String {
+        if self.use_colors {
+            style(text).green().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn red(&self, text: &str) -> String {
+        if self.use_colors {
+            style(text).red().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn dim(&self, text: &str) -> String {
+        if self.use_colors {
+            style(text).dim().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Render an event as a single line of JSON
+    fn render_json(event: &ProgressEvent<'_>) -> String {
+        serde_json::to_string(event).unwrap_or_else(|e| {
+            format!(
+                r#"{{"event":"error","message":"Failed to serialize event: {}"}}"#,
+                e
+            )
+        })
+    }
+
+    /// Render an event as styled text
+    fn render_human(&self, event: &ProgressEvent<'_>) {
+        let indent = |dependency: bool| if dependency { "      " } else { "  " };
+
+        match *event {
+            // The dependency list and each package's own output show the plan
+            ProgressEvent::Pending { .. } => {}
+            ProgressEvent::InstallStart {
+                package,
+                version,
+                path,
+                dependency,
+            } => {
+                let package = if self.use_colors && dependency {
+                    style(package).magenta().to_string()
+                } else if self.use_colors {
+                    style(package).magenta().bold().to_string()
+                } else {
+                    package.to_string()
+                };
+                let indent = if dependency { "    " } else { "" };
+
+                self.print_info(format!(
+                    "{}Installing {} (v{}) from {}",
+                    indent,
+                    package,
+                    version,
+                    path.display()
+                ));
+            }
+            ProgressEvent::Check {
+                installed: true,
+                duration,
+                dependency,
+                ..
+            } => {
+                self.print_success(format!(
+                    "{}✓ Checking installation status: Already installed ({:.1?})",
+                    indent(dependency),
+                    duration
+                ));
+            }
+            ProgressEvent::Check {
+                installed: false,
+                duration,
+                dependency,
+                ..
+            } => {
+                self.print_progress(self.with_duration(
+                    format!(
+                        "{}✓ Checking installation status: Not installed",
+                        indent(dependency)
+                    ),
+                    Some(duration),
+                ));
+            }
+            ProgressEvent::InstallComplete {
+                duration,
+                dependency,
+                ..
+            } => {
+                self.print_success(format!(
+                    "{}✓ Installation complete ({:.1?})",
+                    indent(dependency),
+                    duration
+                ));
+            }
+            ProgressEvent::Skip {
+                package,
+                reason,
+                dependency,
+            } => {
+                if self.verbose {
+                    self.print_info(format!(
+                        "{}Skipping {}: {}",
+                        indent(dependency),
+                        package,
+                        reason
+                    ));
+                }
+            }
+            ProgressEvent::Error {
+                message,
+                dependency,
+                ..
+            } => {
+                self.print_error(format!("{}✗ {}", indent(dependency), message));
+            }
+            ProgressEvent::Summary {
+                package,
+                dependencies,
+                wall_clock,
+                total_duration,
+                dependency_duration,
+                package_duration,
+                attempts,
+                last_error,
+                ..
+            } => {
+                self.print_progress("\n");
+                self.print_success(format!("\nPackage '{}' installation summary:", package));
+
+                if let Some(last_error) = last_error {
+                    self.print_error(format!(
+                        "Install {}: {}",
+                        failed_outcome(attempts),
+                        last_error
+                    ));
+                }
+
+                if dependencies > 0 {
+                    self.print_with_duration("Wall clock:", Some(wall_clock));
+                    self.print_with_duration("Sum of steps:", Some(total_duration));
+                    self.print_with_duration("  Dependencies:", Some(dependency_duration));
+                    self.print_with_duration("  Package:", Some(package_duration));
+                } else {
+                    self.print_with_duration("Total time:", Some(wall_clock));
+                }
+            }
+        }
+    }
+
+    /// Print a line of human-readable output. When progress is reported as
+    /// JSON, this goes to stderr so stdout only carries events.
+    fn print_line(&self, line: impl fmt::Display) {
+        self.write(|| match self.format {
+            ProgressFormat::Human | ProgressFormat::Compact => println!("{}", line),
+            ProgressFormat::Json => eprintln!("{}", line),
+        })
+    }
+
     /// Print a simple progress message (replacement for progress bars)
     pub(crate) fn print_progress(&self, message: impl fmt::Display) {
-        println!("{}", message);
+        self.print_line(message);
     }
 
     /// Print a success message
     pub(crate) fn print_success(&self, message: impl fmt::Display) {
-        println!("{}", self.success(message));
+        self.print_line(self.success(message));
     }
 
-    /// Print an error message
+    /// Print an error message. When progress is reported as JSON, it's
+    /// printed as an error object of no particular kind instead; see
+    /// [`Self::print_error_report`] to give it one.
     pub fn print_error(&self, message: impl fmt::Display) {
-        eprintln!("{}", self.error(message));
+        if self.format == ProgressFormat::Json {
+            self.print_error_json(&ErrorObject::unknown(message.to_string()));
+            return;
+        }
+
+        self.print_error_detail(message);
+    }
+
+    /// Print `message` about `error`, as an error object with the error's
+    /// kind and context when progress is reported as JSON
+    pub(crate) fn print_error_report(&self, error: &dyn ErrorReport, message: impl fmt::Display) {
+        if self.format == ProgressFormat::Json {
+            self.print_error_json(&ErrorObject::new(error, message.to_string()));
+            return;
+        }
+
+        self.print_error_detail(message);
+    }
+
+    /// Print an error that's only part of what went wrong, such as a failed
+    /// dependency ahead of the install's own error. It's always human-readable
+    /// on stderr, leaving JSON error objects to what ends the command.
+    pub(crate) fn print_error_detail(&self, message: impl fmt::Display) {
+        let error = self.error(message);
+        self.write(|| eprintln!("{}", error));
+    }
+
+    /// Print `error` as a JSON object on stdout, where progress events go as
+    /// JSON, so automation can parse why a command failed
+    pub(crate) fn print_error_json(&self, error: &ErrorObject) {
+        #[derive(Serialize)]
+        struct Report<'e> {
+            error: &'e ErrorObject,
+        }
+
+        let json = serde_json::to_string(&Report { error }).unwrap_or_else(|e| {
+            format!(
+                r#"{{"error":{{"kind":"error","message":"Failed to serialize error: {}","context":{{}}}}}}"#,
+                e
+            )
+        });
+        self.write(|| println!("{}", json));
     }
 
     /// Print an info message
     pub fn print_info(&self, message: impl fmt::Display) {
-        println!("{}", self.info(message));
+        self.print_line(self.info(message));
     }
 
     /// Print a warning message
-    pub(crate) fn print_warning(&self, message: impl fmt::Display) {
-        println!("{}", self.warning(message));
+    pub fn print_warning(&self, message: impl fmt::Display) {
+        self.print_line(self.warning(message));
+    }
+
+    /// Leave the terminal usable after an interrupt: clear the half-written
+    /// line (usually just the echoed `^C`) and make sure the cursor is shown
+    pub fn abandon_all(&self) {
+        let mut package_lines = OUTPUT
+            .package_lines
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for (_, bar) in package_lines.drain() {
+            bar.finish_and_clear();
+            OUTPUT.bars.remove(&bar);
+        }
+        drop(package_lines);
+        self.finish_overall();
+
+        for term in [Term::stdout(), Term::stderr()] {
+            if term.is_term() {
+                let _ = term.clear_line();
+                let _ = term.show_cursor();
+            }
+        }
+    }
+
+    /// Ask a yes/no question, defaulting to no. Without a terminal to ask on,
+    /// the answer is always no.
+    pub(crate) fn confirm(&self, question: impl fmt::Display) -> bool {
+        #[cfg(test)]
+        ASKED.with(|asked| asked.borrow_mut().push(question.to_string()));
+
+        // Tests never wait on whoever's at the terminal
+        if cfg!(test) || !std::io::stdin().is_terminal() {
+            return false;
+        }
+
+        // Prompt on stderr so it never ends up in captured output. Other
+        // output waits until it's answered.
+        let prompt = format!("{} [y/N] ", self.warning(question));
+        self.write(|| {
+            let term = Term::stderr();
+            if term.write_str(&prompt).is_err() {
+                return false;
+            }
+
+            term.read_line()
+                .map(|answer| matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Render rows as aligned columns beneath a header row. Widths are measured
+    /// on the visible text, so cells that are already styled still line up.
+    pub(crate) fn format_table(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let mut widths: Vec<usize> = headers.iter().map(|h| measure_text_width(h)).collect();
+        for row in rows {
+            for (i, cell) in row.iter().enumerate() {
+                let width = measure_text_width(cell);
+                match widths.get_mut(i) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        let render_row = |cells: &[String]| -> String {
+            let last = cells.len().saturating_sub(1);
+            let mut line = String::new();
+            for (i, cell) in cells.iter().enumerate() {
+                line.push_str(cell);
+                if i < last {
+                    let padding = widths[i] - measure_text_width(cell);
+                    line.push_str(&" ".repeat(padding + 2));
+                }
+            }
+            line
+        };
+
+        let header_cells: Vec<String> = headers
+            .iter()
+            .map(|h| {
+                if self.use_colors {
+                    style(h).bold().to_string()
+                } else {
+                    h.to_string()
+                }
+            })
+            .collect();
+
+        let mut lines = vec![render_row(&header_cells)];
+        lines.extend(rows.iter().map(|row| render_row(row)));
+        lines.join("\n")
+    }
+
+    /// Print rows as an aligned table
+    pub(crate) fn print_table(&self, headers: &[&str], rows: &[Vec<String>]) {
+        self.print_line(self.format_table(headers, rows));
+    }
+
+    /// Print the last lines of a failed command's output, so the cause shows
+    /// without `--verbose`
+    pub(crate) fn print_output_tail(&self, package: &str, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+
+        let tail = self.format_output_tail(package, lines);
+        self.write(|| eprintln!("{}", tail));
+    }
+
+    pub(crate) fn format_output_tail(&self, package: &str, lines: &[String]) -> String {
+        let count = match lines.len() {
+            1 => "line".to_string(),
+            n => format!("{} lines", n),
+        };
+
+        let mut output = self.warning(format!("Last {} of output from '{}':", count, package));
+        for line in lines {
+            output.push_str(&format!("\n  │ {}", line));
+        }
+        output.push_str("\n  └ (--verbose shows all of it)");
+
+        output
+    }
+
+    /// A unified diff of a file's contents changing from `old` to `new`,
+    /// colored when colors are on. `new_path` differs from `old_path` when
+    /// the file is also renamed.
+    pub(crate) fn format_diff(
+        &self,
+        old_path: &Path,
+        new_path: &Path,
+        old: &str,
+        new: &str,
+    ) -> String {
+        let diff = TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(
+                &old_path.display().to_string(),
+                &new_path.display().to_string(),
+            )
+            .to_string();
+
+        diff.lines()
+            .map(|line| {
+                if !self.use_colors {
+                    line.to_string()
+                } else if line.starts_with("---") || line.starts_with("+++") {
+                    style(line).bold().to_string()
+                } else if line.starts_with("@@") {
+                    style(line).cyan().to_string()
+                } else if line.starts_with('+') {
+                    self.green(line)
+                } else if line.starts_with('-') {
+                    self.red(line)
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
     /// Print verbose output if verbose mode is enabled
     pub(crate) fn print_verbose(&self, message: impl fmt::Display) {
         if self.verbose {
-            println!("  {}", message);
+            self.print_line(format!("  {}", message));
         }
     }
 
@@ -162,7 +924,7 @@ impl ProgressManager {
         message: impl fmt::Display,
         duration: Option<Duration>,
     ) {
-        println!("{}", self.with_duration(message, duration))
+        self.print_line(self.with_duration(message, duration))
     }
 }
 
@@ -171,6 +933,7 @@ impl<'a> From<&'a AppConfig> for ProgressManager {
         Self {
             use_colors: config.use_colors(),
             verbose: config.verbose(),
+            format: config.progress_format(),
         }
     }
 }
@@ -193,6 +956,287 @@ mod tests {
 
         assert!(manager.verbose());
         assert!(!manager.use_colors());
+        assert_eq!(manager.format, ProgressFormat::Human);
+
+        let config = AppConfigBuilder::default()
+            .progress_format(ProgressFormat::Json)
+            .build();
+
+        assert_eq!(ProgressManager::from(&config).format, ProgressFormat::Json);
+    }
+
+    #[test]
+    fn test_format_output_tail() {
+        let manager = ProgressManager::new(false, false);
+
+        assert_eq!(
+            manager.format_output_tail(
+                "ripgrep",
+                &[
+                    "error: linker `cc` not found".to_string(),
+                    "exit 1".to_string()
+                ]
+            ),
+            "[!] Last 2 lines of output from 'ripgrep':
+  │ error: linker `cc` not found
+  │ exit 1
+  └ (--verbose shows all of it)"
+        );
+    }
+
+    #[test]
+    fn test_render_json_events() {
+        let event = ProgressEvent::Pending {
+            package: "rg",
+            dependency: true,
+        };
+        assert_eq!(
+            ProgressManager::render_json(&event),
+            r#"{"event":"pending","package":"rg","dependency":true}"#
+        );
+
+        let event = ProgressEvent::InstallStart {
+            package: "rg",
+            version: "1.0.0",
+            path: Path::new("/packages/rg.yaml"),
+            dependency: false,
+        };
+        assert_eq!(
+            ProgressManager::render_json(&event),
+            r#"{"event":"install_start","package":"rg","version":"1.0.0","path":"/packages/rg.yaml","dependency":false}"#
+        );
+
+        let event = ProgressEvent::Check {
+            package: "rg",
+            installed: false,
+            duration: Duration::from_millis(1500),
+            dependency: true,
+        };
+        assert_eq!(
+            ProgressManager::render_json(&event),
+            r#"{"event":"check","package":"rg","installed":false,"duration_ms":1500,"dependency":true}"#
+        );
+
+        let event = ProgressEvent::InstallComplete {
+            package: "rg",
+            duration: Duration::from_millis(42),
+            dependency: false,
+        };
+        assert_eq!(
+            ProgressManager::render_json(&event),
+            r#"{"event":"install_complete","package":"rg","duration_ms":42,"dependency":false}"#
+        );
+
+        let event = ProgressEvent::Error {
+            package: "rg",
+            message: "Installation failed: boom",
+            dependency: false,
+        };
+        assert_eq!(
+            ProgressManager::render_json(&event),
+            r#"{"event":"error","package":"rg","message":"Installation failed: boom","dependency":false}"#
+        );
+    }
+
+    #[test]
+    fn test_render_json_summary() {
+        let status = InstallationStatus::Failed("boom".to_string());
+        let event = ProgressEvent::Summary {
+            package: "rg",
+            status: &status,
+            dependencies: 2,
+            wall_clock: Duration::from_millis(250),
+            total_duration: Duration::from_millis(300),
+            dependency_duration: Duration::from_millis(200),
+            package_duration: Duration::from_millis(100),
+            attempts: 3,
+            last_error: Some("boom"),
+        };
+        assert_eq!(
+            ProgressManager::render_json(&event),
+            r#"{"event":"summary","package":"rg","status":"failed","dependencies":2,"wall_clock_ms":250,"total_duration_ms":300,"dependency_duration_ms":200,"package_duration_ms":100,"attempts":3,"last_error":"boom"}"#
+        );
+    }
+
+    #[test]
+    fn test_compact_lines_from_concurrent_packages() {
+        let manager = ProgressManager::default().with_format(ProgressFormat::Compact);
+        let packages = ["concurrent-a", "concurrent-b", "concurrent-c"];
+        let in_progress =
+            |package: &str| OUTPUT.package_lines.lock().unwrap().contains_key(package);
+
+        std::thread::scope(|scope| {
+            for package in packages {
+                scope.spawn(move || {
+                    manager.emit(ProgressEvent::InstallStart {
+                        package,
+                        version: "1.0.0",
+                        path: Path::new("/packages"),
+                        dependency: true,
+                    })
+                });
+            }
+        });
+
+        // Each package has a line of its own while it's in progress...
+        assert!(packages.iter().all(|package| in_progress(package)));
+
+        std::thread::scope(|scope| {
+            for package in packages {
+                scope.spawn(move || {
+                    manager.emit(ProgressEvent::Skip {
+                        package,
+                        reason: "testing",
+                        dependency: true,
+                    })
+                });
+            }
+        });
+
+        // ...that's replaced by its final line once it's done
+        assert!(!packages.iter().any(|package| in_progress(package)));
+    }
+
+    #[test]
+    fn test_overall_progress() {
+        let manager = ProgressManager::default().with_format(ProgressFormat::Compact);
+        let overall = || {
+            OUTPUT
+                .overall
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|bar| (bar.position(), bar.length()))
+        };
+
+        manager.start_overall(3);
+        assert_eq!(overall(), Some((0, Some(3))));
+
+        // Package lines go above the bar, which keeps counting
+        manager.emit(ProgressEvent::Pending {
+            package: "overall-a",
+            dependency: false,
+        });
+        manager.advance_overall();
+        manager.advance_overall();
+        assert_eq!(overall(), Some((2, Some(3))));
+
+        manager.emit(ProgressEvent::Skip {
+            package: "overall-a",
+            reason: "testing",
+            dependency: false,
+        });
+        manager.finish_overall();
+        assert_eq!(overall(), None);
+
+        // JSON output is only events
+        ProgressManager::default()
+            .with_format(ProgressFormat::Json)
+            .start_overall(3);
+        assert_eq!(overall(), None);
+    }
+
+    #[test]
+    fn test_compact_lines() {
+        let manager = ProgressManager::default().with_format(ProgressFormat::Compact);
+        assert!(manager.compact());
+
+        let event = ProgressEvent::Pending {
+            package: "rg",
+            dependency: true,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("· rg queued".to_string(), false))
+        );
+
+        let event = ProgressEvent::InstallStart {
+            package: "rg",
+            version: "1.0.0",
+            path: Path::new("/packages/rg.yaml"),
+            dependency: true,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("⌛ rg...".to_string(), false))
+        );
+
+        let event = ProgressEvent::Check {
+            package: "rg",
+            installed: true,
+            duration: Duration::from_millis(5),
+            dependency: true,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("= rg already installed".to_string(), true))
+        );
+
+        let event = ProgressEvent::InstallComplete {
+            package: "rg",
+            duration: Duration::from_millis(42),
+            dependency: false,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("✓ rg (42.0ms)".to_string(), true))
+        );
+
+        let event = ProgressEvent::Skip {
+            package: "rust",
+            reason: "'rg' is installed",
+            dependency: true,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("- rust skipped: 'rg' is installed".to_string(), true))
+        );
+
+        let event = ProgressEvent::Error {
+            package: "rg",
+            message: "Installation failed: boom",
+            dependency: false,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("✗ rg: Installation failed: boom".to_string(), true))
+        );
+
+        let event = ProgressEvent::Summary {
+            package: "rg",
+            status: &InstallationStatus::Complete,
+            dependencies: 2,
+            wall_clock: Duration::from_millis(250),
+            total_duration: Duration::from_millis(300),
+            dependency_duration: Duration::from_millis(200),
+            package_duration: Duration::from_millis(100),
+            attempts: 1,
+            last_error: None,
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some((
+                "rg: installed, with 2 dependencies (250.0ms)".to_string(),
+                true
+            ))
+        );
+
+        let status = InstallationStatus::Failed("boom".to_string());
+        let event = ProgressEvent::Summary {
+            package: "rg",
+            status: &status,
+            dependencies: 0,
+            wall_clock: Duration::from_millis(250),
+            total_duration: Duration::from_millis(250),
+            dependency_duration: Duration::ZERO,
+            package_duration: Duration::from_millis(250),
+            attempts: 3,
+            last_error: Some("boom"),
+        };
+        assert_eq!(
+            manager.compact_line(&event),
+            Some(("rg: failed after 3 attempts (250.0ms)".to_string(), true))
+        );
     }
 
     #[test]
@@ -217,6 +1261,46 @@ mod tests {
         assert!(warning.contains("Warning message"));
     }
 
+    #[test]
+    fn test_format_table() {
+        let manager = ProgressManager::new(false, false);
+        let rows = vec![
+            vec![
+                "ripgrep".to_string(),
+                "v1.0.0".to_string(),
+                "ok".to_string(),
+            ],
+            vec![
+                "fzf".to_string(),
+                "v0.10.0".to_string(),
+                "missing".to_string(),
+            ],
+        ];
+
+        let table = manager.format_table(&["Name", "Version", "Status"], &rows);
+
+        assert_eq!(
+            table,
+            "Name     Version  Status\n\
+             ripgrep  v1.0.0   ok\n\
+             fzf      v0.10.0  missing"
+        );
+    }
+
+    #[test]
+    fn test_format_table_measures_visible_width() {
+        let manager = ProgressManager::new(false, false);
+        let rows = vec![
+            vec!["\x1b[32mripgrep\x1b[0m".to_string(), "a".to_string()],
+            vec!["fzf".to_string(), "b".to_string()],
+        ];
+
+        let table = manager.format_table(&["Name", "Col"], &rows);
+        let plain = console::strip_ansi_codes(&table);
+
+        assert_eq!(plain, "Name     Col\nripgrep  a\nfzf      b");
+    }
+
     #[test]
     fn test_format_duration() {
         let manager = ProgressManager::default();
@@ -1,7 +1,7 @@
 // src/adapters/cli/clap_adapter.rs
-use std::path::PathBuf;
+use std::{num::NonZeroU64, path::PathBuf};
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 use crate::{
     domain,
@@ -28,11 +28,87 @@ pub struct ClapCli {
     #[clap(long, global = true)]
     pub(crate) no_color: bool,
 
+    /// Format used to report progress
+    #[clap(long, alias = "output", global = true, value_enum)]
+    pub(crate) progress_format: Option<ProgressFormatArg>,
+
+    /// Answer yes to confirmation prompts, e.g. before running install
+    /// commands that may need sudo or download content
+    #[clap(long, short = 'y', global = true)]
+    pub(crate) yes: bool,
+
+    /// On Ctrl-C, kill the running command instead of waiting for it to
+    /// finish
+    #[clap(long, global = true)]
+    pub(crate) kill_on_interrupt: bool,
+
+    /// Fail instead of warning when a package doesn't have the current
+    /// environment, only has it under an alias, or depends on a package
+    /// that doesn't exist
+    #[clap(long, global = true)]
+    pub(crate) strict: bool,
+
+    /// Parse every package file afresh instead of reusing the packages
+    /// cached from earlier runs
+    #[clap(long, global = true)]
+    pub(crate) no_cache: bool,
+
+    /// Prefix every install command with this, e.g. "nice -n 19". It's
+    /// prepended verbatim, so it only applies to the first command of a
+    /// pipeline or list. Check commands aren't wrapped.
+    #[clap(long = "wrap", global = true, value_name = "COMMAND")]
+    pub(crate) command_wrapper: Option<String>,
+
+    /// Load `KEY=VALUE` lines from this dotenv-style file into the
+    /// environment of every command, e.g. for tokens an install needs
+    #[clap(long, global = true, value_name = "PATH")]
+    pub(crate) env_file: Option<PathBuf>,
+
+    /// Give up on a check command after this many seconds, reporting the
+    /// check as timed out rather than the package as not installed
+    #[clap(long, global = true, value_name = "SECONDS")]
+    pub(crate) check_timeout: Option<NonZeroU64>,
+
+    /// Read the config from this file instead of searching the standard
+    /// locations
+    #[clap(long = "config", global = true, value_name = "PATH")]
+    pub(crate) config_path: Option<PathBuf>,
+
     /// Subcommand to execute
     #[clap(subcommand)]
     pub(crate) command: ClapCommands,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum ProgressFormatArg {
+    /// Styled, human-readable text
+    Human,
+
+    /// Newline-delimited JSON events on stdout
+    Json,
+
+    /// One line per package, updated in place
+    Compact,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum PlanFormatArg {
+    Yaml,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub(crate) enum ValidateFormatArg {
+    /// Styled, human-readable text
+    Human,
+
+    /// A JSON object with each package's errors and warnings and the totals
+    Json,
+
+    /// A SARIF 2.1.0 log, for code scanning tools
+    Sarif,
+}
+
 // Clap-specific command structure definitions here...
 #[derive(Subcommand, Debug, Clone)]
 pub(crate) enum ClapCommands {
@@ -41,6 +117,40 @@ pub(crate) enum ClapCommands {
 
     /// Configuration management commands
     Config(ConfigCommands),
+
+    /// Commands for the cache of parsed package files
+    Cache(CacheCommands),
+
+    /// Install every package for the current environment that isn't
+    /// installed yet
+    Sync {
+        /// Show the combined install plan without installing anything
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Only sync packages with this tag; repeat to sync packages with any
+        /// of several tags
+        #[clap(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Leave out the named package; may be repeated
+        #[clap(long, value_name = "NAME")]
+        exclude: Vec<String>,
+
+        /// Leave out packages that don't support the current environment
+        /// without mentioning each one, only counting them at the end
+        #[clap(long)]
+        only_environment_matching: bool,
+
+        /// Write the closing summary as JSON
+        #[clap(long, conflicts_with = "dry_run")]
+        json: bool,
+
+        /// Only sync the packages named in this file, one per line; `#`
+        /// starts a comment
+        #[clap(long, value_name = "PATH")]
+        packages_from: Option<PathBuf>,
+    },
 }
 
 #[derive(Args, Debug, Clone)]
@@ -53,12 +163,147 @@ pub(crate) struct PackageCommands {
 pub(crate) enum PackageSubcommands {
     /// Install a package
     Install {
-        /// Name of the package to install
-        package_name: String,
+        /// Name of the package to install, or a glob pattern (e.g. 'rust-*')
+        /// matching the packages to install
+        #[clap(required_unless_present = "packages_from")]
+        package_name: Option<String>,
+
+        /// Install the packages named in this file, one per line; `#` starts
+        /// a comment
+        #[clap(long, value_name = "PATH", conflicts_with_all = ["package_name", "package_file"])]
+        packages_from: Option<PathBuf>,
+
+        /// Install the package's dependencies, but not the package itself
+        #[clap(long)]
+        only_deps: bool,
+
+        /// Install only the package itself, without resolving or installing
+        /// its dependencies
+        #[clap(long, conflicts_with = "only_deps")]
+        no_deps: bool,
+
+        /// Skip check commands and always run install, assuming nothing is
+        /// installed yet
+        #[clap(long)]
+        no_check: bool,
+
+        /// Break down the installation summary by dependency, slowest first
+        #[clap(long)]
+        timings: bool,
+
+        /// Install the package defined in this file rather than the one in the
+        /// package directory; dependencies still come from the directory
+        #[clap(long)]
+        package_file: Option<PathBuf>,
+
+        /// Fail when dependencies go more than N levels deep, showing the
+        /// chain that does; unlimited by default
+        #[clap(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Finish with a summary of every package handled, written as JSON;
+        /// installing a pattern always ends with the summary
+        #[clap(long)]
+        json: bool,
+
+        /// Run each package's check again after installing it, and fail with
+        /// exit status 3 if it still doesn't find the package
+        #[clap(long)]
+        verify: bool,
+
+        /// When installing several packages, carry on with the rest after one
+        /// fails
+        #[clap(long)]
+        continue_on_error: bool,
+
+        /// Install the package with this command instead of its own, for this
+        /// run only; the package file isn't changed. Its dependencies are
+        /// installed as usual.
+        #[clap(long, value_name = "COMMAND", conflicts_with_all = ["packages_from", "only_deps"])]
+        install_command: Option<String>,
     },
 
     /// List available packages
-    List,
+    List {
+        /// Only list packages whose file changed after this point: a
+        /// duration back from now (e.g. `7d`) or a date (e.g. `2024-06-01`)
+        #[clap(long, value_parser = parse_since_arg)]
+        since: Option<jiff::Timestamp>,
+
+        /// Only list packages that support the current environment, counting
+        /// the rest at the end
+        #[clap(long)]
+        only_environment_matching: bool,
+    },
+
+    /// Compare installed packages against what selfie recorded when
+    /// installing them
+    Status,
+
+    /// Compare each package's version with the version its
+    /// installed_version_command reports, for packages on the current
+    /// environment
+    CheckUpdates {
+        /// Write the report as JSON instead of a table
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Show the ordered list of packages an install would run, without
+    /// installing anything
+    Plan {
+        /// Name of the package to plan the installation of
+        package_name: String,
+
+        /// Format to write the plan in
+        #[clap(long, value_enum, default_value_t = PlanFormatArg::Yaml)]
+        format: PlanFormatArg,
+    },
+
+    /// Show a package's dependency graph
+    Deps {
+        /// Name of the package whose dependencies to show
+        #[clap(required_unless_present = "all")]
+        package_name: Option<String>,
+
+        /// Show the dependencies of every package in the package directory,
+        /// including any cycles between them
+        #[clap(long, conflicts_with = "package_name")]
+        all: bool,
+
+        /// Write the graph in Graphviz DOT format, e.g. to pipe into `dot -Tsvg`
+        #[clap(long)]
+        dot: bool,
+
+        /// Fail when dependencies go more than N levels deep, showing the
+        /// chain that does; unlimited by default
+        #[clap(long, value_name = "N", conflicts_with = "all")]
+        max_depth: Option<usize>,
+
+        /// Show every package that depends on this one, directly or through
+        /// other packages, as a tree
+        #[clap(long, conflicts_with_all = ["all", "dot", "max_depth"])]
+        reverse: bool,
+    },
+
+    /// Check every package in the package directory for dependency cycles,
+    /// reporting each one found
+    GraphCheck,
+
+    /// Rename a package, updating the dependency lists of the packages
+    /// that depend on it; shows the changes, and applies them with --yes
+    Rename {
+        /// Current name of the package
+        old_name: String,
+
+        /// Name to rename the package to
+        new_name: String,
+
+        /// Show a diff of each file the rename changes. Nothing is written
+        /// unless --yes is also given.
+        #[clap(long)]
+        diff: bool,
+    },
 
     /// Show information about a package
     Info {
@@ -66,20 +311,114 @@ pub(crate) enum PackageSubcommands {
         package_name: String,
     },
 
+    /// Print the path of a package's file, and nothing else
+    Path {
+        /// Name or alias of the package
+        package_name: String,
+    },
+
+    /// Remove the record that a package is installed, e.g. its install
+    /// marker, so the next install runs it again
+    Forget {
+        /// Name or alias of the package
+        package_name: String,
+    },
+
+    /// Run a package's check to confirm it's installed, exiting with status 3
+    /// if it isn't found
+    Verify {
+        /// Name or alias of the package
+        package_name: String,
+    },
+
     /// Create a new package
     Create {
         /// Name of the package to create
         package_name: String,
     },
 
+    /// Write a commented skeleton package file, for the current
+    /// environment, to the package directory
+    Template {
+        /// Name of the package to write a template for
+        package_name: String,
+
+        /// Overwrite the package's file if it already exists
+        #[clap(long)]
+        force: bool,
+
+        /// Show a diff of the template against the existing file instead of
+        /// writing it; with --yes, write it after showing the diff
+        #[clap(long, requires = "force")]
+        diff: bool,
+    },
+
+    /// Open a package's file in $EDITOR
+    Edit {
+        /// Name of the package to edit
+        package_name: String,
+
+        /// If the package doesn't exist, create a skeleton file for the
+        /// current environment and edit that
+        #[clap(long)]
+        create: bool,
+    },
+
     /// Validate a package
     Validate {
         /// Name of the package to validate
-        package_name: String,
+        #[clap(required_unless_present = "all")]
+        package_name: Option<String>,
+
+        /// Validate every package in the package directory
+        #[clap(long, conflicts_with_all = ["package_name", "package_path", "fix"])]
+        all: bool,
+
+        /// Format to write the results of --all in
+        #[clap(long, value_enum, default_value_t = ValidateFormatArg::Human, requires = "all")]
+        format: ValidateFormatArg,
 
         /// Package file path (optional)
-        #[clap(long)]
+        #[clap(long, alias = "package-file")]
         package_path: Option<PathBuf>,
+
+        /// Skip checks that guess at likely mistakes, such as a check command
+        /// that doesn't match the install command; these can have false
+        /// positives
+        #[clap(long)]
+        no_heuristics: bool,
+
+        /// Apply the safe fixes for issues that have one (e.g. a missing
+        /// version) to the package file
+        #[clap(long)]
+        fix: bool,
+
+        /// Show a diff of the fixes instead of writing them; with --yes,
+        /// write them after showing the diff
+        #[clap(long, requires = "fix")]
+        diff: bool,
+
+        /// Run each package's check command for the current environment to
+        /// make sure the shell can start it; install commands are never run
+        #[clap(long)]
+        run_checks: bool,
+
+        /// Check command availability for every environment a package
+        /// declares, not only the current one
+        #[clap(long)]
+        all_environments: bool,
+    },
+
+    /// Validate packages with every heuristic and stricter rules on, failing
+    /// on warnings too; prints one PASS/FAIL line per package, for CI
+    Lint {
+        /// Names of the packages to lint; every package when none are given
+        package_names: Vec<String>,
+
+        /// Run each package's check command for the current environment to
+        /// make sure the shell can start it; install commands are never run
+        #[clap(long)]
+        run_checks: bool,
     },
 }
 
@@ -95,6 +434,23 @@ pub(crate) enum ConfigSubcommands {
     Validate,
 }
 
+#[derive(Args, Debug, Clone)]
+pub(crate) struct CacheCommands {
+    #[clap(subcommand)]
+    pub(crate) command: CacheSubcommands,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum CacheSubcommands {
+    /// Delete the cached package files, so every package file is parsed
+    /// again on the next run
+    Clear,
+}
+
+fn parse_since_arg(value: &str) -> Result<jiff::Timestamp, String> {
+    domain::application::commands::parse_since(value, &jiff::Zoned::now())
+}
+
 impl ArgumentParser for ClapCli {
     fn parse_arguments() -> Result<ApplicationArguments, anyhow::Error> {
         let clap_args = Self::parse();
@@ -111,11 +467,51 @@ impl From<ClapCli> for ApplicationArguments {
             package_directory: value.package_directory,
             verbose: value.verbose,
             no_color: value.no_color,
+            progress_format: value
+                .progress_format
+                .map(domain::config::ProgressFormat::from),
+            assume_yes: value.yes,
+            kill_on_interrupt: value.kill_on_interrupt,
+            strict: value.strict,
+            no_cache: value.no_cache,
+            command_wrapper: value.command_wrapper,
+            env_file: value.env_file,
+            check_timeout: value.check_timeout,
+            config_path: value.config_path,
             command: domain::application::commands::ApplicationCommand::from(value.command),
         }
     }
 }
 
+impl From<ProgressFormatArg> for domain::config::ProgressFormat {
+    fn from(value: ProgressFormatArg) -> Self {
+        match value {
+            ProgressFormatArg::Human => Self::Human,
+            ProgressFormatArg::Json => Self::Json,
+            ProgressFormatArg::Compact => Self::Compact,
+        }
+    }
+}
+
+impl From<ValidateFormatArg> for domain::application::commands::ValidateFormat {
+    fn from(value: ValidateFormatArg) -> Self {
+        match value {
+            ValidateFormatArg::Human => Self::Human,
+            ValidateFormatArg::Json => Self::Json,
+            ValidateFormatArg::Sarif => Self::Sarif,
+        }
+    }
+}
+
+impl From<PlanFormatArg> for domain::application::commands::PlanFormat {
+    fn from(value: PlanFormatArg) -> Self {
+        match value {
+            PlanFormatArg::Yaml => Self::Yaml,
+            PlanFormatArg::Json => Self::Json,
+        }
+    }
+}
+
 impl From<ClapCommands> for domain::application::commands::ApplicationCommand {
     fn from(value: ClapCommands) -> Self {
         match value {
@@ -125,6 +521,24 @@ impl From<ClapCommands> for domain::application::commands::ApplicationCommand {
             ClapCommands::Config(config_commands) => Self::Config(
                 domain::application::commands::ConfigCommand::from(config_commands.command),
             ),
+            ClapCommands::Cache(cache_commands) => Self::Cache(
+                domain::application::commands::CacheCommand::from(cache_commands.command),
+            ),
+            ClapCommands::Sync {
+                dry_run,
+                tags,
+                exclude,
+                only_environment_matching,
+                json,
+                packages_from,
+            } => Self::Package(domain::application::commands::PackageCommand::Sync {
+                dry_run,
+                tags,
+                exclude,
+                only_environment_matching,
+                json,
+                packages_from,
+            }),
         }
     }
 }
@@ -132,22 +546,132 @@ impl From<ClapCommands> for domain::application::commands::ApplicationCommand {
 impl From<PackageSubcommands> for domain::application::commands::PackageCommand {
     fn from(value: PackageSubcommands) -> Self {
         match value {
-            PackageSubcommands::Install { package_name } => {
-                domain::application::commands::PackageCommand::Install { package_name }
+            PackageSubcommands::Install {
+                package_name,
+                packages_from,
+                only_deps,
+                no_deps,
+                no_check,
+                timings,
+                package_file,
+                max_depth,
+                json,
+                verify,
+                continue_on_error,
+                install_command,
+            } => domain::application::commands::PackageCommand::Install {
+                package_name,
+                packages_from,
+                only_deps,
+                no_deps,
+                no_check,
+                timings,
+                package_file,
+                max_depth,
+                json,
+                verify,
+                continue_on_error,
+                install_command,
+            },
+            PackageSubcommands::List {
+                since,
+                only_environment_matching,
+            } => domain::application::commands::PackageCommand::List {
+                since,
+                only_environment_matching,
+            },
+            PackageSubcommands::Status => domain::application::commands::PackageCommand::Status,
+            PackageSubcommands::CheckUpdates { json } => {
+                domain::application::commands::PackageCommand::CheckUpdates { json }
             }
-            PackageSubcommands::List => domain::application::commands::PackageCommand::List,
+            PackageSubcommands::Plan {
+                package_name,
+                format,
+            } => domain::application::commands::PackageCommand::Plan {
+                package_name,
+                format: domain::application::commands::PlanFormat::from(format),
+            },
+            PackageSubcommands::Deps {
+                package_name,
+                all: _,
+                dot,
+                max_depth,
+                reverse,
+            } => domain::application::commands::PackageCommand::Deps {
+                package_name,
+                dot,
+                max_depth,
+                reverse,
+            },
+            PackageSubcommands::GraphCheck => {
+                domain::application::commands::PackageCommand::GraphCheck
+            }
+            PackageSubcommands::Rename {
+                old_name,
+                new_name,
+                diff,
+            } => domain::application::commands::PackageCommand::Rename {
+                old_name,
+                new_name,
+                diff,
+            },
             PackageSubcommands::Info { package_name } => {
                 domain::application::commands::PackageCommand::Info { package_name }
             }
+            PackageSubcommands::Path { package_name } => {
+                domain::application::commands::PackageCommand::Path { package_name }
+            }
+            PackageSubcommands::Forget { package_name } => {
+                domain::application::commands::PackageCommand::Forget { package_name }
+            }
+            PackageSubcommands::Verify { package_name } => {
+                domain::application::commands::PackageCommand::Verify { package_name }
+            }
             PackageSubcommands::Create { package_name } => {
                 domain::application::commands::PackageCommand::Create { package_name }
             }
+            PackageSubcommands::Template {
+                package_name,
+                force,
+                diff,
+            } => domain::application::commands::PackageCommand::Template {
+                package_name,
+                force,
+                diff,
+            },
+            PackageSubcommands::Edit {
+                package_name,
+                create,
+            } => domain::application::commands::PackageCommand::Edit {
+                package_name,
+                create,
+            },
             PackageSubcommands::Validate {
                 package_name,
+                format,
                 package_path,
+                no_heuristics,
+                fix,
+                diff,
+                run_checks,
+                all_environments,
+                ..
             } => domain::application::commands::PackageCommand::Validate {
                 package_name,
+                format: domain::application::commands::ValidateFormat::from(format),
                 package_path,
+                no_heuristics,
+                fix,
+                diff,
+                run_checks,
+                all_environments,
+            },
+            PackageSubcommands::Lint {
+                package_names,
+                run_checks,
+            } => domain::application::commands::PackageCommand::Lint {
+                package_names,
+                run_checks,
             },
         }
     }
@@ -160,3 +684,11 @@ impl From<ConfigSubcommands> for domain::application::commands::ConfigCommand {
         }
     }
 }
+
+impl From<CacheSubcommands> for domain::application::commands::CacheCommand {
+    fn from(value: CacheSubcommands) -> Self {
+        match value {
+            CacheSubcommands::Clear => domain::application::commands::CacheCommand::Clear,
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// src/api.rs
+// Entry points and types for using selfie as a library
+
+use thiserror::Error;
+
+use crate::{
+    adapters::{
+        command::shell::ShellCommandRunner,
+        filesystem::RealFileSystem,
+        package_repo::{cache::PackageCache, yaml::YamlPackageRepository},
+        progress::ProgressManager,
+    },
+    domain::env_file::load_env_file,
+    services::{
+        command_validator::CommandValidator,
+        enhanced_error_handler::EnhancedErrorHandler,
+        package::{install::PackageInstaller, validate::PackageValidator},
+    },
+};
+
+pub use crate::{
+    domain::{
+        config::{
+            AppConfig, ConfigOverlay, ConfigValidationError, NameSource, NoCheckPolicy,
+            ProgressFormat,
+        },
+        dependency::DependencyGraphError,
+        env_file::EnvFileError,
+        installation::{InstallationError, InstallationReport, InstallationStatus},
+        package::{EnvironmentConfig, InstallCommand, Package, PackageBuilder, PackageParseError},
+        validation::{ValidationErrorCategory, ValidationIssue, ValidationResult},
+    },
+    ports::{
+        command::{CommandError, CommandRunner},
+        filesystem::{FileSystem, FileSystemError},
+        package_repo::PackageRepoError,
+    },
+    services::package::{
+        install::{dependency::DependencyResolverError, PackageInstallerError},
+        validate::PackageValidatorError,
+    },
+};
+pub use tokio_util::sync::CancellationToken;
+
+/// Shell install and check commands are run with, as by the selfie binary
+const SHELL: &str = "/bin/sh";
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error(transparent)]
+    InvalidConfig(#[from] ConfigValidationError),
+
+    #[error(transparent)]
+    EnvFile(#[from] EnvFileError),
+
+    #[error(transparent)]
+    Install(#[from] PackageInstallerError),
+
+    #[error("Installation canceled")]
+    Canceled {
+        /// Installs that finished before the installation was canceled
+        completed: Vec<InstallationReport>,
+    },
+
+    #[error(transparent)]
+    Validate(#[from] PackageValidatorError),
+}
+
+/// Installs and validates packages the way the selfie binary does, with the
+/// file system and command runner swappable for custom ones.
+///
+/// Progress is reported to the terminal as configured by the [`AppConfig`].
+/// The install state isn't read or recorded, so with `no_check_policy:
+/// use_state` a package without a check is installed every time.
+pub struct Selfie<'a, F: FileSystem = RealFileSystem, CR: CommandRunner = ShellCommandRunner> {
+    config: &'a AppConfig,
+    fs: F,
+    runner: CR,
+    cancellation: CancellationToken,
+}
+
+impl<'a> Selfie<'a> {
+    /// Use the real file system, and run commands with `/bin/sh` with the
+    /// variables from the config's `env_file`, if it has one
+    pub fn new(config: &'a AppConfig) -> Result<Self, ApiError> {
+        let fs = RealFileSystem;
+
+        let mut runner = ShellCommandRunner::new(SHELL, config.command_timeout())
+            .with_kill_on_interrupt(config.kill_on_interrupt());
+        if let Some(env_file) = config.env_file() {
+            runner = runner.with_environment(load_env_file(&fs, env_file)?);
+        }
+
+        Ok(Self {
+            config,
+            fs,
+            runner,
+            cancellation: CancellationToken::new(),
+        })
+    }
+}
+
+impl<'a, F: FileSystem, CR: CommandRunner> Selfie<'a, F, CR> {
+    /// Read package files and the rest through `fs` instead
+    pub fn with_file_system<G: FileSystem>(self, fs: G) -> Selfie<'a, G, CR> {
+        Selfie {
+            config: self.config,
+            fs,
+            runner: self.runner,
+            cancellation: self.cancellation,
+        }
+    }
+
+    /// Run install and check commands with `runner` instead
+    pub fn with_command_runner<R: CommandRunner>(self, runner: R) -> Selfie<'a, F, R> {
+        Selfie {
+            config: self.config,
+            fs: self.fs,
+            runner,
+            cancellation: self.cancellation,
+        }
+    }
+
+    /// Stop installing once `cancellation` is triggered. The package or
+    /// dependency being installed is finished first, then
+    /// [`ApiError::Canceled`] is returned with the installs that completed.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Install a package and its dependencies, found by name or alias
+    pub async fn install_package(&self, name: &str) -> Result<InstallationReport, ApiError> {
+        self.config.validate()?;
+
+        let progress_manager = ProgressManager::from(self.config);
+        let package_repo = self.package_repo(progress_manager);
+        let error_handler = EnhancedErrorHandler::new(&self.fs, &package_repo, progress_manager);
+
+        PackageInstaller::new(
+            &self.fs,
+            &package_repo,
+            &error_handler,
+            &self.runner,
+            self.config,
+            progress_manager,
+            true,
+        )
+        .with_cancellation(self.cancellation.clone())
+        .install_package(name)
+        .await
+        .map_err(|err| match err {
+            PackageInstallerError::InstallationCanceled { completed } => {
+                ApiError::Canceled { completed }
+            }
+            err => ApiError::Install(err),
+        })
+    }
+
+    /// Validate a package, found by name, as `selfie package validate` does
+    pub async fn validate_package(&self, name: &str) -> Result<ValidationResult, ApiError> {
+        self.config.validate_minimal()?;
+
+        let package_repo = self.package_repo(ProgressManager::from(self.config));
+        let command_validator = CommandValidator::new(&self.runner);
+
+        PackageValidator::new(&self.fs, self.config, &package_repo, &command_validator)
+            .validate_package_by_name(name)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    fn package_repo(&self, progress_manager: ProgressManager) -> YamlPackageRepository<'_, F> {
+        let package_repo = YamlPackageRepository::new(
+            &self.fs,
+            self.config.expanded_package_directory(),
+            progress_manager,
+        )
+        .with_extensions(self.config.package_file_extensions())
+        .with_name_source(self.config.name_source());
+
+        if !self.config.use_cache() {
+            return package_repo;
+        }
+
+        match PackageCache::in_cache_dir(&self.fs) {
+            Ok(cache) => package_repo.with_cache(cache),
+            Err(_) => package_repo,
+        }
+    }
+}
+
+/// Install a package and its dependencies with the default file system and
+/// command runner; see [`Selfie`]
+pub async fn install_package(
+    config: &AppConfig,
+    name: &str,
+) -> Result<InstallationReport, ApiError> {
+    Selfie::new(config)?.install_package(name).await
+}
+
+/// Validate a package with the default file system and command runner; see
+/// [`Selfie`]
+pub async fn validate_package(
+    config: &AppConfig,
+    name: &str,
+) -> Result<ValidationResult, ApiError> {
+    Selfie::new(config)?.validate_package(name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, num::NonZeroU64};
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{domain::config::AppConfigBuilder, ports::command::MockCommandRunner};
+
+    const YAML: &str = r#"
+name: ripgrep
+version: 1.0.0
+environments:
+  test-env:
+    install: test install
+    check: test check
+"#;
+
+    #[tokio::test]
+    async fn test_install_package() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("ripgrep.yaml"), YAML).unwrap();
+
+        // Configured the way a library user can
+        let config = AppConfig::merge(
+            AppConfig::new("test-env".to_string(), dir.path().to_path_buf()),
+            &ConfigOverlay::default()
+                .use_cache(false)
+                .assume_yes(true)
+                .check_timeout(NonZeroU64::new(15).unwrap()),
+        );
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_streaming_success_1("test check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("test install", 600, "Installed");
+        runner.mock_is_command_available("test", true);
+
+        let report = Selfie::new(&config)
+            .unwrap()
+            .with_command_runner(runner)
+            .install_package("ripgrep")
+            .await
+            .unwrap();
+
+        assert_eq!(report.package_name(), "ripgrep");
+        assert_eq!(*report.status(), InstallationStatus::Complete);
+    }
+
+    #[tokio::test]
+    async fn test_install_package_not_found() {
+        let dir = tempdir().unwrap();
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory(dir.path())
+            .use_cache(false)
+            .build();
+
+        let err = Selfie::new(&config)
+            .unwrap()
+            .with_command_runner(MockCommandRunner::new())
+            .install_package("missing")
+            .await
+            .unwrap_err();
+
+        // The installer's own error comes through, not just its message
+        assert!(matches!(
+            err,
+            ApiError::Install(PackageInstallerError::EnhancedError {
+                kind: "package_not_found",
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_install_package_canceled() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("ripgrep.yaml"), YAML).unwrap();
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory(dir.path())
+            .use_cache(false)
+            .build();
+
+        // Nothing runs once the token is canceled
+        let runner = MockCommandRunner::new();
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let err = Selfie::new(&config)
+            .unwrap()
+            .with_command_runner(runner)
+            .with_cancellation(cancellation)
+            .install_package("ripgrep")
+            .await
+            .unwrap_err();
+
+        match err {
+            ApiError::Canceled { completed } => assert!(completed.is_empty()),
+            other => panic!("Expected the install to be canceled; got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_package() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("ripgrep.yaml"), YAML).unwrap();
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory(dir.path())
+            .use_cache(false)
+            .build();
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_is_command_available("test", true);
+
+        let selfie = Selfie::new(&config).unwrap().with_command_runner(runner);
+
+        let result = selfie.validate_package("ripgrep").await.unwrap();
+        assert!(result.is_valid());
+        assert_eq!(result.package_name(), "ripgrep");
+
+        let err = selfie.validate_package("missing").await.unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::Validate(PackageValidatorError::PackageNotFound(name)) if name == "missing"
+        ));
+    }
+}
@@ -1,7 +1,11 @@
 pub mod application;
 pub mod config;
 pub mod dependency;
+pub mod env_file;
 pub mod errors;
+pub mod install_state;
 pub mod installation;
 pub mod package;
+pub mod package_list;
+pub mod shell;
 pub mod validation;
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use jiff::{civil::Date, tz::TimeZone, Span, Timestamp, Zoned};
+
 #[derive(Debug, Clone, PartialEq)]
 // pub enum Commands {
 pub enum ApplicationCommand {
@@ -8,11 +10,17 @@ pub enum ApplicationCommand {
 
     /// Configuration management commands
     Config(ConfigCommand),
+
+    /// Package parse cache commands
+    Cache(CacheCommand),
 }
 
 impl Default for ApplicationCommand {
     fn default() -> Self {
-        Self::Package(PackageCommand::List)
+        Self::Package(PackageCommand::List {
+            since: None,
+            only_environment_matching: false,
+        })
     }
 }
 
@@ -20,12 +28,101 @@ impl Default for ApplicationCommand {
 pub enum PackageCommand {
     /// Install a package
     Install {
-        /// Name of the package to install
-        package_name: String,
+        /// Name of the package to install, or a glob pattern matching the
+        /// packages to install; absent when they're listed in a file
+        package_name: Option<String>,
+
+        /// Install the packages listed in this file
+        packages_from: Option<PathBuf>,
+
+        /// Install only the package's dependencies
+        only_deps: bool,
+
+        /// Install only the package, leaving its dependencies alone
+        no_deps: bool,
+
+        /// Skip check commands and always run install
+        no_check: bool,
+
+        /// Show how long each dependency took to install
+        timings: bool,
+
+        /// Load the package from this file instead of the package directory
+        package_file: Option<PathBuf>,
+
+        /// Fail when dependencies go more than this many levels deep
+        max_depth: Option<usize>,
+
+        /// Finish with a summary of every package handled, as JSON
+        json: bool,
+
+        /// Run each package's check again after installing it
+        verify: bool,
+
+        /// Carry on with the other packages after one fails to install
+        continue_on_error: bool,
+
+        /// Install the package with this command instead of its own
+        install_command: Option<String>,
     },
 
     /// List available packages
-    List,
+    List {
+        /// Only list packages whose file changed after this point
+        since: Option<Timestamp>,
+
+        /// Only list packages that support the current environment
+        only_environment_matching: bool,
+    },
+
+    /// Report drift between installed packages and the recorded install state
+    Status,
+
+    /// Compare declared package versions with the installed versions
+    CheckUpdates {
+        /// Write the report as JSON
+        json: bool,
+    },
+
+    /// Show the ordered install plan for a package, without installing anything
+    Plan {
+        /// Name of the package to plan the installation of
+        package_name: String,
+
+        /// Format the plan is written in
+        format: PlanFormat,
+    },
+
+    /// Show the dependency graph of a package, or of every package
+    Deps {
+        /// Name of the package whose dependencies to show; every package when
+        /// `None`
+        package_name: Option<String>,
+
+        /// Write the graph in Graphviz DOT format
+        dot: bool,
+
+        /// Fail when dependencies go more than this many levels deep
+        max_depth: Option<usize>,
+
+        /// Show what depends on the package instead of what it depends on
+        reverse: bool,
+    },
+
+    /// Check every package for dependency cycles
+    GraphCheck,
+
+    /// Rename a package, updating the packages that depend on it
+    Rename {
+        /// Current name of the package
+        old_name: String,
+
+        /// Name to rename the package to
+        new_name: String,
+
+        /// Show a diff of each file the rename changes
+        diff: bool,
+    },
 
     /// Show information about a package
     Info {
@@ -33,24 +130,186 @@ pub enum PackageCommand {
         package_name: String,
     },
 
+    /// Print the path of a package's file
+    Path {
+        /// Name or alias of the package
+        package_name: String,
+    },
+
+    /// Remove a package's install marker and install state record
+    Forget {
+        /// Name or alias of the package
+        package_name: String,
+    },
+
+    /// Run a package's check to confirm it's installed
+    Verify {
+        /// Name or alias of the package
+        package_name: String,
+    },
+
     /// Create a new package
     Create {
         /// Name of the package to create
         package_name: String,
     },
 
-    /// Validate a package
-    Validate {
-        /// Name of the package to validate
+    /// Write a skeleton package file to the package directory
+    Template {
+        /// Name of the package to write a template for
         package_name: String,
 
+        /// Overwrite an existing package file
+        force: bool,
+
+        /// Show a diff of the template against the existing file, only
+        /// writing it when confirmed
+        diff: bool,
+    },
+
+    /// Open a package's file in an editor
+    Edit {
+        /// Name of the package to edit
+        package_name: String,
+
+        /// Create a skeleton package file if the package doesn't exist
+        create: bool,
+    },
+
+    /// Validate a package, or every package
+    Validate {
+        /// Name of the package to validate; every package when `None`
+        package_name: Option<String>,
+
+        /// Format the results of validating every package are written in
+        format: ValidateFormat,
+
         /// Package file path (optional)
         package_path: Option<PathBuf>,
+
+        /// Skip checks that guess at mistakes and may have false positives
+        no_heuristics: bool,
+
+        /// Apply the fixes for issues that have one to the package file
+        fix: bool,
+
+        /// Show a diff of the fixes, only writing them when confirmed
+        diff: bool,
+
+        /// Run the current environment's check command to see that it starts
+        run_checks: bool,
+
+        /// Check every environment a package declares, not only the current
+        /// one
+        all_environments: bool,
+    },
+
+    /// Validate packages strictly, treating warnings as failures
+    Lint {
+        /// Names of the packages to lint; every package when empty
+        package_names: Vec<String>,
+
+        /// Run the current environment's check command to see that it starts
+        run_checks: bool,
+    },
+
+    /// Install every package for the current environment that isn't
+    /// installed yet
+    Sync {
+        /// Show what would be checked and installed, without installing
+        dry_run: bool,
+
+        /// Only sync packages with one of these tags; every package when empty
+        tags: Vec<String>,
+
+        /// Names of packages to leave out
+        exclude: Vec<String>,
+
+        /// Only count the packages left out for not supporting the current
+        /// environment, instead of naming each one
+        only_environment_matching: bool,
+
+        /// Write the closing summary as JSON
+        json: bool,
+
+        /// Only sync the packages listed in this file
+        packages_from: Option<PathBuf>,
     },
 }
 
+/// Format an install plan is written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlanFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+/// Format the results of validating every package are written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidateFormat {
+    #[default]
+    Human,
+    Json,
+    Sarif,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConfigCommand {
     /// Validate the selfie configuration
     Validate,
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheCommand {
+    /// Delete every cached package file
+    Clear,
+}
+
+/// Parse a `--since` value: either a span back from `now` (`7d`, `2w 3d`,
+/// `P1M`), a date (`2024-06-01`, midnight in the system time zone) or a
+/// timestamp (`2024-06-01T12:00:00Z`)
+pub(crate) fn parse_since(value: &str, now: &Zoned) -> Result<Timestamp, String> {
+    if let Ok(span) = value.parse::<Span>() {
+        return now
+            .checked_sub(span.abs())
+            .map(|since| since.timestamp())
+            .map_err(|e| format!("'{}' reaches too far back: {}", value, e));
+    }
+
+    if let Ok(date) = value.parse::<Date>() {
+        return date
+            .to_zoned(TimeZone::system())
+            .map(|since| since.timestamp())
+            .map_err(|e| format!("Invalid date '{}': {}", value, e));
+    }
+
+    value
+        .parse::<Timestamp>()
+        .map_err(|_| "expected a duration like '7d' or a date like '2024-06-01'".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        let now: Zoned = "2024-06-15T12:00:00+00:00[UTC]".parse().unwrap();
+
+        assert_eq!(
+            parse_since("7d", &now).unwrap(),
+            "2024-06-08T12:00:00Z".parse::<Timestamp>().unwrap()
+        );
+        assert_eq!(
+            parse_since("P1M", &now).unwrap(),
+            "2024-05-15T12:00:00Z".parse::<Timestamp>().unwrap()
+        );
+        assert_eq!(
+            parse_since("2024-06-01T08:30:00Z", &now).unwrap(),
+            "2024-06-01T08:30:00Z".parse::<Timestamp>().unwrap()
+        );
+        assert!(parse_since("2024-06-01", &now).is_ok());
+        assert!(parse_since("last tuesday", &now).is_err());
+    }
+}
@@ -1,7 +1,9 @@
 // src/domain/config.rs
 
 use std::{
+    collections::HashMap,
     ffi::OsStr,
+    fmt,
     num::{NonZeroU64, NonZeroUsize},
     path::{Path, PathBuf},
     time::Duration,
@@ -11,12 +13,19 @@ use serde::Deserialize;
 use thiserror::Error;
 
 use crate::{
-    domain::package::{EnvironmentConfig, Package, PackageValidationError},
+    domain::{
+        errors::{ErrorContext, ErrorReport},
+        package::{EnvironmentConfig, Package, PackageValidationError},
+    },
     ports::application::ApplicationArguments,
 };
 
+/// Package file extensions used when the config doesn't set any
+pub(crate) const DEFAULT_PACKAGE_FILE_EXTENSIONS: [&str; 2] = ["yaml", "yml"];
+
 const VERBOSE_DEFAULT: bool = false;
 const USE_COLORS_DEFAULT: bool = true;
+const USE_CACHE_DEFAULT: bool = true;
 const STOP_ON_ERROR_DEFAULT: bool = true;
 
 /// Comprehensive application configuration that combines file config and CLI args
@@ -26,6 +35,17 @@ pub struct AppConfig {
     pub(crate) environment: String,
     pub(crate) package_directory: PathBuf,
 
+    /// Extensions of package files, e.g. `yaml` or `pkg.yaml`, tried in order
+    /// when looking a package up by name
+    #[serde(default = "default_package_file_extensions")]
+    pub(crate) package_file_extensions: Vec<String>,
+
+    /// Other names package files use for an environment, e.g.
+    /// `macos: [mac, darwin]`, tried in order when a package doesn't have
+    /// the environment under its own name
+    #[serde(default)]
+    pub(crate) environment_aliases: HashMap<String, Vec<String>>,
+
     // UI settings
     #[serde(default)]
     pub(crate) verbose: bool,
@@ -33,14 +53,64 @@ pub struct AppConfig {
     #[serde(default = "default_use_colors")]
     pub(crate) use_colors: bool,
 
+    #[serde(default)]
+    pub(crate) progress_format: ProgressFormat,
+
+    /// Lines of a failed command's output to show without `--verbose`
+    #[serde(default = "default_failure_output_lines")]
+    pub(crate) failure_output_lines: usize,
+
+    /// Answer yes to every confirmation prompt
+    #[serde(default)]
+    pub(crate) assume_yes: bool,
+
+    /// Kill the running command on Ctrl-C instead of letting it finish
+    #[serde(default)]
+    pub(crate) kill_on_interrupt: bool,
+
+    /// Fail on the warnings about resolving a package's environment or
+    /// dependencies, instead of carrying on
+    #[serde(default)]
+    pub(crate) strict: bool,
+
+    /// Reuse packages parsed on an earlier run while their files are unchanged
+    #[serde(default = "default_use_cache")]
+    pub(crate) use_cache: bool,
+
+    /// Prefix for every install command, e.g. `nice -n 19`
+    #[serde(default)]
+    pub(crate) command_wrapper: Option<String>,
+
+    /// Dotenv-style file of variables to set for every command
+    #[serde(default)]
+    pub(crate) env_file: Option<PathBuf>,
+
+    /// Directory of the markers selfie writes for packages without a check
+    /// once they're installed, which count as installed while they're there
+    #[serde(default)]
+    pub(crate) markers_dir: Option<PathBuf>,
+
     // Execution settings
     // command_timeout: Duration,
     #[serde(default = "default_command_timeout")]
     pub(crate) command_timeout: NonZeroU64,
 
+    /// Seconds a check command may run before it counts as hung; packages
+    /// can set their own with `check_timeout`
+    #[serde(default = "default_check_timeout")]
+    pub(crate) check_timeout: NonZeroU64,
+
     #[serde(default = "default_stop_on_error")]
     pub(crate) stop_on_error: bool,
 
+    /// What to do with packages that have no check
+    #[serde(default)]
+    pub(crate) no_check_policy: NoCheckPolicy,
+
+    /// Where a package's name comes from
+    #[serde(default)]
+    pub(crate) name_source: NameSource,
+
     #[serde(default = "default_max_parallel")]
     pub(crate) max_parallel_installations: NonZeroUsize,
 
@@ -49,6 +119,70 @@ pub struct AppConfig {
     pub(crate) logging: LoggingConfig,
 }
 
+/// How progress is reported to the user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressFormat {
+    /// Styled, human-readable text
+    #[default]
+    Human,
+
+    /// One JSON object per progress event, newline-delimited
+    Json,
+
+    /// A single line per package, updated in place as it's installed
+    Compact,
+}
+
+/// How a package without `check`, `check_file` or `check_path_command` is
+/// treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoCheckPolicy {
+    /// Install it every time
+    #[default]
+    AlwaysInstall,
+
+    /// Install it until the install state records a successful install for
+    /// the environment, then treat it as installed
+    UseState,
+
+    /// Don't allow it: validation and installs fail until it has a check
+    Never,
+}
+
+impl fmt::Display for NoCheckPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlwaysInstall => f.write_str("always_install"),
+            Self::UseState => f.write_str("use_state"),
+            Self::Never => f.write_str("never"),
+        }
+    }
+}
+
+/// Where a package's name is taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameSource {
+    /// The `name` field in its file
+    #[default]
+    Field,
+
+    /// Its file name, without the extension; a `name` field that disagrees
+    /// is ignored, and validation warns about it
+    Filename,
+}
+
+impl fmt::Display for NameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field => f.write_str("field"),
+            Self::Filename => f.write_str("filename"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub(crate) struct LoggingConfig {
     #[serde(default)]
@@ -75,6 +209,198 @@ impl Default for LoggingConfig {
     }
 }
 
+/// The settings one config source sets, to be merged over the sources under it
+/// with [`AppConfig::merge`]. `None` leaves a setting as it was.
+///
+/// Library users build one with its setters to configure more than
+/// [`AppConfig::new`] does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverlay {
+    pub(crate) environment: Option<String>,
+    pub(crate) package_directory: Option<PathBuf>,
+    pub(crate) package_file_extensions: Option<Vec<String>>,
+    pub(crate) environment_aliases: Option<HashMap<String, Vec<String>>>,
+    pub(crate) verbose: Option<bool>,
+    pub(crate) use_colors: Option<bool>,
+    pub(crate) progress_format: Option<ProgressFormat>,
+    pub(crate) failure_output_lines: Option<usize>,
+    pub(crate) assume_yes: Option<bool>,
+    pub(crate) kill_on_interrupt: Option<bool>,
+    pub(crate) strict: Option<bool>,
+    pub(crate) use_cache: Option<bool>,
+    pub(crate) command_wrapper: Option<String>,
+    pub(crate) env_file: Option<PathBuf>,
+    pub(crate) markers_dir: Option<PathBuf>,
+    pub(crate) command_timeout: Option<NonZeroU64>,
+    pub(crate) check_timeout: Option<NonZeroU64>,
+    pub(crate) stop_on_error: Option<bool>,
+    pub(crate) no_check_policy: Option<NoCheckPolicy>,
+    pub(crate) name_source: Option<NameSource>,
+    pub(crate) max_parallel_installations: Option<NonZeroUsize>,
+    pub(crate) log_enabled: Option<bool>,
+    pub(crate) log_directory: Option<PathBuf>,
+    pub(crate) log_max_files: Option<NonZeroUsize>,
+    pub(crate) log_max_size: Option<NonZeroUsize>,
+}
+
+impl ConfigOverlay {
+    /// Environment packages are installed for
+    pub fn environment(mut self, environment: impl Into<String>) -> Self {
+        self.environment = Some(environment.into());
+        self
+    }
+
+    /// Directory package files are read from
+    pub fn package_directory(mut self, package_directory: impl Into<PathBuf>) -> Self {
+        self.package_directory = Some(package_directory.into());
+        self
+    }
+
+    /// Extensions package files may have, without the leading dot
+    pub fn package_file_extensions(mut self, package_file_extensions: &[&str]) -> Self {
+        self.package_file_extensions = Some(
+            package_file_extensions
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+        );
+        self
+    }
+
+    /// Other names packages use for each environment
+    pub fn environment_aliases(
+        mut self,
+        environment_aliases: HashMap<String, Vec<String>>,
+    ) -> Self {
+        self.environment_aliases = Some(environment_aliases);
+        self
+    }
+
+    /// Whether to show command output and other detail
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = Some(verbose);
+        self
+    }
+
+    /// Whether to color output
+    pub fn use_colors(mut self, use_colors: bool) -> Self {
+        self.use_colors = Some(use_colors);
+        self
+    }
+
+    /// How progress is reported
+    pub fn progress_format(mut self, progress_format: ProgressFormat) -> Self {
+        self.progress_format = Some(progress_format);
+        self
+    }
+
+    /// How many lines of a failed command's output to show
+    pub fn failure_output_lines(mut self, failure_output_lines: usize) -> Self {
+        self.failure_output_lines = Some(failure_output_lines);
+        self
+    }
+
+    /// Whether to skip confirmation prompts
+    pub fn assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = Some(assume_yes);
+        self
+    }
+
+    /// Whether an interrupt kills running commands instead of waiting for them
+    pub fn kill_on_interrupt(mut self, kill_on_interrupt: bool) -> Self {
+        self.kill_on_interrupt = Some(kill_on_interrupt);
+        self
+    }
+
+    /// Whether validation warnings count as errors
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = Some(strict);
+        self
+    }
+
+    /// Whether parsed package files are cached between runs
+    pub fn use_cache(mut self, use_cache: bool) -> Self {
+        self.use_cache = Some(use_cache);
+        self
+    }
+
+    /// Command install and check commands are run through
+    pub fn command_wrapper(mut self, command_wrapper: impl Into<String>) -> Self {
+        self.command_wrapper = Some(command_wrapper.into());
+        self
+    }
+
+    /// File of variables commands are run with
+    pub fn env_file(mut self, env_file: impl Into<PathBuf>) -> Self {
+        self.env_file = Some(env_file.into());
+        self
+    }
+
+    /// Directory markers of installed packages are kept in
+    pub fn markers_dir(mut self, markers_dir: impl Into<PathBuf>) -> Self {
+        self.markers_dir = Some(markers_dir.into());
+        self
+    }
+
+    /// Seconds an install command may run for
+    pub fn command_timeout(mut self, command_timeout: NonZeroU64) -> Self {
+        self.command_timeout = Some(command_timeout);
+        self
+    }
+
+    /// Seconds a check command may run for
+    pub fn check_timeout(mut self, check_timeout: NonZeroU64) -> Self {
+        self.check_timeout = Some(check_timeout);
+        self
+    }
+
+    /// Whether to stop at the first failed install
+    pub fn stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = Some(stop_on_error);
+        self
+    }
+
+    /// What to do with packages that have no check
+    pub fn no_check_policy(mut self, no_check_policy: NoCheckPolicy) -> Self {
+        self.no_check_policy = Some(no_check_policy);
+        self
+    }
+
+    /// Where a package file's package gets its name
+    pub fn name_source(mut self, name_source: NameSource) -> Self {
+        self.name_source = Some(name_source);
+        self
+    }
+
+    /// How many packages may be installed at once
+    pub fn max_parallel_installations(mut self, max_parallel_installations: NonZeroUsize) -> Self {
+        self.max_parallel_installations = Some(max_parallel_installations);
+        self
+    }
+}
+
+impl From<&ApplicationArguments> for ConfigOverlay {
+    /// Flags only ever turn their setting on (or colors off); leaving a flag
+    /// out keeps what the config file says
+    fn from(args: &ApplicationArguments) -> Self {
+        Self {
+            environment: args.environment.clone(),
+            package_directory: args.package_directory.clone(),
+            verbose: args.verbose.then_some(true),
+            use_colors: args.no_color.then_some(false),
+            progress_format: args.progress_format,
+            assume_yes: args.assume_yes.then_some(true),
+            kill_on_interrupt: args.kill_on_interrupt.then_some(true),
+            strict: args.strict.then_some(true),
+            use_cache: args.no_cache.then_some(false),
+            command_wrapper: args.command_wrapper.clone(),
+            env_file: args.env_file.clone(),
+            check_timeout: args.check_timeout,
+            ..Self::default()
+        }
+    }
+}
+
 const fn default_log_max_files() -> NonZeroUsize {
     unsafe { NonZeroUsize::new_unchecked(10) }
 }
@@ -86,6 +412,9 @@ const fn default_log_max_size() -> NonZeroUsize {
 fn default_command_timeout() -> NonZeroU64 {
     unsafe { NonZeroU64::new_unchecked(60) }
 }
+fn default_check_timeout() -> NonZeroU64 {
+    unsafe { NonZeroU64::new_unchecked(15) }
+}
 fn default_stop_on_error() -> bool {
     true
 }
@@ -95,6 +424,18 @@ fn default_max_parallel() -> NonZeroUsize {
 fn default_use_colors() -> bool {
     true
 }
+fn default_use_cache() -> bool {
+    USE_CACHE_DEFAULT
+}
+fn default_failure_output_lines() -> usize {
+    10
+}
+fn default_package_file_extensions() -> Vec<String> {
+    DEFAULT_PACKAGE_FILE_EXTENSIONS
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect()
+}
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ConfigValidationError {
@@ -117,17 +458,53 @@ pub enum ConfigValidationError {
     InvalidLogConfig(String),
 }
 
+impl ErrorReport for ConfigValidationError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::MissingField(_) => "config_missing_field",
+            Self::EmptyField(_) => "config_empty_field",
+            Self::InvalidPackageDirectory(_) => "invalid_package_directory",
+            Self::EnvironmentNotFound(_) => "environment_not_found",
+            Self::InvalidPackage(_) => "invalid_package",
+            Self::InvalidLogConfig(_) => "invalid_log_config",
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::EnvironmentNotFound(environment) => {
+                ErrorContext::default().with_environment(environment)
+            }
+            _ => ErrorContext::default(),
+        }
+    }
+}
+
 impl AppConfig {
     /// Create a new AppConfig with default values
-    pub(crate) fn new(environment: String, package_directory: PathBuf) -> Self {
+    pub fn new(environment: String, package_directory: PathBuf) -> Self {
         Self {
             environment,
             package_directory,
+            package_file_extensions: default_package_file_extensions(),
+            environment_aliases: HashMap::new(),
             verbose: VERBOSE_DEFAULT,
             use_colors: USE_COLORS_DEFAULT,
+            progress_format: ProgressFormat::default(),
+            failure_output_lines: default_failure_output_lines(),
+            assume_yes: false,
+            kill_on_interrupt: false,
+            strict: false,
+            use_cache: USE_CACHE_DEFAULT,
+            command_wrapper: None,
+            env_file: None,
+            markers_dir: None,
             command_timeout: default_command_timeout(),
+            check_timeout: default_check_timeout(),
             max_parallel_installations: default_max_parallel(),
             stop_on_error: STOP_ON_ERROR_DEFAULT,
+            no_check_policy: NoCheckPolicy::default(),
+            name_source: NameSource::default(),
             logging: LoggingConfig::default(),
         }
     }
@@ -140,6 +517,10 @@ impl AppConfig {
         &self.package_directory
     }
 
+    pub fn package_file_extensions(&self) -> &[String] {
+        &self.package_file_extensions
+    }
+
     pub fn verbose(&self) -> bool {
         self.verbose
     }
@@ -148,10 +529,57 @@ impl AppConfig {
         self.use_colors
     }
 
+    pub fn progress_format(&self) -> ProgressFormat {
+        self.progress_format
+    }
+
+    pub fn assume_yes(&self) -> bool {
+        self.assume_yes
+    }
+
+    pub fn failure_output_lines(&self) -> usize {
+        self.failure_output_lines
+    }
+
+    pub fn kill_on_interrupt(&self) -> bool {
+        self.kill_on_interrupt
+    }
+
+    /// Whether resolution warnings are errors; see [`AppConfig::strict`]'s
+    /// field for which ones
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn use_cache(&self) -> bool {
+        self.use_cache
+    }
+
+    pub fn command_wrapper(&self) -> Option<&str> {
+        self.command_wrapper.as_deref()
+    }
+
+    pub fn env_file(&self) -> Option<&Path> {
+        self.env_file.as_deref()
+    }
+
+    /// The marker file for `package_name`, when the config has a
+    /// `markers_dir`
+    pub(crate) fn marker_path(&self, package_name: &str) -> Option<PathBuf> {
+        self.markers_dir.as_ref().map(|markers_dir| {
+            let markers_dir = markers_dir.to_string_lossy();
+            PathBuf::from(shellexpand::tilde(&markers_dir).as_ref()).join(package_name)
+        })
+    }
+
     pub fn command_timeout(&self) -> Duration {
         Duration::from_secs(self.command_timeout.into())
     }
 
+    pub fn check_timeout(&self) -> Duration {
+        Duration::from_secs(self.check_timeout.into())
+    }
+
     pub fn max_parallel(&self) -> NonZeroUsize {
         self.max_parallel_installations
     }
@@ -160,6 +588,14 @@ impl AppConfig {
         self.stop_on_error
     }
 
+    pub fn no_check_policy(&self) -> NoCheckPolicy {
+        self.no_check_policy
+    }
+
+    pub fn name_source(&self) -> NameSource {
+        self.name_source
+    }
+
     pub fn logging_enabled(&self) -> bool {
         self.logging.enabled
     }
@@ -177,22 +613,56 @@ impl AppConfig {
     }
 
     /// Apply CLI arguments to override configuration
-    pub fn apply_cli_args(mut self, args: &ApplicationArguments) -> Self {
-        // Override environment if specified
-        if let Some(env) = args.environment.as_ref() {
-            self.environment = env.clone();
-        }
-
-        // Override package directory if specified
-        if let Some(dir) = args.package_directory.as_ref() {
-            self.package_directory = dir.clone();
-        }
+    pub fn apply_cli_args(self, args: &ApplicationArguments) -> Self {
+        Self::merge(self, &ConfigOverlay::from(args))
+    }
 
-        // Apply UI settings
-        self.verbose = args.verbose;
-        self.use_colors = !args.no_color;
+    /// Layer `overlay` over `base`: every setting the overlay has replaces the
+    /// base's, and the rest are kept.
+    ///
+    /// Config sources are merged from lowest to highest precedence, so the
+    /// last one wins: defaults, then detected settings, the config file, the
+    /// profile, and finally the command line.
+    pub fn merge(base: AppConfig, overlay: &ConfigOverlay) -> AppConfig {
+        let overlay = overlay.clone();
 
-        self
+        AppConfig {
+            environment: overlay.environment.unwrap_or(base.environment),
+            package_directory: overlay.package_directory.unwrap_or(base.package_directory),
+            package_file_extensions: overlay
+                .package_file_extensions
+                .unwrap_or(base.package_file_extensions),
+            environment_aliases: overlay
+                .environment_aliases
+                .unwrap_or(base.environment_aliases),
+            verbose: overlay.verbose.unwrap_or(base.verbose),
+            use_colors: overlay.use_colors.unwrap_or(base.use_colors),
+            progress_format: overlay.progress_format.unwrap_or(base.progress_format),
+            failure_output_lines: overlay
+                .failure_output_lines
+                .unwrap_or(base.failure_output_lines),
+            assume_yes: overlay.assume_yes.unwrap_or(base.assume_yes),
+            kill_on_interrupt: overlay.kill_on_interrupt.unwrap_or(base.kill_on_interrupt),
+            strict: overlay.strict.unwrap_or(base.strict),
+            use_cache: overlay.use_cache.unwrap_or(base.use_cache),
+            command_wrapper: overlay.command_wrapper.or(base.command_wrapper),
+            env_file: overlay.env_file.or(base.env_file),
+            markers_dir: overlay.markers_dir.or(base.markers_dir),
+            command_timeout: overlay.command_timeout.unwrap_or(base.command_timeout),
+            check_timeout: overlay.check_timeout.unwrap_or(base.check_timeout),
+            stop_on_error: overlay.stop_on_error.unwrap_or(base.stop_on_error),
+            no_check_policy: overlay.no_check_policy.unwrap_or(base.no_check_policy),
+            name_source: overlay.name_source.unwrap_or(base.name_source),
+            max_parallel_installations: overlay
+                .max_parallel_installations
+                .unwrap_or(base.max_parallel_installations),
+            logging: LoggingConfig {
+                enabled: overlay.log_enabled.unwrap_or(base.logging.enabled),
+                directory: overlay.log_directory.or(base.logging.directory),
+                max_files: overlay.log_max_files.unwrap_or(base.logging.max_files),
+                max_size: overlay.log_max_size.unwrap_or(base.logging.max_size),
+            },
+        }
     }
 
     /// Full validation for the AppConfig
@@ -200,6 +670,12 @@ impl AppConfig {
         Self::validate_environment(&self.environment)?;
         Self::validate_package_directory(&self.package_directory)?;
 
+        if self.package_file_extensions.is_empty() {
+            return Err(ConfigValidationError::EmptyField(
+                "package_file_extensions".to_string(),
+            ));
+        }
+
         // Validate logging settings if enabled
         if self.logging.enabled {
             Self::validate_log_directory(
@@ -243,6 +719,24 @@ impl AppConfig {
         PathBuf::from(expanded_path.as_ref())
     }
 
+    /// Other names packages may have the current environment under, in the
+    /// order they're tried
+    pub(crate) fn environment_aliases(&self) -> &[String] {
+        self.environment_aliases
+            .get(&self.environment)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The environment `package` has for the current one, with the name it's
+    /// declared under; that's an alias when it isn't the current
+    /// environment's own name
+    pub(crate) fn find_environment<'a>(
+        &self,
+        package: &'a Package,
+    ) -> Option<(&'a str, &'a EnvironmentConfig)> {
+        package.find_environment(&self.environment, self.environment_aliases())
+    }
+
     /// Resolve environment configuration for a package
     pub(crate) fn resolve_environment<'a>(
         &self,
@@ -255,7 +749,7 @@ impl AppConfig {
         }
 
         package
-            .resolve_environment(&self.environment)
+            .resolve_environment(&self.environment, self.environment_aliases())
             .map_err(|e| match e {
                 PackageValidationError::EnvironmentNotSupported(_) => {
                     ConfigValidationError::EnvironmentNotFound(self.environment.clone())
@@ -268,6 +762,17 @@ impl AppConfig {
                 ),
             })
     }
+
+    /// Split `packages` into those that support the current environment,
+    /// which bulk commands act on, and those that don't, keeping their order
+    pub(crate) fn partition_by_environment(
+        &self,
+        packages: Vec<Package>,
+    ) -> (Vec<Package>, Vec<Package>) {
+        packages
+            .into_iter()
+            .partition(|package| self.resolve_environment(package).is_ok())
+    }
 }
 
 trait ValidateConfig {
@@ -320,9 +825,20 @@ pub(crate) struct AppConfigBuilder {
     package_directory: PathBuf,
     verbose: bool,
     use_colors: bool,
+    progress_format: ProgressFormat,
+    assume_yes: bool,
+    kill_on_interrupt: bool,
+    strict: bool,
+    use_cache: bool,
+    command_wrapper: Option<String>,
+    markers_dir: Option<PathBuf>,
     command_timeout: NonZeroU64,
+    check_timeout: NonZeroU64,
     max_parallel: NonZeroUsize,
     stop_on_error: bool,
+    no_check_policy: NoCheckPolicy,
+    name_source: NameSource,
+    environment_aliases: HashMap<String, Vec<String>>,
     logging: LoggingConfig,
 }
 
@@ -351,6 +867,39 @@ impl AppConfigBuilder {
         self
     }
 
+    pub(crate) fn progress_format(mut self, progress_format: ProgressFormat) -> Self {
+        self.progress_format = progress_format;
+        self
+    }
+
+    pub(crate) fn assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = assume_yes;
+        self
+    }
+
+    pub(crate) fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub(crate) fn use_cache(mut self, use_cache: bool) -> Self {
+        self.use_cache = use_cache;
+        self
+    }
+
+    pub(crate) fn command_wrapper(mut self, wrapper: &str) -> Self {
+        self.command_wrapper = Some(wrapper.to_string());
+        self
+    }
+
+    pub(crate) fn markers_dir<D>(mut self, markers_dir: D) -> Self
+    where
+        D: AsRef<Path>,
+    {
+        self.markers_dir = Some(markers_dir.as_ref().to_path_buf());
+        self
+    }
+
     pub(crate) fn command_timeout(mut self, timeout: NonZeroU64) -> Self {
         self.command_timeout = timeout;
         self
@@ -361,6 +910,11 @@ impl AppConfigBuilder {
         self
     }
 
+    pub(crate) fn check_timeout_unchecked(mut self, timeout: u64) -> Self {
+        self.check_timeout = NonZeroU64::new(timeout).unwrap();
+        self
+    }
+
     pub(crate) fn max_parallel(mut self, max: NonZeroUsize) -> Self {
         self.max_parallel = max;
         self
@@ -376,6 +930,24 @@ impl AppConfigBuilder {
         self
     }
 
+    pub(crate) fn no_check_policy(mut self, policy: NoCheckPolicy) -> Self {
+        self.no_check_policy = policy;
+        self
+    }
+
+    pub(crate) fn name_source(mut self, source: NameSource) -> Self {
+        self.name_source = source;
+        self
+    }
+
+    pub(crate) fn environment_aliases(mut self, environment: &str, aliases: &[&str]) -> Self {
+        self.environment_aliases.insert(
+            environment.to_string(),
+            aliases.iter().map(|alias| alias.to_string()).collect(),
+        );
+        self
+    }
+
     pub(crate) fn logging_enabled(mut self, enabled: bool) -> Self {
         self.logging.enabled = enabled;
         self
@@ -413,11 +985,25 @@ impl AppConfigBuilder {
         AppConfig {
             environment: self.environment,
             package_directory: self.package_directory,
+            package_file_extensions: default_package_file_extensions(),
+            environment_aliases: self.environment_aliases,
             verbose: self.verbose,
             use_colors: self.use_colors,
+            progress_format: self.progress_format,
+            failure_output_lines: default_failure_output_lines(),
+            assume_yes: self.assume_yes,
+            kill_on_interrupt: self.kill_on_interrupt,
+            strict: self.strict,
+            use_cache: self.use_cache,
+            command_wrapper: self.command_wrapper,
+            env_file: None,
+            markers_dir: self.markers_dir,
             command_timeout: self.command_timeout,
+            check_timeout: self.check_timeout,
             max_parallel_installations: self.max_parallel,
             stop_on_error: self.stop_on_error,
+            no_check_policy: self.no_check_policy,
+            name_source: self.name_source,
             logging: LoggingConfig {
                 enabled: self.logging.enabled,
                 directory: self.logging.directory,
@@ -436,9 +1022,20 @@ impl Default for AppConfigBuilder {
             package_directory: PathBuf::new(),
             verbose: VERBOSE_DEFAULT,
             use_colors: USE_COLORS_DEFAULT,
+            progress_format: ProgressFormat::default(),
+            assume_yes: false,
+            kill_on_interrupt: false,
+            strict: false,
+            use_cache: USE_CACHE_DEFAULT,
+            command_wrapper: None,
+            markers_dir: None,
             command_timeout: default_command_timeout(),
+            check_timeout: default_check_timeout(),
             max_parallel: default_max_parallel(),
             stop_on_error: STOP_ON_ERROR_DEFAULT,
+            no_check_policy: NoCheckPolicy::default(),
+            name_source: NameSource::default(),
+            environment_aliases: HashMap::new(),
             logging: LoggingConfig::default(),
         }
     }
@@ -484,7 +1081,19 @@ mod tests {
             package_directory: Some(PathBuf::from("/cli/path")),
             verbose: true,
             no_color: true,
-            command: ApplicationCommand::Package(PackageCommand::List),
+            progress_format: Some(ProgressFormat::Json),
+            assume_yes: true,
+            kill_on_interrupt: true,
+            strict: true,
+            no_cache: true,
+            command_wrapper: Some("nice -n 19".to_string()),
+            env_file: Some(PathBuf::from("/tmp/selfie.env")),
+            check_timeout: NonZeroU64::new(5),
+            config_path: None,
+            command: ApplicationCommand::Package(PackageCommand::List {
+                since: None,
+                only_environment_matching: false,
+            }),
         };
         let updated = config.apply_cli_args(&args);
 
@@ -492,6 +1101,230 @@ mod tests {
         assert_eq!(updated.package_directory, PathBuf::from("/cli/path"));
         assert!(updated.verbose);
         assert!(!updated.use_colors);
+        assert_eq!(updated.progress_format(), ProgressFormat::Json);
+        assert!(updated.assume_yes());
+        assert!(updated.kill_on_interrupt());
+        assert!(updated.strict());
+        assert!(!updated.use_cache());
+        assert_eq!(updated.command_wrapper(), Some("nice -n 19"));
+        assert_eq!(updated.env_file(), Some(Path::new("/tmp/selfie.env")));
+        assert_eq!(updated.check_timeout(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_app_config_merge_empty_overlay() {
+        let base = AppConfigBuilder::default()
+            .environment("file-env")
+            .package_directory("/file/path")
+            .verbose(true)
+            .use_colors(false)
+            .command_wrapper("nice")
+            .build();
+
+        let merged = AppConfig::merge(base.clone(), &ConfigOverlay::default());
+
+        assert_eq!(merged.environment(), "file-env");
+        assert_eq!(merged.package_directory(), Path::new("/file/path"));
+        assert!(merged.verbose());
+        assert!(!merged.use_colors());
+        assert_eq!(merged.command_wrapper(), Some("nice"));
+        assert_eq!(merged.command_timeout(), base.command_timeout());
+        assert_eq!(merged.logging, base.logging);
+    }
+
+    #[test]
+    fn test_app_config_merge_overrides_each_field() {
+        let base = AppConfig::new("file-env".to_string(), PathBuf::from("/file/path"));
+
+        let overlay = ConfigOverlay {
+            environment: Some("profile-env".to_string()),
+            package_directory: Some(PathBuf::from("/profile/path")),
+            package_file_extensions: Some(vec!["selfie".to_string()]),
+            environment_aliases: Some(HashMap::from([(
+                "macos".to_string(),
+                vec!["mac".to_string()],
+            )])),
+            verbose: Some(true),
+            use_colors: Some(false),
+            progress_format: Some(ProgressFormat::Compact),
+            failure_output_lines: Some(3),
+            assume_yes: Some(true),
+            kill_on_interrupt: Some(true),
+            strict: Some(true),
+            use_cache: Some(false),
+            command_wrapper: Some("nice -n 19".to_string()),
+            env_file: Some(PathBuf::from("/tmp/selfie.env")),
+            markers_dir: Some(PathBuf::from("/tmp/markers")),
+            command_timeout: NonZeroU64::new(5),
+            check_timeout: NonZeroU64::new(2),
+            stop_on_error: Some(false),
+            no_check_policy: Some(NoCheckPolicy::UseState),
+            name_source: Some(NameSource::Filename),
+            max_parallel_installations: NonZeroUsize::new(2),
+            log_enabled: Some(true),
+            log_directory: Some(PathBuf::from("/tmp/logs")),
+            log_max_files: NonZeroUsize::new(3),
+            log_max_size: NonZeroUsize::new(4),
+        };
+        let merged = AppConfig::merge(base, &overlay);
+
+        assert_eq!(merged.environment(), "profile-env");
+        assert_eq!(merged.package_directory(), Path::new("/profile/path"));
+        assert_eq!(merged.package_file_extensions(), ["selfie"]);
+        assert_eq!(merged.environment_aliases["macos"], ["mac"]);
+        assert!(merged.verbose());
+        assert!(!merged.use_colors());
+        assert_eq!(merged.progress_format(), ProgressFormat::Compact);
+        assert_eq!(merged.failure_output_lines(), 3);
+        assert!(merged.assume_yes());
+        assert!(merged.kill_on_interrupt());
+        assert!(merged.strict());
+        assert!(!merged.use_cache());
+        assert_eq!(merged.command_wrapper(), Some("nice -n 19"));
+        assert_eq!(merged.env_file(), Some(Path::new("/tmp/selfie.env")));
+        assert_eq!(
+            merged.marker_path("vimrc"),
+            Some(PathBuf::from("/tmp/markers/vimrc"))
+        );
+        assert_eq!(merged.command_timeout(), Duration::from_secs(5));
+        assert_eq!(merged.check_timeout(), Duration::from_secs(2));
+        assert!(!merged.stop_on_error());
+        assert_eq!(merged.no_check_policy(), NoCheckPolicy::UseState);
+        assert_eq!(merged.name_source(), NameSource::Filename);
+        assert_eq!(merged.max_parallel().get(), 2);
+        assert!(merged.logging_enabled());
+        assert_eq!(merged.log_directory(), Some(&PathBuf::from("/tmp/logs")));
+        assert_eq!(merged.log_max_files().get(), 3);
+        assert_eq!(merged.log_max_size().get(), 4);
+    }
+
+    #[test]
+    fn test_app_config_merge_precedence() {
+        let defaults = AppConfig::new(String::new(), PathBuf::new());
+        let file = ConfigOverlay {
+            environment: Some("file-env".to_string()),
+            package_directory: Some(PathBuf::from("/file/path")),
+            verbose: Some(true),
+            command_wrapper: Some("nice".to_string()),
+            ..ConfigOverlay::default()
+        };
+        let cli = ConfigOverlay {
+            environment: Some("cli-env".to_string()),
+            command_wrapper: Some("ionice".to_string()),
+            ..ConfigOverlay::default()
+        };
+
+        let merged = AppConfig::merge(AppConfig::merge(defaults, &file), &cli);
+
+        // The command line wins where it's set, then the file, then defaults
+        assert_eq!(merged.environment(), "cli-env");
+        assert_eq!(merged.command_wrapper(), Some("ionice"));
+        assert_eq!(merged.package_directory(), Path::new("/file/path"));
+        assert!(merged.verbose());
+        assert!(merged.use_colors());
+        assert_eq!(merged.command_timeout(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_app_config_apply_cli_args_keeps_file_flags() {
+        let config = AppConfigBuilder::default()
+            .environment("file-env")
+            .package_directory("/file/path")
+            .verbose(true)
+            .use_colors(false)
+            .assume_yes(true)
+            .build();
+
+        // Leaving the flags off doesn't turn the file's settings off
+        let updated = config.apply_cli_args(&ApplicationArguments::default());
+
+        assert_eq!(updated.environment(), "file-env");
+        assert!(updated.verbose());
+        assert!(!updated.use_colors());
+        assert!(updated.assume_yes());
+    }
+
+    #[test]
+    fn test_app_config_progress_format_from_yaml() {
+        let yaml = r#"
+            environment: test-env
+            package_directory: /test/path
+            progress_format: json
+        "#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.progress_format(), ProgressFormat::Json);
+
+        let yaml = r#"
+            environment: test-env
+            package_directory: /test/path
+        "#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.progress_format(), ProgressFormat::Human);
+    }
+
+    #[test]
+    fn test_app_config_package_file_extensions_from_yaml() {
+        let yaml = r#"
+            environment: test-env
+            package_directory: /test/path
+        "#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.package_file_extensions(), ["yaml", "yml"]);
+        assert_eq!(config.no_check_policy(), NoCheckPolicy::AlwaysInstall);
+        assert_eq!(config.name_source(), NameSource::Field);
+
+        let yaml = r#"
+            environment: test-env
+            package_directory: /test/path
+            package_file_extensions: [selfie, pkg.yaml]
+        "#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.package_file_extensions(), ["selfie", "pkg.yaml"]);
+        assert!(config.validate().is_ok());
+
+        let yaml = r#"
+            environment: test-env
+            package_directory: /test/path
+            package_file_extensions: []
+        "#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::EmptyField(field)) if field == "package_file_extensions"
+        ));
+    }
+
+    #[test]
+    fn test_app_config_environment_aliases() {
+        let yaml = r#"
+            environment: macos
+            package_directory: /test/path
+            environment_aliases:
+              macos: [mac, darwin]
+        "#;
+        let config: AppConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.environment_aliases(), ["mac", "darwin"]);
+
+        let package = crate::domain::package::PackageBuilder::default()
+            .name("ripgrep")
+            .version("1.0.0")
+            .environment("darwin", "brew install ripgrep")
+            .environment("linux", "apt install ripgrep")
+            .build();
+        let (name, env_config) = config.find_environment(&package).unwrap();
+        assert_eq!(name, "darwin");
+        assert_eq!(config.resolve_environment(&package).unwrap(), env_config);
+
+        // Aliases are only for the environment they're listed under
+        let config = AppConfig::merge(
+            config,
+            &ConfigOverlay {
+                environment: Some("linux-arm".to_string()),
+                ..ConfigOverlay::default()
+            },
+        );
+        assert!(config.environment_aliases().is_empty());
+        assert!(config.resolve_environment(&package).is_err());
     }
 
     #[test]
@@ -8,12 +8,12 @@ use super::package::Package;
 
 /// Errors that can occur during dependency operations
 #[derive(Debug, Error)]
-pub(crate) enum DependencyGraphError {
+pub enum DependencyGraphError {
     #[error("Package not found: {0}")]
     PackageNotFound(String),
 
-    #[error("Circular dependency detected: {0}")]
-    CircularDependency(String, Vec<String>),
+    #[error("Circular dependency detected: {}", .0.join(" -> "))]
+    CircularDependency(Vec<String>),
 
     #[error("Invalid dependency: {0}")]
     InvalidDependency(String),
@@ -27,6 +27,10 @@ pub(crate) struct DependencyGraph {
 
     /// Map of package name to its dependencies
     edges: HashMap<String, HashSet<String>>,
+
+    /// Edges (package, dependency) only the package's check needs, rather
+    /// than its install
+    check_edges: HashSet<(String, String)>,
 }
 
 impl DependencyGraph {
@@ -48,6 +52,51 @@ impl DependencyGraph {
         &mut self,
         package: &str,
         dependency: &str,
+    ) -> Result<(), DependencyGraphError> {
+        self.add_edge(package, dependency)?;
+
+        // Check for cycles after adding the dependency
+        if self.has_cycle() {
+            // Remove the dependency that caused the cycle
+            if let Some(deps) = self.edges.get_mut(package) {
+                deps.remove(dependency);
+            }
+
+            // The new edge closes the cycle, so it runs from the dependency back to the package
+            let mut cycle = vec![package.to_string()];
+            cycle.extend(self.find_path(dependency, package).unwrap_or_default());
+
+            return Err(DependencyGraphError::CircularDependency(cycle));
+        }
+
+        Ok(())
+    }
+
+    /// Add a dependency that only the package's check needs. It's ordered
+    /// like any other dependency, but stays an install dependency if it's
+    /// one already.
+    pub(crate) fn add_check_dependency(
+        &mut self,
+        package: &str,
+        dependency: &str,
+    ) -> Result<(), DependencyGraphError> {
+        let existed = self.has_edge(package, dependency);
+        self.add_dependency(package, dependency)?;
+
+        if !existed {
+            self.check_edges
+                .insert((package.to_string(), dependency.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Add a dependency relationship without rejecting cycles, for graphs
+    /// that are only displayed rather than installed from
+    pub(crate) fn add_edge(
+        &mut self,
+        package: &str,
+        dependency: &str,
     ) -> Result<(), DependencyGraphError> {
         // Verify both packages exist
         if !self.nodes.contains_key(package) {
@@ -59,33 +108,188 @@ impl DependencyGraph {
             ));
         }
 
-        // Add the dependency
         if let Some(deps) = self.edges.get_mut(package) {
             deps.insert(dependency.to_string());
         }
+        self.check_edges
+            .remove(&(package.to_string(), dependency.to_string()));
 
-        // Check for cycles after adding the dependency
-        if self.has_cycle() {
-            // Remove the dependency that caused the cycle
-            if let Some(deps) = self.edges.get_mut(package) {
-                deps.remove(dependency);
-            }
+        Ok(())
+    }
 
-            // Create a simple path for the cycle
-            let path = vec![package.to_string(), dependency.to_string()];
+    /// [`Self::add_edge`] for a dependency only the package's check needs
+    pub(crate) fn add_check_edge(
+        &mut self,
+        package: &str,
+        dependency: &str,
+    ) -> Result<(), DependencyGraphError> {
+        let existed = self.has_edge(package, dependency);
+        self.add_edge(package, dependency)?;
 
-            return Err(DependencyGraphError::CircularDependency(
-                format!(
-                    "Adding {} as dependency of {} would create a cycle",
-                    dependency, package
-                ),
-                path,
-            ));
+        if !existed {
+            self.check_edges
+                .insert((package.to_string(), dependency.to_string()));
         }
 
         Ok(())
     }
 
+    /// Whether `dependency` is only a dependency of `package`'s check
+    pub(crate) fn is_check_dependency(&self, package: &str, dependency: &str) -> bool {
+        self.check_edges
+            .contains(&(package.to_string(), dependency.to_string()))
+    }
+
+    fn has_edge(&self, package: &str, dependency: &str) -> bool {
+        self.edges
+            .get(package)
+            .is_some_and(|deps| deps.contains(dependency))
+    }
+
+    /// Names of a package's direct dependencies, sorted
+    pub(crate) fn dependencies(&self, package: &str) -> Vec<&str> {
+        let mut deps: Vec<&str> = self
+            .edges
+            .get(package)
+            .map(|deps| deps.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        deps.sort_unstable();
+        deps
+    }
+
+    /// Render the graph in Graphviz DOT format, with an edge from each package
+    /// to each of its dependencies; edges that are part of a cycle are red,
+    /// and those only a check needs are dashed
+    pub(crate) fn to_dot(&self) -> String {
+        let cycle_edges: HashSet<(String, String)> = self
+            .find_cycles()
+            .iter()
+            .flat_map(|cycle| {
+                cycle
+                    .windows(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut names = self.get_package_names();
+        names.sort_unstable();
+
+        let mut dot = String::from("digraph dependencies {\n");
+        for name in &names {
+            dot.push_str(&format!("    {};\n", dot_id(name)));
+        }
+        for name in &names {
+            for dep in self.dependencies(name) {
+                let mut attributes = Vec::new();
+                if cycle_edges.contains(&(name.clone(), dep.to_string())) {
+                    attributes.push("color=red");
+                }
+                if self.is_check_dependency(name, dep) {
+                    attributes.push("style=dashed");
+                }
+                let style = if attributes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", attributes.join(", "))
+                };
+                dot.push_str(&format!(
+                    "    {} -> {}{};\n",
+                    dot_id(name),
+                    dot_id(dep),
+                    style
+                ));
+            }
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// The same graph with every edge pointing the other way, from each
+    /// package to the packages that depend on it
+    pub(crate) fn reversed(&self) -> DependencyGraph {
+        let mut reversed = DependencyGraph {
+            nodes: self.nodes.clone(),
+            edges: self
+                .nodes
+                .keys()
+                .map(|name| (name.clone(), HashSet::new()))
+                .collect(),
+            check_edges: self
+                .check_edges
+                .iter()
+                .map(|(package, dependency)| (dependency.clone(), package.clone()))
+                .collect(),
+        };
+
+        for (package, deps) in &self.edges {
+            for dep in deps {
+                reversed
+                    .edges
+                    .entry(dep.clone())
+                    .or_default()
+                    .insert(package.clone());
+            }
+        }
+
+        reversed
+    }
+
+    /// Render the packages reachable from `root` as an indented tree. A
+    /// package that's already been shown is marked `(*)` instead of being
+    /// expanded again, and one that leads back to itself `(cycle)`.
+    pub(crate) fn to_tree(&self, root: &str) -> String {
+        fn visit<'g>(
+            graph: &'g DependencyGraph,
+            node: &'g str,
+            prefix: &str,
+            ancestors: &mut Vec<&'g str>,
+            shown: &mut HashSet<&'g str>,
+            tree: &mut String,
+        ) {
+            ancestors.push(node);
+
+            let children = graph.dependencies(node);
+            for (i, &child) in children.iter().enumerate() {
+                let last = i == children.len() - 1;
+                let (branch, indent) = if last {
+                    ("└── ", "    ")
+                } else {
+                    ("├── ", "│   ")
+                };
+
+                let mut line = format!("{}{}{}", prefix, branch, child);
+                if graph.is_check_dependency(node, child) {
+                    line.push_str(" (check)");
+                }
+
+                if ancestors.contains(&child) {
+                    line.push_str(" (cycle)");
+                    tree.push_str(&line);
+                    tree.push('\n');
+                } else if !shown.insert(child) {
+                    line.push_str(" (*)");
+                    tree.push_str(&line);
+                    tree.push('\n');
+                } else {
+                    tree.push_str(&line);
+                    tree.push('\n');
+                    let prefix = format!("{}{}", prefix, indent);
+                    visit(graph, child, &prefix, ancestors, shown, tree);
+                }
+            }
+
+            ancestors.pop();
+        }
+
+        let mut tree = format!("{}\n", root);
+        let mut shown = HashSet::from([root]);
+        visit(self, root, "", &mut Vec::new(), &mut shown, &mut tree);
+
+        tree
+    }
+
     /// Check if the graph contains any cycles
     pub(crate) fn has_cycle(&self) -> bool {
         let mut visited = HashSet::new();
@@ -141,6 +345,40 @@ impl DependencyGraph {
 
     // Private helper methods
 
+    /// Find a path of dependency edges leading from one package to another,
+    /// including both ends
+    fn find_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        fn visit(
+            graph: &DependencyGraph,
+            node: &str,
+            to: &str,
+            visited: &mut HashSet<String>,
+            path: &mut Vec<String>,
+        ) -> bool {
+            path.push(node.to_string());
+
+            if node == to {
+                return true;
+            }
+
+            if visited.insert(node.to_string()) {
+                if let Some(deps) = graph.edges.get(node) {
+                    for dep in deps {
+                        if visit(graph, dep, to, visited, path) {
+                            return true;
+                        }
+                    }
+                }
+            }
+
+            path.pop();
+            false
+        }
+
+        let mut path = Vec::new();
+        visit(self, from, to, &mut HashSet::new(), &mut path).then_some(path)
+    }
+
     /// Detect cycles using DFS
     fn has_cycle_util(
         &self,
@@ -167,67 +405,106 @@ impl DependencyGraph {
         false
     }
 
-    /// Find all cycles in the graph
+    /// Find all cycles in the graph, each starting and ending at the same
+    /// package. Every cycle is found once, starting from the first of its
+    /// packages in name order, and cycles come out in that order, so the same
+    /// graph always gives the same cycles.
+    ///
+    /// This is Johnson's algorithm: for each package, the cycles through it
+    /// are searched for among the packages after it that are in a strongly
+    /// connected component with it, blocking packages a search through them
+    /// already came back empty from.
     pub(crate) fn find_cycles(&self) -> Vec<Vec<String>> {
-        let mut cycles = Vec::new();
-        let mut visited = HashSet::new();
-        let mut path = Vec::new();
-        let mut on_path = HashSet::new();
+        let mut names: Vec<&str> = self.nodes.keys().map(String::as_str).collect();
+        names.sort_unstable();
 
-        // Start DFS from each node
-        for node in self.nodes.keys() {
-            if !visited.contains(node) {
-                self.find_cycles_util(node, &mut visited, &mut path, &mut on_path, &mut cycles);
-            }
+        let mut cycles = Vec::new();
+        for (index, &start) in names.iter().enumerate() {
+            let component = self.strongly_connected(start, &names[index..]);
+            let mut search = CycleSearch {
+                start,
+                component,
+                blocked: HashSet::new(),
+                blocked_by: HashMap::new(),
+                path: Vec::new(),
+                cycles: &mut cycles,
+            };
+            self.find_cycles_util(start, &mut search);
         }
 
         cycles
     }
 
-    /// Helper for find_cycles that performs the DFS
-    fn find_cycles_util(
-        &self,
-        node: &str,
-        visited: &mut HashSet<String>,
-        path: &mut Vec<String>,
-        on_path: &mut HashSet<String>,
-        cycles: &mut Vec<Vec<String>>,
-    ) {
-        // If already on current path, we found a cycle
-        if on_path.contains(node) {
-            // Find the start of the cycle
-            let cycle_start = path.iter().position(|n| n == node).unwrap();
-
-            // Extract the cycle path
-            let mut cycle = path[cycle_start..].to_vec();
-            cycle.push(node.to_string()); // Complete the cycle
-            cycles.push(cycle);
-            return;
+    /// The packages among `allowed` that are in a strongly connected
+    /// component with `start`, following only edges between them: the ones
+    /// `start` leads to that also lead back to it
+    fn strongly_connected<'a>(&'a self, start: &'a str, allowed: &[&'a str]) -> HashSet<&'a str> {
+        let allowed: HashSet<&str> = allowed.iter().copied().collect();
+
+        let mut reachable = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for dep in self.dependencies(node) {
+                if allowed.contains(dep) && reachable.insert(dep) {
+                    stack.push(dep);
+                }
+            }
         }
 
-        // Mark as visited and on current path
-        visited.insert(node.to_string());
-        on_path.insert(node.to_string());
-        path.push(node.to_string());
+        let mut component = HashSet::from([start]);
+        loop {
+            let before = component.len();
+            for &node in &reachable {
+                if !component.contains(node)
+                    && self
+                        .dependencies(node)
+                        .iter()
+                        .any(|dep| component.contains(dep))
+                {
+                    component.insert(node);
+                }
+            }
+            if component.len() == before {
+                return component;
+            }
+        }
+    }
 
-        // Visit all adjacent nodes
-        if let Some(deps) = self.edges.get(node) {
+    /// Helper for find_cycles that searches for paths from `node` back to
+    /// the search's start, returning whether it found any
+    fn find_cycles_util<'a>(&'a self, node: &'a str, search: &mut CycleSearch<'a, '_>) -> bool {
+        let mut found = false;
+        search.path.push(node);
+        search.blocked.insert(node);
+
+        let deps: Vec<&str> = self
+            .dependencies(node)
+            .into_iter()
+            .filter(|dep| search.component.contains(dep))
+            .collect();
+        for &dep in &deps {
+            if dep == search.start {
+                let mut cycle: Vec<String> = search.path.iter().map(|n| n.to_string()).collect();
+                cycle.push(dep.to_string());
+                search.cycles.push(cycle);
+                found = true;
+            } else if !search.blocked.contains(dep) && self.find_cycles_util(dep, search) {
+                found = true;
+            }
+        }
+
+        if found {
+            search.unblock(node);
+        } else {
+            // Nothing through here leads back yet; look again once one of
+            // its dependencies is unblocked
             for dep in deps {
-                if !visited.contains(dep) {
-                    self.find_cycles_util(dep, visited, path, on_path, cycles);
-                } else if on_path.contains(dep) {
-                    // Cycle with an already visited node on current path
-                    let cycle_start = path.iter().position(|n| n == dep).unwrap();
-                    let mut cycle = path[cycle_start..].to_vec();
-                    cycle.push(dep.to_string());
-                    cycles.push(cycle);
-                }
+                search.blocked_by.entry(dep).or_default().insert(node);
             }
         }
 
-        // Remove from current path when done with this node
-        on_path.remove(node);
-        path.pop();
+        search.path.pop();
+        found
     }
 
     /// Perform topological sort to order packages
@@ -241,16 +518,13 @@ impl DependencyGraph {
         // Check for cycle using temporary visit mark
         if temp_visited.contains(node) {
             // Find the cycle path for better error reporting
-            let mut cycle_path = Vec::new();
-            for n in temp_visited.iter() {
-                cycle_path.push(n.clone());
-            }
-            cycle_path.push(node.to_string());
+            let cycle_path = self
+                .find_cycles()
+                .into_iter()
+                .find(|cycle| cycle.iter().any(|n| n == node))
+                .unwrap_or_else(|| vec![node.to_string(), node.to_string()]);
 
-            return Err(DependencyGraphError::CircularDependency(
-                format!("Circular dependency detected involving {}", node),
-                cycle_path,
-            ));
+            return Err(DependencyGraphError::CircularDependency(cycle_path));
         }
 
         // Skip if already visited
@@ -281,6 +555,46 @@ impl DependencyGraph {
     }
 }
 
+/// State of the search for the cycles through one package, in
+/// [`DependencyGraph::find_cycles`]
+struct CycleSearch<'a, 'c> {
+    /// The package the cycles start and end at
+    start: &'a str,
+
+    /// The packages the search may go through
+    component: HashSet<&'a str>,
+
+    /// Packages on the path, or with no way back to `start` found yet
+    blocked: HashSet<&'a str>,
+
+    /// For each package, the blocked packages to unblock along with it
+    blocked_by: HashMap<&'a str, HashSet<&'a str>>,
+
+    /// The packages from `start` to where the search is
+    path: Vec<&'a str>,
+
+    cycles: &'c mut Vec<Vec<String>>,
+}
+
+impl<'a> CycleSearch<'a, '_> {
+    /// Unblock `node`, and whatever was waiting on it
+    fn unblock(&mut self, node: &'a str) {
+        self.blocked.remove(node);
+        if let Some(waiting) = self.blocked_by.remove(node) {
+            for waiting in waiting {
+                if self.blocked.contains(waiting) {
+                    self.unblock(waiting);
+                }
+            }
+        }
+    }
+}
+
+/// Quote a package name as a DOT identifier
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,7 +687,8 @@ mod tests {
         assert!(graph.add_dependency("package1", "package2").is_ok());
         assert!(matches!(
             graph.add_dependency("package2", "package1"),
-            Err(DependencyGraphError::CircularDependency(_, _))
+            Err(DependencyGraphError::CircularDependency(cycle))
+                if cycle == ["package2", "package1", "package2"]
         ));
     }
 
@@ -389,10 +704,16 @@ mod tests {
         assert!(graph.add_node(package3).is_ok());
         assert!(graph.add_dependency("package1", "package2").is_ok());
         assert!(graph.add_dependency("package2", "package3").is_ok());
+        let err = graph.add_dependency("package3", "package1").unwrap_err();
         assert!(matches!(
-            graph.add_dependency("package3", "package1"),
-            Err(DependencyGraphError::CircularDependency(_, _))
+            &err,
+            DependencyGraphError::CircularDependency(cycle)
+                if cycle == &["package3", "package1", "package2", "package3"]
         ));
+        assert_eq!(
+            err.to_string(),
+            "Circular dependency detected: package3 -> package1 -> package2 -> package3"
+        );
     }
 
     #[test]
@@ -527,4 +848,125 @@ mod tests {
         assert!(b_pos > d_pos);
         assert!(c_pos > d_pos);
     }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = DependencyGraph::default();
+        for name in ["ripgrep", "rust", "a", "b"] {
+            graph.add_node(create_test_package(name)).unwrap();
+        }
+        graph.add_dependency("ripgrep", "rust").unwrap();
+        graph.add_edge("a", "b").unwrap();
+        graph.add_edge("b", "a").unwrap();
+
+        assert_eq!(
+            graph.to_dot(),
+            r#"digraph dependencies {
+    "a";
+    "b";
+    "ripgrep";
+    "rust";
+    "a" -> "b" [color=red];
+    "b" -> "a" [color=red];
+    "ripgrep" -> "rust";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let mut graph = DependencyGraph::default();
+        for name in ["a", "b", "c", "x", "y", "z"] {
+            graph.add_node(create_test_package(name)).unwrap();
+        }
+        graph.add_edge("a", "b").unwrap();
+        graph.add_edge("b", "c").unwrap();
+        graph.add_edge("c", "a").unwrap();
+        graph.add_edge("x", "y").unwrap();
+        graph.add_edge("y", "x").unwrap();
+        graph.add_edge("z", "a").unwrap();
+
+        assert_eq!(
+            graph.find_cycles(),
+            vec![vec!["a", "b", "c", "a"], vec!["x", "y", "x"]]
+        );
+    }
+
+    #[test]
+    fn test_find_cycles_sharing_packages() {
+        let mut graph = DependencyGraph::default();
+        for name in ["a", "b", "c", "d"] {
+            graph.add_node(create_test_package(name)).unwrap();
+        }
+        graph.add_edge("a", "b").unwrap();
+        graph.add_edge("a", "c").unwrap();
+        graph.add_edge("b", "d").unwrap();
+        graph.add_edge("c", "d").unwrap();
+        graph.add_edge("d", "a").unwrap();
+
+        assert_eq!(
+            graph.find_cycles(),
+            vec![vec!["a", "b", "d", "a"], vec!["a", "c", "d", "a"]]
+        );
+    }
+
+    #[test]
+    fn test_check_dependency() {
+        let mut graph = DependencyGraph::default();
+        for name in ["ripgrep", "rust", "jq"] {
+            graph.add_node(create_test_package(name)).unwrap();
+        }
+        graph.add_check_dependency("ripgrep", "jq").unwrap();
+        graph.add_check_dependency("ripgrep", "rust").unwrap();
+        graph.add_dependency("ripgrep", "rust").unwrap();
+
+        assert!(graph.is_check_dependency("ripgrep", "jq"));
+        assert!(!graph.is_check_dependency("ripgrep", "rust"));
+        assert!(matches!(
+            graph.add_check_dependency("jq", "ripgrep"),
+            Err(DependencyGraphError::CircularDependency(_))
+        ));
+        assert!(graph
+            .to_dot()
+            .contains(r#""ripgrep" -> "jq" [style=dashed];"#));
+    }
+
+    #[test]
+    fn test_reversed_tree() {
+        let mut graph = DependencyGraph::default();
+        for name in ["rust", "ripgrep", "cargo-edit", "tool", "jq", "a", "b"] {
+            graph.add_node(create_test_package(name)).unwrap();
+        }
+        graph.add_edge("ripgrep", "rust").unwrap();
+        graph.add_edge("cargo-edit", "rust").unwrap();
+        graph.add_edge("tool", "ripgrep").unwrap();
+        graph.add_edge("tool", "cargo-edit").unwrap();
+        graph.add_check_edge("ripgrep", "jq").unwrap();
+        graph.add_edge("a", "b").unwrap();
+        graph.add_edge("b", "a").unwrap();
+
+        let reversed = graph.reversed();
+        assert_eq!(reversed.dependencies("rust"), ["cargo-edit", "ripgrep"]);
+        assert!(reversed.is_check_dependency("jq", "ripgrep"));
+
+        assert_eq!(
+            reversed.to_tree("rust"),
+            [
+                "rust",
+                "├── cargo-edit",
+                "│   └── tool",
+                "└── ripgrep",
+                "    └── tool (*)",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            reversed.to_tree("jq"),
+            "jq\n└── ripgrep (check)\n    └── tool\n"
+        );
+        assert_eq!(reversed.to_tree("a"), "a\n└── b\n    └── a (cycle)\n");
+        assert_eq!(reversed.to_tree("tool"), "tool\n");
+    }
 }
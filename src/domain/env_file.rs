@@ -0,0 +1,169 @@
+// src/domain/env_file.rs
+// Dotenv-style files of environment variables for commands
+use std::{collections::HashMap, path::Path};
+
+use thiserror::Error;
+
+use crate::ports::filesystem::{FileSystem, FileSystemError};
+
+/// Errors from loading an env file. These never include the file's values,
+/// which are often secrets.
+#[derive(Error, Debug)]
+pub enum EnvFileError {
+    #[error("Failed to read env file: {0}")]
+    Read(#[from] FileSystemError),
+
+    #[error("Env file line {line}: {reason}")]
+    Malformed { line: usize, reason: &'static str },
+}
+
+/// Read `path` and parse it with [`parse_env_file`]. Later lines win when a
+/// key is repeated.
+pub fn load_env_file<F: FileSystem>(
+    fs: &F,
+    path: &Path,
+) -> Result<HashMap<String, String>, EnvFileError> {
+    let content = fs.read_file(&fs.expand_path(path)?)?;
+
+    Ok(parse_env_file(&content)?.into_iter().collect())
+}
+
+/// Parse `KEY=VALUE` lines, in order. Blank lines and lines starting with `#`
+/// are skipped, and an `export ` prefix is allowed. Values may be
+/// double-quoted (with `\n`, `\"` and `\\` escapes), single-quoted (taken
+/// literally), or bare, where a ` #` starts a comment.
+pub fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, EnvFileError> {
+    let mut vars = Vec::new();
+
+    for (index, raw_line) in content.lines().enumerate() {
+        let malformed = |reason| EnvFileError::Malformed {
+            line: index + 1,
+            reason,
+        };
+
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| malformed("expected KEY=VALUE"))?;
+        let key = key.trim();
+
+        if !is_valid_key(key) {
+            return Err(malformed(
+                "keys must be letters, digits and underscores, not starting with a digit",
+            ));
+        }
+
+        vars.push((
+            key.to_string(),
+            parse_value(value.trim()).map_err(malformed)?,
+        ));
+    }
+
+    Ok(vars)
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(value: &str) -> Result<String, &'static str> {
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = rest.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return only_comment_after(chars.as_str()).map(|()| parsed),
+                '\\' => match chars.next() {
+                    Some('n') => parsed.push('\n'),
+                    Some(escaped @ ('"' | '\\')) => parsed.push(escaped),
+                    Some(other) => {
+                        parsed.push('\\');
+                        parsed.push(other);
+                    }
+                    None => break,
+                },
+                _ => parsed.push(c),
+            }
+        }
+
+        Err("unterminated double-quoted value")
+    } else if let Some(rest) = value.strip_prefix('\'') {
+        let (parsed, after) = rest
+            .split_once('\'')
+            .ok_or("unterminated single-quoted value")?;
+
+        only_comment_after(after).map(|()| parsed.to_string())
+    } else {
+        let value = match value.find(" #") {
+            Some(comment) => &value[..comment],
+            None => value,
+        };
+
+        Ok(value.trim_end().to_string())
+    }
+}
+
+/// Anything after a closing quote must be a comment
+fn only_comment_after(rest: &str) -> Result<(), &'static str> {
+    let rest = rest.trim_start();
+
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err("unexpected text after closing quote")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let content = r#"
+# Tokens for private registries
+GITHUB_TOKEN=abc123
+export NPM_TOKEN = 'literal $HOME \n'
+GREETING="hello \"world\"\nbye" # trailing comment
+PLAIN=some value # comment
+URL=https://example.com/#anchor
+EMPTY=
+"#;
+
+        assert_eq!(
+            parse_env_file(content).unwrap(),
+            vec![
+                ("GITHUB_TOKEN".to_string(), "abc123".to_string()),
+                ("NPM_TOKEN".to_string(), r"literal $HOME \n".to_string()),
+                ("GREETING".to_string(), "hello \"world\"\nbye".to_string()),
+                ("PLAIN".to_string(), "some value".to_string()),
+                ("URL".to_string(), "https://example.com/#anchor".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_env_file_errors_hide_values() {
+        let err = parse_env_file("OK=1\nsecret-token-value\n").unwrap_err();
+        assert_eq!(err.to_string(), "Env file line 2: expected KEY=VALUE");
+
+        let err = parse_env_file("TOKEN=\"s3cret").unwrap_err();
+        assert!(!err.to_string().contains("s3cret"));
+        assert!(matches!(err, EnvFileError::Malformed { line: 1, .. }));
+
+        assert!(parse_env_file("1TOKEN=x").is_err());
+        assert!(parse_env_file("TOKEN='x' y").is_err());
+    }
+}
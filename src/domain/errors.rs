@@ -1,29 +1,36 @@
 // src/domain/errors.rs
 // Enhanced error types with context and formatting capabilities
 
+use serde::Serialize;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// Error context provides additional information about where and why an error occurred
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct ErrorContext {
     /// The file or path associated with the error
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<PathBuf>,
 
     /// The command that caused the error
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<String>,
 
     /// The environment where the error occurred
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub environment: Option<String>,
 
     /// The package associated with the error
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub package: Option<String>,
 
     /// Line number in a file where the error occurred
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<usize>,
 
     /// Additional context message
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
 }
 
@@ -117,6 +124,52 @@ impl fmt::Display for ErrorContext {
     }
 }
 
+/// An error as automation sees it, e.g. in the JSON error object printed with
+/// `--output json`
+pub trait ErrorReport: std::error::Error {
+    /// What kind of error this is, in snake_case. Unlike messages, kinds are
+    /// kept stable, so they're safe to match on.
+    fn kind(&self) -> &'static str;
+
+    /// What the error is about, where that's known
+    fn error_context(&self) -> ErrorContext {
+        ErrorContext::default()
+    }
+}
+
+/// The `error` of a machine-readable error report: `kind` and `context` come
+/// from [`ErrorReport`], `message` is the human-readable one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorObject {
+    pub kind: &'static str,
+    pub message: String,
+    pub context: ErrorContext,
+}
+
+impl ErrorObject {
+    /// Kind of an error that doesn't implement [`ErrorReport`]
+    pub const UNKNOWN_KIND: &'static str = "error";
+
+    /// Report `error`, with `message` in place of its own; e.g. the message
+    /// of the error wrapping it
+    pub fn new(error: &dyn ErrorReport, message: impl Into<String>) -> Self {
+        Self {
+            kind: error.kind(),
+            message: message.into(),
+            context: error.error_context(),
+        }
+    }
+
+    /// Report an error selfie has no kind for
+    pub fn unknown(message: impl Into<String>) -> Self {
+        Self {
+            kind: Self::UNKNOWN_KIND,
+            message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+}
+
 /// Enhanced file system error with context
 #[derive(Error, Debug)]
 pub enum EnhancedFileSystemError {
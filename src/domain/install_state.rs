@@ -0,0 +1,57 @@
+// Record of what selfie has installed, persisted between runs
+use std::collections::BTreeMap;
+
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Everything selfie has successfully installed on this machine, keyed by
+/// package name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InstallState {
+    #[serde(default)]
+    pub(crate) packages: BTreeMap<String, InstalledPackage>,
+}
+
+/// A single package installation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InstalledPackage {
+    /// Package file version at the time of installation
+    pub(crate) version: String,
+
+    /// Environment the package was installed for
+    pub(crate) environment: String,
+
+    /// When the installation finished
+    pub(crate) installed_at: Timestamp,
+}
+
+impl InstallState {
+    /// Record an installation, replacing any earlier record for the package
+    pub(crate) fn record(&mut self, name: &str, version: &str, environment: &str) {
+        self.packages.insert(
+            name.to_string(),
+            InstalledPackage {
+                version: version.to_string(),
+                environment: environment.to_string(),
+                installed_at: Timestamp::now(),
+            },
+        );
+    }
+
+    /// Whether a successful install of `name` for `environment` is recorded
+    pub(crate) fn is_recorded(&self, name: &str, environment: &str) -> bool {
+        self.packages
+            .get(name)
+            .is_some_and(|installed| installed.environment == environment)
+    }
+
+    /// Remove the record of a package's install, returning whether there was
+    /// one
+    pub(crate) fn forget(&mut self, name: &str) -> bool {
+        self.packages.remove(name).is_some()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+}
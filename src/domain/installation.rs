@@ -1,13 +1,23 @@
 // src/domain/installation.rs
 // Installation domain model using state machine pattern
 
-use std::time::{Duration, Instant};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 
+use serde::Serialize;
 use thiserror::Error;
 
-use crate::ports::command::{CommandError, CommandOutput, CommandRunner, OutputChunk};
+use crate::ports::{
+    command::{CommandError, CommandOutput, CommandRunner, OutputChunk},
+    filesystem::FileSystem,
+};
 
-use super::package::EnvironmentConfig;
+use super::package::{EnvironmentConfig, InstallCommand};
+
+/// Exit status shells use when a command can't be found
+pub(crate) const COMMAND_NOT_FOUND_STATUS: i32 = 127;
 
 /// Represents a package installation as a state machine
 #[derive(Debug, Clone)]
@@ -45,6 +55,10 @@ pub(crate) enum Installation {
         start_time: Instant,
         duration: Duration,
         error_message: String,
+
+        /// The command error behind the failure, e.g. an install command's
+        /// nonzero exit
+        cause: Option<CommandError>,
     },
     Skipped {
         env_config: EnvironmentConfig,
@@ -52,6 +66,11 @@ pub(crate) enum Installation {
         duration: Duration,
         reason: String,
     },
+    /// The check command ran past its timeout, so whether the package is
+    /// installed is unknown
+    CheckTimedOut {
+        timeout: Duration,
+    },
 }
 
 impl Installation {
@@ -136,6 +155,11 @@ impl Installation {
 
     /// Mark as failed
     fn fail(self, error_message: String) -> Self {
+        self.fail_with(error_message, None)
+    }
+
+    /// Mark as failed, keeping the command error that caused it
+    fn fail_with(self, error_message: String, cause: Option<CommandError>) -> Self {
         match self {
             Self::Checking {
                 env_config,
@@ -145,6 +169,7 @@ impl Installation {
                 start_time,
                 duration: start_time.elapsed(),
                 error_message,
+                cause,
             },
             Self::Installing {
                 env_config,
@@ -155,6 +180,7 @@ impl Installation {
                 start_time,
                 duration: start_time.elapsed(),
                 error_message,
+                cause,
             },
             Self::NotAlreadyInstalled {
                 env_config,
@@ -165,11 +191,20 @@ impl Installation {
                 start_time,
                 duration: start_time.elapsed(),
                 error_message,
+                cause,
             },
             other => other,
         }
     }
 
+    /// Mark the check as having run past `timeout`
+    fn check_timed_out(self, timeout: Duration) -> Self {
+        match self {
+            Self::Checking { .. } => Self::CheckTimedOut { timeout },
+            other => other,
+        }
+    }
+
     /// Mark as skipped
     fn skip(self, reason: String) -> Self {
         match self {
@@ -195,36 +230,102 @@ impl Installation {
         }
     }
 
-    // Add streaming version of execute_check
-    pub(crate) async fn execute_check<CR: CommandRunner, F>(
+    /// Skip the check command entirely and treat the package as not installed
+    pub(crate) fn skip_check(self) -> Self {
+        self.mark_not_already_installed()
+    }
+
+    /// Skip the check and treat the package as installed, e.g. because the
+    /// install state records a successful install of it
+    pub(crate) fn assume_installed(self) -> Self {
+        self.mark_already_installed()
+    }
+
+    /// Fail the check without running it
+    pub(crate) fn fail_check(self, error_message: String) -> Self {
+        self.fail(error_message)
+    }
+
+    /// Check whether the package is already installed. Only the first
+    /// configured check is used: the `check` command, then `check_file`, then
+    /// `check_path_command`. Without any, the package is assumed not to be
+    /// installed. With `check_inverted`, the `check` command passing means
+    /// the package still needs installing, and failing means it doesn't. A
+    /// `check_path_command` that can't be looked up counts as not installed,
+    /// with the reason passed to `output_callback`.
+    ///
+    /// A `check` command the shell can't find (exit status 127) is usually a
+    /// package that isn't installed yet, so it counts as failing, with a
+    /// warning passed to `output_callback` in case it's a typo.
+    ///
+    /// The `check` command gets `check_timeout` to run, unless the package
+    /// sets its own. Running past it doesn't mean the package isn't
+    /// installed, so it ends in [`Self::CheckTimedOut`] instead.
+    pub(crate) async fn execute_check<FS: FileSystem, CR: CommandRunner, F>(
         self,
+        fs: &FS,
         runner: &CR,
-        output_callback: F,
+        check_timeout: Duration,
+        mut output_callback: F,
     ) -> Result<Self, InstallationError>
     where
-        F: FnMut(OutputChunk) + Send + 'static,
+        F: FnMut(OutputChunk) + Clone + Send + 'static,
     {
         match &self {
             Self::Checking { env_config, .. } => {
-                // If there's no check command, assume not installed
-                if env_config.check.is_none() {
+                let Some(check_cmd) = env_config.check() else {
+                    if let Some(check_file) = &env_config.check_file {
+                        return match fs.expand_path(check_file) {
+                            Ok(path) if fs.path_exists(&path) => Ok(self.mark_already_installed()),
+                            Ok(_) => Ok(self.mark_not_already_installed()),
+                            Err(e) => Ok(self.fail(format!("Check file failed: {}", e))),
+                        };
+                    }
+
+                    if let Some(command) = &env_config.check_path_command {
+                        return match runner.is_command_available(command).await {
+                            Ok(true) => Ok(self.mark_already_installed()),
+                            Ok(false) => Ok(self.mark_not_already_installed()),
+                            Err(e) => {
+                                output_callback(OutputChunk::Stderr(format!(
+                                    "Treating '{}' as not installed, since looking it up failed: {}\n",
+                                    command, e
+                                )));
+                                Ok(self.mark_not_already_installed())
+                            }
+                        };
+                    }
+
+                    // If there's no check at all, assume not installed
                     return Ok(self.mark_not_already_installed());
-                }
+                };
 
-                let check_cmd = env_config.check.as_ref().unwrap();
+                let shell_runner = env_config.shell().map(|shell| runner.with_shell(shell));
+                let runner = shell_runner.as_ref().unwrap_or(runner);
 
                 // Execute the check command with streaming
+                let timeout = env_config.check_timeout().unwrap_or(check_timeout);
                 match runner
-                    .execute_streaming(check_cmd, Duration::from_secs(60), output_callback)
+                    .execute_streaming(check_cmd, timeout, output_callback.clone())
                     .await
+                    .or_else(CommandError::into_output)
                 {
                     Ok(output) => {
-                        if output.success {
+                        if output.status == COMMAND_NOT_FOUND_STATUS {
+                            output_callback(OutputChunk::Stderr(format!(
+                                "Warning: check command not found (exit status {}), so the \
+                                 check fails: {}\n",
+                                COMMAND_NOT_FOUND_STATUS, check_cmd
+                            )));
+                        }
+
+                        if output.succeeded() != env_config.check_inverted() {
                             Ok(self.mark_already_installed())
                         } else {
                             Ok(self.mark_not_already_installed())
                         }
                     }
+                    Err(CommandError::Timeout(_)) => Ok(self.check_timed_out(timeout)),
                     Err(e) => {
                         let error_message = format!("Check command failed: {}", e);
                         Ok(self.fail(error_message))
@@ -237,41 +338,115 @@ impl Installation {
         }
     }
 
-    pub(crate) async fn execute_install<CR: CommandRunner, F>(
+    /// Run the install command, or each step of an install script in order.
+    /// `on_step` is called before each step of a script with its 1-based
+    /// number, the step count and the command.
+    pub(crate) async fn execute_install<CR: CommandRunner, F, S>(
         self,
         runner: &CR,
         output_callback: F,
+        mut on_step: S,
     ) -> Result<Self, InstallationError>
     where
-        F: FnMut(OutputChunk) + Send + 'static,
+        F: FnMut(OutputChunk) + Clone + Send + 'static,
+        S: FnMut(usize, usize, &str),
     {
         match &self.clone() {
             Self::NotAlreadyInstalled { env_config, .. } => {
                 let installing = self.start_installing();
 
-                // Execute the install command with streaming
-                match runner
-                    .execute_streaming(
-                        &env_config.install,
-                        Duration::from_secs(600),
-                        output_callback,
-                    )
-                    .await
-                {
-                    Ok(output) => {
-                        if output.success {
-                            Ok(installing.complete(output))
-                        } else {
-                            let error_msg =
-                                format!("Install command failed with status {}", output.status);
-                            Ok(installing.fail(error_msg))
-                        }
+                // Commands given per architecture may leave this one out
+                if env_config.install.for_current_arch().is_none() {
+                    return Ok(installing.fail(format!(
+                        "No install command defined for {}",
+                        std::env::consts::ARCH
+                    )));
+                }
+
+                let commands = env_config.install.commands();
+                let shell_runner = env_config.shell().map(|shell| runner.with_shell(shell));
+                let runner = shell_runner.as_ref().unwrap_or(runner);
+                let is_script = matches!(
+                    env_config.install.for_current_arch(),
+                    Some(InstallCommand::Script(_))
+                );
+
+                let mut last_output = None;
+                for (i, command) in commands.iter().enumerate() {
+                    if is_script {
+                        on_step(i + 1, commands.len(), command);
                     }
-                    Err(e) => {
-                        let error_msg = format!("Install command error: {}", e);
-                        Ok(installing.fail(error_msg))
+
+                    // Name the failing step when there's more than one
+                    let subject = if is_script {
+                        format!("Install step {} of {}", i + 1, commands.len())
+                    } else {
+                        "Install command".to_string()
+                    };
+
+                    let result = match &env_config.stdin {
+                        // Commands reading from stdin can't be streamed; pass
+                        // their output on once they're done
+                        Some(input) => {
+                            let result = runner
+                                .execute_with_stdin(
+                                    command,
+                                    input.as_bytes(),
+                                    Duration::from_secs(600),
+                                )
+                                .await;
+
+                            // Their streams weren't interleaved as they ran, so
+                            // the tail and verbose output get one after the other
+                            let output = match &result {
+                                Ok(output) | Err(CommandError::NonZeroExit { output, .. }) => {
+                                    Some(output)
+                                }
+                                Err(_) => None,
+                            };
+                            if let Some(output) = output {
+                                let combined = output.combined();
+                                if !combined.is_empty() {
+                                    output_callback.clone()(OutputChunk::Stdout(combined));
+                                }
+                            }
+
+                            result
+                        }
+                        // Execute the install command with streaming
+                        None => {
+                            runner
+                                .execute_streaming(
+                                    command,
+                                    Duration::from_secs(600),
+                                    output_callback.clone(),
+                                )
+                                .await
+                        }
+                    };
+
+                    match result {
+                        Ok(output) => last_output = Some(output),
+                        Err(
+                            e @ CommandError::NonZeroExit {
+                                output: CommandOutput { status, .. },
+                                ..
+                            },
+                        ) => {
+                            let error_msg = format!("{} failed with status {}", subject, status);
+                            return Ok(installing.fail_with(error_msg, Some(e)));
+                        }
+                        Err(e) => {
+                            let error_msg = format!("{} error: {}", subject, e);
+                            return Ok(installing.fail(error_msg));
+                        }
                     }
                 }
+
+                match last_output {
+                    Some(output) => Ok(installing.complete(output)),
+                    None => Ok(installing.fail("Install script has no commands".to_string())),
+                }
             }
             _ => Err(InstallationError::InvalidState(
                 "Can only execute install from NotInstalled state".to_string(),
@@ -290,6 +465,7 @@ impl Installation {
             Self::Complete { .. } => InstallationStatus::Complete,
             Self::Failed { error_message, .. } => InstallationStatus::Failed(error_message.clone()),
             Self::Skipped { reason, .. } => InstallationStatus::Skipped(reason.clone()),
+            Self::CheckTimedOut { timeout, .. } => InstallationStatus::CheckTimedOut(*timeout),
         }
     }
 
@@ -304,6 +480,8 @@ impl Installation {
             Self::Complete { duration, .. } => Some(*duration),
             Self::Failed { duration, .. } => Some(*duration),
             Self::Skipped { duration, .. } => Some(*duration),
+            // The check ran for as long as it was given
+            Self::CheckTimedOut { timeout } => Some(*timeout),
         }
     }
 
@@ -317,6 +495,9 @@ impl Installation {
                 package_name,
                 status: InstallationStatus::AlreadyInstalled,
                 duration: check_duration,
+                wall_clock: check_duration,
+                attempts: 1,
+                last_error: None,
                 command_output: None,
                 dependencies: Vec::new(),
             }),
@@ -328,9 +509,15 @@ impl Installation {
                 package_name,
                 status: InstallationStatus::Complete,
                 duration,
+                wall_clock: duration,
+                attempts: 1,
+                last_error: None,
                 command_output: Some(command_output),
                 dependencies: Vec::new(),
             }),
+            Self::Failed {
+                cause: Some(cause), ..
+            } => Err(InstallationError::CommandError(cause)),
             Self::Failed { error_message, .. } => {
                 Err(InstallationError::InstallationFailed(error_message))
             }
@@ -340,9 +527,13 @@ impl Installation {
                 package_name,
                 status: InstallationStatus::Skipped(reason),
                 duration,
+                wall_clock: duration,
+                attempts: 1,
+                last_error: None,
                 command_output: None,
                 dependencies: Vec::new(),
             }),
+            Self::CheckTimedOut { timeout, .. } => Err(InstallationError::CheckTimedOut(timeout)),
             Self::NotStarted { .. } => Err(InstallationError::InvalidState(
                 "Invalid state transition: NotStarted".to_string(),
             )),
@@ -361,7 +552,10 @@ impl Installation {
 
 /// Represents the current status of a package installation
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum InstallationStatus {
+pub enum InstallationStatus {
+    /// Package is planned to be installed, but work on it hasn't begun
+    Pending,
+
     /// Installation has not yet started
     NotStarted,
 
@@ -385,11 +579,15 @@ pub(crate) enum InstallationStatus {
 
     /// Installation was skipped for the given reason
     Skipped(String),
+
+    /// The check command ran past this timeout, so it's unknown whether the
+    /// package is installed
+    CheckTimedOut(Duration),
 }
 
 /// Errors that can occur during installation
 #[derive(Error, Debug)]
-pub(crate) enum InstallationError {
+pub enum InstallationError {
     #[error("Command execution error: {0}")]
     CommandError(#[from] CommandError),
 
@@ -399,22 +597,36 @@ pub(crate) enum InstallationError {
     #[error("Check command failed: {0}")]
     CheckFailed(String),
 
+    #[error("Check command timed out after {} seconds", .0.as_secs())]
+    CheckTimedOut(Duration),
+
     #[error("Invalid state transition: {0}")]
     InvalidState(String),
 }
 
 /// Represents the result of an installation operation
 #[derive(Debug)]
-pub(crate) struct InstallationReport {
+pub struct InstallationReport {
     /// Name of the installed package
     pub(crate) package_name: String,
 
     /// Final installation status
     pub(crate) status: InstallationStatus,
 
-    /// How long the installation took
+    /// How long installing this package took, not counting its dependencies
     pub(crate) duration: Duration,
 
+    /// Elapsed time from starting the install to finishing it, dependencies
+    /// included. Unlike [`Self::total_duration`], this doesn't add up the
+    /// steps, so it stays accurate when they overlap.
+    pub(crate) wall_clock: Duration,
+
+    /// How many times installing the package was tried
+    pub(crate) attempts: u32,
+
+    /// Error from the last attempt, when the install failed
+    pub(crate) last_error: Option<String>,
+
     pub(crate) command_output: Option<CommandOutput>,
 
     /// Results of dependent package installations
@@ -422,13 +634,36 @@ pub(crate) struct InstallationReport {
 }
 
 impl InstallationReport {
+    pub fn package_name(&self) -> &str {
+        &self.package_name
+    }
+
+    pub fn status(&self) -> &InstallationStatus {
+        &self.status
+    }
+
+    /// How long installing this package took, not counting its dependencies
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Error from the last attempt, when the install failed
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Reports for the dependencies installed along with the package
+    pub fn dependencies(&self) -> &[InstallationReport] {
+        &self.dependencies
+    }
+
     /// Add dependencies to the installation result
     pub(crate) fn with_dependencies(mut self, dependencies: Vec<InstallationReport>) -> Self {
         self.dependencies = dependencies;
         self
     }
 
-    /// Calculate the total duration including dependencies
+    /// Sum of the time each step took, dependencies included
     pub(crate) fn total_duration(&self) -> Duration {
         let mut total = self.duration;
         for dep in &self.dependencies {
@@ -445,21 +680,159 @@ impl InstallationReport {
         }
         total
     }
+
+    /// Dependencies with their install durations, slowest first
+    pub(crate) fn dependency_timings(&self) -> Vec<(&str, Duration)> {
+        let mut timings: Vec<(&str, Duration)> = self
+            .dependencies
+            .iter()
+            .map(|dep| (dep.package_name.as_str(), dep.duration))
+            .collect();
+        timings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        timings
+    }
+}
+
+/// How each package fared across a bulk install, such as a sync, counted once
+/// however many of the installed packages depend on it
+#[derive(Debug, Default)]
+pub(crate) struct InstallationSummary {
+    outcomes: BTreeMap<String, InstallationOutcome>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InstallationOutcome {
+    Installed,
+    Upgraded,
+    AlreadyInstalled,
+    Skipped,
+    Failed,
+}
+
+impl From<&InstallationStatus> for InstallationOutcome {
+    fn from(status: &InstallationStatus) -> Self {
+        match status {
+            InstallationStatus::Complete => Self::Installed,
+            InstallationStatus::AlreadyInstalled => Self::AlreadyInstalled,
+            InstallationStatus::Failed(_) | InstallationStatus::CheckTimedOut(_) => Self::Failed,
+            _ => Self::Skipped,
+        }
+    }
+}
+
+/// Counts from an [`InstallationSummary`], with how long the run took
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct InstallationStats {
+    pub(crate) total: usize,
+    pub(crate) installed: usize,
+    pub(crate) already_installed: usize,
+    pub(crate) upgraded: usize,
+    pub(crate) skipped: usize,
+    pub(crate) failed: usize,
+    pub(crate) wall_clock_secs: f64,
+}
+
+impl InstallationSummary {
+    /// Count a package's installation and those of its dependencies. The
+    /// first outcome recorded for a package is the one that counts, since
+    /// later installs only skip it as already handled.
+    pub(crate) fn record(&mut self, report: &InstallationReport) {
+        for dependency in &report.dependencies {
+            self.record(dependency);
+        }
+
+        self.outcomes
+            .entry(report.package_name.clone())
+            .or_insert_with(|| InstallationOutcome::from(&report.status));
+    }
+
+    /// Count the packages an installer got to, as given by its statuses, for
+    /// an install that ended in an error and so has no report. Packages it
+    /// never reached count as skipped.
+    pub(crate) fn record_statuses(&mut self, statuses: &[(String, InstallationStatus)]) {
+        for (package_name, status) in statuses {
+            self.outcomes
+                .entry(package_name.clone())
+                .or_insert_with(|| InstallationOutcome::from(status));
+        }
+    }
+
+    /// Count a package whose install ended in an error
+    pub(crate) fn record_failure(&mut self, package_name: &str) {
+        self.outcomes
+            .entry(package_name.to_string())
+            .or_insert(InstallationOutcome::Failed);
+    }
+
+    /// Count a package that was installed over an earlier install of another
+    /// version as upgraded rather than newly installed
+    pub(crate) fn record_upgrade(&mut self, package_name: &str) {
+        if let Some(outcome) = self.outcomes.get_mut(package_name) {
+            if *outcome == InstallationOutcome::Installed {
+                *outcome = InstallationOutcome::Upgraded;
+            }
+        }
+    }
+
+    /// Whether any package failed to install
+    pub(crate) fn has_failures(&self) -> bool {
+        self.count(InstallationOutcome::Failed) > 0
+    }
+
+    /// The counts, for a run that took `wall_clock`
+    pub(crate) fn stats(&self, wall_clock: Duration) -> InstallationStats {
+        InstallationStats {
+            total: self.outcomes.len(),
+            installed: self.count(InstallationOutcome::Installed),
+            already_installed: self.count(InstallationOutcome::AlreadyInstalled),
+            upgraded: self.count(InstallationOutcome::Upgraded),
+            skipped: self.count(InstallationOutcome::Skipped),
+            failed: self.count(InstallationOutcome::Failed),
+            wall_clock_secs: wall_clock.as_secs_f64(),
+        }
+    }
+
+    fn count(&self, outcome: InstallationOutcome) -> usize {
+        self.outcomes
+            .values()
+            .filter(|recorded| **recorded == outcome)
+            .count()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use std::sync::{Arc, Mutex};
+    use std::{
+        collections::BTreeMap,
+        num::NonZeroU64,
+        sync::{Arc, Mutex},
+    };
 
-    use crate::ports::command::MockCommandRunner;
+    use crate::{
+        adapters::command::shell::{hanging_lookup_shell, ShellCommandRunner},
+        domain::shell::Shell,
+        ports::{command::MockCommandRunner, filesystem::MockFileSystem},
+    };
 
     fn create_test_env_config() -> EnvironmentConfig {
         EnvironmentConfig {
-            install: "test install".to_string(),
-            check: Some("test check".to_string()),
+            description: None,
+            homepage: None,
+            install: "test install".into(),
+            check: Some("test check".into()),
+            check_file: None,
+            check_path_command: None,
+            check_timeout: None,
+            check_inverted: false,
             dependencies: Vec::new(),
+            check_dependencies: Vec::new(),
+            check_implies_dependencies: false,
+            stdin: None,
+            skip_if: None,
+            installed_version_command: None,
+            shell: None,
         }
     }
 
@@ -519,20 +892,33 @@ mod tests {
     #[tokio::test]
     async fn test_execute_check_streaming_no_check_command() {
         let env_config = EnvironmentConfig {
-            install: "test install".to_string(),
+            description: None,
+            homepage: None,
+            install: "test install".into(),
             check: None,
+            check_file: None,
+            check_path_command: None,
+            check_timeout: None,
+            check_inverted: false,
             dependencies: Vec::new(),
+            check_dependencies: Vec::new(),
+            check_implies_dependencies: false,
+            stdin: None,
+            skip_if: None,
+            installed_version_command: None,
+            shell: None,
         };
 
         let installation = Installation::new(env_config).start();
         let runner = MockCommandRunner::new();
+        let fs = MockFileSystem::new();
 
         // Use Arc<Mutex<Vec>> to share ownership between closure and test
         let streamed_outputs = Arc::new(Mutex::new(Vec::new()));
         let outputs_clone = streamed_outputs.clone();
 
         let result = installation
-            .execute_check(&runner, move |chunk| {
+            .execute_check(&fs, &runner, Duration::from_secs(60), move |chunk| {
                 let mut outputs = outputs_clone.lock().unwrap();
                 match chunk {
                     OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
@@ -555,6 +941,7 @@ mod tests {
         let installation = Installation::new(env_config).start();
 
         let mut runner = MockCommandRunner::new();
+        let fs = MockFileSystem::new();
 
         // Add mock for execute_streaming
         let output = CommandOutput {
@@ -579,7 +966,7 @@ mod tests {
         let outputs_clone = streamed_outputs.clone();
 
         let result = installation
-            .execute_check(&runner, move |chunk| {
+            .execute_check(&fs, &runner, Duration::from_secs(60), move |chunk| {
                 let mut outputs = outputs_clone.lock().unwrap();
                 match chunk {
                     OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
@@ -604,6 +991,7 @@ mod tests {
         let installation = Installation::new(env_config).start();
 
         let mut runner = MockCommandRunner::new();
+        let fs = MockFileSystem::new();
 
         // Add mock for execute_streaming
         let output = CommandOutput {
@@ -620,7 +1008,10 @@ mod tests {
             .returning(move |_, _, mut callback| {
                 // Simulate streaming output
                 callback(OutputChunk::Stderr("Not found\n".to_string()));
-                Ok(output.clone())
+                Err(CommandError::NonZeroExit {
+                    command: "test check".to_string(),
+                    output: output.clone(),
+                })
             });
 
         // Use Arc<Mutex<Vec>> for thread-safe sharing
@@ -628,7 +1019,7 @@ mod tests {
         let outputs_clone = streamed_outputs.clone();
 
         let result = installation
-            .execute_check(&runner, move |chunk| {
+            .execute_check(&fs, &runner, Duration::from_secs(60), move |chunk| {
                 let mut outputs = outputs_clone.lock().unwrap();
                 match chunk {
                     OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
@@ -647,6 +1038,181 @@ mod tests {
         assert_eq!(outputs[0], "stderr: Not found\n");
     }
 
+    #[tokio::test]
+    async fn test_execute_check_command_not_found() {
+        // A missing binary is a failing check: the package isn't installed,
+        // or, for an inverted check, it's already absent
+        for (check_inverted, already_installed) in [(false, false), (true, true)] {
+            let env_config = create_test_env_config().with_check_inverted(check_inverted);
+
+            let mut runner = MockCommandRunner::new();
+            runner.mock_execute_streaming_exit("test check", 60, 127, "sh: 1: test: not found\n");
+            let fs = MockFileSystem::new();
+
+            let warnings = Arc::new(Mutex::new(Vec::new()));
+            let warnings_clone = Arc::clone(&warnings);
+            let state = Installation::new(env_config)
+                .start()
+                .execute_check(&fs, &runner, Duration::from_secs(60), move |chunk| {
+                    if let OutputChunk::Stderr(line) = chunk {
+                        warnings_clone.lock().unwrap().push(line);
+                    }
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(
+                matches!(state, Installation::AlreadyInstalled { .. }),
+                already_installed
+            );
+            assert!(!matches!(state, Installation::Failed { .. }));
+
+            let warnings = warnings.lock().unwrap().clone();
+            assert!(warnings
+                .iter()
+                .any(|line| line.contains("check command not found (exit status 127)")));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_check_inverted() {
+        let env_config = create_test_env_config().with_check_inverted(true);
+
+        for (status, already_installed) in [(0, false), (1, true)] {
+            let mut runner = MockCommandRunner::new();
+            runner.mock_execute_streaming_ok(
+                "test check",
+                Duration::from_secs(60),
+                CommandOutput {
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    status,
+                    success: status == 0,
+                    duration: Duration::from_millis(100),
+                },
+            );
+            let fs = MockFileSystem::new();
+
+            let state = Installation::new(env_config.clone())
+                .start()
+                .execute_check(&fs, &runner, Duration::from_secs(60), |_| {})
+                .await
+                .unwrap();
+
+            assert_eq!(
+                matches!(state, Installation::AlreadyInstalled { .. }),
+                already_installed
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_check_timed_out() {
+        // The package's own timeout wins over the one passed in
+        let env_config = create_test_env_config().with_check_timeout(NonZeroU64::new(5).unwrap());
+        let installation = Installation::new(env_config).start();
+
+        let mut runner = MockCommandRunner::new();
+        runner
+            .expect_execute_streaming()
+            .withf(|cmd, timeout, _| cmd == "test check" && *timeout == Duration::from_secs(5))
+            .returning(|_, timeout, _| Err(CommandError::Timeout(timeout)));
+        let fs = MockFileSystem::new();
+
+        let state = installation
+            .execute_check(&fs, &runner, Duration::from_secs(60), |_| {})
+            .await
+            .unwrap();
+
+        // Not the same as the package not being installed
+        assert_eq!(
+            state.status(),
+            InstallationStatus::CheckTimedOut(Duration::from_secs(5))
+        );
+        assert!(matches!(
+            state.into_result("test-package".to_string()),
+            Err(InstallationError::CheckTimedOut(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_check_file() {
+        // check_file takes precedence over check_path_command, so the runner
+        // is never asked
+        let env_config = EnvironmentConfig::new("test install")
+            .with_check_file("~/.cargo/bin/rg")
+            .with_check_path_command("rg");
+
+        let runner = MockCommandRunner::new();
+        let mut fs = MockFileSystem::new();
+        fs.mock_expand_path("~/.cargo/bin/rg", "/home/test/.cargo/bin/rg");
+        fs.mock_path_exists("/home/test/.cargo/bin/rg", true);
+
+        let result = Installation::new(env_config)
+            .start()
+            .execute_check(&fs, &runner, Duration::from_secs(60), |_| {})
+            .await
+            .unwrap();
+
+        assert!(matches!(result, Installation::AlreadyInstalled { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_check_path_command() {
+        let env_config = EnvironmentConfig::new("test install").with_check_path_command("rg");
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_is_command_available("rg", false);
+        let fs = MockFileSystem::new();
+
+        let result = Installation::new(env_config)
+            .start()
+            .execute_check(&fs, &runner, Duration::from_secs(60), |_| {})
+            .await
+            .unwrap();
+
+        assert!(matches!(result, Installation::NotAlreadyInstalled { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_check_path_command_lookup_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = ShellCommandRunner::new(
+            &hanging_lookup_shell(dir.path()),
+            Duration::from_millis(200),
+        );
+        let fs = MockFileSystem::new();
+        let env_config = EnvironmentConfig::new("echo installed").with_check_path_command("rg");
+
+        let reasons = Arc::new(Mutex::new(Vec::new()));
+        let reasons_clone = reasons.clone();
+        let installation = Installation::new(env_config)
+            .start()
+            .execute_check(&fs, &runner, Duration::from_secs(60), move |chunk| {
+                if let OutputChunk::Stderr(line) = chunk {
+                    reasons_clone.lock().unwrap().push(line);
+                }
+            })
+            .await
+            .unwrap();
+
+        // Not installed, rather than failed, with the reason for --verbose
+        assert!(matches!(
+            installation,
+            Installation::NotAlreadyInstalled { .. }
+        ));
+        let reasons = reasons.lock().unwrap().clone();
+        assert_eq!(reasons.len(), 1);
+        assert!(reasons[0].contains("looking it up failed"));
+
+        // And the install carries on
+        let installation = installation
+            .execute_install(&runner, |_| {}, |_, _, _| {})
+            .await
+            .unwrap();
+        assert_eq!(installation.status(), InstallationStatus::Complete);
+    }
+
     #[tokio::test]
     async fn test_execute_install_streaming_success() {
         let env_config = create_test_env_config();
@@ -680,13 +1246,17 @@ mod tests {
         let outputs_clone = streamed_outputs.clone();
 
         let result = installation
-            .execute_install(&runner, move |chunk| {
-                let mut outputs = outputs_clone.lock().unwrap();
-                match chunk {
-                    OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
-                    OutputChunk::Stderr(line) => outputs.push(format!("stderr: {}", line)),
-                }
-            })
+            .execute_install(
+                &runner,
+                move |chunk| {
+                    let mut outputs = outputs_clone.lock().unwrap();
+                    match chunk {
+                        OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
+                        OutputChunk::Stderr(line) => outputs.push(format!("stderr: {}", line)),
+                    }
+                },
+                |_, _, _| {},
+            )
             .await;
 
         assert!(result.is_ok());
@@ -729,7 +1299,10 @@ mod tests {
                 callback(OutputChunk::Stderr(
                     "Error: Installation failed\n".to_string(),
                 ));
-                Ok(output.clone())
+                Err(CommandError::NonZeroExit {
+                    command: "test install".to_string(),
+                    output: output.clone(),
+                })
             });
 
         // Use Arc<Mutex<Vec>> for thread-safe sharing
@@ -737,13 +1310,17 @@ mod tests {
         let outputs_clone = streamed_outputs.clone();
 
         let result = installation
-            .execute_install(&runner, move |chunk| {
-                let mut outputs = outputs_clone.lock().unwrap();
-                match chunk {
-                    OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
-                    OutputChunk::Stderr(line) => outputs.push(format!("stderr: {}", line)),
-                }
-            })
+            .execute_install(
+                &runner,
+                move |chunk| {
+                    let mut outputs = outputs_clone.lock().unwrap();
+                    match chunk {
+                        OutputChunk::Stdout(line) => outputs.push(format!("stdout: {}", line)),
+                        OutputChunk::Stderr(line) => outputs.push(format!("stderr: {}", line)),
+                    }
+                },
+                |_, _, _| {},
+            )
             .await;
 
         assert!(result.is_ok());
@@ -773,7 +1350,9 @@ mod tests {
             .returning(|_, timeout, _| Err(CommandError::Timeout(timeout)));
 
         // Execute the command
-        let result = installation.execute_install(&runner, |_| {}).await;
+        let result = installation
+            .execute_install(&runner, |_| {}, |_, _, _| {})
+            .await;
 
         assert!(result.is_ok());
         let state = result.unwrap();
@@ -787,6 +1366,153 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_execute_install_script_stops_on_failure() {
+        let env_config = EnvironmentConfig {
+            install: InstallCommand::Script(vec![
+                "step one".to_string(),
+                "step two".to_string(),
+                "step three".to_string(),
+            ]),
+            ..create_test_env_config()
+        };
+        let installation = Installation::new(env_config)
+            .start()
+            .mark_not_already_installed();
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_streaming_success_0("step one", 600, "ok");
+        runner.mock_execute_streaming_exit("step two", 600, 2, "broken");
+        // "step three" has no expectation, so running it would panic
+
+        let mut steps = Vec::new();
+        let state = installation
+            .execute_install(
+                &runner,
+                |_| {},
+                |step, total, command: &str| steps.push(format!("{}/{} {}", step, total, command)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(steps, vec!["1/3 step one", "2/3 step two"]);
+        match &state {
+            Installation::Failed { error_message, .. } => {
+                assert_eq!(error_message, "Install step 2 of 3 failed with status 2");
+            }
+            _ => panic!("Expected Failed state, got {:?}", state),
+        }
+
+        // The exit code carries through to the installation's error
+        assert!(matches!(
+            state.into_result("test-package".to_string()),
+            Err(InstallationError::CommandError(CommandError::NonZeroExit { ref command, ref output }))
+                if command == "step two" && output.status == 2 && output.stderr == "broken"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_install_without_command_for_arch() {
+        let env_config = EnvironmentConfig {
+            install: InstallCommand::ByArch(BTreeMap::from([(
+                "not-this-arch".to_string(),
+                InstallCommand::Single("test install".to_string()),
+            )])),
+            ..create_test_env_config()
+        };
+        let installation = Installation::new(env_config)
+            .start()
+            .mark_not_already_installed();
+
+        // Nothing runs
+        let runner = MockCommandRunner::new();
+        let state = installation
+            .execute_install(&runner, |_| {}, |_, _, _: &str| {})
+            .await
+            .unwrap();
+
+        match &state {
+            Installation::Failed { error_message, .. } => assert_eq!(
+                *error_message,
+                format!("No install command defined for {}", std::env::consts::ARCH)
+            ),
+            _ => panic!("Expected Failed state, got {:?}", state),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_install_with_shell() {
+        let env_config = create_test_env_config().with_shell(Shell::Bash);
+        let installation = Installation::new(env_config)
+            .start()
+            .mark_not_already_installed();
+
+        // The install command only runs on the runner for the package's shell
+        let mut runner = MockCommandRunner::new();
+        runner
+            .expect_with_shell()
+            .with(mockall::predicate::eq(Shell::Bash))
+            .times(1)
+            .returning(|_| {
+                let mut bash_runner = MockCommandRunner::new();
+                bash_runner.mock_execute_streaming_success_0("test install", 600, "ok");
+                bash_runner
+            });
+
+        let state = installation
+            .execute_install(&runner, |_| {}, |_, _, _| {})
+            .await
+            .unwrap();
+
+        assert!(matches!(state, Installation::Complete { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_install_with_stdin() {
+        let env_config = EnvironmentConfig {
+            stdin: Some("yes\n".to_string()),
+            ..create_test_env_config()
+        };
+        let installation = Installation::new(env_config)
+            .start()
+            .mark_not_already_installed();
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_with_stdin_ok(
+            "test install",
+            "yes\n",
+            Duration::from_secs(600),
+            CommandOutput {
+                stdout: "installed\n".to_string(),
+                stderr: "warning: old config\n".to_string(),
+                status: 0,
+                success: true,
+                ..Default::default()
+            },
+        );
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let output_clone = Arc::clone(&output);
+        let state = installation
+            .execute_install(
+                &runner,
+                move |chunk| {
+                    if let OutputChunk::Stdout(line) = chunk {
+                        output_clone.lock().unwrap().push(line);
+                    }
+                },
+                |_, _, _| {},
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(state, Installation::Complete { .. }));
+        assert_eq!(
+            *output.lock().unwrap(),
+            vec!["installed\nwarning: old config\n".to_string()]
+        );
+    }
+
     #[test]
     fn test_into_result() {
         let env_config = create_test_env_config();
@@ -841,6 +1567,9 @@ mod tests {
             package_name: "main".to_string(),
             status: InstallationStatus::Complete,
             duration: Duration::from_secs(5),
+            wall_clock: Duration::from_secs(5),
+            attempts: 1,
+            last_error: None,
             command_output: None,
             dependencies: Vec::new(),
         };
@@ -849,6 +1578,9 @@ mod tests {
             package_name: "dep1".to_string(),
             status: InstallationStatus::Complete,
             duration: Duration::from_secs(3),
+            wall_clock: Duration::from_secs(3),
+            attempts: 1,
+            last_error: None,
             command_output: None,
             dependencies: Vec::new(),
         };
@@ -857,6 +1589,9 @@ mod tests {
             package_name: "dep2".to_string(),
             status: InstallationStatus::Complete,
             duration: Duration::from_secs(2),
+            wall_clock: Duration::from_secs(2),
+            attempts: 1,
+            last_error: None,
             command_output: None,
             dependencies: Vec::new(),
         };
@@ -876,4 +1611,92 @@ mod tests {
             Duration::from_secs(5)
         ); // 3 + 2
     }
+
+    #[test]
+    fn test_dependency_timings_sorted_by_duration() {
+        let dep = |name: &str, secs| InstallationReport {
+            package_name: name.to_string(),
+            status: InstallationStatus::Complete,
+            duration: Duration::from_secs(secs),
+            wall_clock: Duration::from_secs(secs),
+            attempts: 1,
+            last_error: None,
+            command_output: None,
+            dependencies: Vec::new(),
+        };
+
+        let result = InstallationReport {
+            package_name: "main".to_string(),
+            status: InstallationStatus::Complete,
+            duration: Duration::from_secs(1),
+            wall_clock: Duration::from_secs(1),
+            attempts: 1,
+            last_error: None,
+            command_output: None,
+            dependencies: Vec::new(),
+        }
+        .with_dependencies(vec![dep("fast", 1), dep("slow", 7), dep("medium", 3)]);
+
+        assert_eq!(
+            result.dependency_timings(),
+            vec![
+                ("slow", Duration::from_secs(7)),
+                ("medium", Duration::from_secs(3)),
+                ("fast", Duration::from_secs(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_installation_summary() {
+        let report = |name: &str, status: InstallationStatus| InstallationReport {
+            package_name: name.to_string(),
+            status,
+            duration: Duration::ZERO,
+            wall_clock: Duration::ZERO,
+            attempts: 1,
+            last_error: None,
+            command_output: None,
+            dependencies: Vec::new(),
+        };
+
+        let mut summary = InstallationSummary::default();
+        summary.record(
+            &report("fd", InstallationStatus::Complete)
+                .with_dependencies(vec![report("rust", InstallationStatus::AlreadyInstalled)]),
+        );
+        // Already handled as fd's dependency, so it isn't counted again
+        summary.record(&report(
+            "rust",
+            InstallationStatus::Skipped("Already handled earlier in this run".to_string()),
+        ));
+        summary.record(&report("bat", InstallationStatus::Complete));
+        summary.record_upgrade("bat");
+        assert!(!summary.has_failures());
+
+        // A failed install counts what it got to before failing
+        summary.record_statuses(&[
+            ("cmake".to_string(), InstallationStatus::Complete),
+            (
+                "ripgrep".to_string(),
+                InstallationStatus::Failed("exit 1".to_string()),
+            ),
+            ("rg-config".to_string(), InstallationStatus::Pending),
+        ]);
+        summary.record_failure("ripgrep");
+        assert!(summary.has_failures());
+
+        assert_eq!(
+            summary.stats(Duration::from_millis(1500)),
+            InstallationStats {
+                total: 6,
+                installed: 2,
+                already_installed: 1,
+                upgraded: 1,
+                skipped: 1,
+                failed: 1,
+                wall_clock_secs: 1.5,
+            }
+        );
+    }
 }
@@ -1,20 +1,26 @@
 // Core package entity and related types
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    num::NonZeroU64,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    domain::validation::{ValidationErrorCategory, ValidationIssue},
-    ports::filesystem::FileSystem,
+    domain::{
+        shell::Shell,
+        validation::{AutoFix, ValidationErrorCategory, ValidationIssue},
+    },
+    ports::filesystem::{FileSystem, FileSystemError},
 };
 
 /// Core package entity representing a package definition
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub(crate) struct Package {
+pub struct Package {
     /// Package name
     pub(crate) name: String,
 
@@ -29,6 +35,15 @@ pub(crate) struct Package {
     #[serde(default)]
     pub(crate) description: Option<String>,
 
+    /// Other names the package can be referred to by, e.g. `rg` for
+    /// `ripgrep`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) aliases: Vec<String>,
+
+    /// Labels for picking out groups of packages, e.g. `rust` or `work`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+
     /// Map of environment configurations
     #[serde(default)]
     pub(crate) environments: HashMap<String, EnvironmentConfig>,
@@ -40,17 +55,522 @@ pub(crate) struct Package {
 
 /// Configuration for a specific environment
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub(crate) struct EnvironmentConfig {
-    /// Command to install the package
-    pub(crate) install: String,
+pub struct EnvironmentConfig {
+    /// Optional description of how the package behaves in this environment
+    #[serde(default)]
+    pub(crate) description: Option<String>,
 
-    /// Optional command to check if the package is already installed
+    /// Optional homepage for what this environment installs, e.g. a fork,
+    /// instead of the package's
     #[serde(default)]
-    pub(crate) check: Option<String>,
+    pub(crate) homepage: Option<String>,
+
+    /// Command (or commands) to install the package, optionally per CPU
+    /// architecture
+    pub(crate) install: InstallCommand,
 
-    /// Dependencies that must be installed before this package
+    /// Optional command to check if the package is already installed,
+    /// optionally per CPU architecture
     #[serde(default)]
-    pub(crate) dependencies: Vec<String>,
+    pub(crate) check: Option<CheckCommand>,
+
+    /// Optional file whose existence means the package is already installed.
+    /// Only used when there's no `check` command.
+    #[serde(default)]
+    pub(crate) check_file: Option<PathBuf>,
+
+    /// Optional binary whose presence on PATH means the package is already
+    /// installed. Only used when there's no `check` command or `check_file`.
+    #[serde(default)]
+    pub(crate) check_path_command: Option<String>,
+
+    /// Optional number of seconds the `check` command may run, instead of
+    /// the configured `check_timeout`
+    #[serde(default)]
+    pub(crate) check_timeout: Option<NonZeroU64>,
+
+    /// Whether the `check` command passing means the package still needs
+    /// installing, e.g. for a package that removes something
+    #[serde(default)]
+    pub(crate) check_inverted: bool,
+
+    /// Dependencies that must be installed before this package, each
+    /// optionally only in some environments or when a command is missing
+    #[serde(default)]
+    pub(crate) dependencies: Vec<Dependency>,
+
+    /// Packages the check command uses, which must be installed before the
+    /// check runs
+    #[serde(default)]
+    pub(crate) check_dependencies: Vec<String>,
+
+    /// Whether a passing check also means every dependency is installed, so
+    /// they don't need to be checked or installed
+    #[serde(default)]
+    pub(crate) check_implies_dependencies: bool,
+
+    /// Optional input piped to the install command (to each step of an
+    /// install script)
+    #[serde(default)]
+    pub(crate) stdin: Option<String>,
+
+    /// Optional guard command; when it exits 0 the package is skipped on this
+    /// machine, without being checked or installed
+    #[serde(default)]
+    pub(crate) skip_if: Option<String>,
+
+    /// Optional command that prints the installed version of the package, for
+    /// comparing with the package's `version`
+    #[serde(default)]
+    pub(crate) installed_version_command: Option<String>,
+
+    /// Optional interpreter for the install and check commands, instead of
+    /// the shell selfie runs everything else with
+    #[serde(default)]
+    pub(crate) shell: Option<Shell>,
+}
+
+impl EnvironmentConfig {
+    /// Create an environment configuration that installs with `install`
+    pub fn new(install: impl Into<InstallCommand>) -> Self {
+        Self {
+            description: None,
+            homepage: None,
+            install: install.into(),
+            check: None,
+            check_file: None,
+            check_path_command: None,
+            check_timeout: None,
+            check_inverted: false,
+            dependencies: Vec::new(),
+            check_dependencies: Vec::new(),
+            check_implies_dependencies: false,
+            stdin: None,
+            skip_if: None,
+            installed_version_command: None,
+            shell: None,
+        }
+    }
+
+    /// Set the description of how the package behaves in this environment
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Set the homepage of what this environment installs
+    pub fn with_homepage(mut self, homepage: &str) -> Self {
+        self.homepage = Some(homepage.to_string());
+        self
+    }
+
+    /// Set the command that checks whether the package is already installed
+    pub fn with_check(mut self, check: &str) -> Self {
+        self.check = Some(check.into());
+        self
+    }
+
+    /// Set the file whose existence means the package is already installed
+    pub fn with_check_file(mut self, check_file: impl Into<PathBuf>) -> Self {
+        self.check_file = Some(check_file.into());
+        self
+    }
+
+    /// Set the binary whose presence on PATH means the package is already
+    /// installed
+    pub fn with_check_path_command(mut self, command: &str) -> Self {
+        self.check_path_command = Some(command.to_string());
+        self
+    }
+
+    /// Set the packages that must be installed first
+    pub fn with_dependencies<I, S>(mut self, dependencies: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Dependency>,
+    {
+        self.dependencies = dependencies.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the packages that must be installed before the check runs
+    pub fn with_check_dependencies<I, S>(mut self, dependencies: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.check_dependencies = dependencies.into_iter().map(|d| d.to_string()).collect();
+        self
+    }
+
+    /// Set whether a passing check means the dependencies are installed too
+    pub fn with_check_implies_dependencies(mut self, implies: bool) -> Self {
+        self.check_implies_dependencies = implies;
+        self
+    }
+
+    /// Set how long the check command may run before it's timed out
+    pub fn with_check_timeout(mut self, timeout: NonZeroU64) -> Self {
+        self.check_timeout = Some(timeout);
+        self
+    }
+
+    /// Set whether a passing check means the package still needs installing
+    pub fn with_check_inverted(mut self, inverted: bool) -> Self {
+        self.check_inverted = inverted;
+        self
+    }
+
+    /// Set the input piped to the install command
+    pub fn with_stdin(mut self, stdin: &str) -> Self {
+        self.stdin = Some(stdin.to_string());
+        self
+    }
+
+    /// Set the command that, when it succeeds, skips the package
+    pub fn with_skip_if(mut self, skip_if: &str) -> Self {
+        self.skip_if = Some(skip_if.to_string());
+        self
+    }
+
+    /// Set the command that prints the installed version of the package
+    pub fn with_installed_version_command(mut self, command: &str) -> Self {
+        self.installed_version_command = Some(command.to_string());
+        self
+    }
+
+    /// Run the install and check commands under `shell`
+    pub fn with_shell(mut self, shell: Shell) -> Self {
+        self.shell = Some(shell);
+        self
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref()
+    }
+
+    pub fn install(&self) -> &InstallCommand {
+        &self.install
+    }
+
+    /// The check command for the architecture selfie is running on
+    pub fn check(&self) -> Option<&str> {
+        self.check.as_ref().and_then(CheckCommand::for_current_arch)
+    }
+
+    pub fn check_file(&self) -> Option<&Path> {
+        self.check_file.as_deref()
+    }
+
+    pub fn check_path_command(&self) -> Option<&str> {
+        self.check_path_command.as_deref()
+    }
+
+    /// How long the check command may run, when the package sets it
+    pub fn check_timeout(&self) -> Option<Duration> {
+        self.check_timeout
+            .map(|timeout| Duration::from_secs(timeout.get()))
+    }
+
+    /// Whether any way of checking if the package is already installed is
+    /// configured
+    pub fn has_check(&self) -> bool {
+        self.check().is_some() || self.check_file.is_some() || self.check_path_command.is_some()
+    }
+
+    pub fn check_inverted(&self) -> bool {
+        self.check_inverted
+    }
+
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.dependencies
+    }
+
+    /// The names of the install dependencies, whatever their conditions
+    pub fn dependency_names(&self) -> impl Iterator<Item = &str> {
+        self.dependencies.iter().map(Dependency::name)
+    }
+
+    pub fn check_dependencies(&self) -> &[String] {
+        &self.check_dependencies
+    }
+
+    /// Every dependency, install and check, in declaration order, whatever
+    /// their conditions
+    pub(crate) fn all_dependencies(&self) -> impl Iterator<Item = &str> {
+        self.dependency_names()
+            .chain(self.check_dependencies.iter().map(String::as_str))
+    }
+
+    pub fn check_implies_dependencies(&self) -> bool {
+        self.check_implies_dependencies
+    }
+
+    pub fn stdin(&self) -> Option<&str> {
+        self.stdin.as_deref()
+    }
+
+    pub fn skip_if(&self) -> Option<&str> {
+        self.skip_if.as_deref()
+    }
+
+    pub fn installed_version_command(&self) -> Option<&str> {
+        self.installed_version_command.as_deref()
+    }
+
+    pub fn shell(&self) -> Option<Shell> {
+        self.shell
+    }
+}
+
+/// Key of the entry used by architectures without one of their own, in
+/// commands given per architecture
+pub(crate) const DEFAULT_ARCH: &str = "default";
+
+/// The entry of a per-architecture map for `arch`, or the default one
+fn for_arch<'a, T>(by_arch: &'a BTreeMap<String, T>, arch: &str) -> Option<&'a T> {
+    by_arch.get(arch).or_else(|| by_arch.get(DEFAULT_ARCH))
+}
+
+/// How a package gets installed: either a single shell command, or a script of
+/// commands that are run in order, stopping at the first one that fails. Either
+/// can be given per CPU architecture, keyed like [`std::env::consts::ARCH`]
+/// (e.g. `aarch64` or `x86_64`), with a `default` for any other.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InstallCommand {
+    Single(String),
+    Script(Vec<String>),
+    ByArch(BTreeMap<String, InstallCommand>),
+}
+
+impl InstallCommand {
+    /// The command for `arch`; `None` when it's given per architecture with
+    /// neither an entry for `arch` nor a default
+    pub fn for_arch(&self, arch: &str) -> Option<&Self> {
+        match self {
+            Self::ByArch(by_arch) => for_arch(by_arch, arch)?.for_arch(arch),
+            command => Some(command),
+        }
+    }
+
+    /// The command for the architecture selfie is running on
+    pub fn for_current_arch(&self) -> Option<&Self> {
+        self.for_arch(std::env::consts::ARCH)
+    }
+
+    /// All commands for the current architecture, in the order they should
+    /// run
+    pub fn commands(&self) -> &[String] {
+        match self.for_current_arch() {
+            Some(Self::Single(command)) => std::slice::from_ref(command),
+            Some(Self::Script(commands)) => commands,
+            Some(Self::ByArch(_)) | None => &[],
+        }
+    }
+
+    /// The first command to run; availability checks are based on this one
+    pub fn first_command(&self) -> &str {
+        self.commands()
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Whether there's nothing to run, on any architecture
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Single(command) => command.is_empty(),
+            Self::Script(commands) => commands.iter().all(String::is_empty),
+            Self::ByArch(by_arch) => by_arch.values().all(Self::is_empty),
+        }
+    }
+
+    /// The same commands, each prefixed verbatim with `wrapper`
+    pub fn wrapped(&self, wrapper: &str) -> Self {
+        let wrap = |command: &String| format!("{} {}", wrapper, command);
+
+        match self {
+            Self::Single(command) => Self::Single(wrap(command)),
+            Self::Script(commands) => Self::Script(commands.iter().map(wrap).collect()),
+            Self::ByArch(by_arch) => Self::ByArch(
+                by_arch
+                    .iter()
+                    .map(|(arch, command)| (arch.clone(), command.wrapped(wrapper)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for InstallCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.commands().join(" && "))
+    }
+}
+
+impl From<&str> for InstallCommand {
+    fn from(command: &str) -> Self {
+        Self::Single(command.to_string())
+    }
+}
+
+impl From<String> for InstallCommand {
+    fn from(command: String) -> Self {
+        Self::Single(command)
+    }
+}
+
+impl From<Vec<String>> for InstallCommand {
+    fn from(commands: Vec<String>) -> Self {
+        Self::Script(commands)
+    }
+}
+
+impl PartialEq<&str> for InstallCommand {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Self::Single(command) if command == other)
+    }
+}
+
+/// A command that checks whether a package is installed, either the same on
+/// every architecture or per architecture like [`InstallCommand`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CheckCommand {
+    Single(String),
+    ByArch(BTreeMap<String, String>),
+}
+
+impl CheckCommand {
+    /// The command for `arch`; `None` when it's given per architecture with
+    /// neither an entry for `arch` nor a default
+    pub fn for_arch(&self, arch: &str) -> Option<&str> {
+        match self {
+            Self::Single(command) => Some(command),
+            Self::ByArch(by_arch) => for_arch(by_arch, arch).map(String::as_str),
+        }
+    }
+
+    /// The command for the architecture selfie is running on
+    pub fn for_current_arch(&self) -> Option<&str> {
+        self.for_arch(std::env::consts::ARCH)
+    }
+}
+
+impl From<&str> for CheckCommand {
+    fn from(command: &str) -> Self {
+        Self::Single(command.to_string())
+    }
+}
+
+impl From<String> for CheckCommand {
+    fn from(command: String) -> Self {
+        Self::Single(command)
+    }
+}
+
+impl PartialEq<&str> for CheckCommand {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Self::Single(command) if command == other)
+    }
+}
+
+/// A package that must be installed first: either just its name, or its name
+/// with conditions on when it's needed. A conditional dependency is only
+/// needed in the environments in `when_env` (any, when empty), and only when
+/// `unless_command` isn't available.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Dependency {
+    Name(String),
+    Conditional {
+        name: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        when_env: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unless_command: Option<String>,
+    },
+}
+
+impl Dependency {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) | Self::Conditional { name, .. } => name,
+        }
+    }
+
+    /// The command whose presence makes the dependency unnecessary
+    pub fn unless_command(&self) -> Option<&str> {
+        match self {
+            Self::Name(_) => None,
+            Self::Conditional { unless_command, .. } => unless_command.as_deref(),
+        }
+    }
+
+    /// When the dependency is needed, e.g. "on ubuntu or debian, unless cc is
+    /// available"; empty for an unconditional one
+    pub fn conditions(&self) -> String {
+        let Self::Conditional {
+            when_env,
+            unless_command,
+            ..
+        } = self
+        else {
+            return String::new();
+        };
+
+        let mut conditions = Vec::new();
+        if !when_env.is_empty() {
+            conditions.push(format!("on {}", when_env.join(" or ")));
+        }
+        if let Some(command) = unless_command {
+            conditions.push(format!("unless {} is available", command));
+        }
+        conditions.join(", ")
+    }
+
+    /// Whether the dependency is needed in `environment`, which is also known
+    /// by `aliases`
+    pub fn applies_to_environment(&self, environment: &str, aliases: &[String]) -> bool {
+        match self {
+            Self::Name(_) => true,
+            Self::Conditional { when_env, .. } => {
+                when_env.is_empty()
+                    || when_env
+                        .iter()
+                        .any(|env| env == environment || aliases.contains(env))
+            }
+        }
+    }
+}
+
+impl From<&str> for Dependency {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for Dependency {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl PartialEq<&str> for Dependency {
+    fn eq(&self, other: &&str) -> bool {
+        self.name() == *other
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 /// Errors related to package validation
@@ -74,7 +594,7 @@ pub(crate) enum PackageValidationError {
 
 /// Errors related to package parsing
 #[derive(Error, Debug)]
-pub(crate) enum PackageParseError {
+pub enum PackageParseError {
     #[error("YAML parsing error: {0}")]
     YamlParse(#[from] serde_yaml::Error),
 
@@ -82,13 +602,12 @@ pub(crate) enum PackageParseError {
     IoError(#[from] std::io::Error),
 
     #[error("File system error: {0}")]
-    FileSystemError(String),
+    FileSystemError(#[from] FileSystemError),
 }
 
 impl Package {
     /// Create a new package with the specified attributes
-    #[cfg(test)]
-    pub(crate) fn new(
+    pub fn new(
         name: String,
         version: String,
         homepage: Option<String>,
@@ -101,43 +620,142 @@ impl Package {
             version,
             homepage,
             description,
+            aliases: Vec::new(),
+            tags: Vec::new(),
             environments,
             path,
         }
     }
 
-    /// Resolve an environment configuration by name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn homepage(&self) -> Option<&str> {
+        self.homepage.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Homepage of what `env_config` installs: the environment's own if it
+    /// has one, else the package's
+    pub fn homepage_for<'p>(&'p self, env_config: &'p EnvironmentConfig) -> Option<&'p str> {
+        env_config.homepage().or(self.homepage())
+    }
+
+    /// Description of the package in `env_config`: the environment's own if
+    /// it has one, else the package's
+    pub fn description_for<'p>(&'p self, env_config: &'p EnvironmentConfig) -> Option<&'p str> {
+        env_config.description().or(self.description())
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Whether `name` is the package's name or one of its aliases
+    pub(crate) fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+
+    /// Real name of the package among `packages` that `name` refers to: the
+    /// one named `name`, else one with it as an alias, else `name` itself
+    pub(crate) fn resolve_name<'p>(packages: &'p [Package], name: &'p str) -> &'p str {
+        packages
+            .iter()
+            .find(|package| package.name == name)
+            .or_else(|| packages.iter().find(|package| package.is_named(name)))
+            .map_or(name, |package| package.name.as_str())
+    }
+
+    pub fn environments(&self) -> &HashMap<String, EnvironmentConfig> {
+        &self.environments
+    }
+
+    /// Path of the file the package was loaded from; empty for packages that
+    /// were built in code
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// File name to save the package under in a package directory
+    pub fn file_name(&self) -> String {
+        format!("{}.yaml", self.name)
+    }
+
+    /// The environment this package declares for `environment_name`, with the
+    /// name it's declared under: its own entry when there is one, otherwise
+    /// the first of `aliases` the package has
+    pub(crate) fn find_environment(
+        &self,
+        environment_name: &str,
+        aliases: &[String],
+    ) -> Option<(&str, &EnvironmentConfig)> {
+        std::iter::once(environment_name)
+            .chain(aliases.iter().map(String::as_str))
+            .find_map(|name| self.environments.get_key_value(name))
+            .map(|(name, env_config)| (name.as_str(), env_config))
+    }
+
+    /// Resolve an environment configuration by name, or by one of its
+    /// `aliases`
     pub(crate) fn resolve_environment(
         &self,
         environment_name: &str,
+        aliases: &[String],
     ) -> Result<&EnvironmentConfig, PackageValidationError> {
-        self.environments.get(environment_name).ok_or_else(|| {
-            if self.environments.is_empty() {
-                PackageValidationError::MissingField("environments".to_string())
-            } else {
-                PackageValidationError::EnvironmentNotSupported(environment_name.to_string())
-            }
-        })
+        self.find_environment(environment_name, aliases)
+            .map(|(_, env_config)| env_config)
+            .ok_or_else(|| {
+                if self.environments.is_empty() {
+                    PackageValidationError::MissingField("environments".to_string())
+                } else {
+                    PackageValidationError::EnvironmentNotSupported(environment_name.to_string())
+                }
+            })
     }
 
-    pub(crate) fn from_yaml(yaml_str: &str) -> Result<Self, PackageParseError> {
-        let mut package: Self = serde_yaml::from_str(yaml_str)?;
+    /// Parse a package from the contents of a package file
+    pub fn from_yaml(yaml_str: &str) -> Result<Self, PackageParseError> {
+        let package: Self = serde_yaml::from_str(yaml_str)?;
 
-        // Ensure defaults are set
-        for env_config in package.environments.values_mut() {
-            if env_config.dependencies.is_empty() {
-                env_config.dependencies = Vec::new();
+        Ok(package)
+    }
+
+    /// Parse a combined package file: a map of package name to package body.
+    ///
+    /// A body may omit `name`, in which case the map key is used.
+    pub(crate) fn from_combined_yaml(yaml_str: &str) -> Result<Vec<Self>, PackageParseError> {
+        let entries: serde_yaml::Mapping = serde_yaml::from_str(yaml_str)?;
+
+        let mut packages = Vec::with_capacity(entries.len());
+        for (key, mut body) in entries {
+            if let (Some(name), serde_yaml::Value::Mapping(fields)) = (key.as_str(), &mut body) {
+                let name_key = serde_yaml::Value::from("name");
+                if !fields.contains_key(&name_key) {
+                    fields.insert(name_key, serde_yaml::Value::from(name));
+                }
             }
+
+            packages.push(serde_yaml::from_value(body)?);
         }
 
-        Ok(package)
+        Ok(packages)
     }
 
     // Load a Package from a file using the FileSystem trait
     pub(crate) fn from_file<F: FileSystem>(fs: &F, path: &Path) -> Result<Self, PackageParseError> {
-        let content = fs
-            .read_file(path)
-            .map_err(|e| PackageParseError::FileSystemError(e.to_string()))?;
+        let content = fs.read_file(path)?;
 
         let mut package = Self::from_yaml(&content)?;
         package.path = path.to_path_buf();
@@ -145,9 +763,8 @@ impl Package {
         Ok(package)
     }
 
-    // Serialize to YAML
-    #[cfg(test)]
-    pub(crate) fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+    /// Serialize to YAML, in the format package files are read in
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
         serde_yaml::to_string(self)
     }
 
@@ -174,15 +791,29 @@ impl Package {
             ));
         }
 
-        // Check version
-        if self.version.is_empty() {
+        // Check aliases; one naming the package itself is redundant at best
+        if !self.name.is_empty() && self.aliases.contains(&self.name) {
             issues.push(ValidationIssue::error(
-                ValidationErrorCategory::RequiredField,
-                "version",
-                "Package version is required",
+                ValidationErrorCategory::InvalidValue,
+                "aliases",
+                "Package lists its own name as an alias",
                 None,
-                Some("Add 'version: \"0.1.0\"' to the package file."),
+                Some("Remove the package's own name from 'aliases'."),
             ));
+        }
+
+        // Check version
+        if self.version.is_empty() {
+            issues.push(
+                ValidationIssue::error(
+                    ValidationErrorCategory::RequiredField,
+                    "version",
+                    "Package version is required",
+                    None,
+                    Some("Add 'version: \"0.1.0\"' to the package file."),
+                )
+                .with_fix(AutoFix::set("version", "0.1.0")),
+            );
         } else if !Self::is_valid_version(&self.version) {
             issues.push(ValidationIssue::warning(
                 ValidationErrorCategory::InvalidValue,
@@ -207,93 +838,246 @@ impl Package {
         issues
     }
 
-    /// Validate URL fields
+    /// Validate URL fields: the homepage, and any environment's own homepage
     pub(crate) fn validate_urls(&self) -> Vec<ValidationIssue> {
         let mut issues = Vec::new();
 
-        // Check homepage URL if present
         if let Some(homepage) = &self.homepage {
-            match url::Url::parse(homepage) {
-                Ok(url) => {
-                    // Check scheme
-                    if url.scheme() != "http" && url.scheme() != "https" {
-                        issues.push(ValidationIssue::warning(
-                            ValidationErrorCategory::UrlFormat,
-                            "homepage",
-                            &format!(
-                                "URL should use http or https scheme, found: {}",
-                                url.scheme()
-                            ),
-                            None,
-                            Some("Use https:// prefix for the URL."),
-                        ));
+            issues.extend(Self::validate_url("homepage", homepage));
+        }
+
+        let mut environments: Vec<_> = self.environments.iter().collect();
+        environments.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (env_name, env_config) in environments {
+            if let Some(homepage) = &env_config.homepage {
+                let field = format!("environments.{}.homepage", env_name);
+                issues.extend(Self::validate_url(&field, homepage));
+            }
+        }
+
+        issues
+    }
+
+    /// Validate a URL given in `field`
+    fn validate_url(field: &str, url: &str) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        match url::Url::parse(url) {
+            Ok(parsed) => {
+                // Check scheme
+                if parsed.scheme() == "http" {
+                    let mut issue = ValidationIssue::warning(
+                        ValidationErrorCategory::UrlFormat,
+                        field,
+                        "URL uses http rather than https",
+                        None,
+                        Some("Use https:// prefix for the URL."),
+                    );
+                    if let Some(rest) = url.strip_prefix("http:") {
+                        issue = issue.with_fix(AutoFix::set(field, &format!("https:{}", rest)));
                     }
-                }
-                Err(err) => {
-                    issues.push(ValidationIssue::error(
+                    issues.push(issue);
+                } else if parsed.scheme() != "https" {
+                    issues.push(ValidationIssue::warning(
                         ValidationErrorCategory::UrlFormat,
-                        "homepage",
-                        &format!("Invalid URL format: {}", err),
+                        field,
+                        &format!(
+                            "URL should use http or https scheme, found: {}",
+                            parsed.scheme()
+                        ),
                         None,
-                        Some("Provide a valid URL with http:// or https:// prefix."),
+                        Some("Use https:// prefix for the URL."),
                     ));
                 }
             }
+            Err(err) => {
+                issues.push(ValidationIssue::error(
+                    ValidationErrorCategory::UrlFormat,
+                    field,
+                    &format!("Invalid URL format: {}", err),
+                    None,
+                    Some("Provide a valid URL with http:// or https:// prefix."),
+                ));
+            }
+        }
+
+        issues
+    }
+
+    /// Validate environments configuration. With `strict`, not having the
+    /// current environment, or only having it under an alias, is an error.
+    pub(crate) fn validate_environments(
+        &self,
+        current_env: &str,
+        env_aliases: &[String],
+        strict: bool,
+    ) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        // Already checked if environments is empty in validate_required_fields
+        if self.environments.is_empty() {
+            return issues;
+        }
+
+        // Check if current environment is configured, under its name or an alias
+        if !current_env.is_empty() {
+            match self.find_environment(current_env, env_aliases) {
+                None => issues.push(
+                    ValidationIssue::warning(
+                        ValidationErrorCategory::Environment,
+                        "environments",
+                        &format!("Current environment '{}' is not configured", current_env),
+                        None,
+                        Some(&format!(
+                            "Add an environment section for '{}' if needed for this environment.",
+                            current_env
+                        )),
+                    )
+                    .escalated_if(strict),
+                ),
+                Some((env_name, _)) if strict && env_name != current_env => {
+                    issues.push(ValidationIssue::error(
+                        ValidationErrorCategory::Environment,
+                        &format!("environments.{}", env_name),
+                        &format!(
+                            "Current environment '{}' is only configured under its alias '{}'",
+                            current_env, env_name
+                        ),
+                        None,
+                        Some(&format!(
+                            "Rename the '{}' environment to '{}'.",
+                            env_name, current_env
+                        )),
+                    ))
+                }
+                Some(_) => {}
+            }
+
+            // The current environment's commands have to cover this machine
+            if let Some((env_name, env_config)) = self.find_environment(current_env, env_aliases) {
+                issues.extend(Self::validate_arch(
+                    env_name,
+                    env_config,
+                    std::env::consts::ARCH,
+                ));
+            }
+        }
+
+        // Validate each environment's required fields
+        for (env_name, env_config) in &self.environments {
+            if env_config.install.is_empty() {
+                issues.push(ValidationIssue::error(
+                    ValidationErrorCategory::RequiredField,
+                    &format!("environments.{}.install", env_name),
+                    "Install command is required",
+                    None,
+                    Some("Add an install command like 'brew install package-name'."),
+                ));
+            }
+
+            // Inverting only makes sense for a command's exit status
+            if env_config.check_inverted && env_config.check.is_none() {
+                issues.push(ValidationIssue::error(
+                    ValidationErrorCategory::InvalidValue,
+                    &format!("environments.{}.check_inverted", env_name),
+                    "check_inverted needs a check command to invert",
+                    None,
+                    Some("Add a check command, or remove check_inverted."),
+                ));
+            }
+
+            // Validate dependencies (check for empty names)
+            let dependencies: Vec<&str> = env_config.dependency_names().collect();
+            let check_dependencies: Vec<&str> = env_config
+                .check_dependencies
+                .iter()
+                .map(String::as_str)
+                .collect();
+            for (field, dependencies) in [
+                ("dependencies", dependencies),
+                ("check_dependencies", check_dependencies),
+            ] {
+                for (i, dep) in dependencies.iter().enumerate() {
+                    if dep.is_empty() {
+                        issues.push(ValidationIssue::error(
+                            ValidationErrorCategory::InvalidValue,
+                            &format!("environments.{}.{}[{}]", env_name, field, i),
+                            "Dependency name cannot be empty",
+                            None,
+                            Some("Remove the empty dependency or provide a valid name."),
+                        ));
+                    }
+                }
+            }
         }
 
         issues
     }
 
-    /// Validate environments configuration
-    pub(crate) fn validate_environments(&self, current_env: &str) -> Vec<ValidationIssue> {
+    /// Validate that commands given per architecture have one for `arch`, or
+    /// a default
+    fn validate_arch(
+        env_name: &str,
+        env_config: &EnvironmentConfig,
+        arch: &str,
+    ) -> Vec<ValidationIssue> {
         let mut issues = Vec::new();
+        let suggestion = format!("Add an '{}' or '{}' entry.", arch, DEFAULT_ARCH);
 
-        // Already checked if environments is empty in validate_required_fields
-        if self.environments.is_empty() {
-            return issues;
+        if env_config.install.for_arch(arch).is_none() {
+            issues.push(ValidationIssue::error(
+                ValidationErrorCategory::Environment,
+                &format!("environments.{}.install", env_name),
+                &format!("No install command for architecture '{}'", arch),
+                None,
+                Some(&suggestion),
+            ));
         }
 
-        // Check if current environment is configured
-        if !current_env.is_empty() && !self.environments.contains_key(current_env) {
+        // Without a check, the package is just always installed
+        if env_config
+            .check
+            .as_ref()
+            .is_some_and(|check| check.for_arch(arch).is_none())
+        {
             issues.push(ValidationIssue::warning(
                 ValidationErrorCategory::Environment,
-                "environments",
-                &format!("Current environment '{}' is not configured", current_env),
+                &format!("environments.{}.check", env_name),
+                &format!("No check command for architecture '{}'", arch),
                 None,
-                Some(&format!(
-                    "Add an environment section for '{}' if needed for this environment.",
-                    current_env
-                )),
+                Some(&suggestion),
             ));
         }
 
-        // Validate each environment's required fields
-        for (env_name, env_config) in &self.environments {
-            if env_config.install.is_empty() {
-                issues.push(ValidationIssue::error(
-                    ValidationErrorCategory::RequiredField,
-                    &format!("environments.{}.install", env_name),
-                    "Install command is required",
-                    None,
-                    Some("Add an install command like 'brew install package-name'."),
-                ));
-            }
+        issues
+    }
 
-            // Validate dependencies (check for empty names)
-            for (i, dep) in env_config.dependencies.iter().enumerate() {
-                if dep.is_empty() {
-                    issues.push(ValidationIssue::error(
-                        ValidationErrorCategory::InvalidValue,
-                        &format!("environments.{}.dependencies[{}]", env_name, i),
-                        "Dependency name cannot be empty",
-                        None,
-                        Some("Remove the empty dependency or provide a valid name."),
+    /// Validate the syntax of `install`, at `field`, and of each of its
+    /// steps and architectures
+    fn validate_install_command(
+        install: &InstallCommand,
+        field: &str,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        match install {
+            InstallCommand::Single(command) => {
+                issues.extend(Self::validate_single_command(command, field))
+            }
+            InstallCommand::Script(commands) => {
+                for (i, command) in commands.iter().enumerate() {
+                    issues.extend(Self::validate_single_command(
+                        command,
+                        &format!("{}[{}]", field, i),
                     ));
                 }
             }
+            InstallCommand::ByArch(by_arch) => {
+                for (arch, command) in by_arch {
+                    Self::validate_install_command(command, &format!("{}.{}", field, arch), issues);
+                }
+            }
         }
-
-        issues
     }
 
     /// Basic command syntax validation that doesn't require external dependencies
@@ -301,17 +1085,36 @@ impl Package {
         let mut issues = Vec::new();
 
         for (env_name, env_config) in &self.environments {
-            // Check install command syntax
-            issues.extend(Self::validate_single_command(
+            // Check install command syntax, step by step for scripts
+            Self::validate_install_command(
                 &env_config.install,
                 &format!("environments.{}.install", env_name),
-            ));
+                &mut issues,
+            );
+
+            // Check check command syntax if present, for every architecture
+            match &env_config.check {
+                Some(CheckCommand::Single(check_cmd)) => {
+                    issues.extend(Self::validate_single_command(
+                        check_cmd,
+                        &format!("environments.{}.check", env_name),
+                    ))
+                }
+                Some(CheckCommand::ByArch(by_arch)) => {
+                    for (arch, check_cmd) in by_arch {
+                        issues.extend(Self::validate_single_command(
+                            check_cmd,
+                            &format!("environments.{}.check.{}", env_name, arch),
+                        ));
+                    }
+                }
+                None => {}
+            }
 
-            // Check check command syntax if present
-            if let Some(check_cmd) = &env_config.check {
+            if let Some(version_cmd) = &env_config.installed_version_command {
                 issues.extend(Self::validate_single_command(
-                    check_cmd,
-                    &format!("environments.{}.check", env_name),
+                    version_cmd,
+                    &format!("environments.{}.installed_version_command", env_name),
                 ));
             }
         }
@@ -320,7 +1123,7 @@ impl Package {
     }
 
     /// Validate a single command for syntax issues
-    fn validate_single_command(command: &str, field_name: &str) -> Vec<ValidationIssue> {
+    pub(crate) fn validate_single_command(command: &str, field_name: &str) -> Vec<ValidationIssue> {
         let mut issues = Vec::new();
 
         // Check for unmatched quotes
@@ -397,7 +1200,7 @@ impl Package {
     }
 
     /// Check if a string is a valid package name
-    fn is_valid_package_name(name: &str) -> bool {
+    pub(crate) fn is_valid_package_name(name: &str) -> bool {
         // Package names should only contain alphanumeric chars, hyphens, and underscores
         !name.is_empty()
             && name
@@ -405,76 +1208,101 @@ impl Package {
                 .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
     }
 
-    /// Check if a string is a valid version (basic semantic versioning check)
+    /// Check if a string is a valid semantic version, including pre-release and
+    /// build metadata
     fn is_valid_version(version: &str) -> bool {
-        // Simple check for semver format: major.minor.patch
-        let semver_regex = regex::Regex::new(r"^\d+\.\d+\.\d+").unwrap();
-        semver_regex.is_match(version)
+        semver::Version::parse(version).is_ok()
     }
 
-    /// Perform all basic domain validations
-    pub(crate) fn validate(&self, current_env: &str) -> Vec<ValidationIssue> {
+    /// Perform all basic domain validations; `strict` is as for
+    /// [`Self::validate_environments`]
+    pub(crate) fn validate(
+        &self,
+        current_env: &str,
+        env_aliases: &[String],
+        strict: bool,
+    ) -> Vec<ValidationIssue> {
         let mut issues = Vec::new();
 
         issues.extend(self.validate_required_fields());
         issues.extend(self.validate_urls());
-        issues.extend(self.validate_environments(current_env));
+        issues.extend(self.validate_environments(current_env, env_aliases, strict));
         issues.extend(self.validate_command_syntax());
 
         issues
     }
 }
 
-// Builder pattern for testing
-#[cfg(test)]
+/// Builds a [`Package`] in code, e.g. to save it with [`Package::to_yaml`]
+/// into a package directory
 #[derive(Default)]
-pub(crate) struct PackageBuilder {
+pub struct PackageBuilder {
     name: String,
     version: String,
     homepage: Option<String>,
     description: Option<String>,
+    aliases: Vec<String>,
+    tags: Vec<String>,
     environments: HashMap<String, EnvironmentConfig>,
     path: PathBuf,
 }
 
-#[cfg(test)]
 impl PackageBuilder {
-    pub(crate) fn name(mut self, name: &str) -> Self {
+    pub fn name(mut self, name: &str) -> Self {
         self.name = name.to_string();
         self
     }
 
-    pub(crate) fn version(mut self, version: &str) -> Self {
+    pub fn version(mut self, version: &str) -> Self {
         self.version = version.to_string();
         self
     }
 
-    pub(crate) fn homepage(mut self, homepage: &str) -> Self {
+    pub fn homepage(mut self, homepage: &str) -> Self {
         self.homepage = Some(homepage.to_string());
         self
     }
 
-    pub(crate) fn description(mut self, description: &str) -> Self {
+    pub fn description(mut self, description: &str) -> Self {
         self.description = Some(description.to_string());
         self
     }
 
-    pub(crate) fn environment<T>(mut self, name: T, install_command: &str) -> Self
+    pub fn aliases(mut self, aliases: &[&str]) -> Self {
+        self.aliases = aliases.iter().map(|alias| alias.to_string()).collect();
+        self
+    }
+
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    pub fn environment<T>(mut self, name: T, install_command: &str) -> Self
     where
         T: ToString,
     {
-        self.environments.insert(
-            name.to_string(),
-            EnvironmentConfig {
-                install: install_command.to_string(),
-                check: None,
-                dependencies: Vec::new(),
-            },
-        );
+        self.environments
+            .insert(name.to_string(), EnvironmentConfig::new(install_command));
+        self
+    }
+
+    /// Set the path of the file the package was read from
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Add an environment with a fully specified configuration
+    pub fn environment_config<T>(mut self, name: T, env_config: EnvironmentConfig) -> Self
+    where
+        T: ToString,
+    {
+        self.environments.insert(name.to_string(), env_config);
         self
     }
 
-    pub(crate) fn environment_with_check<T>(
+    pub fn environment_with_check<T>(
         mut self,
         name: T,
         install_command: &str,
@@ -485,16 +1313,12 @@ impl PackageBuilder {
     {
         self.environments.insert(
             name.to_string(),
-            EnvironmentConfig {
-                install: install_command.to_string(),
-                check: Some(check_command.to_string()),
-                dependencies: Vec::new(),
-            },
+            EnvironmentConfig::new(install_command).with_check(check_command),
         );
         self
     }
 
-    pub(crate) fn environment_with_dependencies<T>(
+    pub fn environment_with_dependencies<T>(
         mut self,
         name: T,
         install_command: &str,
@@ -505,24 +1329,23 @@ impl PackageBuilder {
     {
         self.environments.insert(
             name.to_string(),
-            EnvironmentConfig {
-                install: install_command.to_string(),
-                check: None,
-                dependencies: dependencies.iter().map(|&s| s.to_string()).collect(),
-            },
+            EnvironmentConfig::new(install_command).with_dependencies(dependencies),
         );
         self
     }
 
-    pub(crate) fn build(self) -> Package {
-        Package::new(
+    pub fn build(self) -> Package {
+        let mut package = Package::new(
             self.name,
             self.version,
             self.homepage,
             self.description,
             self.environments,
             self.path,
-        )
+        );
+        package.aliases = self.aliases;
+        package.tags = self.tags;
+        package
     }
 }
 
@@ -577,7 +1400,7 @@ mod tests {
             .environment("test-env", "test install")
             .build();
 
-        assert!(package.validate("test-env").is_empty());
+        assert!(package.validate("test-env", &[], false).is_empty());
     }
 
     #[test]
@@ -585,7 +1408,7 @@ mod tests {
         let package = PackageBuilder::default().build();
 
         pretty_assertions::assert_eq!(
-            package.validate("test-env"),
+            package.validate("test-env", &[], false),
             vec![
                 ValidationIssue {
                     category: ValidationErrorCategory::RequiredField,
@@ -595,7 +1418,8 @@ mod tests {
                     is_warning: false,
                     suggestion: Some(
                         "Add 'name: your-package-name' to the package file.".to_string()
-                    )
+                    ),
+                    fix: None,
                 },
                 ValidationIssue {
                     category: ValidationErrorCategory::RequiredField,
@@ -603,7 +1427,8 @@ mod tests {
                     message: "Package version is required".to_string(),
                     line: None,
                     is_warning: false,
-                    suggestion: Some("Add 'version: \"0.1.0\"' to the package file.".to_string())
+                    suggestion: Some("Add 'version: \"0.1.0\"' to the package file.".to_string()),
+                    fix: Some(AutoFix::set("version", "0.1.0")),
                 },
                 ValidationIssue {
                     category: ValidationErrorCategory::RequiredField,
@@ -613,7 +1438,8 @@ mod tests {
                     is_warning: false,
                     suggestion: Some(
                         "Add an 'environments' section with at least one environment.".to_string()
-                    )
+                    ),
+                    fix: None,
                 }
             ]
         );
@@ -628,14 +1454,15 @@ mod tests {
             .build();
 
         pretty_assertions::assert_eq!(
-            package.validate("test-env"),
+            package.validate("test-env", &[], false),
             vec![ValidationIssue {
                 category: ValidationErrorCategory::RequiredField,
                 field: "name".to_string(),
                 message: "Package name is required".to_string(),
                 line: None,
                 is_warning: false,
-                suggestion: Some("Add 'name: your-package-name' to the package file.".to_string())
+                suggestion: Some("Add 'name: your-package-name' to the package file.".to_string()),
+                fix: None,
             },]
         );
     }
@@ -649,7 +1476,7 @@ mod tests {
             .build();
 
         pretty_assertions::assert_eq!(
-            package.validate("test-env"),
+            package.validate("test-env", &[], false),
             vec![ValidationIssue {
                 category: ValidationErrorCategory::RequiredField,
                 field: "environments.test-env.install".to_string(),
@@ -658,11 +1485,30 @@ mod tests {
                 is_warning: false,
                 suggestion: Some(
                     "Add an install command like 'brew install package-name'.".to_string()
-                )
+                ),
+                fix: None,
             },]
         );
     }
 
+    #[test]
+    fn test_validate_http_homepage() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .homepage("http://example.com/test")
+            .environment("test-env", "test install")
+            .build();
+
+        let issues = package.validate("test-env", &[], false);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_warning);
+        assert_eq!(
+            issues[0].fix,
+            Some(AutoFix::set("homepage", "https://example.com/test"))
+        );
+    }
+
     #[test]
     fn test_resolve_environment_valid() {
         let package = PackageBuilder::default()
@@ -672,7 +1518,7 @@ mod tests {
             .environment("prod-env", "prod install")
             .build();
 
-        let result = package.resolve_environment("test-env");
+        let result = package.resolve_environment("test-env", &[]);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().install, "test install");
     }
@@ -685,7 +1531,7 @@ mod tests {
             .environment("Test-Env", "test install")
             .build();
 
-        let result = package.resolve_environment("test-env");
+        let result = package.resolve_environment("test-env", &[]);
         assert!(result.is_err());
     }
 
@@ -697,7 +1543,7 @@ mod tests {
             .environment("test-env", "test install")
             .build();
 
-        let result = package.resolve_environment("prod-env");
+        let result = package.resolve_environment("prod-env", &[]);
         assert_eq!(
             result,
             Err(PackageValidationError::EnvironmentNotSupported(
@@ -706,6 +1552,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_environment_by_alias() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment("mac", "mac install")
+            .environment("darwin", "darwin install")
+            .build();
+        let aliases = ["darwin".to_string(), "mac".to_string()];
+
+        // The first alias the package has wins, whatever the map's order
+        let (name, env_config) = package.find_environment("macos", &aliases).unwrap();
+        assert_eq!(name, "darwin");
+        assert_eq!(env_config.install.to_string(), "darwin install");
+
+        // Its own name wins over any alias
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment("mac", "mac install")
+            .environment("macos", "macos install")
+            .build();
+        let env_config = package.resolve_environment("macos", &aliases).unwrap();
+        assert_eq!(env_config.install.to_string(), "macos install");
+
+        assert!(package.resolve_environment("linux", &[]).is_err());
+    }
+
     #[test]
     fn test_resolve_environment_empty() {
         let package = PackageBuilder::default()
@@ -713,7 +1587,7 @@ mod tests {
             .version("1.0.0")
             .build();
 
-        let result = package.resolve_environment("test-env");
+        let result = package.resolve_environment("test-env", &[]);
         assert_eq!(
             result,
             Err(PackageValidationError::MissingField(
@@ -753,8 +1627,8 @@ mod tests {
             "brew install ripgrep"
         );
         assert_eq!(
-            package.environments.get("mac").unwrap().check,
-            Some("which rg".to_string())
+            package.environments.get("mac").unwrap().check(),
+            Some("which rg")
         );
         assert_eq!(
             package.environments.get("mac").unwrap().dependencies,
@@ -773,6 +1647,165 @@ mod tests {
             .is_empty());
     }
 
+    #[test]
+    fn test_package_from_yaml_conditional_dependencies() {
+        let yaml = r#"
+            name: native-ext
+            version: 1.0.0
+            environments:
+              linux:
+                install: make install
+                dependencies:
+                  - make
+                  - name: gcc
+                    when_env: [ubuntu, debian]
+                    unless_command: cc
+        "#;
+
+        let package = Package::from_yaml(yaml).unwrap();
+        let dependencies = package.environments["linux"].dependencies();
+        assert_eq!(dependencies[0], Dependency::Name("make".to_string()));
+        assert_eq!(dependencies[1].name(), "gcc");
+        assert_eq!(dependencies[1].unless_command(), Some("cc"));
+        assert_eq!(
+            dependencies[1].conditions(),
+            "on ubuntu or debian, unless cc is available"
+        );
+
+        assert!(dependencies[0].applies_to_environment("macos", &[]));
+        assert!(dependencies[1].applies_to_environment("debian", &[]));
+        assert!(dependencies[1].applies_to_environment("linux", &["ubuntu".to_string()]));
+        assert!(!dependencies[1].applies_to_environment("macos", &[]));
+
+        // Plain names are written back as plain names
+        let parsed = Package::from_yaml(&package.to_yaml().unwrap()).unwrap();
+        assert_eq!(parsed, package);
+        assert!(package.to_yaml().unwrap().contains("- make\n"));
+    }
+
+    #[test]
+    fn test_package_from_yaml_install_script() {
+        let yaml = r#"
+            name: rust
+            version: 1.0.0
+            environments:
+              test-env:
+                install:
+                  - curl -sSf https://sh.rustup.rs -o /tmp/rustup.sh
+                  - sh /tmp/rustup.sh -y
+        "#;
+
+        let package = Package::from_yaml(yaml).unwrap();
+        let install = &package.environments["test-env"].install;
+
+        assert_eq!(install.commands().len(), 2);
+        assert_eq!(
+            install.first_command(),
+            "curl -sSf https://sh.rustup.rs -o /tmp/rustup.sh"
+        );
+        assert_eq!(
+            install.to_string(),
+            "curl -sSf https://sh.rustup.rs -o /tmp/rustup.sh && sh /tmp/rustup.sh -y"
+        );
+        assert!(package.validate_command_syntax().is_empty());
+    }
+
+    #[test]
+    fn test_package_from_yaml_install_by_arch() {
+        let yaml = r#"
+            name: ripgrep
+            version: 1.0.0
+            environments:
+              macos:
+                install:
+                  aarch64: /opt/homebrew/bin/brew install ripgrep
+                  default:
+                    - softwareupdate --install-rosetta
+                    - brew install ripgrep
+                check:
+                  aarch64: test -x /opt/homebrew/bin/rg
+        "#;
+
+        let package = Package::from_yaml(yaml).unwrap();
+        let env_config = &package.environments["macos"];
+
+        assert_eq!(
+            env_config.install.for_arch("aarch64"),
+            Some(&InstallCommand::from(
+                "/opt/homebrew/bin/brew install ripgrep"
+            ))
+        );
+        assert_eq!(
+            env_config
+                .install
+                .for_arch("x86_64")
+                .map(InstallCommand::commands),
+            Some(
+                &[
+                    "softwareupdate --install-rosetta".to_string(),
+                    "brew install ripgrep".to_string()
+                ][..]
+            )
+        );
+
+        let check = env_config.check.as_ref().unwrap();
+        assert_eq!(
+            check.for_arch("aarch64"),
+            Some("test -x /opt/homebrew/bin/rg")
+        );
+        assert_eq!(check.for_arch("x86_64"), None);
+
+        assert!(Package::validate_arch("macos", env_config, "aarch64").is_empty());
+
+        // No check for x86_64 is only a warning; no install would be an error
+        let issues = Package::validate_arch("macos", env_config, "x86_64");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_warning);
+        assert_eq!(issues[0].field, "environments.macos.check");
+
+        let env_config = EnvironmentConfig::new(InstallCommand::ByArch(BTreeMap::from([(
+            "aarch64".to_string(),
+            InstallCommand::from("brew install ripgrep"),
+        )])));
+        let issues = Package::validate_arch("macos", &env_config, "x86_64");
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_warning);
+        assert_eq!(
+            issues[0].message,
+            "No install command for architecture 'x86_64'"
+        );
+    }
+
+    #[test]
+    fn test_package_from_combined_yaml() {
+        let yaml = r#"
+            ripgrep:
+              version: 1.0.0
+              environments:
+                test-env:
+                  install: brew install ripgrep
+            fzf:
+              name: fzf
+              version: 0.2.0
+              environments:
+                test-env:
+                  install: brew install fzf
+                  dependencies:
+                    - ripgrep
+        "#;
+
+        let packages = Package::from_combined_yaml(yaml).unwrap();
+        assert_eq!(packages.len(), 2);
+
+        assert_eq!(packages[0].name, "ripgrep");
+        assert_eq!(packages[0].version, "1.0.0");
+        assert_eq!(packages[1].name, "fzf");
+        assert_eq!(
+            packages[1].environments["test-env"].dependencies,
+            vec!["ripgrep"]
+        );
+    }
+
     #[test]
     fn test_package_to_yaml() {
         let package = PackageBuilder::default()
@@ -793,6 +1826,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_package_builder_round_trip() {
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("0.1.0")
+            .environment_config(
+                "mac",
+                EnvironmentConfig::new(vec![
+                    "brew update".to_string(),
+                    "brew install ripgrep".to_string(),
+                ])
+                .with_description("Homebrew")
+                .with_check("which rg")
+                .with_dependencies(["brew"])
+                .with_stdin("y\n"),
+            )
+            .build();
+
+        assert_eq!(package.file_name(), "ripgrep.yaml");
+
+        let parsed_package = Package::from_yaml(&package.to_yaml().unwrap()).unwrap();
+        assert_eq!(parsed_package, package);
+
+        let mac = &parsed_package.environments()["mac"];
+        assert_eq!(mac.install().commands().len(), 2);
+        assert_eq!(mac.check(), Some("which rg"));
+        assert_eq!(mac.dependencies(), ["brew"]);
+        assert_eq!(mac.stdin(), Some("y\n"));
+    }
+
     #[test]
     fn test_package_from_file() {
         let mut fs = MockFileSystem::default();
@@ -871,6 +1934,52 @@ mod tests {
             .any(|i| i.field == "environments" && !i.is_warning));
     }
 
+    #[test]
+    fn test_validate_self_alias() {
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("1.0.0")
+            .aliases(&["rg", "ripgrep"])
+            .environment("test-env", "echo install")
+            .build();
+
+        let issues = package.validate_required_fields();
+        assert!(issues.iter().any(|i| i.field == "aliases" && !i.is_warning));
+
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("1.0.0")
+            .aliases(&["rg"])
+            .environment("test-env", "echo install")
+            .build();
+        assert!(package.validate_required_fields().is_empty());
+    }
+
+    #[test]
+    fn test_is_valid_version() {
+        assert!(Package::is_valid_version("1.0.0"));
+        assert!(Package::is_valid_version("1.0.0-beta.1"));
+        assert!(Package::is_valid_version("1.0.0+build.5"));
+        assert!(Package::is_valid_version("1.0.0-rc.1+20250101"));
+
+        assert!(!Package::is_valid_version("1.0"));
+        assert!(!Package::is_valid_version("v1.0.0"));
+        assert!(!Package::is_valid_version("1.0.0.0"));
+        assert!(!Package::is_valid_version("latest"));
+    }
+
+    #[test]
+    fn test_validate_prerelease_version_has_no_warning() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("2.0.0-beta.1+build")
+            .environment("test-env", "echo install")
+            .build();
+
+        let issues = package.validate_required_fields();
+        assert!(!issues.iter().any(|i| i.field == "version"));
+    }
+
     #[test]
     fn test_validate_urls() {
         // Test invalid URL
@@ -908,6 +2017,53 @@ mod tests {
 
         let issues = package.validate_urls();
         assert_eq!(issues.len(), 0);
+
+        // An environment's own homepage is checked too, and fixed in place
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .homepage("https://example.com")
+            .environment_config(
+                "test-env",
+                EnvironmentConfig::new("test install").with_homepage("http://fork.example.com"),
+            )
+            .build();
+
+        let issues = package.validate_urls();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "environments.test-env.homepage");
+        assert_eq!(
+            issues[0].fix,
+            Some(AutoFix::set(
+                "environments.test-env.homepage",
+                "https://fork.example.com"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_environment_overrides_metadata() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .homepage("https://example.com")
+            .description("Upstream")
+            .environment("upstream", "test install")
+            .environment_config(
+                "fork",
+                EnvironmentConfig::new("test install")
+                    .with_homepage("https://fork.example.com")
+                    .with_description("A fork"),
+            )
+            .build();
+
+        let upstream = &package.environments["upstream"];
+        assert_eq!(package.homepage_for(upstream), Some("https://example.com"));
+        assert_eq!(package.description_for(upstream), Some("Upstream"));
+
+        let fork = &package.environments["fork"];
+        assert_eq!(package.homepage_for(fork), Some("https://fork.example.com"));
+        assert_eq!(package.description_for(fork), Some("A fork"));
     }
 
     #[test]
@@ -919,11 +2075,29 @@ mod tests {
             .environment("other-env", "test install")
             .build();
 
-        let issues = package.validate_environments("test-env");
+        let issues = package.validate_environments("test-env", &[], false);
         assert_eq!(issues.len(), 1);
         assert!(issues[0].is_warning);
         assert!(issues[0].message.contains("not configured"));
 
+        // An alias of the current environment counts as configured
+        assert!(package
+            .validate_environments("test-env", &["other-env".to_string()], false)
+            .is_empty());
+
+        // Unless strict, where both are errors
+        let issues = package.validate_environments("test-env", &[], true);
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_warning);
+
+        let issues = package.validate_environments("test-env", &["other-env".to_string()], true);
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_warning);
+        assert_eq!(issues[0].field, "environments.other-env");
+        assert!(issues[0]
+            .message
+            .contains("only configured under its alias"));
+
         // Test empty install command
         let mut package = PackageBuilder::default()
             .name("test-package")
@@ -931,19 +2105,62 @@ mod tests {
             .build();
 
         let env_config = EnvironmentConfig {
-            install: String::new(),
+            description: None,
+            homepage: None,
+            install: "".into(),
             check: None,
+            check_file: None,
+            check_path_command: None,
+            check_timeout: None,
+            check_inverted: false,
             dependencies: vec![],
+            check_dependencies: Vec::new(),
+            check_implies_dependencies: false,
+            stdin: None,
+            skip_if: None,
+            installed_version_command: None,
+            shell: None,
         };
 
         package
             .environments
             .insert("test-env".to_string(), env_config);
 
-        let issues = package.validate_environments("test-env");
+        let issues = package.validate_environments("test-env", &[], false);
         assert_eq!(issues.len(), 1);
         assert!(!issues[0].is_warning); // This should be an error
         assert!(issues[0].message.contains("required"));
+
+        // check_inverted only applies to a check command
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment_config(
+                "test-env",
+                EnvironmentConfig::new("rm -f ~/.old")
+                    .with_check_file("~/.old")
+                    .with_check_inverted(true),
+            )
+            .build();
+
+        let issues = package.validate_environments("test-env", &[], false);
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_warning);
+        assert_eq!(issues[0].field, "environments.test-env.check_inverted");
+
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment_config(
+                "test-env",
+                EnvironmentConfig::new("rm -f ~/.old")
+                    .with_check("test -e ~/.old")
+                    .with_check_inverted(true),
+            )
+            .build();
+        assert!(package
+            .validate_environments("test-env", &[], false)
+            .is_empty());
     }
 
     #[test]
@@ -985,6 +2202,46 @@ mod tests {
         assert!(issues[0].message.contains("backticks"));
     }
 
+    fn command_syntax_issues(install: &str) -> Vec<ValidationIssue> {
+        PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment("test-env", install)
+            .build()
+            .validate_command_syntax()
+    }
+
+    #[test]
+    fn test_validate_command_syntax_valid() {
+        assert!(command_syntax_issues("echo hello").is_empty());
+    }
+
+    #[test]
+    fn test_validate_command_syntax_unmatched_quote() {
+        let issues = command_syntax_issues("echo 'hello");
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_warning);
+        assert!(issues[0].message.contains("Unmatched single quote"));
+        assert_eq!(issues[0].field, "environments.test-env.install");
+    }
+
+    #[test]
+    fn test_validate_command_syntax_invalid_pipe() {
+        let issues = command_syntax_issues("echo hello | | grep world");
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_warning);
+        assert!(issues[0].message.contains("Invalid pipe usage"));
+    }
+
+    #[test]
+    fn test_validate_command_syntax_unsafe_redirection() {
+        // Valid, but a warning
+        let issues = command_syntax_issues("echo hello > output.txt");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_warning);
+        assert!(issues[0].message.contains("invalid redirection"));
+    }
+
     #[test]
     fn test_full_validate() {
         // Test a valid package
@@ -996,7 +2253,7 @@ mod tests {
             .environment("test-env", "echo test")
             .build();
 
-        let issues = package.validate("test-env");
+        let issues = package.validate("test-env", &[], false);
         assert_eq!(issues.len(), 0);
 
         // Test an invalid package with multiple issues
@@ -1007,7 +2264,7 @@ mod tests {
             .environment("other-env", "echo `test`")
             .build();
 
-        let issues = package.validate("test-env");
+        let issues = package.validate("test-env", &[], false);
         assert!(issues.len() >= 4); // At least 4 issues should be found
     }
 }
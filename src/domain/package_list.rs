@@ -0,0 +1,64 @@
+// src/domain/package_list.rs
+// Plain-text lists of the packages to install
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::ports::filesystem::{FileSystem, FileSystemError};
+
+/// Errors from loading a package list
+#[derive(Error, Debug)]
+pub enum PackageListError {
+    #[error("Failed to read package list: {0}")]
+    Read(#[from] FileSystemError),
+}
+
+/// Read `path` and parse it with [`parse_package_list`]
+pub fn load_package_list<F: FileSystem>(
+    fs: &F,
+    path: &Path,
+) -> Result<Vec<String>, PackageListError> {
+    let content = fs.read_file(&fs.expand_path(path)?)?;
+
+    Ok(parse_package_list(&content))
+}
+
+/// Package names, one per line, in the order they're listed. Blank lines are
+/// skipped, a `#` starts a comment, and a name listed again is dropped.
+pub fn parse_package_list(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        let name = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line,
+        }
+        .trim();
+
+        if !name.is_empty() && !names.iter().any(|listed| listed == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_list() {
+        let content = "
+# Shell tools
+ripgrep
+fd   # faster find
+
+  bat
+ripgrep
+";
+
+        assert_eq!(parse_package_list(content), vec!["ripgrep", "fd", "bat"]);
+        assert!(parse_package_list("# nothing yet\n\n").is_empty());
+    }
+}
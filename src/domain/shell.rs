@@ -0,0 +1,54 @@
+// src/domain/shell.rs
+// Interpreters a package's commands can be run under
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// An interpreter for a package's install and check commands, instead of the
+/// shell selfie runs everything else with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Sh,
+    Bash,
+    Zsh,
+    Pwsh,
+}
+
+impl Shell {
+    /// The executable to run, looked up on `PATH`
+    pub fn program(&self) -> &'static str {
+        match self {
+            Self::Sh => "sh",
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Pwsh => "pwsh",
+        }
+    }
+
+    /// The arguments that make the interpreter run the command that follows
+    /// them
+    pub fn command_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Pwsh => &["-NoProfile", "-NonInteractive", "-Command"],
+            _ => &["-c"],
+        }
+    }
+
+    /// Whether the interpreter understands POSIX shell, e.g. `trap`
+    pub fn is_posix(&self) -> bool {
+        !matches!(self, Self::Pwsh)
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.program())
+    }
+}
+
+/// Quote `path` as a single word for a POSIX shell, e.g. to paste into a
+/// command line
+pub(crate) fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', r"'\''"))
+}
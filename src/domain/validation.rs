@@ -1,14 +1,20 @@
 // src/domain/validation.rs
-use std::{collections::HashMap, fmt, os::unix::fs::PermissionsExt, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
 
 use console::style;
 use jiff::{fmt::temporal::SpanPrinter, Unit, Zoned};
+use serde::Serialize;
 
 use crate::{adapters::progress::ProgressManager, domain::package::Package};
 
 /// Categories of package validation errors
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) enum ValidationErrorCategory {
+pub enum ValidationErrorCategory {
     /// Missing required fields
     RequiredField,
     /// Invalid field values
@@ -27,6 +33,34 @@ pub(crate) enum ValidationErrorCategory {
     Other,
 }
 
+impl ValidationErrorCategory {
+    /// Every category, in declaration order
+    pub(crate) const ALL: [Self; 8] = [
+        Self::RequiredField,
+        Self::InvalidValue,
+        Self::Environment,
+        Self::CommandSyntax,
+        Self::UrlFormat,
+        Self::FileSystem,
+        Self::Availability,
+        Self::Other,
+    ];
+
+    /// Stable identifier for the category, used as a rule id in reports
+    pub(crate) fn rule_id(&self) -> &'static str {
+        match self {
+            ValidationErrorCategory::RequiredField => "required-field",
+            ValidationErrorCategory::InvalidValue => "invalid-value",
+            ValidationErrorCategory::Environment => "environment",
+            ValidationErrorCategory::CommandSyntax => "command-syntax",
+            ValidationErrorCategory::UrlFormat => "url-format",
+            ValidationErrorCategory::FileSystem => "file-system",
+            ValidationErrorCategory::Availability => "availability",
+            ValidationErrorCategory::Other => "other",
+        }
+    }
+}
+
 impl fmt::Display for ValidationErrorCategory {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -44,7 +78,7 @@ impl fmt::Display for ValidationErrorCategory {
 
 /// A single validation issue (error or warning)
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct ValidationIssue {
+pub struct ValidationIssue {
     /// The category of the issue
     pub(crate) category: ValidationErrorCategory,
     /// The field or context where the issue was found
@@ -57,6 +91,57 @@ pub(crate) struct ValidationIssue {
     pub(crate) is_warning: bool,
     /// Suggested fix for the issue
     pub(crate) suggestion: Option<String>,
+    /// Edit that resolves the issue, when there's a safe and unambiguous one
+    pub(crate) fix: Option<AutoFix>,
+}
+
+/// An edit to a package file: set the field at a dotted path (e.g. `version`
+/// or `environments.macos.check`) to a value, inserting it if it's missing
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AutoFix {
+    pub(crate) field: String,
+    pub(crate) value: String,
+}
+
+impl AutoFix {
+    pub(crate) fn set(field: &str, value: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Apply the edit to a parsed package file, leaving every other field as
+    /// it is. Returns false if the path runs through something that isn't a
+    /// mapping.
+    pub(crate) fn apply(&self, document: &mut serde_yaml::Value) -> bool {
+        let mut current = document;
+        let mut keys = self.field.split('.').peekable();
+
+        while let Some(key) = keys.next() {
+            let serde_yaml::Value::Mapping(mapping) = current else {
+                return false;
+            };
+            let key = serde_yaml::Value::from(key);
+
+            if keys.peek().is_none() {
+                mapping.insert(key, serde_yaml::Value::from(self.value.as_str()));
+                return true;
+            }
+
+            current = mapping
+                .entry(key)
+                .or_insert_with(|| serde_yaml::Value::Mapping(Default::default()));
+        }
+
+        false
+    }
+}
+
+impl fmt::Display for AutoFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "set {} to \"{}\"", self.field, self.value)
+    }
 }
 
 impl ValidationIssue {
@@ -75,6 +160,7 @@ impl ValidationIssue {
             line,
             is_warning: false,
             suggestion: suggestion.map(|s| s.to_string()),
+            fix: None,
         }
     }
 
@@ -93,13 +179,54 @@ impl ValidationIssue {
             line,
             is_warning: true,
             suggestion: suggestion.map(|s| s.to_string()),
+            fix: None,
+        }
+    }
+
+    pub fn category(&self) -> ValidationErrorCategory {
+        self.category
+    }
+
+    /// The field or context the issue was found in, e.g.
+    /// `environments.macos.install`
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.is_warning
+    }
+
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+
+    /// Make a warning an error when `strict` is set, i.e. with `--strict`
+    pub(crate) fn escalated_if(mut self, strict: bool) -> Self {
+        if strict {
+            self.is_warning = false;
         }
+        self
+    }
+
+    /// Attach an edit that resolves the issue
+    pub(crate) fn with_fix(mut self, fix: AutoFix) -> Self {
+        self.fix = Some(fix);
+        self
     }
 }
 
 /// Results of a package validation
 #[derive(Debug, Clone, Default, PartialEq)]
-pub(crate) struct ValidationResult {
+pub struct ValidationResult {
     /// The package that was validated
     pub(crate) package_name: String,
     /// The package file path
@@ -121,6 +248,20 @@ impl ValidationResult {
         }
     }
 
+    /// A result with `err` as its only issue, so that a package that can't be
+    /// validated at all is still reported
+    pub(crate) fn failed(package_name: &str, err: impl fmt::Display) -> Self {
+        let mut result = Self::new(package_name);
+        result.add_issue(ValidationIssue::error(
+            ValidationErrorCategory::Other,
+            "package",
+            &err.to_string(),
+            None,
+            None,
+        ));
+        result
+    }
+
     /// Add an issue to the validation result
     pub(crate) fn add_issue(&mut self, issue: ValidationIssue) {
         self.issues.push(issue);
@@ -131,6 +272,23 @@ impl ValidationResult {
         self.issues.extend(issues);
     }
 
+    /// Fold `other` into this result: its issues are added after this
+    /// result's, and its path and package fill in any this result is missing
+    pub(crate) fn merge(&mut self, other: ValidationResult) {
+        self.issues.extend(other.issues);
+        if self.package_path.is_none() {
+            self.package_path = other.package_path;
+        }
+        if self.package.is_none() {
+            self.package = other.package;
+        }
+    }
+
+    /// What results are sorted and merged by in a [`DirectoryValidationResult`]
+    fn directory_key(&self) -> (&str, Option<&Path>) {
+        (&self.package_name, self.package_path.as_deref())
+    }
+
     /// Set the package file path
     pub(crate) fn with_path(mut self, path: PathBuf) -> Self {
         self.package_path = Some(path);
@@ -143,18 +301,30 @@ impl ValidationResult {
         self
     }
 
+    pub fn package_name(&self) -> &str {
+        &self.package_name
+    }
+
+    pub fn package_path(&self) -> Option<&Path> {
+        self.package_path.as_deref()
+    }
+
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
     /// Returns true if the validation passed (no errors)
-    pub(crate) fn is_valid(&self) -> bool {
+    pub fn is_valid(&self) -> bool {
         !self.has_errors()
     }
 
     /// Returns true if the validation has errors (warnings are okay)
-    pub(crate) fn has_errors(&self) -> bool {
+    pub fn has_errors(&self) -> bool {
         self.issues.iter().any(|issue| !issue.is_warning)
     }
 
     /// Get all errors (not warnings)
-    pub(crate) fn errors(&self) -> Vec<&ValidationIssue> {
+    pub fn errors(&self) -> Vec<&ValidationIssue> {
         self.issues
             .iter()
             .filter(|issue| !issue.is_warning)
@@ -162,13 +332,21 @@ impl ValidationResult {
     }
 
     /// Get all warnings (not errors)
-    pub(crate) fn warnings(&self) -> Vec<&ValidationIssue> {
+    pub fn warnings(&self) -> Vec<&ValidationIssue> {
         self.issues
             .iter()
             .filter(|issue| issue.is_warning)
             .collect()
     }
 
+    /// Issues that have a fix that can be applied to the package file
+    pub(crate) fn fixable(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.fix.is_some())
+            .collect()
+    }
+
     /// Get issues by category
     pub(crate) fn issues_by_category(
         &self,
@@ -225,6 +403,10 @@ impl ValidationResult {
                         };
                         output.push_str(&format!("{}\n", suggestion_text));
                     }
+
+                    if let Some(fix) = &warning.fix {
+                        output.push_str(&format_fix(fix, progress_manager.use_colors()));
+                    }
                 }
             }
         } else {
@@ -357,6 +539,10 @@ impl ValidationResult {
                 };
                 output.push_str(&format!("{}\n", suggestion_text));
             }
+
+            if let Some(fix) = &error.fix {
+                output.push_str(&format_fix(fix, use_colors));
+            }
         }
     }
 
@@ -445,9 +631,13 @@ impl ValidationResult {
             for (name, env) in &package.environments {
                 output.push_str(&format!("    {}:\n", name));
 
+                if let Some(description) = &env.description {
+                    output.push_str(&format!("      Description: {}\n", description));
+                }
+
                 output.push_str(&format!(
                     "      Check: {}\n",
-                    &env.check.as_deref().unwrap_or_default()
+                    &env.check().unwrap_or_default()
                 ));
 
                 output.push_str(&format!("      Install: {}\n", &env.install));
@@ -469,6 +659,170 @@ impl ValidationResult {
     }
 }
 
+/// Results of validating every package in the package directory, one per
+/// package, sorted by package name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirectoryValidationResult {
+    results: Vec<ValidationResult>,
+}
+
+impl DirectoryValidationResult {
+    /// Collect `results`; results for the same package in the same file are
+    /// merged
+    pub(crate) fn new(results: Vec<ValidationResult>) -> Self {
+        let mut directory = Self::default();
+        for result in results {
+            directory.add(result);
+        }
+        directory
+    }
+
+    /// Add a package's result, merging it into any result already there for
+    /// the same package in the same file. Two files can define packages of
+    /// the same name, and each keeps its own result so issues are reported
+    /// against the file they're in.
+    pub(crate) fn add(&mut self, result: ValidationResult) {
+        match self
+            .results
+            .binary_search_by(|existing| existing.directory_key().cmp(&result.directory_key()))
+        {
+            Ok(index) => self.results[index].merge(result),
+            Err(index) => self.results.insert(index, result),
+        }
+    }
+
+    pub fn results(&self) -> &[ValidationResult] {
+        &self.results
+    }
+
+    /// Returns true if every package passed validation
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(ValidationResult::is_valid)
+    }
+
+    /// Errors across every package
+    pub fn total_errors(&self) -> usize {
+        self.results
+            .iter()
+            .map(|result| result.errors().len())
+            .sum()
+    }
+
+    /// Warnings across every package
+    pub fn total_warnings(&self) -> usize {
+        self.results
+            .iter()
+            .map(|result| result.warnings().len())
+            .sum()
+    }
+
+    /// Results of the packages that failed validation
+    pub fn invalid_packages(&self) -> Vec<&ValidationResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.is_valid())
+            .collect()
+    }
+
+    /// Results of the packages with any issue, warnings included
+    pub fn packages_with_issues(&self) -> Vec<&ValidationResult> {
+        self.results
+            .iter()
+            .filter(|result| !result.issues.is_empty())
+            .collect()
+    }
+
+    /// One-line count of the valid and invalid packages
+    pub(crate) fn summary(&self) -> String {
+        let invalid = self.invalid_packages().len();
+
+        format!(
+            "Validated {} packages: {} valid, {} invalid",
+            self.results.len(),
+            self.results.len() - invalid,
+            invalid
+        )
+    }
+
+    /// The results as a JSON object, with each package's errors and warnings
+    /// and the totals
+    pub(crate) fn to_json(&self) -> Result<String, serde_json::Error> {
+        let report = JsonReport {
+            valid: self.is_valid(),
+            total_errors: self.total_errors(),
+            total_warnings: self.total_warnings(),
+            invalid_packages: self
+                .invalid_packages()
+                .into_iter()
+                .map(|result| result.package_name.as_str())
+                .collect(),
+            packages: self
+                .results
+                .iter()
+                .map(|result| JsonPackage {
+                    name: &result.package_name,
+                    path: result.package_path.as_deref(),
+                    valid: result.is_valid(),
+                    errors: result.errors().into_iter().map(JsonIssue::from).collect(),
+                    warnings: result.warnings().into_iter().map(JsonIssue::from).collect(),
+                })
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&report)
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'r> {
+    valid: bool,
+    total_errors: usize,
+    total_warnings: usize,
+    invalid_packages: Vec<&'r str>,
+    packages: Vec<JsonPackage<'r>>,
+}
+
+#[derive(Serialize)]
+struct JsonPackage<'r> {
+    name: &'r str,
+    path: Option<&'r Path>,
+    valid: bool,
+    errors: Vec<JsonIssue<'r>>,
+    warnings: Vec<JsonIssue<'r>>,
+}
+
+#[derive(Serialize)]
+struct JsonIssue<'r> {
+    rule_id: &'static str,
+    field: &'r str,
+    message: &'r str,
+    line: Option<usize>,
+    suggestion: Option<&'r str>,
+}
+
+impl<'r> From<&'r ValidationIssue> for JsonIssue<'r> {
+    fn from(issue: &'r ValidationIssue) -> Self {
+        Self {
+            rule_id: issue.category.rule_id(),
+            field: &issue.field,
+            message: &issue.message,
+            line: issue.line,
+            suggestion: issue.suggestion.as_deref(),
+        }
+    }
+}
+
+/// Line describing a fix that `--fix` would apply
+fn format_fix(fix: &AutoFix, use_colors: bool) -> String {
+    let text = format!("    Fix (with --fix): {}", fix);
+
+    if use_colors {
+        format!("{}\n", style(text).dim())
+    } else {
+        format!("{}\n", text)
+    }
+}
+
 /// Errors that can occur during validation operations
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum ValidationError {
@@ -525,4 +879,126 @@ mod tests {
         assert!(formatted.contains("Package name is required"));
         assert!(formatted.contains("Add 'name: your-package-name'"));
     }
+
+    #[test]
+    fn test_auto_fix_apply() {
+        let mut document: serde_yaml::Value = serde_yaml::from_str(
+            "name: ripgrep\nversion: ''\nx-notes: keep me\nenvironments:\n  macos:\n    install: brew install ripgrep\n",
+        )
+        .unwrap();
+
+        assert!(AutoFix::set("version", "0.1.0").apply(&mut document));
+        assert!(AutoFix::set("environments.macos.check", "which rg").apply(&mut document));
+        assert!(!AutoFix::set("name.first", "rg").apply(&mut document));
+
+        assert_eq!(
+            serde_yaml::to_string(&document).unwrap(),
+            "name: ripgrep\nversion: 0.1.0\nx-notes: keep me\nenvironments:\n  macos:\n    install: brew install ripgrep\n    check: which rg\n"
+        );
+    }
+
+    fn result_with(package_name: &str, errors: usize, warnings: usize) -> ValidationResult {
+        let mut result = ValidationResult::new(package_name);
+        for _ in 0..errors {
+            result.add_issue(ValidationIssue::error(
+                ValidationErrorCategory::RequiredField,
+                "version",
+                "Package version is required",
+                Some(2),
+                None,
+            ));
+        }
+        for _ in 0..warnings {
+            result.add_issue(ValidationIssue::warning(
+                ValidationErrorCategory::UrlFormat,
+                "homepage",
+                "Homepage isn't a URL",
+                None,
+                Some("Use an http(s) URL."),
+            ));
+        }
+        result
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut result = result_with("ripgrep", 1, 0);
+        result.merge(result_with("ripgrep", 0, 2).with_path(PathBuf::from("/pkgs/ripgrep.yaml")));
+
+        assert_eq!(result.errors().len(), 1);
+        assert_eq!(result.warnings().len(), 2);
+        assert_eq!(result.package_path(), Some(Path::new("/pkgs/ripgrep.yaml")));
+    }
+
+    #[test]
+    fn test_directory_validation_result() {
+        let directory = DirectoryValidationResult::new(vec![
+            result_with("ripgrep", 0, 1),
+            result_with("bat", 2, 0),
+            result_with("fd", 0, 0),
+            result_with("bat", 1, 1),
+        ]);
+
+        let names: Vec<&str> = directory
+            .results()
+            .iter()
+            .map(ValidationResult::package_name)
+            .collect();
+        assert_eq!(names, vec!["bat", "fd", "ripgrep"]);
+        assert!(!directory.is_valid());
+        assert_eq!(directory.total_errors(), 3);
+        assert_eq!(directory.total_warnings(), 2);
+        assert_eq!(directory.invalid_packages().len(), 1);
+        assert_eq!(directory.invalid_packages()[0].package_name(), "bat");
+        assert_eq!(directory.packages_with_issues().len(), 2);
+        assert_eq!(
+            directory.summary(),
+            "Validated 3 packages: 2 valid, 1 invalid"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&directory.to_json().unwrap()).unwrap();
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["total_errors"], 3);
+        assert_eq!(json["invalid_packages"], serde_json::json!(["bat"]));
+        assert_eq!(json["packages"][0]["errors"].as_array().unwrap().len(), 3);
+        assert_eq!(
+            json["packages"][0]["errors"][0]["rule_id"],
+            "required-field"
+        );
+        assert_eq!(
+            json["packages"][2]["warnings"][0]["suggestion"],
+            "Use an http(s) URL."
+        );
+
+        assert!(DirectoryValidationResult::default().is_valid());
+    }
+
+    #[test]
+    fn test_directory_validation_result_keeps_files_apart() {
+        let directory = DirectoryValidationResult::new(vec![
+            result_with("tool", 1, 0).with_path(PathBuf::from("/pkgs/tool.yaml")),
+            result_with("tool", 0, 1).with_path(PathBuf::from("/pkgs/other-tool.yaml")),
+            result_with("tool", 1, 0).with_path(PathBuf::from("/pkgs/tool.yaml")),
+        ]);
+
+        let results: Vec<(&str, Option<&Path>, usize, usize)> = directory
+            .results()
+            .iter()
+            .map(|result| {
+                (
+                    result.package_name(),
+                    result.package_path(),
+                    result.errors().len(),
+                    result.warnings().len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("tool", Some(Path::new("/pkgs/other-tool.yaml")), 0, 1),
+                ("tool", Some(Path::new("/pkgs/tool.yaml")), 2, 0),
+            ]
+        );
+    }
 }
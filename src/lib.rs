@@ -2,6 +2,7 @@
 // This file is optional, but useful for exposing modules when used as a library
 
 pub mod adapters;
+pub mod api;
 pub mod domain;
 pub mod ports;
 pub mod services;
@@ -7,12 +7,17 @@ use selfie::{
         command::shell::ShellCommandRunner, config_loader, filesystem::RealFileSystem,
         progress::ProgressManager, user_interface::ClapCli,
     },
+    domain::env_file::load_env_file,
     ports::{
         application::{ApplicationCommandRouter, ArgumentParser},
         config_loader::ConfigLoader,
     },
     services::command::application::ApplicationCommandService,
 };
+use tokio_util::sync::CancellationToken;
+
+/// Exit code after Ctrl-C, following the shell convention of 128 + SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
@@ -37,20 +42,81 @@ async fn main() -> Result<(), anyhow::Error> {
         )
     };
 
-    let cmd_service = {
-        let runner = ShellCommandRunner::new("/bin/sh", app_config.command_timeout());
+    let mut runner = ShellCommandRunner::new("/bin/sh", app_config.command_timeout())
+        .with_kill_on_interrupt(app_config.kill_on_interrupt());
+
+    if let Some(env_file) = app_config.env_file() {
+        let progress_manager = ProgressManager::from(&app_config);
+
+        match load_env_file(&fs, env_file) {
+            Ok(environment) => {
+                if progress_manager.verbose() {
+                    // Only the names: values are often secrets
+                    let mut names: Vec<_> = environment.keys().cloned().collect();
+                    names.sort();
+                    progress_manager.print_info(format!(
+                        "Loaded from {}: {}",
+                        env_file.display(),
+                        names.join(", ")
+                    ));
+                }
+
+                runner = runner.with_environment(environment);
+            }
+            Err(err) => {
+                progress_manager.print_error(format!("Error: {}", err));
+                process::exit(1);
+            }
+        }
+    }
 
-        // Create the command service to route and execute the command
-        ApplicationCommandService::new(&fs, runner, &app_config)
+    // Clones share the interrupt state, so this one can stop the service's
+    let interrupt_runner = runner.clone();
+
+    // Canceled on Ctrl-C, so installs and syncs don't start another package
+    let cancellation = CancellationToken::new();
+
+    // Create the command service to route and execute the command
+    let cmd_service = ApplicationCommandService::new(&fs, runner, &app_config)
+        .with_cancellation(cancellation.clone());
+
+    let mut processing = Box::pin(cmd_service.process_command(args));
+
+    let result = tokio::select! {
+        result = &mut processing => result,
+        _ = tokio::signal::ctrl_c() => {
+            let progress_manager = ProgressManager::from(&app_config);
+            cancellation.cancel();
+
+            if app_config.kill_on_interrupt() {
+                // Dropping the work kills whatever command is running
+                drop(processing);
+            } else {
+                interrupt_runner.interrupt();
+                progress_manager.abandon_all();
+                progress_manager.print_warning(
+                    "Interrupted; waiting for the running command to finish \
+                     (Ctrl-C again or --kill-on-interrupt to stop it)",
+                );
+
+                tokio::select! {
+                    _ = &mut processing => {}
+                    _ = tokio::signal::ctrl_c() => drop(processing),
+                }
+            }
+
+            progress_manager.abandon_all();
+            progress_manager.print_error("Interrupted");
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
     };
 
     // Process the command and get an exit code
-    match cmd_service.process_command(args).await {
+    match result {
         Ok(code) => process::exit(code),
         Err(err) => {
             // Create a progress manager for error formatting
-            let progress_manager =
-                ProgressManager::new(app_config.use_colors(), app_config.verbose());
+            let progress_manager = ProgressManager::from(&app_config);
 
             // Format and print the error
             progress_manager.print_error(format!("Error: {}", err));
@@ -2,4 +2,5 @@ pub mod application;
 pub mod command;
 pub mod config_loader;
 pub mod filesystem;
+pub mod install_state;
 pub mod package_repo;
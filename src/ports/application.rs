@@ -1,7 +1,10 @@
 // src/ports/application.rs
-use std::path::PathBuf;
+use std::{
+    num::NonZeroU64,
+    path::{Path, PathBuf},
+};
 
-use crate::domain::application::commands::ApplicationCommand;
+use crate::domain::{application::commands::ApplicationCommand, config::ProgressFormat};
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct ApplicationArguments {
@@ -9,9 +12,72 @@ pub struct ApplicationArguments {
     pub(crate) package_directory: Option<PathBuf>,
     pub verbose: bool,
     pub no_color: bool,
+    pub(crate) progress_format: Option<ProgressFormat>,
+    pub(crate) assume_yes: bool,
+    pub(crate) kill_on_interrupt: bool,
+    pub(crate) strict: bool,
+    pub(crate) no_cache: bool,
+    pub(crate) command_wrapper: Option<String>,
+    pub(crate) env_file: Option<PathBuf>,
+    pub(crate) check_timeout: Option<NonZeroU64>,
+    pub(crate) config_path: Option<PathBuf>,
     pub(crate) command: ApplicationCommand,
 }
 
+/// Read access for code outside the crate, such as a [`CommandMiddleware`]
+impl ApplicationArguments {
+    /// The command being run
+    pub fn command(&self) -> &ApplicationCommand {
+        &self.command
+    }
+
+    /// The environment given with `--environment`, if any
+    pub fn environment(&self) -> Option<&str> {
+        self.environment.as_deref()
+    }
+
+    pub fn package_directory(&self) -> Option<&Path> {
+        self.package_directory.as_deref()
+    }
+
+    pub fn progress_format(&self) -> Option<ProgressFormat> {
+        self.progress_format
+    }
+
+    pub fn assume_yes(&self) -> bool {
+        self.assume_yes
+    }
+
+    pub fn kill_on_interrupt(&self) -> bool {
+        self.kill_on_interrupt
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn no_cache(&self) -> bool {
+        self.no_cache
+    }
+
+    pub fn command_wrapper(&self) -> Option<&str> {
+        self.command_wrapper.as_deref()
+    }
+
+    pub fn env_file(&self) -> Option<&Path> {
+        self.env_file.as_deref()
+    }
+
+    pub fn check_timeout(&self) -> Option<NonZeroU64> {
+        self.check_timeout
+    }
+
+    /// The config file given with `--config`, if any
+    pub fn config_path(&self) -> Option<&Path> {
+        self.config_path.as_deref()
+    }
+}
+
 #[derive(Debug, Default)]
 #[cfg(test)]
 pub(crate) struct ApplicationArgumentsBuilder {
@@ -19,6 +85,11 @@ pub(crate) struct ApplicationArgumentsBuilder {
     package_directory: Option<PathBuf>,
     verbose: bool,
     no_color: bool,
+    progress_format: Option<ProgressFormat>,
+    assume_yes: bool,
+    kill_on_interrupt: bool,
+    command_wrapper: Option<String>,
+    config_path: Option<PathBuf>,
     command: ApplicationCommand,
 }
 
@@ -47,6 +118,19 @@ impl ApplicationArgumentsBuilder {
         self
     }
 
+    pub(crate) fn assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = assume_yes;
+        self
+    }
+
+    pub(crate) fn config_path<P>(mut self, config_path: P) -> Self
+    where
+        PathBuf: From<P>,
+    {
+        self.config_path = Some(config_path.into());
+        self
+    }
+
     pub(crate) fn command(mut self, command: ApplicationCommand) -> Self {
         self.command = command;
         self
@@ -58,6 +142,15 @@ impl ApplicationArgumentsBuilder {
             package_directory: self.package_directory,
             verbose: self.verbose,
             no_color: self.no_color,
+            progress_format: self.progress_format,
+            assume_yes: self.assume_yes,
+            kill_on_interrupt: self.kill_on_interrupt,
+            strict: false,
+            no_cache: false,
+            command_wrapper: self.command_wrapper,
+            env_file: None,
+            check_timeout: None,
+            config_path: self.config_path,
             command: self.command,
         }
     }
@@ -76,3 +169,14 @@ pub trait ApplicationCommandRouter {
     /// Get a human-readable description of a command
     fn get_command_description(&self, command: &ApplicationCommand) -> String;
 }
+
+/// Logic run around every command a router processes, such as timing,
+/// logging or metrics. Both hooks do nothing by default.
+pub trait CommandMiddleware: Send + Sync {
+    /// Called before the command runs
+    fn before(&self, _args: &ApplicationArguments) {}
+
+    /// Called after the command ran, with its exit code; a command that
+    /// ended in an error counts as exit code 1
+    fn after(&self, _args: &ApplicationArguments, _exit_code: i32) {}
+}
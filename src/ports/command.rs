@@ -1,16 +1,25 @@
 // src/ports/command.rs
 // Command execution port (interface)
+#[cfg(test)]
+use std::collections::HashMap;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use thiserror::Error;
 
+use crate::domain::shell::Shell;
+
+/// How long looking up whether a command is available may take, e.g. when the
+/// shell doing the lookup is stuck
+pub(crate) const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub enum OutputChunk {
     Stdout(String),
     Stderr(String),
 }
 
-/// Port for command execution
+/// Port for command execution. A command that runs but exits with a nonzero
+/// status is a [`CommandError::NonZeroExit`], which still carries its output.
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait CommandRunner: Send + Sync {
@@ -33,8 +42,27 @@ pub trait CommandRunner: Send + Sync {
     where
         F: FnMut(OutputChunk) + Send + 'static;
 
-    /// Check if a command is available in the current environment
-    async fn is_command_available(&self, command: &str) -> bool;
+    /// Execute a command with `stdin` written to its standard input
+    async fn execute_with_stdin(
+        &self,
+        command: &str,
+        stdin: &[u8],
+        timeout: Duration,
+    ) -> Result<CommandOutput, CommandError>;
+
+    /// Execute a command attached to selfie's terminal, e.g. an editor, and
+    /// wait for it to exit. No output is captured and there's no timeout.
+    async fn execute_interactive(&self, command: &str) -> Result<CommandOutput, CommandError>;
+
+    /// Check if a command is available in the current environment. Fails
+    /// when the lookup can't be run, or takes longer than [`PROBE_TIMEOUT`].
+    async fn is_command_available(&self, command: &str) -> Result<bool, CommandError>;
+
+    /// A runner that runs commands under `shell` instead, otherwise set up
+    /// like this one
+    fn with_shell(&self, shell: Shell) -> Self
+    where
+        Self: Sized;
 }
 
 /// Result of executing a command
@@ -56,19 +84,66 @@ pub struct CommandOutput {
     pub(crate) duration: Duration,
 }
 
+impl CommandOutput {
+    /// Standard output with surrounding whitespace removed
+    pub fn stdout_trimmed(&self) -> &str {
+        self.stdout.trim()
+    }
+
+    /// Standard output followed by standard error
+    pub fn combined(&self) -> String {
+        let mut combined = self.stdout.clone();
+
+        if !combined.is_empty() && !self.stderr.is_empty() && !combined.ends_with('\n') {
+            combined.push('\n');
+        }
+        combined.push_str(&self.stderr);
+
+        combined
+    }
+
+    /// Whether the command exited with status code 0
+    pub fn succeeded(&self) -> bool {
+        self.status == 0
+    }
+}
+
 /// Errors that can occur during command execution
 #[derive(Error, Debug, Clone)]
 pub enum CommandError {
     #[error("Command execution failed: {0}")]
     ExecutionError(String),
 
+    /// The command ran but exited with a nonzero status; its output goes
+    /// along for callers to whom the status is an answer
+    #[error("Command exited with status {}: {command}", output.status)]
+    NonZeroExit {
+        command: String,
+        output: CommandOutput,
+    },
+
     #[error("Command timed out after {0:?}")]
     Timeout(Duration),
 
+    #[error("Interrupted before the command could start")]
+    Interrupted,
+
     #[error("IO Error: {0}")]
     IoError(String),
 }
 
+impl CommandError {
+    /// The output of a command that ran to completion, whatever its exit
+    /// status, for checks and the like where a nonzero exit is an answer
+    /// rather than an error. Other errors are passed on.
+    pub fn into_output(self) -> Result<CommandOutput, CommandError> {
+        match self {
+            Self::NonZeroExit { output, .. } => Ok(output),
+            err => Err(err),
+        }
+    }
+}
+
 impl From<std::io::Error> for CommandError {
     fn from(value: std::io::Error) -> Self {
         Self::IoError(value.to_string())
@@ -82,7 +157,7 @@ impl MockCommandRunner {
 
         self.expect_is_command_available()
             .with(mockall::predicate::eq(command))
-            .returning(move |_| result);
+            .returning(move |_| Ok(result));
     }
 
     pub(crate) fn mock_execute_streaming_ok(
@@ -102,6 +177,38 @@ impl MockCommandRunner {
             .return_const(Ok(output));
     }
 
+    pub(crate) fn mock_execute_with_stdin_ok(
+        &mut self,
+        command: &str,
+        stdin: &str,
+        timeout: Duration,
+        output: CommandOutput,
+    ) {
+        let cmd = command.to_string();
+        let stdin = stdin.as_bytes().to_vec();
+
+        self.expect_execute_with_stdin()
+            .with(
+                mockall::predicate::eq(cmd),
+                mockall::predicate::function(move |input: &[u8]| input == stdin.as_slice()),
+                mockall::predicate::eq(timeout),
+            )
+            .return_const(Ok(output));
+    }
+
+    pub(crate) fn mock_execute_interactive_ok(&mut self, command: &str) {
+        let cmd = command.to_string();
+
+        self.expect_execute_interactive()
+            .with(mockall::predicate::eq(cmd))
+            .returning(|_| {
+                Ok(CommandOutput {
+                    success: true,
+                    ..CommandOutput::default()
+                })
+            });
+    }
+
     pub(crate) fn mock_execute_streaming_success_0(
         &mut self,
         command: &str,
@@ -127,16 +234,121 @@ impl MockCommandRunner {
         timeout: u64,
         stderr: &str,
     ) {
-        self.mock_execute_streaming_ok(
-            command,
-            Duration::from_secs(timeout),
-            CommandOutput {
+        self.mock_execute_streaming_exit(command, timeout, 1, stderr);
+    }
+
+    /// Expect `command` to run and exit with the nonzero `status`, which
+    /// runners report as [`CommandError::NonZeroExit`]
+    pub(crate) fn mock_execute_streaming_exit(
+        &mut self,
+        command: &str,
+        timeout: u64,
+        status: i32,
+        stderr: &str,
+    ) {
+        let cmd = command.to_string();
+        let error = CommandError::NonZeroExit {
+            command: cmd.clone(),
+            output: CommandOutput {
                 stdout: String::new(),
                 stderr: stderr.to_string(),
-                status: 1,
+                status,
                 success: false,
                 duration: Duration::from_millis(100),
             },
-        );
+        };
+
+        self.expect_execute_streaming()
+            .with(
+                mockall::predicate::eq(cmd),
+                mockall::predicate::eq(Duration::from_secs(timeout)),
+                mockall::predicate::always(),
+            )
+            .return_const(Err(error));
+    }
+
+    /// Answer the batch availability probe `CommandValidator::probe_commands`
+    /// runs: each command in `available` is available or not as given, and
+    /// any other command isn't
+    pub(crate) fn mock_probe_commands(&mut self, available: &[(&str, bool)]) {
+        let available: HashMap<String, bool> = available
+            .iter()
+            .map(|(command, is_available)| (command.to_string(), *is_available))
+            .collect();
+
+        self.expect_execute_with_timeout()
+            .withf(|script: &str, timeout| {
+                script.contains("command -v '") && *timeout == PROBE_TIMEOUT
+            })
+            .returning(move |script, _| {
+                let stdout = script
+                    .lines()
+                    .map(|line| {
+                        let command = line.split('\'').nth(1).unwrap_or_default();
+                        if available.get(command).copied().unwrap_or(false) {
+                            "1\n"
+                        } else {
+                            "0\n"
+                        }
+                    })
+                    .collect();
+
+                Ok(CommandOutput {
+                    stdout,
+                    success: true,
+                    ..CommandOutput::default()
+                })
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(stdout: &str, stderr: &str, status: i32) -> CommandOutput {
+        CommandOutput {
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            status,
+            success: status == 0,
+            duration: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn test_stdout_trimmed() {
+        assert_eq!(output("  v1.2.3\n", "", 0).stdout_trimmed(), "v1.2.3");
+        assert_eq!(output("\n", "", 0).stdout_trimmed(), "");
+    }
+
+    #[test]
+    fn test_combined_preserves_both_streams() {
+        assert_eq!(output("out\n", "err\n", 1).combined(), "out\nerr\n");
+        assert_eq!(output("out", "err", 1).combined(), "out\nerr");
+        assert_eq!(output("out\n", "", 0).combined(), "out\n");
+        assert_eq!(output("", "err\n", 1).combined(), "err\n");
+    }
+
+    #[test]
+    fn test_succeeded_matches_exit_status() {
+        assert!(output("", "", 0).succeeded());
+        assert!(!output("", "", 1).succeeded());
+        assert!(!output("", "", -1).succeeded());
+    }
+
+    #[test]
+    fn test_into_output() {
+        let error = CommandError::NonZeroExit {
+            command: "false".to_string(),
+            output: output("", "nope", 1),
+        };
+        assert_eq!(error.to_string(), "Command exited with status 1: false");
+        assert_eq!(error.into_output().unwrap(), output("", "nope", 1));
+
+        assert!(matches!(
+            CommandError::Timeout(Duration::from_secs(1)).into_output(),
+            Err(CommandError::Timeout(_))
+        ));
     }
 }
@@ -54,8 +54,11 @@ pub enum ConfigLoadError {
     #[error("Failed to parse configuration file: {0}")]
     ParseError(String),
 
-    #[error("No configuration file found in standard locations")]
-    NotFound,
+    #[error(
+        "No configuration file found. Looked for:{}",
+        .0.iter().map(|path| format!("\n  {}", path.display())).collect::<String>()
+    )]
+    NotFound(Vec<PathBuf>),
 
     #[error("Multiple configuration files found: {0}")]
     MultipleFound(String),
@@ -4,10 +4,16 @@
 use std::{
     io,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+
 use thiserror::Error;
 
+use crate::domain::errors::{ErrorContext, ErrorReport};
+
 /// Port for file system operations
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
@@ -15,6 +21,20 @@ pub trait FileSystem: Send + Sync {
     /// Read a file and return its contents as a string
     fn read_file(&self, path: &Path) -> Result<String, FileSystemError>;
 
+    /// Write `content` to a file, replacing it if it already exists. The file
+    /// either keeps its old content or gets all of the new, never part of it.
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), FileSystemError>;
+
+    /// Create a directory and any missing parents; nothing happens if it
+    /// already exists
+    fn create_dir_all(&self, path: &Path) -> Result<(), FileSystemError>;
+
+    /// Move a file from `from` to `to`, replacing `to` if it already exists
+    fn rename_file(&self, from: &Path, to: &Path) -> Result<(), FileSystemError>;
+
+    /// Delete the file at `path`
+    fn remove_file(&self, path: &Path) -> Result<(), FileSystemError>;
+
     /// Check if a path exists
     fn path_exists(&self, path: &Path) -> bool;
 
@@ -24,10 +44,16 @@ pub trait FileSystem: Send + Sync {
     /// List the contents of a directory
     fn list_directory(&self, path: &Path) -> Result<Vec<PathBuf>, FileSystemError>;
 
-    /// Get the canonical path
+    /// When the file at `path` was last modified
+    fn modified(&self, path: &Path) -> Result<SystemTime, FileSystemError>;
+
+    /// Get the canonical path, with every symlink along it resolved
     fn canonicalize(&self, path: &Path) -> Result<PathBuf, FileSystemError>;
 
     fn config_dir(&self) -> Result<PathBuf, FileSystemError>;
+
+    /// Directory selfie keeps data it can rebuild in, e.g. `~/.cache/selfie`
+    fn cache_dir(&self) -> Result<PathBuf, FileSystemError>;
 }
 
 /// Errors that can occur during file system operations
@@ -42,8 +68,29 @@ pub enum FileSystemError {
     #[error("Invalid path: {0}")]
     InvalidPath(String),
 
-    #[error("Permission denied: {0}")]
-    PermissionDenied(String),
+    #[error("Permission denied: {}", .0.display())]
+    PermissionDenied(PathBuf),
+}
+
+impl ErrorReport for FileSystemError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "io_error",
+            Self::PathNotFound(_) => "path_not_found",
+            Self::InvalidPath(_) => "invalid_path",
+            Self::PermissionDenied(_) => "permission_denied",
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::PathNotFound(path) | Self::InvalidPath(path) => {
+                ErrorContext::default().with_path(path)
+            }
+            Self::PermissionDenied(path) => ErrorContext::default().with_path(path),
+            Self::IoError(_) => ErrorContext::default(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -82,12 +129,50 @@ impl MockFileSystem {
             .returning(move |_| exists);
     }
 
-    pub(crate) fn mock_config_dir<P>(&mut self, path: P)
+    pub(crate) fn mock_modified<P>(&mut self, path: P, modified: SystemTime)
+    where
+        PathBuf: From<P>,
+    {
+        self.expect_modified()
+            .with(mockall::predicate::eq(PathBuf::from(path)))
+            .returning(move |_| Ok(modified));
+    }
+
+    /// Accept every write, recording each path and what was written to it,
+    /// in order
+    pub(crate) fn mock_write_file_recording(&mut self) -> Arc<Mutex<Vec<(PathBuf, String)>>> {
+        let writes = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = writes.clone();
+        self.expect_write_file().returning(move |path, content| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), content.to_string()));
+            Ok(())
+        });
+
+        writes
+    }
+
+    /// Make `link` a symlink to `target`, as far as `canonicalize` goes
+    pub(crate) fn mock_symlink<P>(&mut self, link: P, target: P)
     where
         PathBuf: From<P>,
     {
-        let p = PathBuf::from(path);
-        self.expect_config_dir().return_once(|| Ok(p));
+        let target = PathBuf::from(target);
+
+        self.expect_canonicalize()
+            .with(mockall::predicate::eq(PathBuf::from(link)))
+            .returning(move |_| Ok(target.clone()));
+    }
+
+    /// Canonicalize every path that isn't a symlink set up with
+    /// `mock_symlink` to itself. Expectations match in the order they're
+    /// set, so call this after the symlinks.
+    pub(crate) fn mock_real_paths(&mut self) {
+        self.expect_canonicalize()
+            .returning(|path| Ok(path.to_path_buf()));
     }
 
     pub(crate) fn mock_expand_path<P>(&mut self, input: P, output: P)
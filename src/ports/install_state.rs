@@ -0,0 +1,63 @@
+// src/ports/install_state.rs
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::{
+    domain::{
+        errors::{ErrorContext, ErrorReport},
+        install_state::InstallState,
+    },
+    ports::filesystem::FileSystemError,
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum InstallStateError {
+    #[error(transparent)]
+    FileSystem(#[from] FileSystemError),
+
+    #[error("Invalid install state file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to serialize install state: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+impl ErrorReport for InstallStateError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::FileSystem(err) => err.kind(),
+            Self::Parse { .. } => "invalid_install_state",
+            Self::Serialize(_) => "install_state_not_serializable",
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::FileSystem(err) => err.error_context(),
+            Self::Parse { path, .. } => ErrorContext::default().with_path(path),
+            Self::Serialize(_) => ErrorContext::default(),
+        }
+    }
+}
+
+/// Port for loading and saving the record of installed packages
+#[cfg_attr(test, mockall::automock)]
+pub(crate) trait InstallStateStore: Send + Sync {
+    /// Load the recorded state; an empty state if nothing has been recorded yet
+    fn load(&self) -> Result<InstallState, InstallStateError>;
+
+    /// Replace the recorded state
+    fn save(&self, state: &InstallState) -> Result<(), InstallStateError>;
+}
+
+#[cfg(test)]
+impl MockInstallStateStore {
+    pub(crate) fn mock_load_ok(&mut self, state: InstallState) {
+        self.expect_load().returning(move || Ok(state.clone()));
+    }
+}
@@ -1,25 +1,88 @@
 // src/ports/package_repo.rs
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-use crate::domain::package::{Package, PackageParseError};
+use crate::{
+    domain::{
+        errors::{ErrorContext, ErrorReport},
+        package::{Package, PackageParseError},
+    },
+    ports::filesystem::FileSystemError,
+};
 
 #[derive(Error, Debug)]
-pub(crate) enum PackageRepoError {
+pub enum PackageRepoError {
     #[error("Package not found: {0}")]
     PackageNotFound(String),
 
     #[error("Multiple packages found with name: {0}")]
     MultiplePackagesFound(String),
 
+    #[error("Alias '{alias}' is declared by both '{first}' and '{second}'")]
+    AliasCollision {
+        alias: String,
+        first: String,
+        second: String,
+    },
+
     #[error("Parse error: {0}")]
     ParseError(#[from] PackageParseError),
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error(transparent)]
+    FileSystem(#[from] FileSystemError),
+
     #[error("Directory does not exist: {0}")]
     DirectoryNotFound(String),
+
+    #[error("This package repository can't list its packages")]
+    ListingUnsupported,
+}
+
+impl PackageRepoError {
+    /// The path selfie wasn't allowed to access, when that's what went wrong
+    pub(crate) fn permission_denied_path(&self) -> Option<&Path> {
+        match self {
+            Self::FileSystem(FileSystemError::PermissionDenied(path))
+            | Self::ParseError(PackageParseError::FileSystemError(
+                FileSystemError::PermissionDenied(path),
+            )) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+impl ErrorReport for PackageRepoError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::PackageNotFound(_) => "package_not_found",
+            Self::MultiplePackagesFound(_) => "multiple_packages_found",
+            Self::AliasCollision { .. } => "alias_collision",
+            Self::ParseError(PackageParseError::FileSystemError(err)) | Self::FileSystem(err) => {
+                err.kind()
+            }
+            Self::ParseError(_) => "invalid_package_file",
+            Self::IoError(_) => "io_error",
+            Self::DirectoryNotFound(_) => "path_not_found",
+            Self::ListingUnsupported => "listing_unsupported",
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::PackageNotFound(name) | Self::MultiplePackagesFound(name) => {
+                ErrorContext::default().with_package(name)
+            }
+            Self::AliasCollision { first, .. } => ErrorContext::default().with_package(first),
+            Self::ParseError(PackageParseError::FileSystemError(err)) | Self::FileSystem(err) => {
+                err.error_context()
+            }
+            Self::DirectoryNotFound(path) => ErrorContext::default().with_path(path),
+            _ => ErrorContext::default(),
+        }
+    }
 }
 
 /// Port for package repository operations
@@ -28,11 +91,21 @@ pub(crate) trait PackageRepository: Send + Sync {
     /// Get a package by name
     fn get_package(&self, name: &str) -> Result<Package, PackageRepoError>;
 
-    /// List all available packages in the package directory
-    fn list_packages(&self) -> Result<Vec<Package>, PackageRepoError>;
+    /// List all available packages. Backends that can only look packages up
+    /// by name don't need to support this.
+    fn list_packages(&self) -> Result<Vec<Package>, PackageRepoError> {
+        Err(PackageRepoError::ListingUnsupported)
+    }
 
     /// Find package files that match the given name
     fn find_package_files(&self, name: &str) -> Result<Vec<PathBuf>, PackageRepoError>;
+
+    /// Check whether a package with the given name exists, without parsing it
+    fn package_exists(&self, name: &str) -> Result<bool, PackageRepoError>;
+
+    /// Names of packages that are close to `name`, most similar first, for
+    /// "did you mean" suggestions
+    fn find_similar(&self, name: &str) -> Result<Vec<String>, PackageRepoError>;
 }
 
 #[cfg(test)]
@@ -45,6 +118,28 @@ impl MockPackageRepository {
             .returning(move |_| Ok(result.clone()));
     }
 
+    pub(crate) fn mock_list_packages(&mut self, packages: Vec<Package>) {
+        self.expect_list_packages()
+            .returning(move || Ok(packages.clone()));
+    }
+
+    pub(crate) fn mock_package_exists(&mut self, name: &str, result: bool) {
+        let name = name.to_string();
+
+        self.expect_package_exists()
+            .with(mockall::predicate::eq(name))
+            .returning(move |_| Ok(result));
+    }
+
+    pub(crate) fn mock_find_similar(&mut self, name: &str, result: &[&str]) {
+        let name = name.to_string();
+        let result: Vec<String> = result.iter().map(|s| s.to_string()).collect();
+
+        self.expect_find_similar()
+            .with(mockall::predicate::eq(name))
+            .returning(move |_| Ok(result.clone()));
+    }
+
     pub(crate) fn mock_get_package_err(&mut self, name: &str, result: PackageRepoError) {
         let name = name.to_string();
 
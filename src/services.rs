@@ -1,6 +1,7 @@
 pub mod command_validator;
 pub mod enhanced_error_handler;
 pub mod error_formatter;
+pub mod sarif;
 pub mod suggestion_provider;
 pub mod validation_command;
 
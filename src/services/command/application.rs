@@ -1,23 +1,37 @@
+use tokio_util::sync::CancellationToken;
+
 use crate::{
-    adapters::{package_repo::yaml::YamlPackageRepository, progress::ProgressManager},
+    adapters::{
+        install_state::json::JsonInstallStateStore,
+        package_repo::{cache::PackageCache, yaml::YamlPackageRepository},
+        progress::ProgressManager,
+    },
     domain::{
-        application::commands::{ApplicationCommand, ConfigCommand, PackageCommand},
-        config::AppConfig,
+        application::commands::{ApplicationCommand, CacheCommand, ConfigCommand, PackageCommand},
+        config::{AppConfig, ConfigValidationError, ProgressFormat},
+        errors::{ErrorObject, ErrorReport},
     },
     ports::{
-        application::{ApplicationArguments, ApplicationCommandRouter},
+        application::{ApplicationArguments, ApplicationCommandRouter, CommandMiddleware},
         command::CommandRunner,
-        filesystem::FileSystem,
+        filesystem::{FileSystem, FileSystemError},
+        install_state::InstallStateError,
+    },
+    services::{
+        enhanced_error_handler::EnhancedErrorHandler,
+        package::{install::InstallOptions, sync::SyncOptions},
+        validation_command::ValidateOptions,
     },
-    services::enhanced_error_handler::EnhancedErrorHandler,
 };
 
-use super::package::PackageCommandService;
+use super::package::{PackageCommandService, PackageInstallCommandError, PackageListCommandError};
 
 pub struct ApplicationCommandService<'a, F: FileSystem, R: CommandRunner> {
     fs: &'a F,
     runner: R,
     app_config: &'a AppConfig,
+    middleware: Vec<Box<dyn CommandMiddleware + 'a>>,
+    cancellation: CancellationToken,
 }
 
 impl<'a, F: FileSystem, R: CommandRunner> ApplicationCommandService<'a, F, R> {
@@ -26,15 +40,27 @@ impl<'a, F: FileSystem, R: CommandRunner> ApplicationCommandService<'a, F, R> {
             fs,
             runner,
             app_config,
+            middleware: Vec::new(),
+            cancellation: CancellationToken::new(),
         }
     }
-}
 
-#[async_trait::async_trait]
-impl<F: FileSystem, R: CommandRunner> ApplicationCommandRouter
-    for ApplicationCommandService<'_, F, R>
-{
-    async fn process_command(&self, args: ApplicationArguments) -> Result<i32, anyhow::Error> {
+    /// Stop installs and syncs after the package they're on once
+    /// `cancellation` is triggered, such as on Ctrl-C
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Run `middleware` around every command. Each middleware's `before` is
+    /// called in the order they were added, and `after` in reverse.
+    pub fn with_middleware(mut self, middleware: impl CommandMiddleware + 'a) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Run the command `args` holds, returning its exit code
+    async fn route(&self, args: &ApplicationArguments) -> Result<i32, anyhow::Error> {
         // Create a progress manager using the unified AppConfig
         let progress_manager = ProgressManager::from(self.app_config);
 
@@ -45,79 +71,533 @@ impl<F: FileSystem, R: CommandRunner> ApplicationCommandRouter
         let exit_code = match &args.command {
             ApplicationCommand::Package(pkg_cmd) => {
                 // Create error handler for better error presentation
-                let package_repo = YamlPackageRepository::new(
+                let mut package_repo = YamlPackageRepository::new(
                     self.fs,
                     self.app_config.expanded_package_directory(),
                     progress_manager,
+                )
+                .with_extensions(self.app_config.package_file_extensions())
+                .with_name_source(self.app_config.name_source());
+
+                // Without a cache directory, package files are parsed every run
+                if self.app_config.use_cache() {
+                    if let Ok(cache) = PackageCache::in_cache_dir(self.fs) {
+                        package_repo = package_repo.with_cache(cache);
+                    }
+                }
+                let state_store = JsonInstallStateStore::in_package_directory(
+                    self.fs,
+                    &self.app_config.expanded_package_directory(),
                 );
                 let package_command_service = PackageCommandService::new(
                     self.fs,
                     &self.runner,
                     &package_repo,
+                    &state_store,
                     progress_manager,
                     self.app_config,
-                );
+                )
+                .with_cancellation(self.cancellation.clone());
                 let error_handler =
                     EnhancedErrorHandler::new(self.fs, &package_repo, progress_manager);
 
                 match &pkg_cmd {
-                    PackageCommand::Install { package_name } => {
+                    PackageCommand::Install {
+                        package_name,
+                        packages_from,
+                        only_deps,
+                        no_deps,
+                        no_check,
+                        timings,
+                        package_file,
+                        max_depth,
+                        json,
+                        verify,
+                        continue_on_error,
+                        install_command,
+                    } => {
+                        let options = InstallOptions {
+                            only_deps: *only_deps,
+                            no_deps: *no_deps,
+                            no_check: *no_check,
+                            timings: *timings,
+                            max_depth: *max_depth,
+                            verify: *verify,
+                            continue_on_error: *continue_on_error,
+                            install_command: install_command.clone(),
+                        };
+
                         package_command_service
-                            .install(package_name, &error_handler)
+                            .install(
+                                package_name.as_deref(),
+                                package_file.as_deref(),
+                                packages_from.as_deref(),
+                                options,
+                                *json,
+                                &error_handler,
+                            )
                             .await?
                     }
-                    PackageCommand::List => package_command_service.list().await?,
+                    PackageCommand::List {
+                        since,
+                        only_environment_matching,
+                    } => {
+                        package_command_service
+                            .list(*since, *only_environment_matching)
+                            .await?
+                    }
+                    PackageCommand::Status => package_command_service.status().await?,
+                    PackageCommand::CheckUpdates { json } => {
+                        package_command_service.check_updates(*json).await?
+                    }
+                    PackageCommand::Plan {
+                        package_name,
+                        format,
+                    } => package_command_service.plan(package_name, *format)?,
+                    PackageCommand::Deps {
+                        package_name,
+                        dot,
+                        max_depth,
+                        reverse,
+                    } => package_command_service.deps(
+                        package_name.as_deref(),
+                        *dot,
+                        *max_depth,
+                        *reverse,
+                    )?,
+                    PackageCommand::GraphCheck => package_command_service.graph_check()?,
+                    PackageCommand::Rename {
+                        old_name,
+                        new_name,
+                        diff,
+                    } => package_command_service.rename(old_name, new_name, *diff)?,
                     PackageCommand::Info { package_name } => {
                         package_command_service.info(package_name)?
                     }
+                    PackageCommand::Path { package_name } => {
+                        package_command_service.path(package_name)?
+                    }
+                    PackageCommand::Forget { package_name } => {
+                        package_command_service.forget(package_name)?
+                    }
+                    PackageCommand::Verify { package_name } => {
+                        package_command_service.verify(package_name).await?
+                    }
                     PackageCommand::Create { package_name } => {
                         package_command_service.create(package_name)?
                     }
+                    PackageCommand::Template {
+                        package_name,
+                        force,
+                        diff,
+                    } => package_command_service.template(package_name, *force, *diff)?,
+                    PackageCommand::Edit {
+                        package_name,
+                        create,
+                    } => package_command_service.edit(package_name, *create).await?,
                     PackageCommand::Validate {
                         package_name,
+                        format,
                         package_path,
+                        no_heuristics,
+                        fix,
+                        diff,
+                        run_checks,
+                        all_environments,
                     } => {
+                        let options = ValidateOptions {
+                            heuristics: !*no_heuristics,
+                            fix: *fix,
+                            diff: *diff,
+                            run_checks: *run_checks,
+                            all_environments: *all_environments,
+                        };
+
                         package_command_service
-                            .validate(package_name, package_path.as_deref())
+                            .validate(
+                                package_name.as_deref(),
+                                *format,
+                                package_path.as_deref(),
+                                options,
+                            )
                             .await
                     }
+                    PackageCommand::Lint {
+                        package_names,
+                        run_checks,
+                    } => {
+                        package_command_service
+                            .lint(package_names, *run_checks)
+                            .await?
+                    }
+                    PackageCommand::Sync {
+                        dry_run,
+                        tags,
+                        exclude,
+                        only_environment_matching,
+                        json,
+                        packages_from,
+                    } => {
+                        let options = SyncOptions {
+                            dry_run: *dry_run,
+                            tags: tags.clone(),
+                            exclude: exclude.clone(),
+                            only_environment_matching: *only_environment_matching,
+                            json: *json,
+                            packages_from: packages_from.clone(),
+                        };
+
+                        package_command_service
+                            .sync(options, &error_handler)
+                            .await?
+                    }
                 }
             }
             ApplicationCommand::Config(_cfg_cmd) => {
                 progress_manager.info("Config commands not implemented yet");
                 0
             }
+            ApplicationCommand::Cache(CacheCommand::Clear) => {
+                match PackageCache::in_cache_dir(self.fs).and_then(|cache| cache.clear()) {
+                    Ok(removed) => {
+                        progress_manager
+                            .print_success(format!("Removed {} cached package files", removed));
+                        0
+                    }
+                    Err(err) => {
+                        progress_manager.print_error(format!("Error: {}", err));
+                        1
+                    }
+                }
+            }
         };
 
         Ok(exit_code)
     }
+}
+
+#[async_trait::async_trait]
+impl<F: FileSystem, R: CommandRunner> ApplicationCommandRouter
+    for ApplicationCommandService<'_, F, R>
+{
+    async fn process_command(&self, args: ApplicationArguments) -> Result<i32, anyhow::Error> {
+        for middleware in &self.middleware {
+            middleware.before(&args);
+        }
+
+        let result = self.route(&args).await;
+
+        // An error exits with 1, as it does for the selfie binary
+        let exit_code = *result.as_ref().unwrap_or(&1);
+        for middleware in self.middleware.iter().rev() {
+            middleware.after(&args, exit_code);
+        }
+
+        // Automation gets the error as JSON instead of the human message
+        match result {
+            Err(err) if self.app_config.progress_format() == ProgressFormat::Json => {
+                ProgressManager::from(self.app_config).print_error_json(&error_object(&err));
+                Ok(1)
+            }
+            result => result,
+        }
+    }
 
     fn get_command_description(&self, command: &ApplicationCommand) -> String {
         match command {
             ApplicationCommand::Package(pkg_cmd) => match pkg_cmd {
-                PackageCommand::Install { package_name } => {
+                PackageCommand::Install {
+                    packages_from: Some(path),
+                    ..
+                } => format!("Install packages listed in {}", path.display()),
+                PackageCommand::Install {
+                    package_name: Some(package_name),
+                    only_deps: true,
+                    ..
+                } => format!("Install dependencies of package '{}'", package_name),
+                PackageCommand::Install {
+                    package_name: Some(package_name),
+                    no_deps: true,
+                    ..
+                } => format!("Install package '{}' without dependencies", package_name),
+                PackageCommand::Install {
+                    package_name: Some(package_name),
+                    package_file: Some(path),
+                    ..
+                } => format!("Install package '{}' ({})", package_name, path.display()),
+                PackageCommand::Install {
+                    package_name: Some(package_name),
+                    ..
+                } => {
                     format!("Install package '{}'", package_name)
                 }
-                PackageCommand::List => "List available packages".to_string(),
+                PackageCommand::Install { .. } => "Install packages".to_string(),
+                PackageCommand::List { since: None, .. } => "List available packages".to_string(),
+                PackageCommand::List {
+                    since: Some(since), ..
+                } => {
+                    format!("List packages changed since {}", since)
+                }
+                PackageCommand::Status => "Show status of installed packages".to_string(),
+                PackageCommand::CheckUpdates { .. } => {
+                    "Check packages for version updates".to_string()
+                }
+                PackageCommand::Plan { package_name, .. } => {
+                    format!("Plan installation of package '{}'", package_name)
+                }
+                PackageCommand::Deps {
+                    package_name: Some(package_name),
+                    reverse: true,
+                    ..
+                } => format!("Show packages depending on '{}'", package_name),
+                PackageCommand::Deps {
+                    package_name: Some(package_name),
+                    ..
+                } => format!("Show dependencies of package '{}'", package_name),
+                PackageCommand::Deps {
+                    package_name: None, ..
+                } => "Show dependencies of all packages".to_string(),
+                PackageCommand::GraphCheck => "Check packages for dependency cycles".to_string(),
+                PackageCommand::Rename {
+                    old_name, new_name, ..
+                } => {
+                    format!("Rename package '{}' to '{}'", old_name, new_name)
+                }
                 PackageCommand::Info { package_name } => {
                     format!("Show information about package '{}'", package_name)
                 }
+                PackageCommand::Path { package_name } => {
+                    format!("Show the path of package '{}'", package_name)
+                }
+                PackageCommand::Forget { package_name } => {
+                    format!("Forget the install of package '{}'", package_name)
+                }
+                PackageCommand::Verify { package_name } => {
+                    format!("Verify that package '{}' is installed", package_name)
+                }
                 PackageCommand::Create { package_name } => {
                     format!("Create package '{}'", package_name)
                 }
+                PackageCommand::Template { package_name, .. } => {
+                    format!("Write a template for package '{}'", package_name)
+                }
+                PackageCommand::Edit { package_name, .. } => {
+                    format!("Edit package '{}'", package_name)
+                }
+                PackageCommand::Validate {
+                    package_name: None, ..
+                } => "Validate all packages".to_string(),
                 PackageCommand::Validate {
-                    package_name,
+                    package_name: Some(package_name),
                     package_path,
+                    ..
                 } => match package_path {
                     Some(path) => {
                         format!("Validate package '{}' ({})", package_name, path.display())
                     }
                     None => format!("Validate package '{}'", package_name),
                 },
+                PackageCommand::Lint { package_names, .. } if package_names.is_empty() => {
+                    "Lint all packages".to_string()
+                }
+                PackageCommand::Lint { package_names, .. } => {
+                    format!("Lint packages: {}", package_names.join(", "))
+                }
+                PackageCommand::Sync { dry_run: true, .. } => {
+                    "Plan sync of packages for the current environment".to_string()
+                }
+                PackageCommand::Sync { .. } => {
+                    "Sync packages for the current environment".to_string()
+                }
             },
             ApplicationCommand::Config(cfg_cmd) => match cfg_cmd {
                 ConfigCommand::Validate => "Validate configuration".to_string(),
             },
+            ApplicationCommand::Cache(CacheCommand::Clear) => "Clear the package cache".to_string(),
         }
     }
 }
+
+/// The JSON error object for `err`: its message, with the kind and context of
+/// the first error in its chain that has them
+fn error_object(err: &anyhow::Error) -> ErrorObject {
+    let message = err.to_string();
+
+    err.chain()
+        .find_map(|cause| -> Option<&dyn ErrorReport> {
+            if let Some(err) = cause.downcast_ref::<PackageInstallCommandError>() {
+                Some(err)
+            } else if let Some(err) = cause.downcast_ref::<PackageListCommandError>() {
+                Some(err)
+            } else if let Some(err) = cause.downcast_ref::<ConfigValidationError>() {
+                Some(err)
+            } else if let Some(err) = cause.downcast_ref::<InstallStateError>() {
+                Some(err)
+            } else if let Some(err) = cause.downcast_ref::<FileSystemError>() {
+                Some(err)
+            } else {
+                None
+            }
+        })
+        .map(|report| ErrorObject::new(report, &message))
+        .unwrap_or_else(|| ErrorObject::unknown(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{
+        domain::config::AppConfigBuilder,
+        ports::{
+            application::ApplicationArgumentsBuilder, command::MockCommandRunner,
+            filesystem::MockFileSystem,
+        },
+    };
+
+    /// Records what it's called with, labeled so the call order shows
+    struct RecordingMiddleware {
+        label: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl CommandMiddleware for RecordingMiddleware {
+        fn before(&self, args: &ApplicationArguments) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} before {:?}", self.label, args.command()));
+        }
+
+        fn after(&self, args: &ApplicationArguments, exit_code: i32) {
+            self.calls.lock().unwrap().push(format!(
+                "{} after {:?}: {}",
+                self.label,
+                args.command(),
+                exit_code
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_observes_command_and_exit_code() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .use_cache(false)
+            .build();
+
+        // No package directory, so the command fails
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists("/test/packages", false);
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let service = ApplicationCommandService::new(&fs, MockCommandRunner::new(), &config)
+            .with_middleware(RecordingMiddleware {
+                label: "outer",
+                calls: calls.clone(),
+            })
+            .with_middleware(RecordingMiddleware {
+                label: "inner",
+                calls: calls.clone(),
+            });
+
+        let command = ApplicationCommand::Package(PackageCommand::Info {
+            package_name: "ripgrep".to_string(),
+        });
+        let args = ApplicationArgumentsBuilder::default()
+            .command(command.clone())
+            .build();
+
+        assert_eq!(service.process_command(args).await.unwrap(), 1);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                format!("outer before {:?}", command),
+                format!("inner before {:?}", command),
+                format!("inner after {:?}: 1", command),
+                format!("outer after {:?}: 1", command),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware_reads_arguments() {
+        /// Keeps what it can read of the arguments through the public accessors
+        struct ArgumentsMiddleware {
+            seen: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl CommandMiddleware for ArgumentsMiddleware {
+            fn before(&self, args: &ApplicationArguments) {
+                self.seen.lock().unwrap().push(format!(
+                    "{:?} in {:?}",
+                    args.command(),
+                    args.environment()
+                ));
+            }
+        }
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .use_cache(false)
+            .build();
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists("/test/packages", false);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let service = ApplicationCommandService::new(&fs, MockCommandRunner::new(), &config)
+            .with_middleware(ArgumentsMiddleware { seen: seen.clone() });
+
+        let command = ApplicationCommand::Package(PackageCommand::Info {
+            package_name: "ripgrep".to_string(),
+        });
+        let args = ApplicationArgumentsBuilder::default()
+            .environment("test-env")
+            .command(command.clone())
+            .build();
+        service.process_command(args).await.unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![format!("{:?} in Some(\"test-env\")", command)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_as_json() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("relative/packages")
+            .progress_format(ProgressFormat::Json)
+            .use_cache(false)
+            .build();
+        let fs = MockFileSystem::default();
+        let service = ApplicationCommandService::new(&fs, MockCommandRunner::new(), &config);
+
+        // The error is printed as JSON rather than returned for main to print
+        let args = ApplicationArgumentsBuilder::default()
+            .command(ApplicationCommand::Package(PackageCommand::Create {
+                package_name: "ripgrep".to_string(),
+            }))
+            .build();
+        assert_eq!(service.process_command(args).await.unwrap(), 1);
+
+        let err = anyhow::Error::from(PackageInstallCommandError::ConfigError(
+            ConfigValidationError::EnvironmentNotFound("test-env".to_string()),
+        ));
+        assert_eq!(
+            serde_json::to_value(error_object(&err)).unwrap(),
+            serde_json::json!({
+                "kind": "environment_not_found",
+                "message": "Environment not found: test-env",
+                "context": { "environment": "test-env" },
+            })
+        );
+
+        let err = anyhow::anyhow!("Something else");
+        assert_eq!(error_object(&err).kind, ErrorObject::UNKNOWN_KIND);
+    }
+}
@@ -1,24 +1,63 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use jiff::Timestamp;
 
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    adapters::progress::ProgressManager,
-    domain::config::{AppConfig, ConfigValidationError},
+    adapters::{package_repo::overlay::OverlayPackageRepository, progress::ProgressManager},
+    domain::{
+        application::commands::{PlanFormat, ValidateFormat},
+        config::{AppConfig, ConfigValidationError, NoCheckPolicy},
+        errors::{ErrorContext, ErrorReport},
+        install_state::InstallState,
+        installation::{
+            InstallationError, InstallationReport, InstallationStatus, InstallationSummary,
+        },
+        package::Package,
+        package_list::load_package_list,
+    },
     ports::{
-        command::CommandRunner,
+        command::{CommandError, CommandRunner},
         filesystem::{FileSystem, FileSystemError},
+        install_state::{InstallStateError, InstallStateStore},
         package_repo::PackageRepository,
     },
     services::{
+        command_validator::CommandValidator,
         enhanced_error_handler::EnhancedErrorHandler,
         package::{
-            install::{PackageInstaller, PackageInstallerError},
+            check_updates::PackageCheckUpdatesService,
+            deps::PackageDepsService,
+            edit::PackageEditService,
+            forget::PackageForgetService,
+            info::PackageInfoService,
+            install::{
+                is_package_pattern, listed_packages, matching_packages, InstallOptions,
+                PackageInstaller, PackageInstallerError,
+            },
+            lint::{format_lint_result, format_lint_summary, PackageLintService},
             list::{PackageListResult, PackageListService},
+            path::PackagePathService,
+            plan::PackagePlanService,
+            rename::PackageRenameService,
+            status::{PackageStatusReport, PackageStatusService},
+            sync::{PackageSyncService, SyncOptions, SyncTargets},
+            template::PackageTemplateService,
+            verify::{PackageVerifyService, Verification},
         },
+        validation_command::ValidateOptions,
     },
 };
 
+/// Exit status when a package's check doesn't find it after it was
+/// installed, set apart from an install that failed outright
+const NOT_DETECTED_EXIT_CODE: i32 = 3;
+
 #[derive(Debug, Error)]
 pub(super) enum PackageInstallCommandError {
     #[error(transparent)]
@@ -31,22 +70,58 @@ pub(super) enum PackageListCommandError {
     ConfigError(#[from] ConfigValidationError),
 }
 
-pub(super) struct PackageCommandService<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
-{
+impl ErrorReport for PackageInstallCommandError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::ConfigError(err) => err.kind(),
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::ConfigError(err) => err.error_context(),
+        }
+    }
+}
+
+impl ErrorReport for PackageListCommandError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::ConfigError(err) => err.kind(),
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::ConfigError(err) => err.error_context(),
+        }
+    }
+}
+
+pub(super) struct PackageCommandService<
+    'a,
+    F: FileSystem,
+    CR: CommandRunner,
+    PR: PackageRepository,
+    S: InstallStateStore,
+> {
     fs: &'a F,
     runner: &'a CR,
     package_repo: &'a PR,
+    state_store: &'a S,
     progress_manager: ProgressManager,
     app_config: &'a AppConfig,
+    cancellation: CancellationToken,
 }
 
-impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
-    PackageCommandService<'a, F, CR, PR>
+impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository, S: InstallStateStore>
+    PackageCommandService<'a, F, CR, PR, S>
 {
     pub(super) fn new(
         fs: &'a F,
         runner: &'a CR,
         package_repo: &'a PR,
+        state_store: &'a S,
         progress_manager: ProgressManager,
         app_config: &'a AppConfig,
     ) -> Self {
@@ -54,70 +129,576 @@ impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
             fs,
             runner,
             package_repo,
+            state_store,
             progress_manager,
             app_config,
+            cancellation: CancellationToken::new(),
         }
     }
 
+    /// Stop installs and syncs from starting another package once
+    /// `cancellation` is triggered
+    pub(super) fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     pub(super) async fn install(
         &self,
-        package_name: &str,
+        package_name: Option<&str>,
+        package_file: Option<&Path>,
+        packages_from: Option<&Path>,
+        options: InstallOptions,
+        json: bool,
         error_handler: &EnhancedErrorHandler<'_>,
     ) -> Result<i32, PackageInstallCommandError> {
         self.app_config.validate()?;
 
+        let package_name = match (package_name, packages_from) {
+            (_, Some(path)) => {
+                let package_names = match self.load_package_list(path) {
+                    Ok(package_names) => package_names,
+                    Err(message) => {
+                        self.progress_manager.print_error(message);
+                        return Ok(1);
+                    }
+                };
+
+                if package_names.is_empty() {
+                    self.progress_manager
+                        .print_info(format!("No packages listed in {}", path.display()));
+                    return Ok(0);
+                }
+
+                return Ok(self
+                    .install_from(
+                        self.package_repo,
+                        &package_names,
+                        options,
+                        Some(json),
+                        error_handler,
+                    )
+                    .await);
+            }
+            (Some(package_name), None) => package_name,
+            (None, None) => {
+                self.progress_manager
+                    .print_error("Name a package to install, or list them with --packages-from");
+                return Ok(1);
+            }
+        };
+
+        if options.install_command.is_some() && is_package_pattern(package_name) {
+            self.progress_manager
+                .print_error("--install-command can only be used to install a single package");
+            return Ok(1);
+        }
+
+        match package_file {
+            Some(_) if is_package_pattern(package_name) => {
+                self.progress_manager
+                    .print_error("--package-file can't be used with a package name pattern");
+                Ok(1)
+            }
+            Some(path) => {
+                let package = match self.load_package_file(package_name, path) {
+                    Ok(package) => package,
+                    Err(message) => {
+                        self.progress_manager.print_error(message);
+                        return Ok(1);
+                    }
+                };
+
+                let package_repo = OverlayPackageRepository::new(package, self.package_repo);
+                Ok(self
+                    .install_from(
+                        &package_repo,
+                        &[package_name.to_string()],
+                        options,
+                        json.then_some(true),
+                        error_handler,
+                    )
+                    .await)
+            }
+            None if is_package_pattern(package_name) => {
+                let package_names = match matching_packages(self.package_repo, package_name) {
+                    Ok(package_names) => package_names,
+                    Err(err) => {
+                        self.progress_manager.print_error(err.to_string());
+                        return Ok(1);
+                    }
+                };
+
+                Ok(self
+                    .install_from(
+                        self.package_repo,
+                        &package_names,
+                        options,
+                        Some(json),
+                        error_handler,
+                    )
+                    .await)
+            }
+            None => Ok(self
+                .install_from(
+                    self.package_repo,
+                    &[package_name.to_string()],
+                    options,
+                    json.then_some(true),
+                    error_handler,
+                )
+                .await),
+        }
+    }
+
+    /// Load a package from a file given on the command line, making sure it's
+    /// the package that was asked for
+    fn load_package_file(&self, package_name: &str, path: &Path) -> Result<Package, String> {
+        let package = Package::from_file(self.fs, path)
+            .map_err(|err| format!("Failed to load {}: {}", path.display(), err))?;
+
+        if package.name != package_name {
+            return Err(format!(
+                "{} defines package '{}', not '{}'",
+                path.display(),
+                package.name,
+                package_name
+            ));
+        }
+
+        Ok(package)
+    }
+
+    /// Real names of the packages listed in the file at `path`. Names that
+    /// aren't packages are all reported at once, before anything is
+    /// installed.
+    fn load_package_list(&self, path: &Path) -> Result<Vec<String>, String> {
+        let names = load_package_list(self.fs, path)
+            .map_err(|err| format!("Failed to load {}: {}", path.display(), err))?;
+
+        listed_packages(self.package_repo, &names).map_err(|err| match err {
+            PackageInstallerError::ListedPackagesNotFound(not_found) => {
+                let lines: Vec<String> = not_found
+                    .iter()
+                    .map(|name| format!("  • {}", name))
+                    .collect();
+                format!(
+                    "Packages listed in {} that don't exist; nothing was installed:\n{}",
+                    path.display(),
+                    lines.join("\n")
+                )
+            }
+            err => err.to_string(),
+        })
+    }
+
+    /// Install `package_names` in turn, stopping at the first failure unless
+    /// `continue_on_error` is set. With
+    /// `summary`, finish with [`Self::report_bulk_summary`], as JSON when it's
+    /// `Some(true)`.
+    async fn install_from<R: PackageRepository>(
+        &self,
+        package_repo: &R,
+        package_names: &[String],
+        options: InstallOptions,
+        summary_json: Option<bool>,
+        error_handler: &EnhancedErrorHandler<'_>,
+    ) -> i32 {
+        let started = Instant::now();
+        let continue_on_error = options.continue_on_error;
+
         // For install commands, we need a fully valid config
         // Use the consolidated package installer with our unified config
         let installer = PackageInstaller::new(
-            self.package_repo,
+            self.fs,
+            package_repo,
             error_handler,
             self.runner,
             self.app_config,
             self.progress_manager,
             true, // Enable command checking
-        );
+        )
+        .with_options(options)
+        .with_install_state(self.no_check_install_state())
+        .with_cancellation(self.cancellation.clone());
+
+        // The installer remembers what it has handled, so packages matched by
+        // a pattern that share dependencies only install them once
+        let mut summary = InstallationSummary::default();
+        let mut exit_code = 0;
+        if package_names.len() > 1 {
+            self.progress_manager.start_overall(package_names.len());
+        }
+        for package_name in package_names {
+            let result = installer.install_package(package_name).await;
+            self.progress_manager.advance_overall();
+
+            match result {
+                Ok(report) => self.record_report(package_repo, &report, &mut summary),
+                Err(err) => {
+                    self.print_install_error(&err, error_handler);
+                    // The name asked for may be an alias; the installer's
+                    // statuses, merged in later, go by the real name
+                    let real_name = package_repo
+                        .get_package(package_name)
+                        .map_or_else(|_| package_name.clone(), |package| package.name);
+                    summary.record_failure(&real_name);
+                    self.record_canceled(package_repo, &err, &mut summary);
 
-        match installer.install_package(package_name).await {
-            Ok(_) => Ok(0),
-            Err(err) => {
-                // Check for filesystem errors specifically
-                match &err {
-                    PackageInstallerError::FileSystemError(fs_err) => {
-                        if let FileSystemError::PathNotFound(path_str) = fs_err {
-                            let error_msg =
-                                error_handler.handle_path_not_found(Path::new(path_str));
-                            self.progress_manager.print_error(&error_msg);
-                        }
+                    // The first failure decides the exit status
+                    if exit_code == 0 {
+                        exit_code = match err {
+                            PackageInstallerError::NotDetected { .. } => NOT_DETECTED_EXIT_CODE,
+                            _ => 1,
+                        };
                     }
-                    PackageInstallerError::EnhancedError(msg) => {
-                        // Print the enhanced error message directly
-                        self.progress_manager.print_error(msg);
+                    if continue_on_error && !self.cancellation.is_cancelled() {
+                        continue;
                     }
-                    // Handle other error variants as needed
-                    _ => {
-                        self.progress_manager
-                            .print_error(format!("Installation failed: {}", err));
+
+                    let not_reached: Vec<String> = installer
+                        .statuses()
+                        .into_iter()
+                        .filter(|(_, status)| *status == InstallationStatus::Pending)
+                        .map(|(name, _)| name)
+                        .collect();
+                    if !not_reached.is_empty() {
+                        self.progress_manager.print_info(format!(
+                            "Not installed, since the install stopped first: {}",
+                            not_reached.join(", ")
+                        ));
                     }
+
+                    break;
                 }
-                Ok(1)
             }
         }
+        self.progress_manager.finish_overall();
+
+        if let Some(json) = summary_json {
+            summary.record_statuses(&installer.statuses());
+            self.report_bulk_summary(&summary, started.elapsed(), json);
+        }
+
+        exit_code
+    }
+
+    /// Count a successful install in `summary`, and record what it installed
+    /// in the install state. The install itself succeeded, so failing to
+    /// record it is only a warning.
+    fn record_report<R: PackageRepository>(
+        &self,
+        package_repo: &R,
+        report: &InstallationReport,
+        summary: &mut InstallationSummary,
+    ) {
+        summary.record(report);
+
+        match self.record_installs(package_repo, report) {
+            Ok(upgraded) => {
+                for name in upgraded {
+                    summary.record_upgrade(&name);
+                }
+            }
+            Err(err) => {
+                self.progress_manager
+                    .print_warning(format!("Failed to record install state: {}", err));
+            }
+        }
+    }
+
+    /// Record the dependencies a canceled install finished before it
+    /// stopped, which are installed even though the package isn't
+    fn record_canceled<R: PackageRepository>(
+        &self,
+        package_repo: &R,
+        err: &PackageInstallerError,
+        summary: &mut InstallationSummary,
+    ) {
+        if let PackageInstallerError::InstallationCanceled { completed } = err {
+            for report in completed {
+                self.record_report(package_repo, report, summary);
+            }
+        }
+    }
+
+    /// Print the statistics closing an install of many packages, such as a
+    /// sync, either as a block of counts or as JSON
+    fn report_bulk_summary(&self, summary: &InstallationSummary, wall_clock: Duration, json: bool) {
+        let stats = summary.stats(wall_clock);
+
+        if json {
+            match serde_json::to_string_pretty(&stats) {
+                Ok(json) => self.progress_manager.print_progress(json),
+                Err(err) => self
+                    .progress_manager
+                    .print_error(format!("Failed to write the summary: {}", err)),
+            }
+            return;
+        }
+
+        let block = format!(
+            "Handled {} packages in {}\n  \
+             Newly installed:   {}\n  \
+             Already installed: {}\n  \
+             Upgraded:          {}\n  \
+             Skipped:           {}\n  \
+             Failed:            {}",
+            stats.total,
+            self.progress_manager.format_duration(wall_clock),
+            stats.installed,
+            stats.already_installed,
+            stats.upgraded,
+            stats.skipped,
+            stats.failed,
+        );
+
+        if summary.has_failures() {
+            self.progress_manager.print_warning(block);
+        } else {
+            self.progress_manager.print_success(block);
+        }
+    }
+
+    fn print_install_error(
+        &self,
+        err: &PackageInstallerError,
+        error_handler: &EnhancedErrorHandler<'_>,
+    ) {
+        // Word the error for people; as JSON, its kind goes along with it
+        let message = match err {
+            PackageInstallerError::FileSystemError(FileSystemError::PathNotFound(path_str)) => {
+                error_handler.handle_path_not_found(Path::new(path_str))
+            }
+            PackageInstallerError::FileSystemError(FileSystemError::PermissionDenied(path)) => {
+                error_handler.handle_permission_denied(path)
+            }
+            // Already worded for the user
+            PackageInstallerError::EnhancedError { message, .. } => message.clone(),
+            PackageInstallerError::InstallationError(InstallationError::CommandError(
+                CommandError::NonZeroExit { command, output },
+            )) => {
+                // --verbose or the output tail has already shown the output,
+                // unless the tail is turned off
+                let stderr =
+                    if self.app_config.verbose() || self.app_config.failure_output_lines() > 0 {
+                        ""
+                    } else {
+                        output.stderr.as_str()
+                    };
+                error_handler.handle_command_error(command, output.status, "", stderr)
+            }
+            PackageInstallerError::NotDetected { .. } => err.to_string(),
+            PackageInstallerError::InstallationCanceled { completed }
+                if self.cancellation.is_cancelled() =>
+            {
+                format!(
+                    "Installation interrupted after {} install(s) finished",
+                    completed.len()
+                )
+            }
+            PackageInstallerError::InstallationCanceled { .. } => {
+                "Installation canceled. Pass --yes to install without confirmation.".to_string()
+            }
+            _ => format!("Installation failed: {}", err),
+        };
+
+        self.progress_manager.print_error_report(err, message);
+    }
+
+    pub(super) async fn sync(
+        &self,
+        options: SyncOptions,
+        error_handler: &EnhancedErrorHandler<'_>,
+    ) -> Result<i32, PackageInstallCommandError> {
+        self.app_config.validate()?;
+        let started = Instant::now();
+        let SyncOptions {
+            dry_run,
+            tags,
+            exclude,
+            only_environment_matching,
+            json,
+            packages_from,
+        } = options;
+
+        let mut sync_cmd = PackageSyncService::new(self.app_config, self.package_repo);
+        if let Some(path) = &packages_from {
+            match self.load_package_list(path) {
+                Ok(package_names) => sync_cmd = sync_cmd.with_listed(package_names),
+                Err(message) => {
+                    self.progress_manager.print_error(message);
+                    return Ok(1);
+                }
+            }
+        }
+
+        let SyncTargets {
+            applicable: targets,
+            not_applicable,
+        } = match sync_cmd.targets(&tags, &exclude) {
+            Ok(targets) => targets,
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                return Ok(1);
+            }
+        };
+
+        // Either name each package left out up front, or only count them
+        // once the sync is done
+        let not_applicable_count = if only_environment_matching {
+            not_applicable.len()
+        } else {
+            for name in &not_applicable {
+                self.progress_manager.print_info(format!(
+                    "Skipping '{}', which doesn't support environment '{}'",
+                    name,
+                    self.app_config.environment()
+                ));
+            }
+            0
+        };
+        let print_not_applicable = || {
+            if not_applicable_count > 0 {
+                self.progress_manager.print_info(format!(
+                    "Packages left out for not supporting environment '{}': {}",
+                    self.app_config.environment(),
+                    not_applicable_count
+                ));
+            }
+        };
+
+        if targets.is_empty() {
+            self.progress_manager.print_info(format!(
+                "No packages to sync for environment '{}'",
+                self.app_config.environment()
+            ));
+            print_not_applicable();
+            return Ok(0);
+        }
+
+        match sync_cmd.excluded_dependencies(&targets, &exclude) {
+            Ok(needed) => {
+                for (name, dependent) in needed {
+                    self.progress_manager.print_info(format!(
+                        "'{}' is excluded, but '{}' depends on it, so it's still installed as a dependency",
+                        name, dependent
+                    ));
+                }
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                return Ok(1);
+            }
+        }
+
+        if dry_run {
+            return match sync_cmd.plan(&targets) {
+                Ok(plan) => {
+                    let lines: Vec<String> = plan
+                        .iter()
+                        .map(|step| format!("  • {} ({})", step.name, step.version))
+                        .collect();
+                    self.progress_manager.print_progress(format!(
+                        "Would check and install {} packages, in order:\n{}",
+                        plan.len(),
+                        lines.join("\n")
+                    ));
+                    print_not_applicable();
+                    Ok(0)
+                }
+                Err(err) => {
+                    self.progress_manager.print_error(format!("Error: {}", err));
+                    Ok(1)
+                }
+            };
+        }
+
+        let installer = PackageInstaller::new(
+            self.fs,
+            self.package_repo,
+            error_handler,
+            self.runner,
+            self.app_config,
+            self.progress_manager,
+            true, // Enable command checking
+        )
+        .with_install_state(self.no_check_install_state())
+        .with_cancellation(self.cancellation.clone());
+
+        // Resolve every target's dependencies and check the commands they
+        // all need once, up front
+        if let Err(err) = installer.plan_packages(&targets).await {
+            self.print_install_error(&err, error_handler);
+            print_not_applicable();
+            return Ok(1);
+        }
+
+        // As with patterns, the installer only installs shared dependencies
+        // once; unlike them, one failure doesn't end the sync unless
+        // stop_on_error is set
+        let mut summary = InstallationSummary::default();
+        let mut exit_code = 0;
+        self.progress_manager.start_overall(targets.len());
+        for package_name in &targets {
+            let result = installer.install_package(package_name).await;
+            self.progress_manager.advance_overall();
+
+            match result {
+                Ok(report) => self.record_report(self.package_repo, &report, &mut summary),
+                Err(err) => {
+                    self.print_install_error(&err, error_handler);
+                    summary.record_failure(package_name);
+                    self.record_canceled(self.package_repo, &err, &mut summary);
+                    exit_code = 1;
+
+                    if self.app_config.stop_on_error() || self.cancellation.is_cancelled() {
+                        break;
+                    }
+                }
+            }
+        }
+        self.progress_manager.finish_overall();
+
+        // Dependencies a failed install got to, and any packages it never
+        // reached, have no report
+        summary.record_statuses(&installer.statuses());
+        self.report_bulk_summary(&summary, started.elapsed(), json);
+        print_not_applicable();
+
+        Ok(exit_code)
     }
 
-    pub(super) async fn list(&self) -> Result<i32, PackageListCommandError> {
+    pub(super) async fn list(
+        &self,
+        since: Option<Timestamp>,
+        only_environment_matching: bool,
+    ) -> Result<i32, PackageListCommandError> {
         self.app_config.validate_minimal()?;
 
         let list_cmd = PackageListService::new(
+            self.fs,
             self.runner,
             self.app_config,
             self.progress_manager,
             self.package_repo,
-        );
+        )
+        .with_since(since)
+        .with_only_environment_matching(only_environment_matching);
 
         match list_cmd.execute().await {
-            PackageListResult::Success(output) => {
-                // Just print the package list
-                self.progress_manager.print_progress(output);
+            PackageListResult::Success(listing) => {
+                let text = listing.text.trim_end();
+                if !text.is_empty() {
+                    self.progress_manager.print_progress(text);
+                }
+                if let Some(table) = &listing.table {
+                    self.progress_manager
+                        .print_table(table.headers, &table.rows);
+                }
+                if let Some(note) = &listing.note {
+                    self.progress_manager.print_progress(note);
+                }
                 Ok(0)
             }
             PackageListResult::Error(error) => {
@@ -128,14 +709,343 @@ impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
         }
     }
 
+    /// The install state, when the config has packages without a check go by
+    /// it; otherwise the installer doesn't need it
+    fn no_check_install_state(&self) -> InstallState {
+        if self.app_config.no_check_policy() != NoCheckPolicy::UseState {
+            return InstallState::default();
+        }
+
+        self.state_store.load().unwrap_or_else(|err| {
+            self.progress_manager.print_warning(format!(
+                "Failed to load install state, so packages without a check will be installed: {}",
+                err
+            ));
+            InstallState::default()
+        })
+    }
+
+    /// Add every package the installer actually installed to the install
+    /// state, returning those that replaced a recorded install of another
+    /// version for this environment: the ones upgraded
+    fn record_installs<R: PackageRepository>(
+        &self,
+        package_repo: &R,
+        report: &InstallationReport,
+    ) -> Result<Vec<String>, InstallStateError> {
+        let mut installed = Vec::new();
+        collect_installed(report, &mut installed);
+
+        if installed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut state = self.state_store.load()?;
+        let environment = self.app_config.environment();
+
+        let mut upgraded = Vec::new();
+        for name in installed {
+            // The report doesn't carry versions, so look them up again
+            if let Ok(package) = package_repo.get_package(name) {
+                if state.packages.get(name).is_some_and(|previous| {
+                    previous.environment == environment && previous.version != package.version
+                }) {
+                    upgraded.push(name.to_string());
+                }
+                state.record(name, &package.version, environment);
+            }
+        }
+
+        self.state_store.save(&state)?;
+
+        Ok(upgraded)
+    }
+
+    pub(super) async fn status(&self) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let status_cmd = PackageStatusService::new(
+            self.fs,
+            self.runner,
+            self.app_config,
+            self.package_repo,
+            self.state_store,
+        );
+
+        match status_cmd.execute().await {
+            Ok(report) => {
+                if report.rows.is_empty() {
+                    self.progress_manager
+                        .print_progress("No packages have been installed by selfie yet.");
+                } else {
+                    self.progress_manager.print_progress("Installed packages:");
+                    self.progress_manager
+                        .print_table(PackageStatusReport::HEADERS, &report.rows);
+                }
+                Ok(if report.has_drift { 1 } else { 0 })
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) async fn check_updates(&self, json: bool) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let check_updates_cmd = PackageCheckUpdatesService::new(
+            self.runner,
+            self.app_config,
+            self.progress_manager,
+            self.package_repo,
+        )
+        .with_json(json);
+
+        match check_updates_cmd.execute().await {
+            Ok(report) => {
+                self.progress_manager
+                    .print_progress(report.output.trim_end());
+                Ok(if report.has_outdated { 1 } else { 0 })
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) fn plan(
+        &self,
+        package_name: &str,
+        format: PlanFormat,
+    ) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let plan_cmd = PackagePlanService::new(self.app_config, self.package_repo);
+
+        match plan_cmd.execute(package_name, format) {
+            Ok(output) => {
+                self.progress_manager.print_progress(output.trim_end());
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) fn deps(
+        &self,
+        package_name: Option<&str>,
+        dot: bool,
+        max_depth: Option<usize>,
+        reverse: bool,
+    ) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let deps_cmd =
+            PackageDepsService::new(self.app_config, self.package_repo).with_max_depth(max_depth);
+
+        let result = match package_name {
+            Some(package_name) if reverse => deps_cmd.execute_reverse(package_name),
+            _ => deps_cmd.execute(package_name, dot),
+        };
+
+        match result {
+            Ok(output) => {
+                self.progress_manager.print_progress(output.trim_end());
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) fn graph_check(&self) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let deps_cmd = PackageDepsService::new(self.app_config, self.package_repo);
+
+        match deps_cmd.find_cycles() {
+            Ok(cycles) if cycles.is_empty() => {
+                self.progress_manager
+                    .print_success("No dependency cycles found");
+                Ok(0)
+            }
+            Ok(cycles) => {
+                for cycle in &cycles {
+                    self.progress_manager
+                        .print_error(format!("Circular dependency: {}", cycle.join(" -> ")));
+                }
+                self.progress_manager.print_error(format!(
+                    "Found {} dependency cycle{}",
+                    cycles.len(),
+                    if cycles.len() == 1 { "" } else { "s" }
+                ));
+                Ok(1)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) fn rename(
+        &self,
+        old_name: &str,
+        new_name: &str,
+        diff: bool,
+    ) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let rename_cmd = PackageRenameService::new(self.fs, self.package_repo);
+
+        let plan = match rename_cmd.plan(old_name, new_name) {
+            Ok(plan) => plan,
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                return Ok(1);
+            }
+        };
+
+        self.progress_manager.print_progress(plan.summary());
+        if diff {
+            self.progress_manager
+                .print_progress(plan.diff(&self.progress_manager));
+        }
+
+        if !self.app_config.assume_yes() {
+            self.progress_manager
+                .print_info("Nothing changed. Pass --yes to apply the rename.");
+            return Ok(0);
+        }
+
+        match rename_cmd.apply(&plan) {
+            Ok(()) => {
+                self.progress_manager.print_success(format!(
+                    "Renamed '{}' to '{}'",
+                    plan.old_name, plan.new_name
+                ));
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
     pub(super) fn info(&self, package_name: &str) -> Result<i32, anyhow::Error> {
         self.app_config.validate_minimal()?;
 
-        self.progress_manager.print_warning(format!(
-            "Package info for '{}' not implemented yet",
-            package_name
-        ));
-        Ok(0)
+        let info_cmd = PackageInfoService::new(self.fs, self.app_config, self.package_repo);
+
+        match info_cmd.execute(package_name) {
+            Ok(output) => {
+                self.progress_manager.print_progress(output);
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) fn path(&self, package_name: &str) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let path_cmd = PackagePathService::new(self.fs, self.package_repo);
+
+        match path_cmd.execute(package_name) {
+            Ok(path) => {
+                self.progress_manager
+                    .print_progress(path.display().to_string());
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) fn forget(&self, package_name: &str) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let forget_cmd = PackageForgetService::new(
+            self.fs,
+            self.app_config,
+            self.package_repo,
+            self.state_store,
+        );
+
+        match forget_cmd.execute(package_name) {
+            Ok(true) => {
+                self.progress_manager.print_success(format!(
+                    "Forgot '{}'; it will be installed again next time",
+                    package_name
+                ));
+                Ok(0)
+            }
+            Ok(false) => {
+                self.progress_manager.print_info(format!(
+                    "'{}' isn't recorded as installed, so there's nothing to forget",
+                    package_name
+                ));
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) async fn verify(&self, package_name: &str) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let verify_cmd =
+            PackageVerifyService::new(self.fs, self.runner, self.app_config, self.package_repo);
+
+        match verify_cmd.verify(package_name).await {
+            Ok(Verification::Detected) => {
+                self.progress_manager.print_success(format!(
+                    "'{}' is installed: its check finds it",
+                    package_name
+                ));
+                Ok(0)
+            }
+            Ok(Verification::NoCheck) => {
+                self.progress_manager.print_warning(format!(
+                    "'{}' has no check for environment '{}', so it can't be verified",
+                    package_name,
+                    self.app_config.environment()
+                ));
+                Ok(1)
+            }
+            Ok(Verification::NotDetected) => {
+                self.progress_manager.print_error(format!(
+                    "'{}' isn't installed: its check doesn't find it",
+                    package_name
+                ));
+                Ok(NOT_DETECTED_EXIT_CODE)
+            }
+            Ok(Verification::CheckFailed(err)) => {
+                self.progress_manager
+                    .print_error(format!("Checking '{}' failed: {}", package_name, err));
+                Ok(1)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
     }
 
     pub(super) fn create(&self, package_name: &str) -> Result<i32, anyhow::Error> {
@@ -148,23 +1058,121 @@ impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
         Ok(0)
     }
 
-    pub(super) async fn validate(&self, package_name: &str, package_path: Option<&Path>) -> i32 {
-        use crate::services::{
-            command_validator::CommandValidator,
-            validation_command::{ValidationCommand, ValidationCommandResult},
-        };
+    pub(super) fn template(
+        &self,
+        package_name: &str,
+        force: bool,
+        diff: bool,
+    ) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let template_cmd = PackageTemplateService::new(self.fs, self.app_config, self.package_repo)
+            .with_force(force);
+
+        if diff {
+            match template_cmd.diff(package_name, &self.progress_manager) {
+                Ok(diff) => self.progress_manager.print_progress(diff),
+                Err(err) => {
+                    self.progress_manager.print_error(format!("Error: {}", err));
+                    return Ok(1);
+                }
+            }
+
+            if !self.app_config.assume_yes() {
+                self.progress_manager
+                    .print_info("Nothing changed. Pass --yes to write the template.");
+                return Ok(0);
+            }
+        }
+
+        match template_cmd.execute(package_name) {
+            Ok(path) => {
+                self.progress_manager
+                    .print_success(format!("Created {}", path.display()));
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) async fn edit(
+        &self,
+        package_name: &str,
+        create: bool,
+    ) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let editor = std::env::var("EDITOR")
+            .ok()
+            .filter(|editor| !editor.trim().is_empty());
+
+        let edit_cmd =
+            PackageEditService::new(self.fs, self.runner, self.app_config, self.package_repo)
+                .with_editor(editor)
+                .with_create(create);
+
+        match edit_cmd.execute(package_name).await {
+            Ok(edited) => {
+                if edited.created {
+                    self.progress_manager
+                        .print_success(format!("Created {}", edited.path.display()));
+                }
+                Ok(0)
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+
+    pub(super) async fn validate(
+        &self,
+        package_name: Option<&str>,
+        format: ValidateFormat,
+        package_path: Option<&Path>,
+        options: ValidateOptions,
+    ) -> i32 {
+        use crate::services::validation_command::{ValidationCommand, ValidationCommandResult};
 
         // Don't propagate the error; let the ?command run through even if the
         // config is bad.
         let _ = self.app_config.validate();
-        let command_validator = CommandValidator::new(self.runner);
+        let command_validator =
+            CommandValidator::new(self.runner).with_progress_manager(self.progress_manager);
 
         let validate_cmd = ValidationCommand::new(
             self.fs,
             self.app_config,
             self.progress_manager,
             &command_validator,
-        );
+        )
+        .with_heuristics(options.heuristics)
+        .with_fix(options.fix)
+        .with_diff(options.diff)
+        .with_run_checks(options.run_checks)
+        .with_all_environments(options.all_environments);
+
+        let Some(package_name) = package_name else {
+            // Already a report per package, so printed as is
+            return match validate_cmd.execute_all(format).await {
+                ValidationCommandResult::Valid(output) => {
+                    self.progress_manager.print_progress(output);
+                    0
+                }
+                ValidationCommandResult::Invalid(output) => {
+                    self.progress_manager.print_progress(output);
+                    1
+                }
+                ValidationCommandResult::Error(error) => {
+                    self.progress_manager.print_error(error);
+                    1
+                }
+            };
+        };
 
         match validate_cmd.execute(package_name, package_path).await {
             ValidationCommandResult::Valid(output) => {
@@ -181,4 +1189,54 @@ impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
             }
         }
     }
+
+    pub(super) async fn lint(
+        &self,
+        package_names: &[String],
+        run_checks: bool,
+    ) -> Result<i32, anyhow::Error> {
+        self.app_config.validate_minimal()?;
+
+        let command_validator =
+            CommandValidator::new(self.runner).with_progress_manager(self.progress_manager);
+        let lint_cmd = PackageLintService::new(
+            self.fs,
+            self.app_config,
+            self.package_repo,
+            &command_validator,
+        )
+        .with_run_checks(run_checks);
+
+        match lint_cmd.lint(package_names).await {
+            Ok(results) => {
+                for result in results.results() {
+                    self.progress_manager
+                        .print_progress(format_lint_result(result));
+                }
+                self.progress_manager
+                    .print_progress(format_lint_summary(&results));
+
+                Ok(if results.packages_with_issues().is_empty() {
+                    0
+                } else {
+                    1
+                })
+            }
+            Err(err) => {
+                self.progress_manager.print_error(format!("Error: {}", err));
+                Ok(1)
+            }
+        }
+    }
+}
+
+/// Names of the packages in a report (dependencies first) that were installed
+fn collect_installed<'r>(report: &'r InstallationReport, names: &mut Vec<&'r str>) {
+    for dependency in &report.dependencies {
+        collect_installed(dependency, names);
+    }
+
+    if report.status == InstallationStatus::Complete {
+        names.push(&report.package_name);
+    }
 }
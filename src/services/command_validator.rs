@@ -4,13 +4,36 @@
 // This module provides enhanced command validation capabilities for the package
 // validation service, making better use of the CommandRunner trait.
 
+use std::{
+    borrow::Cow, collections::HashMap, env::VarError, path::PathBuf, sync::Mutex, time::Duration,
+};
+
 use thiserror::Error;
 
 use crate::{
-    domain::package::EnvironmentConfig,
-    ports::command::{CommandError, CommandRunner},
+    adapters::progress::ProgressManager,
+    domain::{
+        installation::COMMAND_NOT_FOUND_STATUS,
+        package::{EnvironmentConfig, Package},
+    },
+    ports::command::{CommandError, CommandRunner, PROBE_TIMEOUT},
 };
 
+/// Exit status shells use when a command was found but can't be executed
+const NOT_EXECUTABLE_STATUS: i32 = 126;
+
+/// Commands that run the rest of their arguments as another command, with the
+/// options of theirs that take a value
+const WRAPPER_COMMANDS: &[(&str, &[&str])] = &[
+    (
+        "sudo",
+        &["-u", "-g", "-p", "-C", "-D", "-r", "-t", "-U", "-T"],
+    ),
+    ("env", &["-u", "-C", "--unset", "--chdir"]),
+    ("nice", &["-n", "--adjustment"]),
+    ("nohup", &[]),
+];
+
 /// Errors that can occur during command validation
 #[derive(Error, Debug)]
 pub(crate) enum CommandValidationError {
@@ -33,16 +56,12 @@ pub(crate) enum CommandValidationError {
 /// Result of a command validation
 #[derive(Debug, Clone)]
 pub(crate) struct CommandValidationResult {
-    /// Whether the command is valid
-    pub(crate) is_valid: bool,
     /// The command that was validated
     pub(crate) command: String,
     /// Error message (if any)
     pub(crate) error: Option<String>,
     /// Whether the command is available in the current environment
     pub(crate) is_available: bool,
-    /// Is this a warning rather than an error
-    pub(crate) is_warning: bool,
     /// The environment this validation applies to
     pub(crate) environment: String,
 }
@@ -50,12 +69,198 @@ pub(crate) struct CommandValidationResult {
 /// Service for validating shell commands in package definitions
 pub(crate) struct CommandValidator<'a, CR: CommandRunner> {
     runner: &'a CR,
+
+    /// Says why a command couldn't be looked up, with `--verbose`
+    progress_manager: ProgressManager,
+
+    /// Availability of commands already looked up, since many packages share
+    /// the same base command (e.g. `brew`)
+    availability: Mutex<HashMap<String, bool>>,
+
+    /// What `~` and `$HOME` in a command expand to
+    home_dir: Option<PathBuf>,
 }
 
 impl<'a, CR: CommandRunner> CommandValidator<'a, CR> {
     /// Create a new command validator
     pub(crate) fn new(runner: &'a CR) -> Self {
-        Self { runner }
+        Self {
+            runner,
+            progress_manager: ProgressManager::default(),
+            availability: Mutex::default(),
+            home_dir: dirs::home_dir(),
+        }
+    }
+
+    /// Expand `~` and `$HOME` in commands to `home_dir` rather than the
+    /// user's home directory
+    pub(crate) fn with_home_dir(mut self, home_dir: Option<PathBuf>) -> Self {
+        self.home_dir = home_dir;
+        self
+    }
+
+    /// `command` with `~` and variables expanded, as the shell does before
+    /// running it, e.g. `/home/me/bin/tool` for `~/bin/tool`
+    fn expand<'c>(&self, command: &'c str) -> Cow<'c, str> {
+        let home_dir = self
+            .home_dir
+            .as_ref()
+            .and_then(|home_dir| home_dir.to_str());
+
+        shellexpand::full_with_context(
+            command,
+            || home_dir,
+            |var| match var {
+                "HOME" => Ok(home_dir.map(String::from)),
+                var => match std::env::var(var) {
+                    Ok(value) => Ok(Some(value)),
+                    Err(VarError::NotPresent) => Ok(None),
+                    Err(err) => Err(err),
+                },
+            },
+        )
+        .unwrap_or(Cow::Borrowed(command))
+    }
+
+    /// Report failed lookups to `progress_manager`, which only shows them
+    /// with `--verbose`
+    pub(crate) fn with_progress_manager(mut self, progress_manager: ProgressManager) -> Self {
+        self.progress_manager = progress_manager;
+        self
+    }
+
+    /// Whether `command` is available, only asking the runner the first time
+    /// each command is looked up
+    pub(crate) async fn is_command_available(&self, command: &str) -> bool {
+        if let Some(&is_available) = self.availability.lock().unwrap().get(command) {
+            return is_available;
+        }
+
+        // Look up what the shell would run
+        let expanded = self.expand(command);
+        let is_available = match self.runner.is_command_available(&expanded).await {
+            Ok(is_available) => is_available,
+            Err(err) => {
+                self.progress_manager.print_verbose(format!(
+                    "Treating '{}' as unavailable, since looking it up failed: {}",
+                    command, err
+                ));
+                false
+            }
+        };
+        self.availability
+            .lock()
+            .unwrap()
+            .insert(command.to_string(), is_available);
+
+        is_available
+    }
+
+    /// Look up whether each of `commands` is available with a single shell
+    /// invocation, rather than one per command, so later
+    /// [`Self::is_command_available`] calls are answered from the cache. If
+    /// the batch can't be run or its output doesn't make sense, each command
+    /// is looked up on its own instead.
+    pub(crate) async fn probe_commands<'c>(&self, commands: impl IntoIterator<Item = &'c str>) {
+        let mut pending: Vec<&str> = Vec::new();
+        {
+            let availability = self.availability.lock().unwrap();
+            for command in commands {
+                if !availability.contains_key(command) && !pending.contains(&command) {
+                    pending.push(command);
+                }
+            }
+        }
+
+        if pending.is_empty() {
+            return;
+        }
+
+        match self.probe_batch(&pending).await {
+            Ok(Some(results)) => {
+                let mut availability = self.availability.lock().unwrap();
+                for (command, is_available) in pending.into_iter().zip(results) {
+                    availability.insert(command.to_string(), is_available);
+                }
+            }
+            // A shell that's stuck would hold up each lookup on its own just
+            // as long
+            Err(err @ CommandError::Timeout(_)) => {
+                self.progress_manager.print_verbose(format!(
+                    "Treating {} as unavailable, since looking them up failed: {}",
+                    pending.join(", "),
+                    err
+                ));
+                let mut availability = self.availability.lock().unwrap();
+                for command in pending {
+                    availability.insert(command.to_string(), false);
+                }
+            }
+            Ok(None) | Err(_) => {
+                for command in pending {
+                    self.is_command_available(command).await;
+                }
+            }
+        }
+    }
+
+    /// Probe the base install and check commands of every environment of
+    /// `packages` at once; see [`Self::probe_commands`]
+    pub(crate) async fn probe_packages(&self, packages: &[Package]) {
+        let commands: Vec<&str> = packages
+            .iter()
+            .flat_map(|package| package.environments.values())
+            .flat_map(|env_config| {
+                [
+                    Some(env_config.install.first_command()),
+                    env_config.check(),
+                    env_config.shell().map(|shell| shell.program()),
+                ]
+            })
+            .flatten()
+            .filter_map(Self::extract_base_command)
+            .collect();
+
+        self.probe_commands(commands).await;
+    }
+
+    /// Run one script that prints `1` or `0` per command, in order, for
+    /// whether it's available, or `None` if its output didn't make sense
+    async fn probe_batch(&self, commands: &[&str]) -> Result<Option<Vec<bool>>, CommandError> {
+        let script = commands
+            .iter()
+            .map(|command| {
+                // Expanded as in `is_command_available`, then quoted so the
+                // script runs each lookup as written
+                let expanded = self.expand(command);
+                format!(
+                    "if command -v '{}' >/dev/null 2>&1; then echo 1; else echo 0; fi",
+                    expanded.replace('\'', "'\\''")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let output = self
+            .runner
+            .execute_with_timeout(&script, PROBE_TIMEOUT)
+            .await
+            .or_else(CommandError::into_output)?;
+        if !output.succeeded() {
+            return Ok(None);
+        }
+
+        let results = output
+            .stdout
+            .lines()
+            .map(|line| match line.trim() {
+                "1" => Some(true),
+                "0" => Some(false),
+                _ => None,
+            })
+            .collect::<Option<Vec<bool>>>();
+
+        Ok(results.filter(|results| results.len() == commands.len()))
     }
 
     /// Validate a command in a package environment configuration
@@ -66,18 +271,9 @@ impl<'a, CR: CommandRunner> CommandValidator<'a, CR> {
     ) -> Vec<CommandValidationResult> {
         let mut results = Vec::new();
 
-        // Always validate the install command
-        let install_result = self.validate_command_syntax(env_name, &env_config.install);
-        results.push(install_result);
-
-        // Validate check command if present
-        if let Some(check_cmd) = &env_config.check {
-            let check_result = self.validate_command_syntax(env_name, check_cmd);
-            results.push(check_result);
-        }
-
-        // If validation enabled AND we have a shell command, check if basic shell commands are available
-        if let Some(command) = Self::extract_base_command(&env_config.install) {
+        // Syntax is checked by Package::validate_command_syntax; here it's
+        // whether the install command's program is available
+        if let Some(command) = Self::extract_base_command(env_config.install.first_command()) {
             let avail_result = self.check_command_availability(env_name, command).await;
             results.push(avail_result);
         }
@@ -85,66 +281,79 @@ impl<'a, CR: CommandRunner> CommandValidator<'a, CR> {
         results
     }
 
-    /// Validate command syntax without executing
-    pub(crate) fn validate_command_syntax(
-        &self,
-        env_name: &str,
-        command: &str,
-    ) -> CommandValidationResult {
-        // Check for unmatched quotes
-        let mut in_single_quotes = false;
-        let mut in_double_quotes = false;
-        let mut is_valid = true;
-        let mut error = None;
-
-        for c in command.chars() {
-            match c {
-                '\'' if !in_double_quotes => in_single_quotes = !in_single_quotes,
-                '"' if !in_single_quotes => in_double_quotes = !in_double_quotes,
-                _ => {}
-            }
+    /// Run a check command to see that it starts at all, returning why it
+    /// didn't: not found (exit status 127), not executable (126), or rejected
+    /// by the shell's parser. A check that runs and fails only means the
+    /// package isn't installed, and one that times out is left to `install`.
+    pub(crate) async fn run_check(&self, check: &str, timeout: Duration) -> Option<String> {
+        let output = match self
+            .runner
+            .execute_with_timeout(check, timeout)
+            .await
+            .or_else(CommandError::into_output)
+        {
+            Ok(output) => output,
+            Err(CommandError::Timeout(_)) => return None,
+            Err(err) => return Some(format!("Check command couldn't be run: {}", err)),
+        };
+        if output.succeeded() {
+            return None;
         }
 
-        if in_single_quotes {
-            is_valid = false;
-            error = Some("Unmatched single quote in command".to_string());
-        } else if in_double_quotes {
-            is_valid = false;
-            error = Some("Unmatched double quote in command".to_string());
+        let stderr = output.stderr.to_lowercase();
+        if output.status == COMMAND_NOT_FOUND_STATUS {
+            Some(format!(
+                "Check command not found (exit status {}): {}",
+                COMMAND_NOT_FOUND_STATUS,
+                output.stderr.trim()
+            ))
+        } else if output.status == NOT_EXECUTABLE_STATUS {
+            Some(format!(
+                "Check command isn't executable (exit status {}): {}",
+                NOT_EXECUTABLE_STATUS,
+                output.stderr.trim()
+            ))
+        } else if stderr.contains("syntax error") || stderr.contains("parse error") {
+            Some(format!(
+                "Check command isn't valid shell: {}",
+                output.stderr.trim()
+            ))
+        } else {
+            None
         }
+    }
 
-        // Check for invalid pipe usage
-        if command.contains("| |") {
-            is_valid = false;
-            error = Some("Invalid pipe usage in command".to_string());
-        }
+    /// Extract the base command from a command string: its first word, after
+    /// any `VAR=value` assignments and wrappers like `sudo` or `env`, so
+    /// `sudo apt install x` is `apt`. A wrapper with nothing after it is the
+    /// base command itself.
+    pub(crate) fn extract_base_command(command: &str) -> Option<&str> {
+        let mut words = command.split_whitespace().peekable();
+        let mut first_wrapper = None;
+
+        while let Some(word) = words.next() {
+            if is_assignment(word) {
+                continue;
+            }
 
-        // Check for other common syntax issues
-        if command.contains(" > ") && !command.contains("> /") && !command.contains("> ~/") {
-            return CommandValidationResult {
-                is_valid: true, // Not a critical error
-                command: command.to_string(),
-                error: Some("Potential unsafe redirection in command".to_string()),
-                is_available: true, // Don't know yet
-                is_warning: true,   // This is just a warning
-                environment: env_name.to_string(),
+            let Some((_, value_options)) = WRAPPER_COMMANDS.iter().find(|(name, _)| *name == word)
+            else {
+                return Some(word);
             };
-        }
+            first_wrapper.get_or_insert(word);
 
-        CommandValidationResult {
-            is_valid,
-            command: command.to_string(),
-            error,
-            is_available: true, // Don't know yet
-            is_warning: false,
-            environment: env_name.to_string(),
+            // Skip the wrapper's options, and the values of those that take one
+            while let Some(option) = words.next_if(|word| word.starts_with('-')) {
+                if option == "--" {
+                    break;
+                }
+                if value_options.contains(&option) {
+                    words.next();
+                }
+            }
         }
-    }
 
-    /// Extract the base command from a command string
-    pub(crate) fn extract_base_command(command: &str) -> Option<&str> {
-        // Simple extraction of the first word before a space, pipe, etc.
-        command.split_whitespace().next()
+        first_wrapper
     }
 
     /// Check if a command is available in the current environment
@@ -153,7 +362,7 @@ impl<'a, CR: CommandRunner> CommandValidator<'a, CR> {
         env_name: &str,
         command: &str,
     ) -> CommandValidationResult {
-        let is_available = self.runner.is_command_available(command).await;
+        let is_available = self.is_command_available(command).await;
 
         // Generate more environment-aware message
         let error_message = if !is_available {
@@ -166,11 +375,9 @@ impl<'a, CR: CommandRunner> CommandValidator<'a, CR> {
         };
 
         CommandValidationResult {
-            is_valid: true, // The command itself could be valid even if not available
             command: command.to_string(),
             error: error_message,
             is_available,
-            is_warning: true, // This is a warning rather than an error
             environment: env_name.to_string(),
         }
     }
@@ -259,57 +466,99 @@ impl<'a, CR: CommandRunner> CommandValidator<'a, CR> {
             .iter()
             .any(|&indicator| command.contains(indicator))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ports::command::MockCommandRunner;
+    /// Check if the command pipes something it downloads straight into a
+    /// shell, e.g. `curl -fsSL https://example.com/install.sh | sh`
+    pub(crate) fn pipes_download_into_shell(&self, command: &str) -> bool {
+        const SHELLS: [&str; 3] = ["sh", "bash", "zsh"];
 
-    #[test]
-    fn test_validate_command_syntax_valid() {
-        let runner = MockCommandRunner::new();
-        let validator = CommandValidator::new(&runner);
+        // `||` isn't a pipe, so keep it from being split on
+        let command = command.replace("||", "\0");
+        let mut downloading = false;
 
-        let result = validator.validate_command_syntax("test-env", "echo hello");
-        assert!(result.is_valid);
-        assert!(result.error.is_none());
-        assert_eq!(result.environment, "test-env");
-    }
+        for segment in command.split('|') {
+            let mut words = segment.split_whitespace();
+            let mut program = words.next();
+            if program == Some("sudo") {
+                program = words.next();
+            }
 
-    #[test]
-    fn test_validate_command_syntax_unmatched_quote() {
-        let runner = MockCommandRunner::new();
-        let validator = CommandValidator::new(&runner);
+            // Also match full paths such as /bin/bash
+            let is_shell = program
+                .and_then(|program| program.rsplit('/').next())
+                .is_some_and(|name| SHELLS.contains(&name));
+
+            if downloading && is_shell {
+                return true;
+            }
+
+            // Only the segment directly before a pipe feeds into it
+            downloading = segment.contains("curl ") || segment.contains("wget ");
+        }
 
-        let result = validator.validate_command_syntax("test-env", "echo 'hello");
-        assert!(!result.is_valid);
-        assert!(result.error.unwrap().contains("Unmatched single quote"));
-        assert_eq!(result.environment, "test-env");
+        false
     }
 
-    #[test]
-    fn test_validate_command_syntax_invalid_pipe() {
-        let runner = MockCommandRunner::new();
-        let validator = CommandValidator::new(&runner);
+    /// The program a check command looks for, e.g. `fd` for `which fd` or
+    /// `rg` for `rg --version`. `None` for checks that test something other
+    /// than a program, like `test -f ~/.config/app`.
+    pub(crate) fn check_target(check: &str) -> Option<&str> {
+        const LOOKUPS: [&str; 3] = ["which", "type", "hash"];
+        const NON_PROGRAM_CHECKS: [&str; 3] = ["test", "[", "[["];
 
-        let result = validator.validate_command_syntax("test-env", "echo hello | | grep world");
-        assert!(!result.is_valid);
-        assert!(result.error.unwrap().contains("Invalid pipe usage"));
-        assert_eq!(result.environment, "test-env");
+        let first_command = check.split(['|', ';', '&']).next()?;
+        let mut words = first_command.split_whitespace();
+        let program = words.next()?;
+
+        if LOOKUPS.contains(&program) || program == "command" {
+            words.find(|word| !word.starts_with('-'))
+        } else if NON_PROGRAM_CHECKS.contains(&program) {
+            None
+        } else {
+            program.rsplit('/').next()
+        }
     }
 
-    #[test]
-    fn test_validate_command_syntax_unsafe_redirection() {
-        let runner = MockCommandRunner::new();
-        let validator = CommandValidator::new(&runner);
+    /// Whether the program a check command looks for plausibly comes from the
+    /// install command, by comparing it with the install command's words.
+    /// Abbreviated names count, e.g. `rg` for `ripgrep` or `nvim` for `neovim`.
+    pub(crate) fn check_matches_install(&self, install: &str, check: &str) -> bool {
+        let Some(target) = Self::check_target(check) else {
+            return true;
+        };
 
-        let result = validator.validate_command_syntax("test-env", "echo hello > output.txt");
-        assert!(result.is_valid); // This is valid but generates a warning
-        assert!(result.is_warning);
-        assert!(result.error.unwrap().contains("unsafe redirection"));
-        assert_eq!(result.environment, "test-env");
+        install
+            .split(|c: char| c.is_whitespace() || "/@:=".contains(c))
+            .filter(|word| !word.is_empty() && !word.starts_with('-'))
+            .any(|word| is_subsequence(target, word) || (word.len() >= 3 && target.contains(word)))
     }
+}
+
+/// Whether `word` is a shell variable assignment, like `FOO=1`
+fn is_assignment(word: &str) -> bool {
+    let Some((name, _)) = word.split_once('=') else {
+        return false;
+    };
+
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether all of `needle`'s characters appear in `haystack`, in order
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::{
+        adapters::command::shell::{hanging_lookup_shell, ShellCommandRunner},
+        ports::command::{CommandOutput, MockCommandRunner},
+    };
 
     #[test]
     fn test_extract_base_command() {
@@ -329,24 +578,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_base_command_skips_prefixes() {
+        let extract = CommandValidator::<MockCommandRunner>::extract_base_command;
+
+        assert_eq!(extract("sudo apt install x"), Some("apt"));
+        assert_eq!(extract("sudo -u admin -E apt install x"), Some("apt"));
+        assert_eq!(extract("env FOO=1 BAR=2 tool --flag"), Some("tool"));
+        assert_eq!(extract("env -i -u HOME tool"), Some("tool"));
+        assert_eq!(extract("nice -n 19 make install"), Some("make"));
+        assert_eq!(extract("nohup ./install.sh"), Some("./install.sh"));
+        assert_eq!(extract("CC=clang RUSTFLAGS=-g cargo build"), Some("cargo"));
+        assert_eq!(extract("sudo -- env FOO=1 nice cargo"), Some("cargo"));
+
+        // Only a wrapper, or not quite an assignment
+        assert_eq!(extract("sudo -v"), Some("sudo"));
+        assert_eq!(extract("env"), Some("env"));
+        assert_eq!(extract("=x tool"), Some("=x"));
+        assert_eq!(extract("1FOO=x tool"), Some("1FOO=x"));
+        assert_eq!(extract(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_command_availability_cached() {
+        let mut runner = MockCommandRunner::new();
+        runner
+            .expect_is_command_available()
+            .with(mockall::predicate::eq("brew"))
+            .times(1)
+            .returning(|_| Ok(true));
+
+        let validator = CommandValidator::new(&runner);
+
+        for package in ["ripgrep", "fd", "bat"] {
+            let env_config = EnvironmentConfig::new(format!("brew install {package}"));
+            let results = validator
+                .validate_environment_commands("mac-env", &env_config)
+                .await;
+
+            assert!(results.iter().all(|result| result.is_available));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_command_availability_expands_home() {
+        let mut runner = MockCommandRunner::new();
+        runner.mock_is_command_available("/home/test/bin/tool", true);
+
+        let validator =
+            CommandValidator::new(&runner).with_home_dir(Some(PathBuf::from("/home/test")));
+
+        for command in ["~/bin/tool", "$HOME/bin/tool"] {
+            let result = validator
+                .check_command_availability("mac-env", command)
+                .await;
+            assert!(result.is_available);
+            assert_eq!(result.command, command);
+        }
+    }
+
     #[tokio::test]
     async fn test_check_command_availability() {
         let mut runner = MockCommandRunner::new();
         runner
             .expect_is_command_available()
             .with(mockall::predicate::eq("echo"))
-            .returning(|_| true);
+            .returning(|_| Ok(true));
         runner
             .expect_is_command_available()
             .with(mockall::predicate::eq("nonexistent"))
-            .returning(|_| false);
+            .returning(|_| Ok(false));
 
         let validator = CommandValidator::new(&runner);
 
         let result = validator
             .check_command_availability("mac-env", "echo")
             .await;
-        assert!(result.is_valid);
         assert!(result.is_available);
         assert!(result.error.is_none());
         assert_eq!(result.environment, "mac-env");
@@ -354,7 +661,6 @@ mod tests {
         let result = validator
             .check_command_availability("mac-env", "nonexistent")
             .await;
-        assert!(result.is_valid); // The command syntax is valid even if not available
         assert!(!result.is_available);
         assert!(result.error.unwrap().contains("not found"));
         assert_eq!(result.environment, "mac-env");
@@ -393,43 +699,87 @@ mod tests {
         assert!(!validator.might_download_content("echo hello"));
     }
 
+    #[test]
+    fn test_pipes_download_into_shell() {
+        let runner = MockCommandRunner::new();
+        let validator = CommandValidator::new(&runner);
+
+        assert!(
+            validator.pipes_download_into_shell("curl -fsSL https://example.com/install.sh | sh")
+        );
+        assert!(validator.pipes_download_into_shell("wget -qO- https://example.com/i.sh | bash"));
+        assert!(validator.pipes_download_into_shell("curl https://example.com/i.sh|zsh -s -- -y"));
+        assert!(validator.pipes_download_into_shell("curl https://example.com/i.sh | sudo bash"));
+        assert!(validator.pipes_download_into_shell("curl https://example.com/i.sh | /bin/sh"));
+
+        // Downloads that aren't executed, and shells fed something local
+        assert!(
+            !validator.pipes_download_into_shell("curl -L https://example.com/x.tar.gz | tar xz")
+        );
+        assert!(!validator.pipes_download_into_shell("curl -O https://example.com/install.sh"));
+        assert!(!validator.pipes_download_into_shell("cat install.sh | sh"));
+        assert!(
+            !validator.pipes_download_into_shell("curl https://example.com/i.sh || sh fallback.sh")
+        );
+        assert!(
+            !validator.pipes_download_into_shell("curl https://example.com/x | grep sh | shasum")
+        );
+    }
+
+    #[test]
+    fn test_check_matches_install() {
+        let runner = MockCommandRunner::new();
+        let validator = CommandValidator::new(&runner);
+
+        assert!(validator.check_matches_install("brew install ripgrep", "which rg"));
+        assert!(validator.check_matches_install("cargo install fd-find", "command -v fd"));
+        assert!(validator.check_matches_install("brew install neovim", "nvim --version"));
+        assert!(validator.check_matches_install("brew install python@3.12", "which python3"));
+        assert!(validator.check_matches_install("apt install jq", "test -x /usr/bin/jq"));
+
+        // A check copied from another package
+        assert!(!validator.check_matches_install("brew install rg", "which fd"));
+        assert!(!validator.check_matches_install("cargo install tokei", "bat --version"));
+    }
+
     #[tokio::test]
     async fn test_validate_environment_commands() {
         let mut runner = MockCommandRunner::new();
         runner
             .expect_is_command_available()
             .with(mockall::predicate::eq("brew"))
-            .returning(|_| true);
+            .returning(|_| Ok(true));
 
         let validator = CommandValidator::new(&runner);
 
         // Create a test environment config
         let env_config = EnvironmentConfig {
-            install: "brew install ripgrep".to_string(),
-            check: Some("which rg".to_string()),
+            description: None,
+            homepage: None,
+            install: "brew install ripgrep".into(),
+            check: Some("which rg".into()),
+            check_file: None,
+            check_path_command: None,
+            check_timeout: None,
+            check_inverted: false,
             dependencies: vec![],
+            check_dependencies: Vec::new(),
+            check_implies_dependencies: false,
+            stdin: None,
+            skip_if: None,
+            installed_version_command: None,
+            shell: None,
         };
 
         let results = validator
             .validate_environment_commands("mac-env", &env_config)
             .await;
 
-        // Should have 3 results: install syntax, check syntax, and command availability
-        assert_eq!(results.len(), 3);
-
-        // All should be valid
-        assert!(results.iter().all(|r| r.is_valid));
-
-        // The first two are syntax checks
-        assert_eq!(results[0].command, "brew install ripgrep");
-        assert_eq!(results[1].command, "which rg");
+        // Only the availability of the install command's program
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].command, "brew");
+        assert!(results[0].is_available);
         assert_eq!(results[0].environment, "mac-env");
-        assert_eq!(results[1].environment, "mac-env");
-
-        // The third is availability check
-        assert_eq!(results[2].command, "brew");
-        assert!(results[2].is_available);
-        assert_eq!(results[2].environment, "mac-env");
     }
 
     #[test]
@@ -464,4 +814,130 @@ mod tests {
         assert!(windows_result.is_some());
         assert!(windows_result.unwrap().contains("choco"));
     }
+
+    #[tokio::test]
+    async fn test_probe_commands_batches_lookups() {
+        let commands: Vec<String> = (0..50).map(|i| format!("tool-{}", i)).collect();
+
+        // One shell for all of them, and none per command
+        let mut runner = MockCommandRunner::new();
+        runner
+            .expect_execute_with_timeout()
+            .withf(|script, timeout| script.lines().count() == 50 && *timeout == PROBE_TIMEOUT)
+            .times(1)
+            .returning(|script, _| {
+                // Every other tool is available
+                let stdout = (0..script.lines().count())
+                    .map(|i| if i % 2 == 0 { "1\n" } else { "0\n" })
+                    .collect();
+                Ok(CommandOutput {
+                    stdout,
+                    ..CommandOutput::default()
+                })
+            });
+        runner.expect_is_command_available().never();
+        let validator = CommandValidator::new(&runner);
+
+        validator
+            .probe_commands(commands.iter().map(String::as_str))
+            .await;
+        // Already probed, so nothing is looked up again
+        validator
+            .probe_commands(commands.iter().map(String::as_str))
+            .await;
+
+        for (i, command) in commands.iter().enumerate() {
+            assert_eq!(validator.is_command_available(command).await, i % 2 == 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_commands_falls_back_to_each_command() {
+        let mut runner = MockCommandRunner::new();
+        runner
+            .expect_execute_with_timeout()
+            .returning(|_, _| Err(CommandError::ExecutionError("no shell".to_string())));
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("port", false);
+
+        let validator = CommandValidator::new(&runner);
+        validator.probe_commands(["brew", "port", "brew"]).await;
+
+        assert!(validator.is_command_available("brew").await);
+        assert!(!validator.is_command_available("port").await);
+    }
+
+    #[tokio::test]
+    async fn test_probe_timeout_is_unavailable() {
+        // A shell that's stuck, as far as lookups go
+        let dir = tempfile::tempdir().unwrap();
+        let runner = ShellCommandRunner::new(
+            &hanging_lookup_shell(dir.path()),
+            Duration::from_millis(200),
+        );
+        let validator = CommandValidator::new(&runner);
+
+        let start = Instant::now();
+        assert!(!validator.is_command_available("brew").await);
+        assert!(start.elapsed() < PROBE_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_probe_batch_timeout_skips_lookups() {
+        let mut runner = MockCommandRunner::new();
+        runner
+            .expect_execute_with_timeout()
+            .withf(|_, timeout| *timeout == PROBE_TIMEOUT)
+            .times(1)
+            .returning(|_, timeout| Err(CommandError::Timeout(timeout)));
+        runner.expect_is_command_available().never();
+
+        let validator = CommandValidator::new(&runner);
+
+        // The batch doesn't fall back to looking up each command, which would
+        // wait as long again for every one of them
+        validator.probe_commands(["port", "nix"]).await;
+        assert!(!validator.is_command_available("port").await);
+        assert!(!validator.is_command_available("nix").await);
+    }
+
+    #[tokio::test]
+    async fn test_probe_commands_with_shell() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10));
+        let validator = CommandValidator::new(&runner);
+
+        validator
+            .probe_commands(["sh", "selfie-no-such-command", "it's-not-here-either"])
+            .await;
+
+        let availability = validator.availability.lock().unwrap().clone();
+        assert_eq!(
+            availability,
+            HashMap::from([
+                ("sh".to_string(), true),
+                ("selfie-no-such-command".to_string(), false),
+                ("it's-not-here-either".to_string(), false),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_check_with_shell() {
+        let runner = ShellCommandRunner::new("/bin/sh", Duration::from_secs(10));
+        let validator = CommandValidator::new(&runner);
+        let timeout = Duration::from_secs(10);
+
+        // Running and failing only means the package isn't installed
+        assert_eq!(validator.run_check("true", timeout).await, None);
+        assert_eq!(validator.run_check("false", timeout).await, None);
+
+        let not_found = validator
+            .run_check("selfie-no-such-command --version", timeout)
+            .await
+            .unwrap();
+        assert!(not_found.starts_with("Check command not found (exit status 127)"));
+
+        let unparsable = validator.run_check("if then", timeout).await.unwrap();
+        assert!(unparsable.starts_with("Check command isn't valid shell"));
+    }
 }
@@ -6,6 +6,7 @@ use std::error::Error;
 use std::path::Path;
 
 use crate::adapters::progress::{MessageType, ProgressManager};
+use crate::domain::shell::shell_quote;
 use crate::domain::validation::ValidationResult;
 use crate::ports::filesystem::FileSystem;
 use crate::ports::package_repo::PackageRepository;
@@ -82,6 +83,11 @@ impl<'a> EnhancedErrorHandler<'a> {
         self.formatter.format_circular_dependency(cycle)
     }
 
+    /// Handle dependencies that can't be found, given how they were reached
+    pub(crate) fn handle_missing_dependency(&self, chain: &[String]) -> String {
+        self.formatter.format_missing_dependency(chain)
+    }
+
     /// Handle path not found errors with suggestions
     pub(crate) fn handle_path_not_found(&self, path: &Path) -> String {
         let suggestions = self.suggestion_provider.suggest_path(path);
@@ -107,6 +113,27 @@ impl<'a> EnhancedErrorHandler<'a> {
         message
     }
 
+    /// Handle paths selfie isn't allowed to read or write, suggesting how to
+    /// check and fix their ownership and permissions
+    pub(crate) fn handle_permission_denied(&self, path: &Path) -> String {
+        // Quoted for the commands, which may be pasted as they are
+        let quoted = shell_quote(path);
+        let path = path.display();
+
+        format!(
+            "Permission denied: {path}\n\n\
+             selfie can't access this path as the current user. It may belong to \
+             another user, be missing read or write permission, or sit in a \
+             directory you can't enter.\n\n\
+             Check its owner and permissions:\n  \
+             ls -ld {quoted}\n\n\
+             If it should be yours, take ownership or grant yourself access:\n  \
+             sudo chown \"$(whoami)\" {quoted}\n  \
+             chmod u+rw {quoted}    # a file\n  \
+             chmod u+rwx {quoted}   # a directory"
+        )
+    }
+
     /// Handle general errors with context extraction
     pub(crate) fn handle_error(&self, error: &dyn Error) -> String {
         // Analyze the error string to see if we can provide more specific handling
@@ -215,7 +242,6 @@ impl<'a> EnhancedErrorHandler<'a> {
 mod tests {
     use super::*;
     use crate::domain::errors::{EnhancedPackageError, ErrorContext};
-    use crate::domain::package::PackageBuilder;
     use crate::ports::{filesystem::MockFileSystem, package_repo::MockPackageRepository};
 
     #[test]
@@ -224,25 +250,8 @@ mod tests {
         let mut package_repo = MockPackageRepository::new();
         let progress_manager = ProgressManager::default();
 
-        // Create test packages
-        let packages = vec![
-            PackageBuilder::default()
-                .name("ripgrep")
-                .version("1.0.0")
-                .build(),
-            PackageBuilder::default()
-                .name("ripgrep-all")
-                .version("1.0.0")
-                .build(),
-            PackageBuilder::default()
-                .name("fzf")
-                .version("1.0.0")
-                .build(),
-        ];
-
-        package_repo
-            .expect_list_packages()
-            .returning(move || Ok(packages.clone()));
+        package_repo.mock_find_similar("rigrep", &["ripgrep"]);
+        package_repo.mock_find_similar("something-completely-different", &[]);
 
         let handler = EnhancedErrorHandler::new(&fs, &package_repo, progress_manager);
 
@@ -293,6 +302,25 @@ mod tests {
         assert!(error_msg.contains("Parent directory doesn't exist"));
     }
 
+    #[test]
+    fn test_handle_permission_denied() {
+        let fs = MockFileSystem::default();
+        let package_repo = MockPackageRepository::new();
+        let handler = EnhancedErrorHandler::new(&fs, &package_repo, ProgressManager::default());
+
+        let error_msg = handler.handle_permission_denied(Path::new("/test/packages"));
+        assert!(error_msg.starts_with("Permission denied: /test/packages"));
+        assert!(error_msg.contains("ls -ld '/test/packages'"));
+        assert!(error_msg.contains("sudo chown \"$(whoami)\" '/test/packages'"));
+        assert!(error_msg.contains("chmod u+rwx '/test/packages'"));
+        assert!(!error_msg.contains("not found"));
+
+        // Paths with spaces or quotes stay one word
+        let error_msg = handler.handle_permission_denied(Path::new("/my packages/it's"));
+        assert!(error_msg.starts_with("Permission denied: /my packages/it's"));
+        assert!(error_msg.contains(r"chmod u+rw '/my packages/it'\''s'"));
+    }
+
     #[test]
     fn test_handle_circular_dependency() {
         let fs = MockFileSystem::default();
@@ -357,12 +385,7 @@ mod tests {
             });
 
         // Set up suggestion provider
-        package_repo.expect_list_packages().returning(move || {
-            Ok(vec![crate::domain::package::PackageBuilder::default()
-                .name("ripgrep")
-                .version("1.0.0")
-                .build()])
-        });
+        package_repo.mock_find_similar("rigrep", &["ripgrep"]);
 
         // Create the error handler
         let error_handler = EnhancedErrorHandler::new(&fs, &package_repo, progress_manager);
@@ -410,23 +433,9 @@ mod tests {
         let fs = MockFileSystem::default();
         let mut package_repo = MockPackageRepository::new();
 
-        // Set up package repository to list some packages
-        package_repo.expect_list_packages().returning(move || {
-            Ok(vec![
-                crate::domain::package::PackageBuilder::default()
-                    .name("ripgrep")
-                    .version("1.0.0")
-                    .build(),
-                crate::domain::package::PackageBuilder::default()
-                    .name("ripgrep-all")
-                    .version("1.0.0")
-                    .build(),
-                crate::domain::package::PackageBuilder::default()
-                    .name("fzf")
-                    .version("1.0.0")
-                    .build(),
-            ])
-        });
+        // Set up package repository to find similar names
+        package_repo.mock_find_similar("rigrep", &["ripgrep"]);
+        package_repo.mock_find_similar("xyz", &[]);
 
         // Create the suggestion provider
         let provider = SuggestionProvider::new(&fs, &package_repo);
@@ -232,6 +232,39 @@ impl ErrorFormatter {
         output
     }
 
+    /// Format a missing dependency, showing the chain of packages that
+    /// required it
+    pub(crate) fn format_missing_dependency(&self, chain: &[String]) -> String {
+        let mut output = String::new();
+
+        let error_header = self.format_header("Dependency not found", MessageType::Error);
+        output.push_str(&error_header);
+
+        let missing = chain.last().map(String::as_str).unwrap_or_default();
+        output.push_str(&format!(
+            "No package file was found for dependency '{}'.\n\n",
+            missing
+        ));
+
+        output.push_str("Required via:\n");
+
+        let chain_text = if self.progress_manager.use_colors() {
+            let packages: Vec<String> = chain
+                .iter()
+                .map(|pkg| style(pkg).magenta().to_string())
+                .collect();
+            packages.join(" → ")
+        } else {
+            chain.join(" → ")
+        };
+
+        output.push_str(&format!("  {}\n\n", chain_text));
+
+        output.push_str("Please add the missing package or fix the reference to it.\n");
+
+        output
+    }
+
     /// Format a validation result
     pub(crate) fn format_validation(&self, result: &ValidationResult) -> String {
         // Use the existing validation formatter
@@ -332,6 +365,23 @@ mod tests {
         assert!(output.contains("package-a → package-b → package-c → package-a"));
     }
 
+    #[test]
+    fn test_format_missing_dependency() {
+        let progress_manager = ProgressManager::default();
+        let formatter = ErrorFormatter::new(progress_manager);
+
+        let chain = vec![
+            "main".to_string(),
+            "dep1".to_string(),
+            "missing".to_string(),
+        ];
+        let output = formatter.format_missing_dependency(&chain);
+
+        assert!(output.contains("Error: Dependency not found"));
+        assert!(output.contains("dependency 'missing'"));
+        assert!(output.contains("main → dep1 → missing"));
+    }
+
     #[test]
     fn test_format_permission_error() {
         let progress_manager = ProgressManager::default();
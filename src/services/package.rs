@@ -1,3 +1,16 @@
+pub mod check_updates;
+pub mod deps;
+pub mod edit;
+pub mod forget;
+pub mod info;
 pub mod install;
+pub mod lint;
 pub mod list;
+pub mod path;
+pub mod plan;
+pub mod rename;
+pub mod status;
+pub mod sync;
+pub mod template;
 pub mod validate;
+pub mod verify;
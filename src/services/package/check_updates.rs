@@ -0,0 +1,410 @@
+// src/services/package/check_updates.rs
+// Implementation of the 'selfie package check-updates' command
+
+use console::style;
+use semver::Version;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    adapters::progress::ProgressManager,
+    domain::{config::AppConfig, package::Package},
+    ports::{
+        command::{CommandError, CommandRunner},
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+/// How a package's declared `version` compares with the version its
+/// `installed_version_command` reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub(crate) enum UpdateStatus {
+    /// The declared version matches the installed one
+    UpToDate,
+
+    /// The declared version is older than the installed one
+    Outdated,
+
+    /// The declared version is newer than the installed one
+    Ahead,
+
+    /// The versions can't be compared, and why
+    Unknown(String),
+}
+
+impl UpdateStatus {
+    fn describe(&self) -> String {
+        match self {
+            Self::UpToDate => "Up to date".to_string(),
+            Self::Outdated => "Outdated".to_string(),
+            Self::Ahead => "Ahead".to_string(),
+            Self::Unknown(reason) => format!("Unknown: {}", reason),
+        }
+    }
+}
+
+/// One package's row of the check-updates report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct PackageUpdate {
+    pub(crate) name: String,
+    pub(crate) declared: String,
+    pub(crate) installed: Option<String>,
+    #[serde(flatten)]
+    pub(crate) status: UpdateStatus,
+}
+
+/// Output of the check-updates command
+pub(crate) struct CheckUpdatesReport {
+    pub(crate) output: String,
+
+    /// Whether any package's declared version is behind the installed one
+    pub(crate) has_outdated: bool,
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum CheckUpdatesError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error("Failed to serialize report as JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Handles the 'package check-updates' command
+pub(crate) struct PackageCheckUpdatesService<'a, CR: CommandRunner, PR: PackageRepository> {
+    runner: &'a CR,
+    config: &'a AppConfig,
+    progress_manager: ProgressManager,
+    package_repo: &'a PR,
+    json: bool,
+}
+
+impl<'a, CR: CommandRunner, PR: PackageRepository> PackageCheckUpdatesService<'a, CR, PR> {
+    /// Create a new check-updates command handler
+    pub(crate) fn new(
+        runner: &'a CR,
+        config: &'a AppConfig,
+        progress_manager: ProgressManager,
+        package_repo: &'a PR,
+    ) -> Self {
+        Self {
+            runner,
+            config,
+            progress_manager,
+            package_repo,
+            json: false,
+        }
+    }
+
+    /// Write the report as JSON instead of a table
+    pub(crate) fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Compare the declared version of every package for the current
+    /// environment with the version it reports being installed
+    pub(crate) async fn execute(&self) -> Result<CheckUpdatesReport, CheckUpdatesError> {
+        let updates = self.updates().await?;
+        let has_outdated = updates
+            .iter()
+            .any(|update| update.status == UpdateStatus::Outdated);
+
+        let output = if self.json {
+            serde_json::to_string_pretty(&updates)?
+        } else if updates.is_empty() {
+            format!(
+                "No packages support environment '{}'.",
+                self.config.environment()
+            )
+        } else {
+            self.format_updates(&updates)
+        };
+
+        Ok(CheckUpdatesReport {
+            output,
+            has_outdated,
+        })
+    }
+
+    /// Each package for the current environment, sorted by name, with how its
+    /// versions compare
+    pub(crate) async fn updates(&self) -> Result<Vec<PackageUpdate>, CheckUpdatesError> {
+        let (mut packages, _) = self
+            .config
+            .partition_by_environment(self.package_repo.list_packages()?);
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut updates = Vec::with_capacity(packages.len());
+        for package in packages {
+            updates.push(self.package_update(&package).await);
+        }
+
+        Ok(updates)
+    }
+
+    async fn package_update(&self, package: &Package) -> PackageUpdate {
+        let mut update = PackageUpdate {
+            name: package.name.clone(),
+            declared: package.version.clone(),
+            installed: None,
+            status: UpdateStatus::Unknown("no installed_version_command".to_string()),
+        };
+
+        let Some(version_cmd) = self
+            .config
+            .resolve_environment(package)
+            .ok()
+            .and_then(|env_config| env_config.installed_version_command())
+        else {
+            return update;
+        };
+
+        let installed = match self.installed_version(version_cmd).await {
+            Ok(installed) => installed,
+            Err(reason) => {
+                update.status = UpdateStatus::Unknown(reason);
+                return update;
+            }
+        };
+        update.installed = Some(installed.to_string());
+
+        update.status = match Version::parse(&package.version) {
+            Ok(declared) if declared < installed => UpdateStatus::Outdated,
+            Ok(declared) if declared > installed => UpdateStatus::Ahead,
+            Ok(_) => UpdateStatus::UpToDate,
+            Err(_) => UpdateStatus::Unknown(format!(
+                "declared version '{}' isn't semver",
+                package.version
+            )),
+        };
+
+        update
+    }
+
+    /// Run `version_cmd` and find the version in what it prints
+    async fn installed_version(&self, version_cmd: &str) -> Result<Version, String> {
+        let output = self
+            .runner
+            .execute_with_timeout(version_cmd, self.config.check_timeout())
+            .await
+            .map_err(|err| match err {
+                CommandError::Timeout(_) => "installed_version_command timed out".to_string(),
+                err => err.to_string(),
+            })?;
+
+        parse_installed_version(output.stdout_trimmed()).ok_or_else(|| {
+            format!(
+                "no version in output of installed_version_command: '{}'",
+                output.stdout_trimmed()
+            )
+        })
+    }
+
+    fn format_updates(&self, updates: &[PackageUpdate]) -> String {
+        let rows: Vec<Vec<String>> = updates
+            .iter()
+            .map(|update| {
+                vec![
+                    update.name.clone(),
+                    format!("v{}", update.declared),
+                    update
+                        .installed
+                        .as_ref()
+                        .map_or_else(|| "-".to_string(), |installed| format!("v{}", installed)),
+                    self.style_status(&update.status),
+                ]
+            })
+            .collect();
+
+        let mut output = String::from("Package versions:\n");
+        output.push_str(
+            &self
+                .progress_manager
+                .format_table(&["Name", "Declared", "Installed", "Status"], &rows),
+        );
+        output.push('\n');
+
+        output
+    }
+
+    fn style_status(&self, status: &UpdateStatus) -> String {
+        let text = status.describe();
+
+        if !self.config.use_colors() {
+            return text;
+        }
+
+        match status {
+            UpdateStatus::UpToDate => style(text).green().to_string(),
+            UpdateStatus::Outdated => style(text).yellow().to_string(),
+            UpdateStatus::Ahead => style(text).cyan().to_string(),
+            UpdateStatus::Unknown(_) => style(text).dim().to_string(),
+        }
+    }
+}
+
+/// The first word of `output` that's a semantic version, allowing a leading
+/// `v`, e.g. `14.1.0` from `ripgrep 14.1.0 (rev abc123)`
+fn parse_installed_version(output: &str) -> Option<Version> {
+    output
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v').trim_end_matches([',', ';']))
+        .find_map(|word| Version::parse(word).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        domain::{
+            config::AppConfigBuilder,
+            package::{EnvironmentConfig, PackageBuilder},
+        },
+        ports::{
+            command::{CommandOutput, MockCommandRunner},
+            package_repo::MockPackageRepository,
+        },
+    };
+
+    fn package_with_version_command(name: &str, version: &str, command: &str) -> Package {
+        PackageBuilder::default()
+            .name(name)
+            .version(version)
+            .environment_config(
+                "test-env",
+                EnvironmentConfig::new(format!("cargo install {}", name))
+                    .with_installed_version_command(command),
+            )
+            .build()
+    }
+
+    fn mock_version_output(runner: &mut MockCommandRunner, command: &str, stdout: &str) {
+        let stdout = stdout.to_string();
+
+        runner
+            .expect_execute_with_timeout()
+            .with(
+                mockall::predicate::eq(command.to_string()),
+                mockall::predicate::eq(Duration::from_secs(15)),
+            )
+            .returning(move |_, _| {
+                Ok(CommandOutput {
+                    stdout: stdout.clone(),
+                    success: true,
+                    ..CommandOutput::default()
+                })
+            });
+    }
+
+    #[test]
+    fn test_parse_installed_version() {
+        assert_eq!(
+            parse_installed_version("ripgrep 14.1.0 (rev abc123)"),
+            Some(Version::new(14, 1, 0))
+        );
+        assert_eq!(
+            parse_installed_version("v1.2.3"),
+            Some(Version::new(1, 2, 3))
+        );
+        assert_eq!(parse_installed_version("fd 9.0"), None);
+        assert_eq!(parse_installed_version(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_updates_compares_versions() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .use_colors(false)
+            .build();
+
+        let mut repo = MockPackageRepository::new();
+        repo.mock_list_packages(vec![
+            package_with_version_command("ripgrep", "14.0.0", "rg --version"),
+            package_with_version_command("fd", "9.0.0", "fd --version"),
+            package_with_version_command("bat", "0.25.0", "bat --version"),
+            PackageBuilder::default()
+                .name("tokei")
+                .version("1.0.0")
+                .environment("test-env", "cargo install tokei")
+                .build(),
+            PackageBuilder::default()
+                .name("other")
+                .version("1.0.0")
+                .environment("other-env", "cargo install other")
+                .build(),
+        ]);
+
+        let mut runner = MockCommandRunner::new();
+        mock_version_output(&mut runner, "rg --version", "ripgrep 14.1.0\n");
+        mock_version_output(&mut runner, "fd --version", "fd 9.0.0\n");
+        mock_version_output(&mut runner, "bat --version", "bat 0.24.0\n");
+
+        let service = PackageCheckUpdatesService::new(
+            &runner,
+            &config,
+            ProgressManager::from(&config),
+            &repo,
+        );
+        let report = service.execute().await.unwrap();
+
+        assert!(report.has_outdated);
+
+        let lines: Vec<&str> = report.output.lines().collect();
+        assert_eq!(lines[0], "Package versions:");
+        assert_eq!(lines.len(), 6);
+        assert!(lines[2].starts_with("bat      v0.25.0   v0.24.0"));
+        assert!(lines[2].ends_with("Ahead"));
+        assert!(lines[3].ends_with("Up to date"));
+        assert!(lines[4].starts_with("ripgrep  v14.0.0   v14.1.0"));
+        assert!(lines[4].ends_with("Outdated"));
+        assert!(lines[5].starts_with("tokei    v1.0.0    -"));
+        assert!(lines[5].ends_with("Unknown: no installed_version_command"));
+    }
+
+    #[tokio::test]
+    async fn test_check_updates_json() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let mut repo = MockPackageRepository::new();
+        repo.mock_list_packages(vec![package_with_version_command(
+            "ripgrep",
+            "14.1.0",
+            "rg --version",
+        )]);
+
+        let mut runner = MockCommandRunner::new();
+        mock_version_output(&mut runner, "rg --version", "ripgrep 14.1.0\n");
+
+        let report = PackageCheckUpdatesService::new(
+            &runner,
+            &config,
+            ProgressManager::from(&config),
+            &repo,
+        )
+        .with_json(true)
+        .execute()
+        .await
+        .unwrap();
+
+        assert!(!report.has_outdated);
+
+        let json: serde_json::Value = serde_json::from_str(&report.output).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "name": "ripgrep",
+                "declared": "14.1.0",
+                "installed": "14.1.0",
+                "status": "up_to_date",
+            }])
+        );
+    }
+}
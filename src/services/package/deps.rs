@@ -0,0 +1,294 @@
+// src/services/package/deps.rs
+// Implementation of the 'selfie package deps' command
+
+use thiserror::Error;
+
+use crate::{
+    domain::{
+        config::AppConfig,
+        dependency::{DependencyGraph, DependencyGraphError},
+        package::Package,
+    },
+    ports::package_repo::{PackageRepoError, PackageRepository},
+    services::package::install::dependency::{DependencyResolver, DependencyResolverError},
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageDepsError {
+    #[error(transparent)]
+    Resolve(#[from] DependencyResolverError),
+
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error("Package '{package}' depends on '{dependency}', which doesn't exist")]
+    MissingDependency { package: String, dependency: String },
+
+    #[error("Dependency graph error: {0}")]
+    Graph(#[from] DependencyGraphError),
+}
+
+/// Handles the 'package deps' command
+pub(crate) struct PackageDepsService<'a, PR: PackageRepository> {
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+    max_depth: Option<usize>,
+}
+
+impl<'a, PR: PackageRepository> PackageDepsService<'a, PR> {
+    /// Create a new deps command handler
+    pub(crate) fn new(config: &'a AppConfig, package_repo: &'a PR) -> Self {
+        Self {
+            config,
+            package_repo,
+            max_depth: None,
+        }
+    }
+
+    /// Fail when a package's dependencies go more than this many levels deep
+    pub(crate) fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The dependency graph for the current environment: of `package_name` and
+    /// everything it depends on, or of every package when there's no name
+    fn graph(&self, package_name: Option<&str>) -> Result<DependencyGraph, PackageDepsError> {
+        match package_name {
+            Some(name) => Ok(DependencyResolver::new(self.package_repo, self.config)
+                .with_max_depth(self.max_depth)
+                .resolve_graph(name)?),
+            None => self.directory_graph(),
+        }
+    }
+
+    /// Render the dependency graph, either in DOT format or as one line per
+    /// package listing its dependencies
+    pub(crate) fn execute(
+        &self,
+        package_name: Option<&str>,
+        dot: bool,
+    ) -> Result<String, PackageDepsError> {
+        let graph = self.graph(package_name)?;
+
+        if dot {
+            return Ok(graph.to_dot());
+        }
+
+        let mut names = graph.get_package_names();
+        names.sort();
+
+        Ok(names
+            .iter()
+            .map(|name| {
+                let deps: Vec<String> = graph
+                    .dependencies(name)
+                    .into_iter()
+                    .map(|dep| {
+                        if graph.is_check_dependency(name, dep) {
+                            format!("{} (check)", dep)
+                        } else {
+                            dep.to_string()
+                        }
+                    })
+                    .collect();
+
+                if deps.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{} -> {}", name, deps.join(", "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Render everything that depends on `package_name`, directly or through
+    /// other packages, as a tree rooted at it
+    pub(crate) fn execute_reverse(&self, package_name: &str) -> Result<String, PackageDepsError> {
+        // Looking the package up resolves an alias, and fails for a package
+        // that doesn't exist rather than showing it with no dependents
+        let package = self.package_repo.get_package(package_name)?;
+
+        Ok(self.directory_graph()?.reversed().to_tree(&package.name))
+    }
+
+    /// Every dependency cycle between the packages in the directory, each as
+    /// the chain of packages that leads back to where it started
+    pub(crate) fn find_cycles(&self) -> Result<Vec<Vec<String>>, PackageDepsError> {
+        Ok(self.directory_graph()?.find_cycles())
+    }
+
+    /// Every package in the directory; unlike installing, cycles are kept so
+    /// they can be shown
+    fn directory_graph(&self) -> Result<DependencyGraph, PackageDepsError> {
+        let packages = self.package_repo.list_packages()?;
+
+        let mut graph = DependencyGraph::default();
+        for package in &packages {
+            graph.add_node(package.clone())?;
+        }
+
+        for package in &packages {
+            // Packages that don't support this environment have no
+            // dependencies in it
+            let Ok(env_config) = self.config.resolve_environment(package) else {
+                continue;
+            };
+
+            let dependencies = env_config
+                .dependencies()
+                .iter()
+                .filter(|dependency| {
+                    dependency.applies_to_environment(
+                        self.config.environment(),
+                        self.config.environment_aliases(),
+                    )
+                })
+                .map(|dependency| (dependency.name(), false))
+                .chain(
+                    env_config
+                        .check_dependencies()
+                        .iter()
+                        .map(|name| (name.as_str(), true)),
+                );
+
+            for (dependency, is_check_dependency) in dependencies {
+                let dependency_name = Package::resolve_name(&packages, dependency);
+                let added = if is_check_dependency {
+                    graph.add_check_edge(&package.name, dependency_name)
+                } else {
+                    graph.add_edge(&package.name, dependency_name)
+                };
+
+                added.map_err(|err| match err {
+                    DependencyGraphError::PackageNotFound(_) => {
+                        PackageDepsError::MissingDependency {
+                            package: package.name.clone(),
+                            dependency: dependency.to_string(),
+                        }
+                    }
+                    other => PackageDepsError::Graph(other),
+                })?;
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{config::AppConfigBuilder, package::PackageBuilder},
+        ports::package_repo::MockPackageRepository,
+    };
+
+    fn create_config() -> AppConfig {
+        AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build()
+    }
+
+    fn create_repo() -> MockPackageRepository {
+        let packages = vec![
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "cargo install ripgrep", vec!["rust"])
+                .build(),
+            PackageBuilder::default()
+                .name("rust")
+                .version("1.0.0")
+                .environment("test-env", "rustup-init")
+                .build(),
+            PackageBuilder::default()
+                .name("a")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "a", vec!["b"])
+                .build(),
+            PackageBuilder::default()
+                .name("b")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "b", vec!["a"])
+                .build(),
+        ];
+
+        let mut repo = MockPackageRepository::new();
+        for package in &packages {
+            repo.mock_get_package_ok(&package.name, package.clone());
+        }
+        repo.mock_list_packages(packages);
+        repo
+    }
+
+    #[test]
+    fn test_deps_of_package() {
+        let config = create_config();
+        let repo = create_repo();
+        let service = PackageDepsService::new(&config, &repo);
+
+        assert_eq!(
+            service.execute(Some("ripgrep"), false).unwrap(),
+            "ripgrep -> rust\nrust"
+        );
+        assert!(matches!(
+            service.execute(Some("a"), true),
+            Err(PackageDepsError::Resolve(
+                DependencyResolverError::CircularDependency(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_deps_of_directory_keeps_cycles() {
+        let config = create_config();
+        let repo = create_repo();
+
+        let dot = PackageDepsService::new(&config, &repo)
+            .execute(None, true)
+            .unwrap();
+
+        assert!(dot.contains("    \"a\" -> \"b\" [color=red];\n"));
+        assert!(dot.contains("    \"ripgrep\" -> \"rust\";\n"));
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let config = create_config();
+        let repo = create_repo();
+
+        assert_eq!(
+            PackageDepsService::new(&config, &repo)
+                .find_cycles()
+                .unwrap(),
+            vec![vec!["a", "b", "a"]]
+        );
+    }
+
+    #[test]
+    fn test_reverse_deps() {
+        let config = create_config();
+        let mut repo = create_repo();
+        repo.mock_get_package_err(
+            "missing",
+            PackageRepoError::PackageNotFound("missing".to_string()),
+        );
+        let service = PackageDepsService::new(&config, &repo);
+
+        assert_eq!(
+            service.execute_reverse("rust").unwrap(),
+            "rust\n└── ripgrep\n"
+        );
+        assert_eq!(
+            service.execute_reverse("a").unwrap(),
+            "a\n└── b\n    └── a (cycle)\n"
+        );
+        assert!(matches!(
+            service.execute_reverse("missing"),
+            Err(PackageDepsError::Repo(PackageRepoError::PackageNotFound(_)))
+        ));
+    }
+}
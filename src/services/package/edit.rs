@@ -0,0 +1,303 @@
+// src/services/package/edit.rs
+// Implementation of the 'selfie package edit' command
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::{
+    domain::{config::AppConfig, shell::shell_quote},
+    ports::{
+        command::{CommandError, CommandRunner},
+        filesystem::{FileSystem, FileSystemError},
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+/// Editors to try, in order, when `$EDITOR` isn't set
+const FALLBACK_EDITORS: [&str; 2] = ["vi", "nano"];
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageEditError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error("Package not found: {0} (pass --create to create it)")]
+    PackageNotFound(String),
+
+    #[error(
+        "Multiple package files found for '{name}':{}",
+        .paths.iter().map(|path| format!("\n  • {}", path.display())).collect::<String>()
+    )]
+    MultiplePackageFiles { name: String, paths: Vec<PathBuf> },
+
+    #[error("Failed to create package file: {0}")]
+    Create(#[from] FileSystemError),
+
+    #[error("No editor found; set $EDITOR")]
+    NoEditor,
+
+    #[error("Failed to run editor: {0}")]
+    Editor(#[from] CommandError),
+
+    #[error("Editor exited with status {0}")]
+    EditorFailed(i32),
+}
+
+/// The package file that was edited
+#[derive(Debug, PartialEq)]
+pub(crate) struct EditedPackage {
+    pub(crate) path: PathBuf,
+
+    /// Whether the file was created from a skeleton first
+    pub(crate) created: bool,
+}
+
+/// Handles the 'package edit' command
+pub(crate) struct PackageEditService<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository> {
+    fs: &'a F,
+    runner: &'a CR,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+    editor: Option<String>,
+    create: bool,
+}
+
+impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
+    PackageEditService<'a, F, CR, PR>
+{
+    /// Create a new edit command handler
+    pub(crate) fn new(
+        fs: &'a F,
+        runner: &'a CR,
+        config: &'a AppConfig,
+        package_repo: &'a PR,
+    ) -> Self {
+        Self {
+            fs,
+            runner,
+            config,
+            package_repo,
+            editor: None,
+            create: false,
+        }
+    }
+
+    /// Editor command to run, usually `$EDITOR`; without one, the first
+    /// available fallback editor is used
+    pub(crate) fn with_editor(mut self, editor: Option<String>) -> Self {
+        self.editor = editor;
+        self
+    }
+
+    /// Whether to create a skeleton package file when the package doesn't
+    /// exist yet
+    pub(crate) fn with_create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// Open the package's file in the editor, waiting for it to exit
+    pub(crate) async fn execute(
+        &self,
+        package_name: &str,
+    ) -> Result<EditedPackage, PackageEditError> {
+        let mut paths = self.package_repo.find_package_files(package_name)?;
+
+        let edited = match paths.len() {
+            0 if self.create => {
+                // Use the first extension, so the new file is found first
+                let extension = self
+                    .config
+                    .package_file_extensions()
+                    .first()
+                    .map_or("yaml", |ext| ext.trim_start_matches('.'));
+                let path = self
+                    .config
+                    .expanded_package_directory()
+                    .join(format!("{}.{}", package_name, extension));
+                self.fs.write_file(&path, &self.skeleton(package_name))?;
+
+                EditedPackage {
+                    path,
+                    created: true,
+                }
+            }
+            0 => return Err(PackageEditError::PackageNotFound(package_name.to_string())),
+            1 => EditedPackage {
+                path: paths.remove(0),
+                created: false,
+            },
+            _ => {
+                return Err(PackageEditError::MultiplePackageFiles {
+                    name: package_name.to_string(),
+                    paths,
+                })
+            }
+        };
+
+        let editor = self.editor().await.ok_or(PackageEditError::NoEditor)?;
+        let result = self
+            .runner
+            .execute_interactive(&format!("{} {}", editor, shell_quote(&edited.path)))
+            .await;
+
+        if let Err(CommandError::NonZeroExit { output, .. }) = result {
+            return Err(PackageEditError::EditorFailed(output.status));
+        }
+        result?;
+
+        Ok(edited)
+    }
+
+    async fn editor(&self) -> Option<String> {
+        if let Some(editor) = &self.editor {
+            return Some(editor.clone());
+        }
+
+        for editor in FALLBACK_EDITORS {
+            if self
+                .runner
+                .is_command_available(editor)
+                .await
+                .unwrap_or(false)
+            {
+                return Some(editor.to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Starting point for a new package, with the current environment filled in
+    fn skeleton(&self, package_name: &str) -> String {
+        format!(
+            r#"name: {name}
+version: 0.1.0
+description: ""
+environments:
+  {environment}:
+    install: ""
+    # check: which {name}
+"#,
+            name = package_name,
+            environment = self.config.environment(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        domain::config::AppConfigBuilder,
+        ports::{
+            command::MockCommandRunner, filesystem::MockFileSystem,
+            package_repo::MockPackageRepository,
+        },
+    };
+
+    fn create_config() -> AppConfig {
+        AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_edit_existing_package() {
+        let config = create_config();
+        let fs = MockFileSystem::new();
+
+        let mut repo = MockPackageRepository::new();
+        repo.expect_find_package_files()
+            .returning(|_| Ok(vec![PathBuf::from("/test/packages/ripgrep.yaml")]));
+
+        // Without $EDITOR, the first available fallback is used
+        let mut runner = MockCommandRunner::new();
+        runner.mock_is_command_available("vi", false);
+        runner.mock_is_command_available("nano", true);
+        runner.mock_execute_interactive_ok("nano '/test/packages/ripgrep.yaml'");
+
+        let edited = PackageEditService::new(&fs, &runner, &config, &repo)
+            .execute("ripgrep")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            edited,
+            EditedPackage {
+                path: PathBuf::from("/test/packages/ripgrep.yaml"),
+                created: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_edit_missing_package() {
+        let config = create_config();
+
+        let mut repo = MockPackageRepository::new();
+        repo.expect_find_package_files().returning(|_| Ok(vec![]));
+
+        let runner = MockCommandRunner::new();
+        let fs = MockFileSystem::new();
+        let service = PackageEditService::new(&fs, &runner, &config, &repo)
+            .with_editor(Some("code --wait".to_string()));
+
+        assert!(matches!(
+            service.execute("ripgrep").await,
+            Err(PackageEditError::PackageNotFound(_))
+        ));
+
+        // With --create, a skeleton for the current environment is written
+        let mut fs = MockFileSystem::new();
+        fs.expect_write_file()
+            .withf(|path, content| {
+                path == Path::new("/test/packages/ripgrep.yaml")
+                    && content.starts_with("name: ripgrep\n")
+                    && content.contains("\n  test-env:\n")
+            })
+            .returning(|_, _| Ok(()));
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_interactive_ok("code --wait '/test/packages/ripgrep.yaml'");
+
+        let edited = PackageEditService::new(&fs, &runner, &config, &repo)
+            .with_editor(Some("code --wait".to_string()))
+            .with_create(true)
+            .execute("ripgrep")
+            .await
+            .unwrap();
+
+        assert!(edited.created);
+    }
+
+    #[tokio::test]
+    async fn test_edit_multiple_package_files() {
+        let config = create_config();
+        let fs = MockFileSystem::new();
+        let runner = MockCommandRunner::new();
+
+        let mut repo = MockPackageRepository::new();
+        repo.expect_find_package_files().returning(|_| {
+            Ok(vec![
+                PathBuf::from("/test/packages/ripgrep.yaml"),
+                PathBuf::from("/test/packages/ripgrep.yml"),
+            ])
+        });
+
+        let err = PackageEditService::new(&fs, &runner, &config, &repo)
+            .with_editor(Some("vi".to_string()))
+            .execute("ripgrep")
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Multiple package files found for 'ripgrep':\n  • /test/packages/ripgrep.yaml\n  • /test/packages/ripgrep.yml"
+        );
+    }
+}
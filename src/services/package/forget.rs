@@ -0,0 +1,167 @@
+// src/services/package/forget.rs
+// Implementation of the 'selfie package forget' command
+
+use thiserror::Error;
+
+use crate::{
+    domain::config::AppConfig,
+    ports::{
+        filesystem::{FileSystem, FileSystemError},
+        install_state::{InstallStateError, InstallStateStore},
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageForgetError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error("Failed to remove install marker: {0}")]
+    Marker(#[from] FileSystemError),
+
+    #[error(transparent)]
+    InstallState(#[from] InstallStateError),
+}
+
+/// Handles the 'package forget' command
+pub(crate) struct PackageForgetService<
+    'a,
+    F: FileSystem,
+    PR: PackageRepository,
+    S: InstallStateStore,
+> {
+    fs: &'a F,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+    state_store: &'a S,
+}
+
+impl<'a, F: FileSystem, PR: PackageRepository, S: InstallStateStore>
+    PackageForgetService<'a, F, PR, S>
+{
+    /// Create a new forget command handler
+    pub(crate) fn new(
+        fs: &'a F,
+        config: &'a AppConfig,
+        package_repo: &'a PR,
+        state_store: &'a S,
+    ) -> Self {
+        Self {
+            fs,
+            config,
+            package_repo,
+            state_store,
+        }
+    }
+
+    /// Remove a package's install marker and its record in the install
+    /// state, so the next install runs it again. Returns whether there was
+    /// anything to remove.
+    pub(crate) fn execute(&self, package_name: &str) -> Result<bool, PackageForgetError> {
+        // Looking the package up resolves an alias
+        let package = self.package_repo.get_package(package_name)?;
+
+        let mut forgotten = false;
+
+        if let Some(marker) = self.config.marker_path(&package.name) {
+            if self.fs.path_exists(&marker) {
+                self.fs.remove_file(&marker)?;
+                forgotten = true;
+            }
+        }
+
+        let mut state = self.state_store.load()?;
+        if state.forget(&package.name) {
+            self.state_store.save(&state)?;
+            forgotten = true;
+        }
+
+        Ok(forgotten)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        domain::{config::AppConfigBuilder, install_state::InstallState, package::PackageBuilder},
+        ports::{
+            filesystem::MockFileSystem, install_state::MockInstallStateStore,
+            package_repo::MockPackageRepository,
+        },
+    };
+
+    fn create_repo() -> MockPackageRepository {
+        let mut repo = MockPackageRepository::new();
+        repo.mock_get_package_ok(
+            "vimrc",
+            PackageBuilder::default()
+                .name("vimrc")
+                .version("1.0.0")
+                .environment("test-env", "cp vimrc ~/.vimrc")
+                .build(),
+        );
+        repo
+    }
+
+    #[test]
+    fn test_forget_removes_marker_and_record() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .markers_dir("/test/markers")
+            .build();
+        let repo = create_repo();
+
+        let mut fs = MockFileSystem::new();
+        fs.mock_path_exists("/test/markers/vimrc", true);
+        fs.expect_remove_file()
+            .withf(|path| path == Path::new("/test/markers/vimrc"))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut state = InstallState::default();
+        state.record("vimrc", "1.0.0", "test-env");
+        let mut store = MockInstallStateStore::new();
+        store.mock_load_ok(state);
+        store
+            .expect_save()
+            .withf(|state| state.is_empty())
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = PackageForgetService::new(&fs, &config, &repo, &store);
+
+        assert!(service.execute("vimrc").unwrap());
+    }
+
+    #[test]
+    fn test_forget_nothing_recorded() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let mut repo = create_repo();
+        repo.mock_get_package_err(
+            "missing",
+            PackageRepoError::PackageNotFound("missing".to_string()),
+        );
+
+        let fs = MockFileSystem::new();
+        let mut store = MockInstallStateStore::new();
+        store.mock_load_ok(InstallState::default());
+
+        let service = PackageForgetService::new(&fs, &config, &repo, &store);
+
+        assert!(!service.execute("vimrc").unwrap());
+        assert!(matches!(
+            service.execute("missing"),
+            Err(PackageForgetError::Repo(PackageRepoError::PackageNotFound(
+                _
+            )))
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+// src/services/package/info.rs
+// Implementation of the 'selfie package info' command
+
+use console::style;
+
+use crate::{
+    domain::{
+        config::AppConfig,
+        package::{CheckCommand, InstallCommand},
+    },
+    ports::{
+        filesystem::FileSystem,
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+/// Handles the 'package info' command
+pub(crate) struct PackageInfoService<'a, F: FileSystem, PR: PackageRepository> {
+    fs: &'a F,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+}
+
+impl<'a, F: FileSystem, PR: PackageRepository> PackageInfoService<'a, F, PR> {
+    /// Create a new info command handler
+    pub(crate) fn new(fs: &'a F, config: &'a AppConfig, package_repo: &'a PR) -> Self {
+        Self {
+            fs,
+            config,
+            package_repo,
+        }
+    }
+
+    /// Describe a package and each of its environments
+    pub(crate) fn execute(&self, package_name: &str) -> Result<String, PackageRepoError> {
+        let package = self.package_repo.get_package(package_name)?;
+
+        let name = if self.config.use_colors() {
+            style(&package.name).magenta().bold().to_string()
+        } else {
+            package.name.clone()
+        };
+
+        let mut output = format!("{} (v{})\n", name, package.version);
+
+        if let Some(description) = &package.description {
+            output.push_str(&format!("  Description: {}\n", description));
+        }
+
+        if let Some(homepage) = &package.homepage {
+            output.push_str(&format!("  Homepage: {}\n", homepage));
+        }
+
+        // Show the file a symlink in the package directory points to
+        let path = self
+            .fs
+            .canonicalize(&package.path)
+            .unwrap_or_else(|_| package.path.clone());
+        output.push_str(&format!("  Path: {}\n", path.display()));
+
+        output.push_str("\nEnvironments:\n");
+
+        // Sort environments by name for consistent output
+        let mut environments: Vec<_> = package.environments.iter().collect();
+        environments.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (env_name, env_config) in environments {
+            if env_name == self.config.environment() {
+                let current = if self.config.use_colors() {
+                    style("(current)").green().to_string()
+                } else {
+                    "(current)".to_string()
+                };
+                output.push_str(&format!("  {} {}\n", env_name, current));
+            } else {
+                output.push_str(&format!("  {}\n", env_name));
+            }
+
+            if let Some(description) = &env_config.description {
+                output.push_str(&format!("    Description: {}\n", description));
+            }
+
+            if let Some(homepage) = &env_config.homepage {
+                output.push_str(&format!("    Homepage: {}\n", homepage));
+            }
+
+            // Commands given per architecture get a line for each one
+            match &env_config.install {
+                InstallCommand::ByArch(by_arch) => {
+                    for (arch, install) in by_arch {
+                        output.push_str(&format!("    Install ({}): {}\n", arch, install));
+                    }
+                }
+                install => output.push_str(&format!("    Install: {}\n", install)),
+            }
+
+            match &env_config.check {
+                Some(CheckCommand::Single(check)) => {
+                    output.push_str(&format!("    Check: {}\n", check));
+                }
+                Some(CheckCommand::ByArch(by_arch)) => {
+                    for (arch, check) in by_arch {
+                        output.push_str(&format!("    Check ({}): {}\n", arch, check));
+                    }
+                }
+                None => {}
+            }
+
+            if let Some(check_file) = &env_config.check_file {
+                output.push_str(&format!("    Check file: {}\n", check_file.display()));
+            }
+
+            if let Some(command) = &env_config.check_path_command {
+                output.push_str(&format!("    Check on PATH: {}\n", command));
+            }
+
+            if !env_config.dependencies.is_empty() {
+                output.push_str(&format!(
+                    "    Dependencies: {}\n",
+                    env_config
+                        .dependencies
+                        .iter()
+                        .map(|dependency| match dependency.conditions() {
+                            conditions if conditions.is_empty() => dependency.to_string(),
+                            conditions => format!("{} ({})", dependency, conditions),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+
+            if !env_config.check_dependencies().is_empty() {
+                output.push_str(&format!(
+                    "    Check dependencies: {}\n",
+                    env_config.check_dependencies().join(", ")
+                ));
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{config::AppConfigBuilder, package::Package},
+        ports::{filesystem::MockFileSystem, package_repo::MockPackageRepository},
+    };
+
+    #[test]
+    fn test_package_info() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .use_colors(false)
+            .build();
+
+        let mut package = Package::from_yaml(
+            r#"
+            name: ripgrep
+            version: 1.0.0
+            description: Fast search tool
+            environments:
+              test-env:
+                description: Built from source
+                homepage: https://github.com/fork/ripgrep
+                install: cargo install ripgrep
+                dependencies:
+                  - rust
+              other-env:
+                install: brew install ripgrep
+                check: which rg
+            "#,
+        )
+        .unwrap();
+        package.path = "/test/packages/ripgrep.yaml".into();
+
+        let mut repo = MockPackageRepository::default();
+        repo.mock_get_package_ok("ripgrep", package);
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_symlink("/test/packages/ripgrep.yaml", "/dotfiles/ripgrep.yaml");
+
+        let output = PackageInfoService::new(&fs, &config, &repo)
+            .execute("ripgrep")
+            .unwrap();
+
+        assert!(output.contains("ripgrep (v1.0.0)"));
+        assert!(output.contains("  Description: Fast search tool"));
+        assert!(output.contains("  test-env (current)"));
+        assert!(output.contains("    Description: Built from source"));
+        assert!(output.contains("    Homepage: https://github.com/fork/ripgrep"));
+        assert!(output.contains("    Dependencies: rust"));
+        assert!(output.contains("    Check: which rg"));
+        assert!(output.contains("  Path: /dotfiles/ripgrep.yaml\n"));
+    }
+
+    #[test]
+    fn test_package_info_not_found() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let mut repo = MockPackageRepository::default();
+        repo.mock_get_package_err(
+            "missing",
+            PackageRepoError::PackageNotFound("missing".to_string()),
+        );
+
+        let fs = MockFileSystem::default();
+        let result = PackageInfoService::new(&fs, &config, &repo).execute("missing");
+        assert!(matches!(result, Err(PackageRepoError::PackageNotFound(_))));
+    }
+}
@@ -1,32 +1,47 @@
 // src/services/package/installer.rs
-mod dependency;
+pub(crate) mod dependency;
 
-use std::{path::Path, time::Instant};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
-use console::style;
 use dependency::{DependencyResolver, DependencyResolverError};
+use futures::{stream, StreamExt};
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
-    adapters::{command::CommandOutputBuffer, progress::ProgressManager},
+    adapters::{
+        command::CommandOutputBuffer,
+        progress::{ProgressEvent, ProgressManager},
+    },
     domain::{
-        config::AppConfig,
+        config::{AppConfig, NoCheckPolicy},
         errors::{
             EnhancedCommandError, EnhancedDependencyError, EnhancedPackageError, ErrorContext,
+            ErrorReport,
         },
+        install_state::InstallState,
         installation::{Installation, InstallationError, InstallationReport, InstallationStatus},
-        package::Package,
+        package::{EnvironmentConfig, InstallCommand, Package},
     },
     ports::{
         command::{CommandError, CommandRunner},
-        filesystem::FileSystemError,
+        filesystem::{FileSystem, FileSystemError},
         package_repo::{PackageRepoError, PackageRepository},
     },
-    services::{command_validator::CommandValidator, enhanced_error_handler::EnhancedErrorHandler},
+    services::{
+        command_validator::CommandValidator,
+        enhanced_error_handler::EnhancedErrorHandler,
+        package::verify::{verify_installation, Verification},
+    },
 };
 
 #[derive(Error, Debug)]
-pub(crate) enum PackageInstallerError {
+pub enum PackageInstallerError {
     #[error("Package not found: {0}")]
     PackageNotFound(String),
 
@@ -39,18 +54,30 @@ pub(crate) enum PackageInstallerError {
     #[error("Dependency error: {0}")]
     DependencyResolverError(#[from] DependencyResolverError),
 
-    #[error("Circular dependency detected: {0}")]
-    CircularDependency(String),
+    #[error("Circular dependency detected: {}", .0.join(" -> "))]
+    CircularDependency(Vec<String>),
 
     #[error("Installation error: {0}")]
     InstallationError(#[from] InstallationError),
 
-    #[error("Installation canceled by user")]
-    InstallationCanceled,
+    #[error("Installation canceled")]
+    InstallationCanceled {
+        /// Installs that finished before the installation was canceled
+        completed: Vec<InstallationReport>,
+    },
 
     #[error("Multiple packages found with name: {0}")]
     MultiplePackagesFound(String),
 
+    #[error("Invalid package name pattern '{0}': {1}")]
+    InvalidPattern(String, glob::PatternError),
+
+    #[error("No packages match pattern '{0}'")]
+    NoPackagesMatch(String),
+
+    #[error("Listed packages not found: {}", .0.join(", "))]
+    ListedPackagesNotFound(Vec<String>),
+
     #[error("Command execution error: {0}")]
     CommandError(#[from] CommandError),
 
@@ -60,8 +87,18 @@ pub(crate) enum PackageInstallerError {
     #[error("Required command not available: {0}")]
     CommandNotAvailable(String),
 
-    #[error("{0}")]
-    EnhancedError(String),
+    /// An error already worded for the user, with suggestions where there
+    /// are any; `kind` and `context` are what automation sees of it
+    #[error("{message}")]
+    EnhancedError {
+        kind: &'static str,
+        message: String,
+        context: Box<ErrorContext>,
+    },
+
+    /// The install succeeded, but the package's check still doesn't find it
+    #[error("Installed '{package}', but {reason}")]
+    NotDetected { package: String, reason: String },
 }
 
 // Add conversions from enhanced errors
@@ -72,7 +109,67 @@ impl From<EnhancedPackageError> for PackageInstallerError {
             EnhancedPackageError::MultiplePackagesFound { name, .. } => {
                 Self::MultiplePackagesFound(name)
             }
-            _ => Self::EnhancedError(error.to_string()),
+            EnhancedPackageError::ValidationError { ref context, .. }
+            | EnhancedPackageError::ParseError { ref context, .. } => Self::EnhancedError {
+                kind: "invalid_package",
+                message: error.to_string(),
+                context: Box::new(context.clone()),
+            },
+            EnhancedPackageError::EnvironmentNotSupported { ref context, .. } => {
+                Self::EnhancedError {
+                    kind: "environment_not_supported",
+                    message: error.to_string(),
+                    context: Box::new(context.clone()),
+                }
+            }
+        }
+    }
+}
+
+impl ErrorReport for PackageInstallerError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::PackageNotFound(_) | Self::ListedPackagesNotFound(_) => "package_not_found",
+            Self::FileSystemError(err) => err.kind(),
+            Self::PackageRepoError(err) => err.kind(),
+            Self::DependencyResolverError(DependencyResolverError::PackageNotFound { .. }) => {
+                "package_not_found"
+            }
+            Self::DependencyResolverError(DependencyResolverError::RepoError(err)) => err.kind(),
+            Self::CircularDependency(_)
+            | Self::DependencyResolverError(DependencyResolverError::CircularDependency(_)) => {
+                "circular_dependency"
+            }
+            Self::DependencyResolverError(_) => "dependency_error",
+            Self::InstallationError(_) | Self::CommandError(_) => "installation_failed",
+            Self::InstallationCanceled { .. } => "installation_canceled",
+            Self::MultiplePackagesFound(_) => "multiple_packages_found",
+            Self::InvalidPattern(..) => "invalid_pattern",
+            Self::NoPackagesMatch(_) => "no_packages_match",
+            Self::EnvironmentError(_) => "environment_not_supported",
+            Self::CommandNotAvailable(_) => "command_not_available",
+            Self::EnhancedError { kind, .. } => kind,
+            Self::NotDetected { .. } => "not_detected",
+        }
+    }
+
+    fn error_context(&self) -> ErrorContext {
+        match self {
+            Self::PackageNotFound(name)
+            | Self::MultiplePackagesFound(name)
+            | Self::NotDetected { package: name, .. } => ErrorContext::default().with_package(name),
+            Self::FileSystemError(err) => err.error_context(),
+            Self::PackageRepoError(err)
+            | Self::DependencyResolverError(DependencyResolverError::RepoError(err)) => {
+                err.error_context()
+            }
+            Self::DependencyResolverError(DependencyResolverError::PackageNotFound {
+                name,
+                ..
+            }) => ErrorContext::default().with_package(name),
+            Self::CommandNotAvailable(command) => ErrorContext::default().with_command(command),
+            Self::EnhancedError { context, .. } => (**context).clone(),
+            _ => ErrorContext::default(),
         }
     }
 }
@@ -91,8 +188,8 @@ impl From<EnhancedCommandError> for PackageInstallerError {
 impl From<EnhancedDependencyError> for PackageInstallerError {
     fn from(error: EnhancedDependencyError) -> Self {
         match error {
-            EnhancedDependencyError::CircularDependency { cycle, .. } => {
-                Self::CircularDependency(cycle)
+            EnhancedDependencyError::CircularDependency { path, .. } => {
+                Self::CircularDependency(path)
             }
             _ => Self::DependencyResolverError(DependencyResolverError::GraphError(
                 crate::domain::dependency::DependencyGraphError::InvalidDependency(
@@ -103,7 +200,39 @@ impl From<EnhancedDependencyError> for PackageInstallerError {
     }
 }
 
-pub(crate) struct PackageInstaller<'a, PR: PackageRepository, CR: CommandRunner> {
+/// Options that change which packages get installed and how
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InstallOptions {
+    /// Install the package's dependencies, but not the package itself
+    pub(crate) only_deps: bool,
+
+    /// Install the package itself without resolving its dependencies
+    pub(crate) no_deps: bool,
+
+    /// Don't run check commands; assume nothing is installed and install it
+    pub(crate) no_check: bool,
+
+    /// Break down the summary's dependency time per dependency
+    pub(crate) timings: bool,
+
+    /// Fail when dependencies go more than this many levels deep
+    pub(crate) max_depth: Option<usize>,
+
+    /// Run each package's check again after installing it, failing when it
+    /// still doesn't find the package
+    pub(crate) verify: bool,
+
+    /// When installing several packages, carry on with the rest after one
+    /// fails instead of stopping
+    pub(crate) continue_on_error: bool,
+
+    /// Install the requested package, but not its dependencies, with this
+    /// command instead of its own
+    pub(crate) install_command: Option<String>,
+}
+
+pub(crate) struct PackageInstaller<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> {
+    fs: &'a F,
     package_repo: &'a PR,
     error_handler: &'a EnhancedErrorHandler<'a>,
     runner: &'a CR,
@@ -111,10 +240,103 @@ pub(crate) struct PackageInstaller<'a, PR: PackageRepository, CR: CommandRunner>
     progress_manager: ProgressManager,
     check_commands: bool,
     command_validator: CommandValidator<'a, CR>,
+    options: InstallOptions,
+
+    /// Packages already installed, or found to be, by this installer, so
+    /// installing several packages that share dependencies only handles
+    /// each of them once
+    handled: Mutex<HashSet<String>>,
+
+    /// Recorded installs, for packages without a check under
+    /// [`NoCheckPolicy::UseState`]
+    install_state: InstallState,
+
+    /// Status of each package planned by this installer, in plan order
+    statuses: Mutex<Vec<(String, InstallationStatus)>>,
+
+    /// Packages each target given to [`Self::plan_packages`] resolved to, in
+    /// installation order, taken when the target is installed
+    planned: Mutex<HashMap<String, Vec<Package>>>,
+
+    /// Packages whose install commands have been found available, so the
+    /// pre-flight check doesn't look for them again
+    commands_checked: Mutex<HashSet<String>>,
+
+    /// Stops the installer from starting any more installs once triggered
+    cancellation: CancellationToken,
+}
+
+/// Whether `name` is a glob pattern rather than a package name
+pub(crate) fn is_package_pattern(name: &str) -> bool {
+    name.contains(['*', '?', '['])
+}
+
+/// Names of the packages matching the glob `pattern`, sorted
+pub(crate) fn matching_packages<PR: PackageRepository>(
+    package_repo: &PR,
+    pattern: &str,
+) -> Result<Vec<String>, PackageInstallerError> {
+    let glob = glob::Pattern::new(pattern)
+        .map_err(|err| PackageInstallerError::InvalidPattern(pattern.to_string(), err))?;
+
+    let mut names: Vec<String> = package_repo
+        .list_packages()?
+        .into_iter()
+        .map(|package| package.name)
+        .filter(|name| glob.matches(name))
+        .collect();
+
+    if names.is_empty() {
+        return Err(PackageInstallerError::NoPackagesMatch(pattern.to_string()));
+    }
+
+    names.sort();
+    names.dedup();
+
+    Ok(names)
+}
+
+/// Real names of the packages `names` refer to, by name or alias, in the
+/// order given and without duplicates. Every name that isn't a package is
+/// reported together, so a list can be fixed in one pass.
+pub(crate) fn listed_packages<PR: PackageRepository>(
+    package_repo: &PR,
+    names: &[String],
+) -> Result<Vec<String>, PackageInstallerError> {
+    let packages = package_repo.list_packages()?;
+
+    let mut listed: Vec<String> = Vec::new();
+    let mut not_found = Vec::new();
+    for name in names {
+        let resolved = Package::resolve_name(&packages, name);
+
+        if !packages.iter().any(|package| package.name == resolved) {
+            not_found.push(name.clone());
+        } else if !listed.iter().any(|listed| listed == resolved) {
+            listed.push(resolved.to_string());
+        }
+    }
+
+    if !not_found.is_empty() {
+        return Err(PackageInstallerError::ListedPackagesNotFound(not_found));
+    }
+
+    Ok(listed)
+}
+
+/// The status a package's install ends in when it fails with `err`
+fn failure_status(err: &PackageInstallerError) -> InstallationStatus {
+    match err {
+        PackageInstallerError::InstallationError(InstallationError::CheckTimedOut(timeout)) => {
+            InstallationStatus::CheckTimedOut(*timeout)
+        }
+        err => InstallationStatus::Failed(err.to_string()),
+    }
 }
 
-impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR> {
+impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, F, PR, CR> {
     pub(crate) fn new(
+        fs: &'a F,
         package_repo: &'a PR,
         error_handler: &'a EnhancedErrorHandler<'_>,
         runner: &'a CR,
@@ -123,9 +345,11 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
         check_commands: bool,
     ) -> Self {
         // Create CommandValidator instance
-        let command_validator = CommandValidator::new(runner);
+        let command_validator =
+            CommandValidator::new(runner).with_progress_manager(progress_manager);
 
         Self {
+            fs,
             package_repo,
             error_handler,
             runner,
@@ -133,7 +357,121 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
             progress_manager,
             check_commands,
             command_validator,
+            options: InstallOptions::default(),
+            handled: Mutex::default(),
+            install_state: InstallState::default(),
+            statuses: Mutex::default(),
+            planned: Mutex::default(),
+            commands_checked: Mutex::default(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    fn is_handled(&self, package_name: &str) -> bool {
+        self.handled.lock().unwrap().contains(package_name)
+    }
+
+    fn mark_handled(&self, package_name: &str) {
+        self.handled
+            .lock()
+            .unwrap()
+            .insert(package_name.to_string());
+    }
+
+    fn set_status(&self, package_name: &str, status: InstallationStatus) {
+        let mut statuses = self.statuses.lock().unwrap();
+        match statuses.iter_mut().find(|(name, _)| name == package_name) {
+            Some((_, current)) => *current = status,
+            None => statuses.push((package_name.to_string(), status)),
+        }
+    }
+
+    /// Mark `package` as waiting to be installed
+    fn queue(&self, package: &Package, dependency: bool) {
+        self.set_status(&package.name, InstallationStatus::Pending);
+        self.progress_manager.emit(ProgressEvent::Pending {
+            package: &package.name,
+            dependency,
+        });
+    }
+
+    /// Every package planned so far with its status, in plan order. Packages
+    /// a failure stopped the run from reaching are still
+    /// [`InstallationStatus::Pending`].
+    pub(crate) fn statuses(&self) -> Vec<(String, InstallationStatus)> {
+        self.statuses.lock().unwrap().clone()
+    }
+
+    /// Use the given install options instead of the defaults
+    pub(crate) fn with_options(mut self, options: InstallOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Recorded installs to go by for packages without a check, when the
+    /// config's `no_check_policy` is `use_state`
+    pub(crate) fn with_install_state(mut self, install_state: InstallState) -> Self {
+        self.install_state = install_state;
+        self
+    }
+
+    /// Stop starting installs once `cancellation` is triggered. An install
+    /// that's already running is finished first, then
+    /// [`PackageInstallerError::InstallationCanceled`] is returned with the
+    /// installs that completed.
+    pub(crate) fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Fail with the installs in `completed` if the installer's been canceled
+    fn check_canceled(
+        &self,
+        completed: &mut Vec<InstallationReport>,
+    ) -> Result<(), PackageInstallerError> {
+        if self.cancellation.is_cancelled() {
+            return Err(PackageInstallerError::InstallationCanceled {
+                completed: std::mem::take(completed),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the dependencies of all of `package_names` and check the
+    /// commands their installs need in one go, so installing them in turn
+    /// doesn't do either again for each. A missing command fails the whole
+    /// plan, before anything is installed; a package whose dependencies can't
+    /// be resolved is left to fail when it's installed.
+    pub(crate) async fn plan_packages(
+        &self,
+        package_names: &[String],
+    ) -> Result<(), PackageInstallerError> {
+        let mut combined: Vec<Package> = Vec::new();
+        for package_name in package_names {
+            let Ok(packages) = self
+                .resolve_dependencies(package_name, self.package_repo)
+                .await
+            else {
+                continue;
+            };
+
+            for package in &packages {
+                if !combined.iter().any(|planned| planned.name == package.name) {
+                    combined.push(package.clone());
+                }
+            }
+            self.planned
+                .lock()
+                .unwrap()
+                .insert(package_name.clone(), packages);
+        }
+
+        if self.check_commands {
+            self.verify_commands(&combined, None).await?;
         }
+
+        Ok(())
     }
 
     /// Install a package by name with enhanced progress reporting and dependency handling
@@ -144,120 +482,297 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
         // Start timing the entire process
         let start_time = Instant::now();
 
+        self.check_canceled(&mut Vec::new())?;
+
         let main_package = self.get_package(package_name)?;
 
-        // Print initial package info header
-        let header = if self.progress_manager.use_colors() {
-            format!(
-                "Installing {} (v{}) from {}",
-                style(&main_package.name).magenta().bold(),
-                main_package.version,
-                main_package.path.display()
-            )
+        // Already installed as a dependency of an earlier package
+        if !self.options.only_deps && self.is_handled(&main_package.name) {
+            let reason = "Already handled earlier in this run";
+            self.progress_manager.emit(ProgressEvent::Skip {
+                package: &main_package.name,
+                reason,
+                dependency: false,
+            });
+
+            return Ok(InstallationReport {
+                package_name: main_package.name.clone(),
+                status: InstallationStatus::Skipped(reason.to_string()),
+                duration: Duration::ZERO,
+                wall_clock: Duration::ZERO,
+                attempts: 1,
+                last_error: None,
+                command_output: None,
+                dependencies: Vec::new(),
+            });
+        }
+
+        if self.options.only_deps {
+            if !self.progress_manager.compact() {
+                self.progress_manager.print_info(format!(
+                    "Installing dependencies of {} (v{}) from {}",
+                    main_package.name,
+                    main_package.version,
+                    main_package.path.display()
+                ));
+            }
         } else {
-            format!(
-                "Installing {} (v{}) from {}",
-                main_package.name,
-                main_package.version,
-                main_package.path.display()
-            )
-        };
+            self.progress_manager.emit(ProgressEvent::InstallStart {
+                package: &main_package.name,
+                version: &main_package.version,
+                path: &main_package.path,
+                dependency: false,
+            });
+        }
 
-        self.progress_manager.print_info(header);
+        if self.options.no_deps {
+            return self
+                .install_without_dependencies(&main_package, start_time)
+                .await;
+        }
 
         // ╭──────────────────────╮
         // │ Resolve dependencies │
         // ╰──────────────────────╯
-        let packages = match self.resolve_dependencies(package_name, self.package_repo) {
+        let planned = self.planned.lock().unwrap().remove(package_name);
+        let resolved = match planned {
+            Some(packages) => Ok(packages),
+            None => {
+                self.resolve_dependencies(package_name, self.package_repo)
+                    .await
+            }
+        };
+        let packages = match resolved {
             Ok(packages) => packages,
             Err(err) => {
                 // Use enhanced error handling for dependency errors
-                if let DependencyResolverError::CircularDependency(cycle_str) = &err {
-                    if let Some(cycle) = self.parse_cycle_string(cycle_str) {
-                        let error_msg = self.error_handler.handle_circular_dependency(&cycle);
-                        self.progress_manager.print_error(&error_msg);
+                match &err {
+                    DependencyResolverError::CircularDependency(cycle) => {
+                        let error_msg = self.error_handler.handle_circular_dependency(cycle);
+                        self.progress_manager.print_error_detail(&error_msg);
                     }
+                    DependencyResolverError::PackageNotFound { chain, .. } if chain.len() > 1 => {
+                        let error_msg = self.error_handler.handle_missing_dependency(chain);
+                        self.progress_manager.print_error_detail(&error_msg);
+                    }
+                    _ => {}
                 }
 
                 self.progress_manager
-                    .print_error(format!("Dependency resolution failed: {}", err));
+                    .print_error_detail(format!("Dependency resolution failed: {}", err));
                 return Err(err.into());
             }
         };
 
+        // The main package is always last in installation order; resolving it
+        // gives at least the package itself, unless it couldn't be found
+        let Some((main_package, dependencies)) = packages.split_last() else {
+            return Err(PackageInstallerError::PackageNotFound(
+                package_name.to_string(),
+            ));
+        };
+
         // Pre-flight check: check if all required commands are available
-        if self.check_commands && !self.verify_commands(&packages).await? {
+        let packages_to_verify = if self.options.only_deps {
+            dependencies
+        } else {
+            &packages[..]
+        };
+
+        if self.check_commands
+            && !self
+                .verify_commands(packages_to_verify, Some(&main_package.name))
+                .await?
+        {
             return Err(PackageInstallerError::CommandNotAvailable(
                 "Required commands not available".to_string(),
             ));
         }
 
+        if self.options.only_deps && dependencies.is_empty() {
+            self.progress_manager.print_info(format!(
+                "Package '{}' has no dependencies; nothing to do",
+                main_package.name
+            ));
+
+            return Ok(InstallationReport {
+                package_name: main_package.name.clone(),
+                status: InstallationStatus::Skipped("Package has no dependencies".to_string()),
+                duration: start_time.elapsed(),
+                wall_clock: start_time.elapsed(),
+                attempts: 1,
+                last_error: None,
+                command_output: None,
+                dependencies: Vec::new(),
+            });
+        }
+
+        // Everything this run will get to shows as pending until it's reached
+        let planned = if self.options.only_deps {
+            dependencies
+        } else {
+            &packages[..]
+        };
+        for package in planned {
+            if !self.is_handled(&package.name) {
+                self.queue(package, package.name != main_package.name);
+            }
+        }
+
         // Install all packages in order
         let mut dependency_results = Vec::new();
 
         // Show dependency section if we have dependencies
-        if packages.len() > 1 {
-            self.progress_manager.print_info("  Dependencies:");
+        if !dependencies.is_empty() {
+            if !self.progress_manager.compact() {
+                self.progress_manager.print_info("  Dependencies:");
+            }
+
+            let implied = self.implied_dependencies(&packages).await;
+
+            for package in dependencies {
+                if self.is_handled(&package.name) {
+                    continue;
+                }
 
-            // All packages except the last one are dependencies
-            for package in packages.iter().take(packages.len() - 1) {
-                self.install_dependency(package, start_time, &mut dependency_results)
+                if let Some(implied_by) = implied.get(&package.name) {
+                    let reason = format!(
+                        "'{}' is installed and its check covers its dependencies",
+                        implied_by
+                    );
+                    self.progress_manager.emit(ProgressEvent::Skip {
+                        package: &package.name,
+                        reason: &reason,
+                        dependency: true,
+                    });
+                    self.set_status(&package.name, InstallationStatus::Skipped(reason));
+                    continue;
+                }
+
+                self.check_canceled(&mut dependency_results)?;
+                self.install_dependency(package, &mut dependency_results)
                     .await?
             }
         }
 
+        if self.options.only_deps {
+            // Report over the dependencies only; the main package takes no time
+            let final_result = InstallationReport {
+                package_name: main_package.name.clone(),
+                status: InstallationStatus::Skipped("Only dependencies were installed".to_string()),
+                duration: Duration::ZERO,
+                wall_clock: start_time.elapsed(),
+                attempts: 1,
+                last_error: None,
+                command_output: None,
+                dependencies: dependency_results,
+            };
+
+            self.report_final_status(&final_result);
+
+            return Ok(final_result);
+        }
+
         // Now install the main package
-        let main_package = packages.last().unwrap();
-        let main_result = self.install_single_package(main_package, 2).await?;
+        self.check_canceled(&mut dependency_results)?;
+        let main_result = match self.install_single_package(main_package, false).await {
+            Ok(report) => report,
+            Err(err) => {
+                self.report_failure(&main_package.name, &err, start_time, dependency_results);
+                return Err(err);
+            }
+        };
+        self.mark_handled(&main_package.name);
 
-        // Get the total installation time and create the final result
-        let total_duration = start_time.elapsed();
+        // The main package's duration stays its own; the elapsed time covers
+        // the dependencies and anything between the steps
         let mut final_result = main_result.with_dependencies(dependency_results);
-
-        // Override the duration with the main package duration only
-        final_result.duration = total_duration;
+        final_result.wall_clock = start_time.elapsed();
 
         // Print summary
-        self.progress_manager.print_progress("\n");
         self.report_final_status(&final_result);
 
         Ok(final_result)
     }
 
-    async fn install_dependency(
+    /// Install just `package`, trusting that its dependencies are already in
+    /// place
+    async fn install_without_dependencies(
         &self,
         package: &Package,
         start_time: Instant,
+    ) -> Result<InstallationReport, PackageInstallerError> {
+        if let Ok(env_config) = self.config.resolve_environment(package) {
+            let dependencies: Vec<&str> = env_config.all_dependencies().collect();
+            if !dependencies.is_empty() {
+                self.progress_manager.print_warning(format!(
+                    "Not installing the dependencies of '{}' (--no-deps): {}",
+                    package.name,
+                    dependencies.join(", ")
+                ));
+            }
+        }
+
+        if self.check_commands
+            && !self
+                .verify_commands(std::slice::from_ref(package), Some(&package.name))
+                .await?
+        {
+            return Err(PackageInstallerError::CommandNotAvailable(
+                "Required commands not available".to_string(),
+            ));
+        }
+
+        self.queue(package, false);
+        let mut final_result = match self.install_single_package(package, false).await {
+            Ok(report) => report,
+            Err(err) => {
+                self.report_failure(&package.name, &err, start_time, Vec::new());
+                return Err(err);
+            }
+        };
+        self.mark_handled(&package.name);
+        final_result.wall_clock = start_time.elapsed();
+
+        self.report_final_status(&final_result);
+
+        Ok(final_result)
+    }
+
+    async fn install_dependency(
+        &self,
+        package: &Package,
         dependency_results: &mut Vec<InstallationReport>,
     ) -> Result<(), PackageInstallerError> {
-        // Show dependency name and version
-        let dependency_header = if self.progress_manager.use_colors() {
-            format!(
-                "    Installing {} (v{}) from {}",
-                style(&package.name).magenta(),
-                package.version,
-                package.path.display()
-            )
-        } else {
-            format!(
-                "    Installing {} (v{}) from {}",
-                package.name,
-                package.version,
-                package.path.display()
-            )
-        };
-        self.progress_manager.print_info(dependency_header);
+        self.progress_manager.emit(ProgressEvent::InstallStart {
+            package: &package.name,
+            version: &package.version,
+            path: &package.path,
+            dependency: true,
+        });
 
         // Make sure the dep has info for this environment
-        if !package.environments.contains_key(self.config.environment()) {
+        if self.config.resolve_environment(package).is_err() {
+            let reason = format!(
+                "Package `{}` does not support current environment (`{}` section)",
+                &package.name,
+                self.config.environment()
+            );
+
+            self.progress_manager.emit(ProgressEvent::Skip {
+                package: &package.name,
+                reason: &reason,
+                dependency: true,
+            });
+            self.set_status(&package.name, InstallationStatus::Skipped(reason.clone()));
+
             dependency_results.push(InstallationReport {
                 package_name: package.name.clone(),
-                status: InstallationStatus::Skipped(format!(
-                    "Package `{}` does not support current environment (`{}` section)",
-                    &package.name,
-                    self.config.environment()
-                )),
-                duration: start_time.elapsed(),
+                status: InstallationStatus::Skipped(reason),
+                duration: Duration::ZERO,
+                wall_clock: Duration::ZERO,
+                attempts: 1,
+                last_error: None,
                 dependencies: vec![],
                 command_output: None,
             });
@@ -266,18 +781,22 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
         }
 
         // Install the dependency
-        match self.install_single_package(package, 6).await {
+        match self.install_single_package(package, true).await {
             Ok(result) => {
                 // Only continue if installation was successful or package was already installed
                 match result.status {
                     InstallationStatus::Complete
                     | InstallationStatus::AlreadyInstalled
                     | InstallationStatus::Skipped(_) => {
+                        self.mark_handled(&package.name);
                         dependency_results.push(result);
                     }
                     _ => {
-                        self.progress_manager
-                            .print_error("      ✗ Dependency installation failed");
+                        // Compact output already reported it on the package's line
+                        if !self.progress_manager.compact() {
+                            self.progress_manager
+                                .print_error_detail("      ✗ Dependency installation failed");
+                        }
 
                         return Err(PackageInstallerError::InstallationError(
                             InstallationError::InstallationFailed(format!(
@@ -289,10 +808,12 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
                 }
             }
             Err(err) => {
-                self.progress_manager.print_error(format!(
-                    "      ✗ Failed to install dependency '{}': {}",
-                    package.name, err,
-                ));
+                if !self.progress_manager.compact() {
+                    self.progress_manager.print_error_detail(format!(
+                        "      ✗ Failed to install dependency '{}': {}",
+                        package.name, err,
+                    ));
+                }
                 return Err(err);
             }
         }
@@ -309,15 +830,15 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
         let package = self.get_package(package_name)?;
 
         // Check if package supports current environment
-        if !package.environments.contains_key(self.config.environment()) {
+        if self.config.resolve_environment(&package).is_err() {
             return Ok(false);
         }
 
         // Check if required commands are available
         if self.check_commands {
-            if let Some(env_config) = package.environments.get(self.config.environment()) {
+            if let Ok(env_config) = self.config.resolve_environment(&package) {
                 if let Some(base_cmd) =
-                    CommandValidator::<CR>::extract_base_command(&env_config.install)
+                    CommandValidator::<CR>::extract_base_command(env_config.install.first_command())
                 {
                     let availability_result = self
                         .command_validator
@@ -332,79 +853,242 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
         }
 
         // Check dependencies if requested
-        let _ = self.resolve_dependencies(package_name, self.package_repo)?;
+        let _ = self
+            .resolve_dependencies(package_name, self.package_repo)
+            .await?;
 
         // If we got here, the package is installable
         Ok(true)
     }
 
     fn get_package(&self, package_name: &str) -> Result<Package, PackageInstallerError> {
-        self.package_repo
-            .get_package(package_name)
-            .map_err(|e| match e {
+        self.package_repo.get_package(package_name).map_err(|e| {
+            // Not being allowed to read the package shouldn't look like
+            // it doesn't exist
+            if let Some(path) = e.permission_denied_path() {
+                return PackageInstallerError::EnhancedError {
+                    kind: "permission_denied",
+                    message: self.error_handler.handle_permission_denied(path),
+                    context: Box::new(ErrorContext::default().with_path(path)),
+                };
+            }
+
+            match e {
                 PackageRepoError::DirectoryNotFound(dir_path) => {
                     // This is a path not found error
                     let error_msg = self
                         .error_handler
                         .handle_path_not_found(Path::new(&dir_path));
-                    PackageInstallerError::EnhancedError(error_msg)
+                    PackageInstallerError::EnhancedError {
+                        kind: "path_not_found",
+                        message: error_msg,
+                        context: Box::new(ErrorContext::default().with_path(&dir_path)),
+                    }
                 }
                 PackageRepoError::IoError(ref io_err) => {
                     // Check if it's a file not found error
                     if io_err.kind() == std::io::ErrorKind::NotFound {
                         // Unfortunately we don't have the specific path here
                         // We could extract it from the error message
-                        let error_text = io_err.to_string();
-                        PackageInstallerError::EnhancedError(error_text)
+                        PackageInstallerError::EnhancedError {
+                            kind: "path_not_found",
+                            message: io_err.to_string(),
+                            context: Box::new(ErrorContext::default()),
+                        }
                     } else {
                         PackageInstallerError::PackageRepoError(e)
                     }
                 }
                 PackageRepoError::PackageNotFound(name) => {
                     // Use enhanced error handling for not found errors
-                    let error_msg = self.error_handler.handle_package_not_found(&name);
-                    PackageInstallerError::EnhancedError(error_msg)
+                    PackageInstallerError::EnhancedError {
+                        kind: "package_not_found",
+                        message: self.error_handler.handle_package_not_found(&name),
+                        context: Box::new(ErrorContext::default().with_package(&name)),
+                    }
                 }
                 PackageRepoError::MultiplePackagesFound(name) => {
                     PackageInstallerError::MultiplePackagesFound(name)
                 }
                 _ => PackageInstallerError::PackageRepoError(e),
-            })
+            }
+        })
     }
 
-    /// Resolve dependencies for a package
-    fn resolve_dependencies(
+    /// Resolve dependencies for a package. Whether a dependency with an
+    /// `unless_command` is needed depends on that command, which can only be
+    /// looked up once the dependency is reached, so resolving is repeated
+    /// until every one it reaches has been looked up.
+    async fn resolve_dependencies(
         &self,
         package_name: &str,
         package_repo: &dyn PackageRepository,
     ) -> Result<Vec<Package>, DependencyResolverError> {
-        // Create dependency resolver and resolve dependencies
-        DependencyResolver::new(package_repo, self.config).resolve_dependencies(package_name)
+        let mut available_commands = HashSet::new();
+        let mut looked_up = BTreeSet::new();
+
+        loop {
+            let (resolved, unless_commands) = {
+                let resolver = DependencyResolver::new(package_repo, self.config)
+                    .with_max_depth(self.options.max_depth)
+                    .with_available_commands(available_commands.clone());
+                (
+                    resolver.resolve_dependencies(package_name),
+                    resolver.unless_commands(),
+                )
+            };
+
+            let pending: Vec<String> = unless_commands.difference(&looked_up).cloned().collect();
+            if pending.is_empty() {
+                return resolved;
+            }
+
+            self.command_validator
+                .probe_commands(pending.iter().map(String::as_str))
+                .await;
+            for command in pending {
+                if self.command_validator.is_command_available(&command).await {
+                    available_commands.insert(command.clone());
+                }
+                looked_up.insert(command);
+            }
+        }
     }
 
-    /// Verify that all required commands are available
-    async fn verify_commands(&self, packages: &[Package]) -> Result<bool, PackageInstallerError> {
-        // Check commands for each package
-        let mut missing_commands = Vec::new();
+    /// Dependencies that don't need to be checked or installed because a
+    /// package depending on them sets `check_implies_dependencies` and its
+    /// check passes, mapped to the name of that package. `packages` is in
+    /// installation order, so walking it backwards visits every package
+    /// before its dependencies.
+    async fn implied_dependencies(&self, packages: &[Package]) -> HashMap<String, String> {
+        let mut implied = HashMap::new();
 
-        for package in packages {
-            if let Some(env_config) = package.environments.get(self.config.environment()) {
-                // Extract and check base command
-                if let Some(base_cmd) =
-                    CommandValidator::<CR>::extract_base_command(&env_config.install)
-                {
-                    let availability_result = self
-                        .command_validator
-                        .check_command_availability(self.config.environment(), base_cmd)
-                        .await;
+        if self.options.no_check {
+            return implied;
+        }
 
-                    if !availability_result.is_available {
-                        missing_commands.push((package.name.clone(), base_cmd.to_string()));
-                    }
+        let Some((main_package, dependencies)) = packages.split_last() else {
+            return implied;
+        };
+
+        // Packages some package that isn't covered by a passing check depends on
+        let mut needed = HashSet::from([main_package.name.as_str()]);
+
+        for package in packages.iter().rev() {
+            if !needed.contains(package.name.as_str()) {
+                continue;
+            }
+
+            let Ok(env_config) = self.config.resolve_environment(package) else {
+                continue;
+            };
+
+            // Whatever the package's check needs has to be there for it to run
+            needed.extend(
+                env_config
+                    .check_dependencies()
+                    .iter()
+                    .map(|dependency| Package::resolve_name(packages, dependency)),
+            );
+
+            if env_config.check_implies_dependencies
+                && env_config.has_check()
+                && self.is_installed(env_config).await
+            {
+                continue;
+            }
+
+            needed.extend(
+                env_config
+                    .dependency_names()
+                    .map(|dependency| Package::resolve_name(packages, dependency)),
+            );
+        }
+
+        // Name the nearest dependent whose check covered each skipped package
+        for package in packages.iter().rev() {
+            let Ok(env_config) = self.config.resolve_environment(package) else {
+                continue;
+            };
+
+            for dependency in env_config.dependency_names() {
+                let dependency = Package::resolve_name(packages, dependency);
+                if needed.contains(dependency) || implied.contains_key(dependency) {
+                    continue;
                 }
+
+                let implied_by = implied
+                    .get(&package.name)
+                    .cloned()
+                    .unwrap_or_else(|| package.name.clone());
+                implied.insert(dependency.to_string(), implied_by);
             }
         }
 
+        implied.retain(|name, _| dependencies.iter().any(|dep| &dep.name == name));
+        implied
+    }
+
+    /// Whether the package's check passes, without reporting anything
+    async fn is_installed(&self, env_config: &EnvironmentConfig) -> bool {
+        matches!(
+            Installation::new(env_config.clone())
+                .start()
+                .execute_check(self.fs, self.runner, self.config.check_timeout(), |_| {})
+                .await,
+            Ok(Installation::AlreadyInstalled { .. })
+        )
+    }
+
+    /// Verify that all required commands are available, for `main_package`
+    /// and the dependencies among `packages`
+    async fn verify_commands(
+        &self,
+        packages: &[Package],
+        main_package: Option<&str>,
+    ) -> Result<bool, PackageInstallerError> {
+        // The base command each package's install needs, unless an earlier
+        // check found it
+        let checked = self.commands_checked.lock().unwrap().clone();
+        let required: Vec<(&str, &str)> = packages
+            .iter()
+            .filter(|package| !checked.contains(&package.name))
+            .filter_map(|package| {
+                let env_config = self.config.resolve_environment(package).ok()?;
+                let install = match self.options.install_command.as_deref() {
+                    Some(install_command) if Some(package.name.as_str()) == main_package => {
+                        install_command
+                    }
+                    _ => env_config.install.first_command(),
+                };
+                let base_cmd = CommandValidator::<CR>::extract_base_command(install)?;
+                Some((package.name.as_str(), base_cmd))
+            })
+            .collect();
+
+        // Many packages share a base command (e.g. `brew`), so look each one
+        // up once, several at a time
+        let mut distinct: Vec<String> = required
+            .iter()
+            .map(|(_, base_cmd)| base_cmd.to_string())
+            .collect();
+        distinct.sort_unstable();
+        distinct.dedup();
+
+        let availability: HashMap<String, bool> = stream::iter(distinct)
+            .map(|base_cmd| async move {
+                let is_available = self.command_validator.is_command_available(&base_cmd).await;
+                (base_cmd, is_available)
+            })
+            .buffer_unordered(self.config.max_parallel().get())
+            .collect()
+            .await;
+
+        let missing_commands: Vec<(&str, &str)> = required
+            .into_iter()
+            .filter(|(_, base_cmd)| !availability[*base_cmd])
+            .collect();
+
         // If any commands are missing, report and return false
         if !missing_commands.is_empty() {
             let mut error_msg = String::from(
@@ -417,15 +1101,38 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
             return Err(PackageInstallerError::CommandNotAvailable(error_msg));
         }
 
+        self.commands_checked
+            .lock()
+            .unwrap()
+            .extend(packages.iter().map(|package| package.name.clone()));
+
         Ok(true)
     }
 
-    /// Install a single package (no dependency handling) with progress reporting
+    /// Install a single package (no dependency handling) with progress
+    /// reporting, keeping its status up to date
     async fn install_single_package(
         &self,
         package: &Package,
-        indent_level: usize,
+        dependency: bool,
+    ) -> Result<InstallationReport, PackageInstallerError> {
+        let result = self.run_single_install(package, dependency).await;
+
+        let status = match &result {
+            Ok(report) => report.status.clone(),
+            Err(err) => failure_status(err),
+        };
+        self.set_status(&package.name, status);
+
+        result
+    }
+
+    async fn run_single_install(
+        &self,
+        package: &Package,
+        dependency: bool,
     ) -> Result<InstallationReport, PackageInstallerError> {
+        let indent_level = if dependency { 6 } else { 2 };
         let indent = " ".repeat(indent_level);
 
         // Resolve environment configuration with enhanced error context
@@ -440,87 +1147,244 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
                 self.config.environment(),
                 &package.name,
             )
-            .with_context(context);
+            .with_context(context.clone());
 
             let user_message = self
                 .error_handler
                 .handle_environment_not_found(self.config.environment(), &package.name);
 
-            PackageInstallerError::EnhancedError(user_message)
+            PackageInstallerError::EnhancedError {
+                kind: "environment_not_supported",
+                message: user_message,
+                context: Box::new(context),
+            }
         })?;
 
-        // Create installation and start it
-        let installation = Installation::new(env_config.clone()).start();
-
-        self.progress_manager
-            .print_progress(format!("{}⌛ Checking installation status...", indent));
+        if let Some((env_name, _)) = self.config.find_environment(package) {
+            if env_name != self.config.environment() && self.config.strict() {
+                return Err(PackageInstallerError::EnvironmentError(format!(
+                    "Package '{}' only has the '{}' environment, an alias of '{}'",
+                    package.name,
+                    env_name,
+                    self.config.environment()
+                )));
+            }
 
-        let output_buffer =
-            CommandOutputBuffer::new(self.progress_manager, indent_level, self.config.verbose());
+            if env_name != self.config.environment() && !self.progress_manager.compact() {
+                self.progress_manager.print_verbose(format!(
+                    "{}Using '{}' environment of '{}', an alias of '{}'",
+                    indent,
+                    env_name,
+                    package.name,
+                    self.config.environment()
+                ));
+            }
+        }
 
-        let output_callback = output_buffer.clone().into_callback();
+        if let Some(guard) = env_config.skip_if() {
+            let output = self
+                .runner
+                .execute(guard)
+                .await
+                .or_else(CommandError::into_output)?;
+
+            if !self.progress_manager.compact() {
+                self.progress_manager.print_verbose(format!(
+                    "{}skip_if `{}` exited with status {}",
+                    indent, guard, output.status
+                ));
+            }
 
-        // Check if already installed
-        let installation = match installation
-            .execute_check(self.runner, output_callback)
-            .await
-        {
-            Ok(state) => state,
-            Err(err) => return Err(PackageInstallerError::InstallationError(err)),
-        };
-
-        // Handle the result based on the state
-        match &installation {
-            Installation::AlreadyInstalled { check_duration, .. } => {
-                // Print "Already installed" with duration
-                let status_message = format!(
-                    "{}✓ Checking installation status: Already installed ({:.1?})",
-                    indent, check_duration
-                );
-                self.progress_manager.print_success(status_message);
+            if output.succeeded() {
+                let reason = "skip_if matched";
+                self.progress_manager.emit(ProgressEvent::Skip {
+                    package: &package.name,
+                    reason,
+                    dependency,
+                });
+
+                return Ok(InstallationReport {
+                    package_name: package.name.clone(),
+                    status: InstallationStatus::Skipped(reason.to_string()),
+                    duration: output.duration,
+                    wall_clock: output.duration,
+                    attempts: 1,
+                    last_error: None,
+                    command_output: None,
+                    dependencies: Vec::new(),
+                });
+            }
+        }
 
-                // Return the result directly - no need to install
-                return installation
-                    .into_result(package.name.clone())
-                    .map_err(PackageInstallerError::InstallationError);
+        // Only the install commands get wrapped; checks and availability
+        // lookups use the package's own commands
+        let mut env_config = env_config.clone();
+        if let Some(install_command) = self.options.install_command.as_deref() {
+            if !dependency {
+                self.progress_manager.print_warning(format!(
+                    "{}Installing '{}' with `{}` instead of its own install command; this is only for this run, and isn't saved to the package file",
+                    indent, package.name, install_command
+                ));
+                env_config.install = InstallCommand::from(install_command);
             }
-            Installation::NotAlreadyInstalled { check_duration, .. } => {
-                // Print "Not installed" with duration
+        }
+        if let Some(wrapper) = self.config.command_wrapper() {
+            env_config.install = env_config.install.wrapped(wrapper);
+        }
+
+        // Create installation and start it
+        let installation = Installation::new(env_config.clone()).start();
+        self.set_status(&package.name, installation.status());
+
+        // Compact output only reports each package's outcome
+        let compact = self.progress_manager.compact();
+        let output_buffer = || {
+            CommandOutputBuffer::new(
+                self.progress_manager,
+                indent_level,
+                self.config.verbose() && !compact,
+            )
+            .with_tail_lines(self.config.failure_output_lines())
+        };
+
+        let installation = if self.options.no_check {
+            if !compact {
                 self.progress_manager
-                    .print_progress(self.progress_manager.with_duration(
-                        format!("{}✓ Checking installation status: Not installed", indent),
-                        Some(*check_duration),
-                    ));
+                    .print_progress(format!("{}Skipping installation check", indent));
             }
-            Installation::Failed { error_message, .. } => {
-                // Check failed, print error and return
-                self.progress_manager.print_error(format!(
-                    "{}✗ Checking installation status failed: {}",
-                    indent, error_message
-                ));
-                return installation
-                    .into_result(package.name.clone())
-                    .map_err(PackageInstallerError::InstallationError);
+            installation.skip_check()
+        } else {
+            if !compact {
+                self.progress_manager
+                    .print_progress(format!("{}⌛ Checking installation status...", indent));
             }
-            _ => {
-                // Shouldn't get here with proper state transitions
-                return Err(PackageInstallerError::InstallationError(
-                    InstallationError::InvalidState(format!(
-                        "Unexpected state after check: {:?}",
-                        installation.status()
-                    )),
-                ));
+
+            let check_output = output_buffer();
+            let output_callback = check_output.clone().into_callback();
+
+            // Check if already installed; without a check, its marker or
+            // the config's policy decides
+            let installation = match (env_config.has_check(), self.config.no_check_policy()) {
+                (false, policy)
+                    if policy != NoCheckPolicy::Never && self.has_marker(&package.name) =>
+                {
+                    installation.assume_installed()
+                }
+                (false, NoCheckPolicy::UseState) => {
+                    if self
+                        .install_state
+                        .is_recorded(&package.name, self.config.environment())
+                    {
+                        installation.assume_installed()
+                    } else {
+                        installation.skip_check()
+                    }
+                }
+                (false, NoCheckPolicy::Never) => installation.fail_check(format!(
+                    "'{}' has no check, and no_check_policy is '{}'",
+                    package.name,
+                    NoCheckPolicy::Never
+                )),
+                _ => match installation
+                    .execute_check(
+                        self.fs,
+                        self.runner,
+                        self.config.check_timeout(),
+                        output_callback,
+                    )
+                    .await
+                {
+                    Ok(state) => state,
+                    Err(err) => return Err(PackageInstallerError::InstallationError(err)),
+                },
+            };
+
+            // Handle the result based on the state
+            match &installation {
+                Installation::AlreadyInstalled { check_duration, .. } => {
+                    self.progress_manager.emit(ProgressEvent::Check {
+                        package: &package.name,
+                        installed: true,
+                        duration: *check_duration,
+                        dependency,
+                    });
+
+                    // Return the result directly - no need to install
+                    return installation
+                        .into_result(package.name.clone())
+                        .map_err(PackageInstallerError::InstallationError);
+                }
+                Installation::NotAlreadyInstalled { check_duration, .. } => {
+                    self.progress_manager.emit(ProgressEvent::Check {
+                        package: &package.name,
+                        installed: false,
+                        duration: *check_duration,
+                        dependency,
+                    });
+                }
+                Installation::CheckTimedOut { timeout, .. } => {
+                    // A hung check says nothing about whether the package is
+                    // installed, so installing it again could do harm
+                    self.progress_manager.emit(ProgressEvent::Error {
+                        package: &package.name,
+                        message: &format!(
+                            "Check timed out after {} seconds; not installing, since it may already be installed",
+                            timeout.as_secs()
+                        ),
+                        dependency,
+                    });
+                    self.report_output_tail(&package.name, &check_output);
+                    return installation
+                        .into_result(package.name.clone())
+                        .map_err(PackageInstallerError::InstallationError);
+                }
+                Installation::Failed { error_message, .. } => {
+                    // Check failed, report error and return
+                    self.progress_manager.emit(ProgressEvent::Error {
+                        package: &package.name,
+                        message: &format!("Checking installation status failed: {}", error_message),
+                        dependency,
+                    });
+                    self.report_output_tail(&package.name, &check_output);
+                    return installation
+                        .into_result(package.name.clone())
+                        .map_err(PackageInstallerError::InstallationError);
+                }
+                _ => {
+                    // Shouldn't get here with proper state transitions
+                    return Err(PackageInstallerError::InstallationError(
+                        InstallationError::InvalidState(format!(
+                            "Unexpected state after check: {:?}",
+                            installation.status()
+                        )),
+                    ));
+                }
             }
-        }
+
+            installation
+        };
+
+        self.confirm_install(package, &env_config)?;
 
         // Print installing message
-        self.progress_manager
-            .print_progress(format!("{}⌛ Installing...", indent));
-        let output_callback = output_buffer.clone().into_callback();
+        if !compact {
+            self.progress_manager
+                .print_progress(format!("{}⌛ Installing...", indent));
+        }
+        let install_output = output_buffer();
+        let output_callback = install_output.clone().into_callback();
+        let on_step = |step: usize, total: usize, command: &str| {
+            if !compact {
+                self.progress_manager.print_progress(format!(
+                    "{}  ⌛ Step {}/{}: {}",
+                    indent, step, total, command
+                ));
+            }
+        };
 
         // Execute installation
         let installation = match installation
-            .execute_install(self.runner, output_callback)
+            .execute_install(self.runner, output_callback, on_step)
             .await
         {
             Ok(state) => state,
@@ -530,15 +1394,28 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
         // Handle the result based on the final state
         match &installation {
             Installation::Complete { duration, .. } => {
-                // Print completion message
-                let complete_message =
-                    format!("{}✓ Installation complete ({:.1?})", indent, duration);
-                self.progress_manager.print_success(complete_message);
+                self.progress_manager.emit(ProgressEvent::InstallComplete {
+                    package: &package.name,
+                    duration: *duration,
+                    dependency,
+                });
+
+                if self.options.verify {
+                    self.verify_install(package, &env_config, dependency, &indent)
+                        .await?;
+                }
+
+                if !env_config.has_check() {
+                    self.write_marker(package);
+                }
             }
             Installation::Failed { error_message, .. } => {
-                // Print error message
-                let error_message = format!("{}✗ Installation failed: {}", indent, error_message);
-                self.progress_manager.print_error(error_message);
+                self.progress_manager.emit(ProgressEvent::Error {
+                    package: &package.name,
+                    message: &format!("Installation failed: {}", error_message),
+                    dependency,
+                });
+                self.report_output_tail(&package.name, &install_output);
             }
             _ => {
                 // Shouldn't get here with proper state transitions
@@ -557,61 +1434,193 @@ impl<'a, PR: PackageRepository, CR: CommandRunner> PackageInstaller<'a, PR, CR>
             .map_err(PackageInstallerError::InstallationError)
     }
 
-    /// Report the final installation status with timing information
-    fn report_final_status(&self, result: &InstallationReport) {
-        let total_duration = result.total_duration();
-        let dep_duration = result.dependency_duration();
-        let package_duration = result.duration;
+    /// Run the package's check again, now that it's installed, failing when it
+    /// still doesn't find the package
+    async fn verify_install(
+        &self,
+        package: &Package,
+        env_config: &EnvironmentConfig,
+        dependency: bool,
+        indent: &str,
+    ) -> Result<(), PackageInstallerError> {
+        let verification = verify_installation(self.fs, self.runner, self.config, env_config).await;
 
-        self.progress_manager.print_success(format!(
-            "\nPackage '{}' installation summary:",
-            result.package_name
-        ));
-        // Display timing information according to spec
-        if !result.dependencies.is_empty() {
-            self.progress_manager
-                .print_with_duration("Total time", Some(total_duration));
-            self.progress_manager
-                .print_with_duration("Dependencies:", Some(dep_duration));
-            self.progress_manager
-                .print_with_duration("Package:", Some(package_duration));
-        } else {
+        if let Some(reason) = verification.failure() {
+            self.progress_manager.emit(ProgressEvent::Error {
+                package: &package.name,
+                message: &format!("Verification failed: {}", reason),
+                dependency,
+            });
+            return Err(PackageInstallerError::NotDetected {
+                package: package.name.clone(),
+                reason,
+            });
+        }
+
+        if !self.progress_manager.compact() {
+            if verification == Verification::NoCheck {
+                self.progress_manager.print_warning(format!(
+                    "{}Not verified: '{}' has no check",
+                    indent, package.name
+                ));
+            } else {
+                self.progress_manager
+                    .print_success(format!("{}✓ Verified: the check finds it", indent));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the marker of an earlier install of the package is there
+    fn has_marker(&self, package_name: &str) -> bool {
+        self.config
+            .marker_path(package_name)
+            .is_some_and(|marker| self.fs.path_exists(&marker))
+    }
+
+    /// Mark a package without a check as installed, so it isn't installed
+    /// again; the install still counts when the marker can't be written
+    fn write_marker(&self, package: &Package) {
+        let Some(marker) = self.config.marker_path(&package.name) else {
+            return;
+        };
+
+        let written = marker
+            .parent()
+            .map_or(Ok(()), |markers_dir| self.fs.create_dir_all(markers_dir))
+            .and_then(|()| {
+                self.fs
+                    .write_file(&marker, &format!("{}\n", package.version))
+            });
+
+        if let Err(err) = written {
+            self.progress_manager.print_warning(format!(
+                "Failed to write the install marker for '{}', so it will be installed again: {}",
+                package.name, err
+            ));
+        }
+    }
+
+    /// Show the end of a failed command's output, unless it was already shown
+    /// as it ran
+    fn report_output_tail(&self, package_name: &str, output: &CommandOutputBuffer) {
+        if !output.displays_output() {
             self.progress_manager
-                .print_with_duration("Total time:", Some(total_duration));
+                .print_output_tail(package_name, &output.tail());
         }
     }
 
-    /// Extract the base command from a command string
-    fn extract_base_command(command: &str) -> Option<&str> {
-        CommandValidator::<CR>::extract_base_command(command)
+    /// Report a failed install's final status as [`Self::report_final_status`]
+    /// does a finished one's, so the summary line and JSON consumers get its
+    /// error. Installs are tried once, so `attempts` stays 1.
+    fn report_failure(
+        &self,
+        package_name: &str,
+        err: &PackageInstallerError,
+        start_time: Instant,
+        dependencies: Vec<InstallationReport>,
+    ) {
+        let dependency_duration: Duration = dependencies.iter().map(|dep| dep.duration).sum();
+        let wall_clock = start_time.elapsed();
+
+        self.report_final_status(&InstallationReport {
+            package_name: package_name.to_string(),
+            status: failure_status(err),
+            duration: wall_clock.saturating_sub(dependency_duration),
+            wall_clock,
+            attempts: 1,
+            last_error: Some(err.to_string()),
+            command_output: None,
+            dependencies,
+        });
     }
 
-    /// Parse a cycle string into a vector of package names
-    fn parse_cycle_string(&self, cycle_str: &str) -> Option<Vec<String>> {
-        // Example format: "package1 -> package2 -> package3"
-        let output = cycle_str
-            .split(" -> ")
-            .map(|s| s.trim().to_string())
-            .collect::<Vec<String>>()
-            .into_iter()
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>();
+    /// Report the final installation status with timing information
+    fn report_final_status(&self, result: &InstallationReport) {
+        self.progress_manager.emit(ProgressEvent::Summary {
+            package: &result.package_name,
+            status: &result.status,
+            dependencies: result.dependencies.len(),
+            wall_clock: result.wall_clock,
+            total_duration: result.total_duration(),
+            dependency_duration: result.dependency_duration(),
+            package_duration: result.duration,
+            attempts: result.attempts,
+            last_error: result.last_error.as_deref(),
+        });
+
+        if self.options.timings && result.dependencies.len() > 1 {
+            self.progress_manager
+                .print_progress("  Dependency timings:");
+
+            for (name, duration) in result.dependency_timings() {
+                self.progress_manager
+                    .print_with_duration(format!("    {}", name), Some(duration));
+            }
+        }
+    }
+
+    /// Ask before running install commands that might need sudo or download
+    /// content, unless the user said yes up front
+    fn confirm_install(
+        &self,
+        package: &Package,
+        env_config: &EnvironmentConfig,
+    ) -> Result<(), PackageInstallerError> {
+        if self.config.assume_yes() {
+            return Ok(());
+        }
+
+        let install = env_config.install.to_string();
+        let mut risks = Vec::new();
+
+        if self.command_validator.might_require_sudo(&install) {
+            risks.push("might require sudo privileges");
+        }
+
+        if self.command_validator.might_download_content(&install) {
+            risks.push("may download content from the internet");
+        }
+
+        if risks.is_empty() {
+            return Ok(());
+        }
+
+        let question = format!(
+            "The install command for '{}' {}. Continue?",
+            package.name,
+            risks.join(" and ")
+        );
 
-        if output.is_empty() {
-            None
+        if self.progress_manager.confirm(question) {
+            Ok(())
         } else {
-            Some(output)
+            Err(PackageInstallerError::InstallationCanceled {
+                completed: Vec::new(),
+            })
         }
     }
+
+    /// Extract the base command from a command string
+    fn extract_base_command(command: &str) -> Option<&str> {
+        CommandValidator::<CR>::extract_base_command(command)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        domain::{config::AppConfigBuilder, package::PackageBuilder},
+        adapters::progress::{take_asked, take_emitted},
+        domain::{
+            config::AppConfigBuilder,
+            errors::ErrorObject,
+            package::{PackageBuilder, PackageParseError},
+        },
         ports::{
-            command::MockCommandRunner, filesystem::MockFileSystem,
+            command::{CommandOutput, MockCommandRunner},
+            filesystem::MockFileSystem,
             package_repo::MockPackageRepository,
         },
     };
@@ -655,12 +1664,12 @@ mod tests {
 
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
 
-        runner.mock_execute_streaming_success_1("test check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("test check", 15, "Not found");
         runner.mock_execute_streaming_success_0("test install", 600, "Installed successfully");
         runner.mock_is_command_available("test", true);
 
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, true);
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
         let result = installer.install_package(&package.name).await;
 
         assert!(result.is_ok());
@@ -668,6 +1677,142 @@ mod tests {
         assert_eq!(installation.status, InstallationStatus::Complete);
     }
 
+    #[tokio::test]
+    async fn test_install_unreadable_package_file() {
+        let config = create_test_config();
+        let (fs, runner, mut repo, progress_manager) = create_installer_deps();
+
+        let path = std::path::PathBuf::from("/test/packages/ripgrep.yaml");
+        repo.mock_get_package_err(
+            "ripgrep",
+            PackageRepoError::ParseError(PackageParseError::FileSystemError(
+                FileSystemError::PermissionDenied(path),
+            )),
+        );
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+
+        match installer.install_package("ripgrep").await {
+            Err(PackageInstallerError::EnhancedError { kind, message, .. }) => {
+                assert_eq!(kind, "permission_denied");
+                assert!(message.starts_with("Permission denied: /test/packages/ripgrep.yaml"));
+                assert!(message.contains("chmod u+rw"));
+            }
+            other => panic!("Expected a permission error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_package_not_found_as_json() {
+        let config = create_test_config();
+        let (fs, runner, mut repo, progress_manager) = create_installer_deps();
+        repo.mock_get_package_err(
+            "nosuchpkg",
+            PackageRepoError::PackageNotFound("nosuchpkg".to_string()),
+        );
+        repo.mock_find_similar("nosuchpkg", &[]);
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+        let err = installer.install_package("nosuchpkg").await.unwrap_err();
+
+        // What `--output json` prints for it, rather than only the message
+        let json = serde_json::to_value(ErrorObject::new(&err, "Package not found")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "package_not_found",
+                "message": "Package not found",
+                "context": { "package": "nosuchpkg" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_skip_if() {
+        let package = PackageBuilder::default()
+            .name("docker-tools")
+            .version("1.0.0")
+            .environment_config(
+                "test-env",
+                EnvironmentConfig::new("test install")
+                    .with_check("test check")
+                    .with_skip_if("test -n \"$CI\""),
+            )
+            .build();
+        let config = create_test_config();
+
+        for (guard_status, expected) in [
+            (
+                0,
+                InstallationStatus::Skipped("skip_if matched".to_string()),
+            ),
+            (1, InstallationStatus::Complete),
+        ] {
+            let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+            repo.mock_get_package_ok(&package.name, package.clone());
+            let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+            runner
+                .expect_execute()
+                .with(mockall::predicate::eq("test -n \"$CI\"".to_string()))
+                .times(1)
+                .returning(move |_| {
+                    Ok(CommandOutput {
+                        status: guard_status,
+                        success: guard_status == 0,
+                        ..CommandOutput::default()
+                    })
+                });
+            runner.mock_is_command_available("test", true);
+
+            // A matching guard means neither the check nor the install runs
+            if guard_status != 0 {
+                runner.mock_execute_streaming_success_1("test check", 15, "Not found");
+                runner.mock_execute_streaming_success_0("test install", 600, "Installed");
+            }
+
+            let installer =
+                PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+            let installation = installer.install_package(&package.name).await.unwrap();
+
+            assert_eq!(installation.status, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_with_command_wrapper() {
+        let package = create_test_package();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/path")
+            .command_wrapper("nice -n 19")
+            .build();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // The check and the availability lookup ignore the wrapper
+        runner.mock_execute_streaming_success_1("test check", 15, "Not found");
+        runner.mock_execute_streaming_success_0(
+            "nice -n 19 test install",
+            600,
+            "Installed successfully",
+        );
+        runner.mock_is_command_available("test", true);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+        let installation = installer.install_package(&package.name).await.unwrap();
+
+        assert_eq!(installation.status, InstallationStatus::Complete);
+    }
+
     #[tokio::test]
     async fn test_already_installed() {
         let package = create_test_package();
@@ -678,11 +1823,11 @@ mod tests {
 
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
 
-        runner.mock_execute_streaming_success_0("test check", 60, "Found");
+        runner.mock_execute_streaming_success_0("test check", 15, "Found");
         runner.mock_is_command_available("test", true);
 
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, true);
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
         let result = installer.install_package(&package.name).await;
 
         assert!(result.is_ok());
@@ -700,15 +1845,25 @@ mod tests {
 
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
 
-        runner.mock_execute_streaming_success_1("test check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("test check", 15, "Not found");
         runner.mock_execute_streaming_success_1("test install", 600, "Installation failed");
         runner.mock_is_command_available("test", true);
 
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, true);
-        let result = installer.install_package(&package.name).await;
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+        take_emitted();
+        let err = installer.install_package(&package.name).await.unwrap_err();
 
-        assert!(result.is_err());
+        // The failure still gets a summary, carrying the error
+        let summary = take_emitted()
+            .into_iter()
+            .find(|event| event["event"] == "summary")
+            .expect("Expected a summary of the failed install");
+        assert_eq!(summary["package"], "test-package");
+        assert_eq!(summary["status"], "failed");
+        assert_eq!(summary["attempts"], 1);
+        assert_eq!(summary["last_error"], err.to_string());
+        assert!(err.to_string().contains("test install"));
     }
 
     #[tokio::test]
@@ -725,35 +1880,45 @@ mod tests {
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
 
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, true);
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
         let result = installer.install_package(&package.name).await;
 
         assert!(result.is_err());
     }
 
-    #[test]
-    fn test_parse_cycle_string() {
+    #[tokio::test]
+    async fn test_install_circular_dependency() {
         let config = create_test_config();
-        let (fs, runner, repo, progress_manager) = create_installer_deps();
+        let (fs, runner, mut repo, progress_manager) = create_installer_deps();
+
+        let package1 = PackageBuilder::default()
+            .name("package1")
+            .version("1.0.0")
+            .environment_with_dependencies("test-env", "p1 install", vec!["package2"])
+            .build();
+        let package2 = PackageBuilder::default()
+            .name("package2")
+            .version("1.0.0")
+            .environment_with_dependencies("test-env", "p2 install", vec!["package1"])
+            .build();
+
+        repo.mock_get_package_ok("package1", package1);
+        repo.mock_get_package_ok("package2", package2);
+
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
-        let manager = PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, true);
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
 
-        // Test basic cycle parsing
-        let cycle_str = "package1 -> package2 -> package3 -> package1";
-        let parsed = manager.parse_cycle_string(cycle_str).unwrap();
-        assert_eq!(
-            parsed,
-            vec![
-                "package1".to_string(),
-                "package2".to_string(),
-                "package3".to_string(),
-                "package1".to_string(),
-            ]
-        );
+        let result = installer.install_package("package1").await;
 
-        // Test empty string
-        let empty = manager.parse_cycle_string("");
-        assert!(empty.is_none() || empty.unwrap().is_empty());
+        match result {
+            Err(PackageInstallerError::DependencyResolverError(
+                DependencyResolverError::CircularDependency(cycle),
+            )) => {
+                assert_eq!(cycle, ["package2", "package1", "package2"]);
+            }
+            other => panic!("Expected circular dependency error; got {:?}", other),
+        }
     }
 
     #[tokio::test]
@@ -780,7 +1945,7 @@ mod tests {
         repo.mock_get_package_ok("ripgrep", Package::from_yaml(package_yaml).unwrap());
 
         // Set up mock command responses
-        runner.mock_execute_streaming_success_1("rg check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("rg check", 15, "Not found");
         runner.mock_execute_streaming_success_0("rg install", 600, "Installed successfully");
 
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
@@ -789,7 +1954,7 @@ mod tests {
 
         // Create package installer (using the new consolidated version)
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, false);
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
 
         // Run the installation
         let result = installer.install_package("ripgrep").await;
@@ -800,6 +1965,83 @@ mod tests {
         assert_eq!(install_result.package_name, "ripgrep");
     }
 
+    #[tokio::test]
+    async fn test_install_skips_dependency_when_command_available() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let package_yaml = r#"
+        name: native-ext
+        version: 1.0.0
+        environments:
+          test-env:
+            install: ext install
+            check: ext check
+            dependencies:
+              - { name: gcc, unless_command: cc }
+    "#;
+        repo.mock_get_package_ok("native-ext", Package::from_yaml(package_yaml).unwrap());
+        // Not even a package for it, which only matters when it's needed
+        repo.mock_get_package_err("gcc", PackageRepoError::PackageNotFound("gcc".to_string()));
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // cc is there, so gcc is neither checked nor installed
+        runner.mock_probe_commands(&[("cc", true)]);
+        runner.mock_execute_streaming_success_1("ext check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("ext install", 600, "Installed successfully");
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
+        let install_result = installer.install_package("native-ext").await.unwrap();
+
+        assert_eq!(install_result.package_name, "native-ext");
+        assert!(install_result.dependencies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_install_verify_fails_when_check_still_fails() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let package_yaml = r#"
+        name: ripgrep
+        version: 1.0.0
+        environments:
+          test-env:
+            install: rg install
+            check: rg check
+    "#;
+        repo.mock_get_package_ok("ripgrep", Package::from_yaml(package_yaml).unwrap());
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // The install exits 0, but the check fails before and after it
+        runner.mock_execute_streaming_success_1("rg check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("rg install", 600, "Installed successfully");
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false)
+                .with_options(InstallOptions {
+                    verify: true,
+                    ..InstallOptions::default()
+                });
+        let result = installer.install_package("ripgrep").await;
+
+        assert!(matches!(
+            result,
+            Err(PackageInstallerError::NotDetected { package, .. }) if package == "ripgrep"
+        ));
+        assert!(matches!(
+            installer.statuses().as_slice(),
+            [(name, InstallationStatus::Failed(_))] if name == "ripgrep"
+        ));
+    }
+
     #[tokio::test]
     async fn test_package_install_with_dependencies() {
         // Create mock environment
@@ -838,16 +2080,16 @@ mod tests {
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
 
         // Set up mock command responses
-        runner.mock_execute_streaming_success_1("rg check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("rg check", 15, "Not found");
         runner.mock_execute_streaming_success_0("rg install", 600, "Installed successfully");
-        runner.mock_execute_streaming_success_1("rust check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("rust check", 15, "Not found");
         runner.mock_execute_streaming_success_0("rust install", 600, "Installed successfully");
 
         let progress_manager = ProgressManager::new(false, true);
 
         // Create package installer
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, false);
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
 
         // Run the installation
         let result = installer.install_package("ripgrep").await;
@@ -858,6 +2100,574 @@ mod tests {
         assert_eq!(install_result.package_name, "ripgrep");
         assert_eq!(install_result.dependencies.len(), 1);
         assert_eq!(install_result.dependencies[0].package_name, "rust");
+
+        // Steps run one after another, so they can't add up to more than the
+        // elapsed time
+        assert!(install_result.total_duration() <= install_result.wall_clock);
+    }
+
+    #[tokio::test]
+    async fn test_install_canceled_after_dependency() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        repo.mock_get_package_ok(
+            "ripgrep",
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "rg install", vec!["rust"])
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "rust",
+            PackageBuilder::default()
+                .name("rust")
+                .version("1.0.0")
+                .environment("test-env", "rust install")
+                .build(),
+        );
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // Canceled while rust installs; ripgrep's install must not start
+        let cancellation = CancellationToken::new();
+        let token = cancellation.clone();
+        runner
+            .expect_execute_streaming()
+            .with(
+                mockall::predicate::eq("rust install".to_string()),
+                mockall::predicate::always(),
+                mockall::predicate::always(),
+            )
+            .returning(move |_, _, _| {
+                token.cancel();
+                Ok(CommandOutput {
+                    success: true,
+                    ..CommandOutput::default()
+                })
+            });
+
+        let installer = PackageInstaller::new(
+            &fs,
+            &repo,
+            &eeh,
+            &runner,
+            &config,
+            ProgressManager::new(false, true),
+            false,
+        )
+        .with_cancellation(cancellation);
+
+        match installer.install_package("ripgrep").await {
+            Err(PackageInstallerError::InstallationCanceled { completed }) => {
+                assert_eq!(completed.len(), 1);
+                assert_eq!(completed[0].package_name, "rust");
+                assert!(matches!(completed[0].status, InstallationStatus::Complete));
+            }
+            other => panic!("Expected installation to be canceled; got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strict_install_fails_on_environment_alias() {
+        let (fs, runner, mut repo, progress_manager) = create_installer_deps();
+
+        let config = AppConfigBuilder::default()
+            .environment("macos")
+            .environment_aliases("macos", &["mac"])
+            .package_directory("/test/packages")
+            .strict(true)
+            .build();
+
+        repo.mock_get_package_ok(
+            "ripgrep",
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .environment("mac", "rg install")
+                .build(),
+        );
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let installer = PackageInstaller::new(
+            &fs,
+            &repo,
+            &eeh,
+            &runner,
+            &config,
+            ProgressManager::new(false, true),
+            false,
+        );
+
+        // Without --strict the alias is used; with it, nothing runs
+        match installer.install_package("ripgrep").await {
+            Err(PackageInstallerError::EnvironmentError(message)) => {
+                assert_eq!(
+                    message,
+                    "Package 'ripgrep' only has the 'mac' environment, an alias of 'macos'"
+                );
+            }
+            other => panic!("Expected an environment error; got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_statuses_after_failed_dependency() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+        let config = create_test_config();
+
+        repo.mock_get_package_ok(
+            "ripgrep",
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "rg install", vec!["rust"])
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "rust",
+            PackageBuilder::default()
+                .name("rust")
+                .version("1.0.0")
+                .environment("test-env", "rust install")
+                .build(),
+        );
+
+        runner.mock_execute_streaming_exit("rust install", 600, 1, "no network");
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
+
+        assert!(installer.install_package("ripgrep").await.is_err());
+
+        // The failure stops the run before it gets to ripgrep
+        let statuses = installer.statuses();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].0, "rust");
+        assert!(matches!(statuses[0].1, InstallationStatus::Failed(_)));
+        assert_eq!(
+            statuses[1],
+            ("ripgrep".to_string(), InstallationStatus::Pending)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_no_deps() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+        let config = create_test_config();
+
+        // The dependency isn't even looked up
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("1.0.0")
+            .environment_with_dependencies("test-env", "rg install", vec!["rust"])
+            .build();
+        repo.mock_get_package_ok("ripgrep", package);
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        runner.mock_execute_streaming_success_0("rg install", 600, "Installed successfully");
+        runner.mock_is_command_available("rg", true);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    no_deps: true,
+                    ..Default::default()
+                });
+
+        let install_result = installer.install_package("ripgrep").await.unwrap();
+
+        assert_eq!(install_result.status, InstallationStatus::Complete);
+        assert!(install_result.dependencies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_install_only_deps() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+        let config = create_test_config();
+
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("1.0.0")
+            .environment_with_dependencies("test-env", "rg install", vec!["rust"])
+            .build();
+        let dependency = PackageBuilder::default()
+            .name("rust")
+            .version("1.0.0")
+            .environment_with_check("test-env", "rust install", "rust check")
+            .build();
+
+        repo.mock_get_package_ok("ripgrep", package);
+        repo.mock_get_package_ok("rust", dependency);
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // Only the dependency's commands are expected to run
+        runner.mock_execute_streaming_success_1("rust check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("rust install", 600, "Installed successfully");
+        runner.mock_is_command_available("rust", true);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    only_deps: true,
+                    ..Default::default()
+                });
+
+        let install_result = installer.install_package("ripgrep").await.unwrap();
+
+        assert_eq!(install_result.package_name, "ripgrep");
+        assert!(matches!(
+            install_result.status,
+            InstallationStatus::Skipped(_)
+        ));
+        assert_eq!(install_result.dependencies.len(), 1);
+        assert_eq!(install_result.dependencies[0].package_name, "rust");
+        assert_eq!(
+            install_result.dependencies[0].status,
+            InstallationStatus::Complete
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_command_override() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+        let config = create_test_config();
+
+        let package = PackageBuilder::default()
+            .name("ripgrep")
+            .version("1.0.0")
+            .environment_with_dependencies("test-env", "rg install", vec!["rust"])
+            .build();
+        let dependency = PackageBuilder::default()
+            .name("rust")
+            .version("1.0.0")
+            .environment_with_check("test-env", "rust install", "rust check")
+            .build();
+        repo.mock_get_package_ok("ripgrep", package);
+        repo.mock_get_package_ok("rust", dependency);
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // The dependency installs as usual, and the package with the override;
+        // its own command isn't even looked up
+        runner.mock_execute_streaming_success_1("rust check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("rust install", 600, "Installed successfully");
+        runner.mock_execute_streaming_success_0(
+            "cargo install ripgrep --locked",
+            600,
+            "Installed successfully",
+        );
+        runner.mock_is_command_available("rust", true);
+        runner.mock_is_command_available("cargo", true);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    install_command: Some("cargo install ripgrep --locked".to_string()),
+                    ..Default::default()
+                });
+
+        let install_result = installer.install_package("ripgrep").await.unwrap();
+
+        assert_eq!(install_result.status, InstallationStatus::Complete);
+        assert_eq!(
+            install_result.dependencies[0].status,
+            InstallationStatus::Complete
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_only_deps_without_dependencies() {
+        let package = create_test_package();
+        let config = create_test_config();
+        let (fs, runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // No commands should be run at all
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    only_deps: true,
+                    ..Default::default()
+                });
+
+        let install_result = installer.install_package(&package.name).await.unwrap();
+
+        assert!(matches!(
+            install_result.status,
+            InstallationStatus::Skipped(_)
+        ));
+        assert!(install_result.dependencies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_install_no_check() {
+        let package = create_test_package();
+        let config = create_test_config();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // "test check" would report the package as installed, but must never run
+        runner.mock_execute_streaming_success_0("test install", 600, "Installed successfully");
+        runner.mock_is_command_available("test", true);
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    no_check: true,
+                    ..Default::default()
+                });
+
+        let install_result = installer.install_package(&package.name).await.unwrap();
+
+        assert_eq!(install_result.status, InstallationStatus::Complete);
+    }
+
+    #[tokio::test]
+    async fn test_verify_commands_probes_each_command_once() {
+        let config = create_test_config();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        let packages = [
+            ("ripgrep", "brew install ripgrep"),
+            ("fd", "brew install fd"),
+            ("bat", "cargo install bat"),
+            ("eza", "cargo install eza"),
+            ("jq", "apt install jq"),
+        ];
+        for (name, install) in packages {
+            repo.mock_get_package_ok(
+                name,
+                PackageBuilder::default()
+                    .name(name)
+                    .version("1.0.0")
+                    .environment("test-env", install)
+                    .build(),
+            );
+        }
+        repo.mock_get_package_ok(
+            "tools",
+            PackageBuilder::default()
+                .name("tools")
+                .version("1.0.0")
+                .environment_with_dependencies(
+                    "test-env",
+                    "brew install tools",
+                    packages.iter().map(|(name, _)| *name).collect(),
+                )
+                .build(),
+        );
+
+        for (command, available) in [("brew", true), ("cargo", false), ("apt", false)] {
+            runner
+                .expect_is_command_available()
+                .with(mockall::predicate::eq(command))
+                .times(1)
+                .returning(move |_| Ok(available));
+        }
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let err = PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+            .install_package("tools")
+            .await
+            .unwrap_err();
+
+        let PackageInstallerError::CommandNotAvailable(message) = err else {
+            panic!("Expected CommandNotAvailable, got {:?}", err);
+        };
+        for (name, command) in [("bat", "cargo"), ("eza", "cargo"), ("jq", "apt")] {
+            assert!(message.contains(&format!("  • Package '{}' requires '{}'\n", name, command)));
+        }
+        assert!(!message.contains("'brew'"));
+    }
+
+    #[tokio::test]
+    async fn test_install_no_check_policy() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment("test-env", "test install")
+            .build();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+        runner.mock_is_command_available("test", true);
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        // With a recorded install for this environment, nothing runs
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .no_check_policy(NoCheckPolicy::UseState)
+            .build();
+        let mut install_state = InstallState::default();
+        install_state.record("test-package", "1.0.0", "test-env");
+
+        let install_result =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_install_state(install_state)
+                .install_package(&package.name)
+                .await
+                .unwrap();
+        assert_eq!(install_result.status, InstallationStatus::AlreadyInstalled);
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .no_check_policy(NoCheckPolicy::Never)
+            .build();
+
+        let err = PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+            .install_package(&package.name)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no_check_policy is 'never'"));
+    }
+
+    #[tokio::test]
+    async fn test_install_markers() {
+        let package = PackageBuilder::default()
+            .name("vimrc")
+            .version("1.0.0")
+            .environment("test-env", "cp vimrc ~/.vimrc")
+            .build();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .markers_dir("/test/markers")
+            .build();
+        let (mut fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+        runner.mock_is_command_available("cp", true);
+        runner.mock_execute_streaming_success_0("cp vimrc ~/.vimrc", 600, "");
+
+        // Without a marker it's installed, and marked
+        fs.mock_path_exists("/test/markers/vimrc", false);
+        fs.expect_create_dir_all()
+            .withf(|path| path == Path::new("/test/markers"))
+            .returning(|_| Ok(()));
+        let writes = fs.mock_write_file_recording();
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let install_result =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .install_package(&package.name)
+                .await
+                .unwrap();
+        assert_eq!(install_result.status, InstallationStatus::Complete);
+        assert_eq!(
+            *writes.lock().unwrap(),
+            vec![(
+                std::path::PathBuf::from("/test/markers/vimrc"),
+                "1.0.0\n".to_string()
+            )]
+        );
+
+        // With one, nothing runs
+        let mut fs = MockFileSystem::new();
+        fs.mock_path_exists("/test/markers/vimrc", true);
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let install_result =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .install_package(&package.name)
+                .await
+                .unwrap();
+        assert_eq!(install_result.status, InstallationStatus::AlreadyInstalled);
+    }
+
+    #[tokio::test]
+    async fn test_install_assume_yes_skips_confirmation() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment("test-env", "sudo apt install test-package")
+            .build();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .assume_yes(true)
+            .build();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        runner.mock_execute_streaming_success_0("sudo apt install test-package", 600, "");
+        runner.expect_is_command_available().returning(|_| Ok(true));
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    no_check: true,
+                    ..Default::default()
+                });
+
+        let install_result = installer.install_package(&package.name).await.unwrap();
+
+        assert_eq!(install_result.status, InstallationStatus::Complete);
+        assert!(take_asked().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_install_declined_confirmation_installs_nothing() {
+        let package = PackageBuilder::default()
+            .name("test-package")
+            .version("1.0.0")
+            .environment("test-env", "sudo apt install test-package")
+            .build();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        repo.mock_get_package_ok(&package.name, package.clone());
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+
+        runner.expect_execute_streaming().never();
+        runner.expect_execute_with_stdin().never();
+        runner.expect_is_command_available().returning(|_| Ok(true));
+
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true)
+                .with_options(InstallOptions {
+                    no_check: true,
+                    ..Default::default()
+                });
+
+        // Tests answer no, as does anyone without a terminal
+        let result = installer.install_package(&package.name).await;
+
+        assert!(matches!(
+            result,
+            Err(PackageInstallerError::InstallationCanceled { ref completed }) if completed.is_empty()
+        ));
+        assert_eq!(
+            take_asked(),
+            vec![
+                "The install command for 'test-package' might require sudo privileges. Continue?"
+                    .to_string()
+            ]
+        );
     }
 
     // Update the test in tests/integration_test.rs to test dependency resolution
@@ -920,13 +2730,13 @@ mod tests {
         repo.mock_get_package_ok("dep3", Package::from_yaml(dep3_yaml).unwrap());
 
         // Set up mock command responses - all need to be installed
-        runner.mock_execute_streaming_success_1("main-check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("main-check", 15, "Not found");
         runner.mock_execute_streaming_success_0("main-install", 600, "Installed successfully");
-        runner.mock_execute_streaming_success_1("dep1-check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("dep1-check", 15, "Not found");
         runner.mock_execute_streaming_success_0("dep1-install", 600, "Installed successfully");
-        runner.mock_execute_streaming_success_1("dep2-check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("dep2-check", 15, "Not found");
         runner.mock_execute_streaming_success_0("dep2-install", 600, "Installed successfully");
-        runner.mock_execute_streaming_success_1("dep3-check", 60, "Not found");
+        runner.mock_execute_streaming_success_1("dep3-check", 15, "Not found");
         runner.mock_execute_streaming_success_0("dep3-install", 600, "Installed successfully");
 
         let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
@@ -934,7 +2744,7 @@ mod tests {
 
         // Create package installer
         let installer =
-            PackageInstaller::new(&repo, &eeh, &runner, &config, progress_manager, false);
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
 
         // Run the installation
         let result = installer.install_package("main-pkg").await;
@@ -973,4 +2783,308 @@ mod tests {
             .unwrap();
         assert_eq!(dep2_result.status, InstallationStatus::Complete);
     }
+
+    #[tokio::test]
+    async fn test_check_implies_dependencies() {
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        // dep1's passing check covers dep3, so dep3 is never checked or
+        // installed; dep2 is still handled as usual
+        repo.mock_get_package_ok(
+            "main-pkg",
+            PackageBuilder::default()
+                .name("main-pkg")
+                .version("1.0.0")
+                .environment_config(
+                    "test-env",
+                    EnvironmentConfig::new("main-install")
+                        .with_check("main-check")
+                        .with_dependencies(["dep1", "dep2"]),
+                )
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "dep1",
+            PackageBuilder::default()
+                .name("dep1")
+                .version("1.0.0")
+                .environment_config(
+                    "test-env",
+                    EnvironmentConfig::new("dep1-install")
+                        .with_check("dep1-check")
+                        .with_dependencies(["dep3"])
+                        .with_check_implies_dependencies(true),
+                )
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "dep2",
+            PackageBuilder::default()
+                .name("dep2")
+                .version("1.0.0")
+                .environment_with_check("test-env", "dep2-install", "dep2-check")
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "dep3",
+            PackageBuilder::default()
+                .name("dep3")
+                .version("1.0.0")
+                .environment_with_check("test-env", "dep3-install", "dep3-check")
+                .build(),
+        );
+
+        runner.mock_execute_streaming_success_1("main-check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("main-install", 600, "Installed successfully");
+        runner.mock_execute_streaming_success_0("dep1-check", 15, "Found");
+        runner.mock_execute_streaming_success_1("dep2-check", 15, "Not found");
+        runner.mock_execute_streaming_success_0("dep2-install", 600, "Installed successfully");
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
+
+        let result = installer.install_package("main-pkg").await.unwrap();
+
+        assert_eq!(result.status, InstallationStatus::Complete);
+
+        let statuses: Vec<_> = result
+            .dependencies
+            .iter()
+            .map(|d| (d.package_name.as_str(), &d.status))
+            .collect();
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.contains(&("dep1", &InstallationStatus::AlreadyInstalled)));
+        assert!(statuses.contains(&("dep2", &InstallationStatus::Complete)));
+    }
+
+    #[test]
+    fn test_matching_packages() {
+        let mut repo = MockPackageRepository::default();
+        repo.mock_list_packages(
+            ["rust-analyzer", "ripgrep", "rust-src"]
+                .into_iter()
+                .map(|name| {
+                    PackageBuilder::default()
+                        .name(name)
+                        .version("1.0.0")
+                        .environment("test-env", "install")
+                        .build()
+                })
+                .collect(),
+        );
+
+        assert!(is_package_pattern("rust-*"));
+        assert!(!is_package_pattern("rust-src"));
+
+        assert_eq!(
+            matching_packages(&repo, "rust-*").unwrap(),
+            vec!["rust-analyzer", "rust-src"]
+        );
+        assert_eq!(
+            matching_packages(&repo, "node-*").unwrap_err().to_string(),
+            "No packages match pattern 'node-*'"
+        );
+    }
+
+    #[test]
+    fn test_listed_packages() {
+        let mut repo = MockPackageRepository::default();
+        repo.mock_list_packages(vec![
+            PackageBuilder::default()
+                .name("ripgrep")
+                .aliases(&["rg"])
+                .version("1.0.0")
+                .environment("test-env", "install")
+                .build(),
+            PackageBuilder::default()
+                .name("fd")
+                .version("1.0.0")
+                .environment("test-env", "install")
+                .build(),
+        ]);
+
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Aliases resolve to the package, which is only listed once
+        assert_eq!(
+            listed_packages(&repo, &names(&["rg", "fd", "ripgrep"])).unwrap(),
+            vec!["ripgrep", "fd"]
+        );
+
+        // Every missing name is reported, not only the first
+        assert_eq!(
+            listed_packages(&repo, &names(&["bat", "fd", "exa"]))
+                .unwrap_err()
+                .to_string(),
+            "Listed packages not found: bat, exa"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_shared_dependency_once() {
+        let config = create_test_config();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        for name in ["rust-analyzer", "rust-src"] {
+            repo.mock_get_package_ok(
+                name,
+                PackageBuilder::default()
+                    .name(name)
+                    .version("1.0.0")
+                    .environment_with_dependencies(
+                        "test-env",
+                        &format!("{name}-install"),
+                        vec!["rustup"],
+                    )
+                    .build(),
+            );
+        }
+        repo.mock_get_package_ok(
+            "rustup",
+            PackageBuilder::default()
+                .name("rustup")
+                .version("1.0.0")
+                .environment("test-env", "rustup-install")
+                .build(),
+        );
+
+        runner
+            .expect_execute_streaming()
+            .with(
+                mockall::predicate::eq("rustup-install".to_string()),
+                mockall::predicate::always(),
+                mockall::predicate::always(),
+            )
+            .times(1)
+            .returning(|_, _, _| {
+                Ok(CommandOutput {
+                    success: true,
+                    ..CommandOutput::default()
+                })
+            });
+        runner.mock_execute_streaming_success_0("rust-analyzer-install", 600, "");
+        runner.mock_execute_streaming_success_0("rust-src-install", 600, "");
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, false);
+
+        let first = installer.install_package("rust-analyzer").await.unwrap();
+        assert_eq!(first.dependencies.len(), 1);
+
+        let second = installer.install_package("rust-src").await.unwrap();
+        assert_eq!(second.status, InstallationStatus::Complete);
+        assert!(second.dependencies.is_empty());
+
+        // Installed earlier in the run as a dependency
+        let third = installer.install_package("rustup").await.unwrap();
+        assert!(matches!(third.status, InstallationStatus::Skipped(_)));
+    }
+
+    #[tokio::test]
+    async fn test_plan_packages_checks_every_target_up_front() {
+        let config = create_test_config();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        for (name, install) in [
+            ("ripgrep", "brew install ripgrep"),
+            ("bat", "cargo install bat"),
+            ("jq", "apt install jq"),
+        ] {
+            repo.mock_get_package_ok(
+                name,
+                PackageBuilder::default()
+                    .name(name)
+                    .version("1.0.0")
+                    .environment_with_check("test-env", install, &format!("which {}", name))
+                    .build(),
+            );
+        }
+        for (command, available) in [("brew", true), ("cargo", false), ("apt", false)] {
+            runner
+                .expect_is_command_available()
+                .with(mockall::predicate::eq(command))
+                .times(1)
+                .returning(move |_| Ok(available));
+        }
+
+        // Nothing is checked or installed once a command is missing
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+        let targets = ["bat", "jq", "ripgrep"].map(String::from);
+        let err = installer.plan_packages(&targets).await.unwrap_err();
+
+        let PackageInstallerError::CommandNotAvailable(message) = err else {
+            panic!("Expected CommandNotAvailable, got {:?}", err);
+        };
+        assert!(message.contains("  • Package 'bat' requires 'cargo'\n"));
+        assert!(message.contains("  • Package 'jq' requires 'apt'\n"));
+    }
+
+    #[tokio::test]
+    async fn test_plan_packages_then_install() {
+        let config = create_test_config();
+        let (fs, mut runner, mut repo, progress_manager) = create_installer_deps();
+
+        for name in ["rust-analyzer", "rust-src"] {
+            repo.mock_get_package_ok(
+                name,
+                PackageBuilder::default()
+                    .name(name)
+                    .version("1.0.0")
+                    .environment_with_dependencies(
+                        "test-env",
+                        &format!("{name}-install"),
+                        vec!["rustup"],
+                    )
+                    .build(),
+            );
+        }
+        repo.mock_get_package_ok(
+            "rustup",
+            PackageBuilder::default()
+                .name("rustup")
+                .version("1.0.0")
+                .environment("test-env", "rustup-install")
+                .build(),
+        );
+
+        // Each install command is only looked for by the plan
+        for command in [
+            "rust-analyzer-install",
+            "rust-src-install",
+            "rustup-install",
+        ] {
+            runner
+                .expect_is_command_available()
+                .with(mockall::predicate::eq(command))
+                .times(1)
+                .returning(|_| Ok(true));
+            runner.mock_execute_streaming_success_0(command, 600, "");
+        }
+
+        let eeh = EnhancedErrorHandler::new(&fs, &repo, progress_manager);
+        let installer =
+            PackageInstaller::new(&fs, &repo, &eeh, &runner, &config, progress_manager, true);
+        let targets = ["rust-analyzer", "rust-src"].map(String::from);
+        installer.plan_packages(&targets).await.unwrap();
+
+        let first = installer.install_package("rust-analyzer").await.unwrap();
+        assert_eq!(first.dependencies.len(), 1);
+        let second = installer.install_package("rust-src").await.unwrap();
+        assert_eq!(second.status, InstallationStatus::Complete);
+        assert!(second.dependencies.is_empty());
+    }
 }
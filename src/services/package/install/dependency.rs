@@ -1,20 +1,27 @@
 // src/services/package/installer/dependency.rs
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashSet},
+};
+
 use thiserror::Error;
 
 use crate::{
     domain::config::AppConfig,
     domain::dependency::{DependencyGraph, DependencyGraphError},
-    domain::package::Package,
+    domain::package::{Dependency, Package},
     ports::package_repo::{PackageRepoError, PackageRepository},
 };
 
 #[derive(Error, Debug)]
-pub(crate) enum DependencyResolverError {
-    #[error("Package not found: {0}")]
-    PackageNotFound(String),
+pub enum DependencyResolverError {
+    /// `chain` is how the package was reached, from the requested package to
+    /// the missing one
+    #[error("Package not found: {name}{}", via(chain))]
+    PackageNotFound { name: String, chain: Vec<String> },
 
-    #[error("Circular dependency detected: {0}")]
-    CircularDependency(String),
+    #[error("Circular dependency detected: {}", .0.join(" -> "))]
+    CircularDependency(Vec<String>),
 
     #[error("Multiple packages found with name: {0}")]
     MultiplePackagesFound(String),
@@ -23,15 +30,47 @@ pub(crate) enum DependencyResolverError {
     RepoError(#[from] PackageRepoError),
 
     #[error("Dependency graph error: {0}")]
-    GraphError(#[from] DependencyGraphError),
+    GraphError(DependencyGraphError),
 
     #[error("Environment {0} not supported by package {1}")]
     EnvironmentNotSupported(String, String),
+
+    /// The chain from the requested package to the first one past the
+    /// maximum depth
+    #[error("Dependency chain exceeds the maximum depth: {}", .0.join(" -> "))]
+    MaxDepthExceeded(Vec<String>),
+}
+
+/// The chain leading to a missing dependency, for error messages; nothing for
+/// the requested package itself
+fn via(chain: &[String]) -> String {
+    if chain.len() > 1 {
+        format!(" (required via {})", chain.join(" -> "))
+    } else {
+        String::new()
+    }
+}
+
+impl From<DependencyGraphError> for DependencyResolverError {
+    fn from(error: DependencyGraphError) -> Self {
+        match error {
+            DependencyGraphError::CircularDependency(cycle) => Self::CircularDependency(cycle),
+            other => Self::GraphError(other),
+        }
+    }
 }
 
 pub(crate) struct DependencyResolver<'a> {
     package_repo: &'a dyn PackageRepository,
     config: &'a AppConfig,
+    max_depth: Option<usize>,
+
+    /// Commands known to be available, so dependencies with one of them as
+    /// their `unless_command` are left out
+    available_commands: HashSet<String>,
+
+    /// Every `unless_command` met while resolving, available or not
+    unless_commands: RefCell<BTreeSet<String>>,
 }
 
 impl<'a> DependencyResolver<'a> {
@@ -39,43 +78,57 @@ impl<'a> DependencyResolver<'a> {
         Self {
             package_repo,
             config,
+            max_depth: None,
+            available_commands: HashSet::new(),
+            unless_commands: RefCell::default(),
         }
     }
 
+    /// Commands known to be available. Any other `unless_command` is taken
+    /// to be missing, so its dependency is kept.
+    pub(crate) fn with_available_commands(mut self, available_commands: HashSet<String>) -> Self {
+        self.available_commands = available_commands;
+        self
+    }
+
+    /// The `unless_command` of every dependency met so far, for looking up
+    /// before resolving again
+    pub(crate) fn unless_commands(&self) -> BTreeSet<String> {
+        self.unless_commands.borrow().clone()
+    }
+
+    /// How many levels of dependencies below the requested package to
+    /// resolve before giving up; unlimited when `None`
+    pub(crate) fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
     /// Resolve dependencies for a package and return an ordered list of packages
     /// that need to be installed
     pub(crate) fn resolve_dependencies(
         &self,
         package_name: &str,
     ) -> Result<Vec<Package>, DependencyResolverError> {
-        // Build the dependency graph starting with the requested package
-        let mut graph = DependencyGraph::default();
-        self.build_dependency_graph(&mut graph, package_name, &mut Vec::new())?;
+        let graph = self.resolve_graph(package_name)?;
 
         // Get the installation order
-        let installation_order = match graph.installation_order() {
-            Ok(order) => order,
-            Err(DependencyGraphError::CircularDependency(msg, path)) => {
-                let cycles = graph.find_cycles();
-
-                // Convert the graph error to our error type with the cycle path
-                return Err(DependencyResolverError::CircularDependency(format!(
-                    "{} (path: {}) | {}",
-                    msg,
-                    path.join(" -> "),
-                    cycles
-                        .into_iter()
-                        .map(|v| v.join(", "))
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                )));
-            }
-            Err(e) => return Err(DependencyResolverError::GraphError(e)),
-        };
+        let installation_order = graph.installation_order()?;
 
         Ok(installation_order.into_iter().cloned().collect())
     }
 
+    /// Build the dependency graph of a package and everything it depends on
+    pub(crate) fn resolve_graph(
+        &self,
+        package_name: &str,
+    ) -> Result<DependencyGraph, DependencyResolverError> {
+        let mut graph = DependencyGraph::default();
+        self.build_dependency_graph(&mut graph, package_name, &mut Vec::new())?;
+
+        Ok(graph)
+    }
+
     /// Recursively build the dependency graph
     fn build_dependency_graph(
         &self,
@@ -84,29 +137,26 @@ impl<'a> DependencyResolver<'a> {
         visited: &mut Vec<String>,
     ) -> Result<(), DependencyResolverError> {
         // Check for circular dependencies during traversal
-        if visited.contains(&package_name.to_string()) {
-            let mut cycle_path = visited.clone();
+        if let Some(cycle_start) = visited.iter().position(|name| name == package_name) {
+            let mut cycle_path = visited[cycle_start..].to_vec();
             cycle_path.push(package_name.to_string());
 
-            return Err(DependencyResolverError::CircularDependency(format!(
-                "Circular dependency detected: {}",
-                visited.join(" -> ") + " -> " + package_name
-            )));
+            return Err(DependencyResolverError::CircularDependency(cycle_path));
+        }
+
+        // Everything visited is above this package, one level each
+        if self
+            .max_depth
+            .is_some_and(|max_depth| visited.len() > max_depth)
+        {
+            let mut chain = visited.clone();
+            chain.push(package_name.to_string());
+
+            return Err(DependencyResolverError::MaxDepthExceeded(chain));
         }
 
         // Get the package
-        let package = self
-            .package_repo
-            .get_package(package_name)
-            .map_err(|e| match e {
-                PackageRepoError::PackageNotFound(name) => {
-                    DependencyResolverError::PackageNotFound(name)
-                }
-                PackageRepoError::MultiplePackagesFound(name) => {
-                    DependencyResolverError::MultiplePackagesFound(name)
-                }
-                other => DependencyResolverError::RepoError(other),
-            })?;
+        let package = self.get_package(package_name, visited)?;
 
         // Get environment configuration for this package
         let env_config = self.config.resolve_environment(&package).map_err(|_| {
@@ -124,23 +174,27 @@ impl<'a> DependencyResolver<'a> {
             graph.add_node(package.clone())?;
         }
 
-        // Process dependencies
-        visited.push(package_name.to_string());
-
-        for dep_name in &env_config.dependencies {
+        // Process dependencies, under their real names, so a package reached
+        // through an alias is still only one node
+        visited.push(package.name.clone());
+
+        // Check dependencies are ordered like install dependencies, so a
+        // cycle through either kind is caught the same way
+        let dependencies = env_config
+            .dependencies()
+            .iter()
+            .filter(|dependency| self.applies(dependency))
+            .map(|dependency| (dependency.name(), false))
+            .chain(
+                env_config
+                    .check_dependencies()
+                    .iter()
+                    .map(|name| (name.as_str(), true)),
+            );
+
+        for (dep_name, is_check_dependency) in dependencies {
             // Get dependency package
-            let dep_package = self
-                .package_repo
-                .get_package(dep_name)
-                .map_err(|e| match e {
-                    PackageRepoError::PackageNotFound(name) => {
-                        DependencyResolverError::PackageNotFound(name)
-                    }
-                    PackageRepoError::MultiplePackagesFound(name) => {
-                        DependencyResolverError::MultiplePackagesFound(name)
-                    }
-                    other => DependencyResolverError::RepoError(other),
-                })?;
+            let dep_package = self.get_package(dep_name, visited)?;
 
             // Add dependency node if not already in the graph
             if !graph
@@ -151,15 +205,61 @@ impl<'a> DependencyResolver<'a> {
             }
 
             // Add dependency relationship
-            graph.add_dependency(&package.name, dep_name)?;
+            if is_check_dependency {
+                graph.add_check_dependency(&package.name, &dep_package.name)?;
+            } else {
+                graph.add_dependency(&package.name, &dep_package.name)?;
+            }
 
             // Recursively process this dependency
             let mut dep_visited = visited.clone();
-            self.build_dependency_graph(graph, dep_name, &mut dep_visited)?;
+            self.build_dependency_graph(graph, &dep_package.name, &mut dep_visited)?;
         }
 
         Ok(())
     }
+
+    /// Whether `dependency` is needed here: in the current environment, and
+    /// without its `unless_command` known to be available
+    fn applies(&self, dependency: &Dependency) -> bool {
+        if !dependency
+            .applies_to_environment(self.config.environment(), self.config.environment_aliases())
+        {
+            return false;
+        }
+
+        match dependency.unless_command() {
+            Some(command) => {
+                self.unless_commands
+                    .borrow_mut()
+                    .insert(command.to_string());
+                !self.available_commands.contains(command)
+            }
+            None => true,
+        }
+    }
+
+    /// Look up `package_name`, reached through the packages in `visited`
+    fn get_package(
+        &self,
+        package_name: &str,
+        visited: &[String],
+    ) -> Result<Package, DependencyResolverError> {
+        self.package_repo
+            .get_package(package_name)
+            .map_err(|e| match e {
+                PackageRepoError::PackageNotFound(name) => {
+                    let mut chain = visited.to_vec();
+                    chain.push(name.clone());
+
+                    DependencyResolverError::PackageNotFound { name, chain }
+                }
+                PackageRepoError::MultiplePackagesFound(name) => {
+                    DependencyResolverError::MultiplePackagesFound(name)
+                }
+                other => DependencyResolverError::RepoError(other),
+            })
+    }
 }
 
 #[cfg(test)]
@@ -250,6 +350,22 @@ environments:
         assert_eq!(packages[1].name, "dep2");
         assert_eq!(packages[2].name, "dep1");
         assert_eq!(packages[3].name, "main-pkg");
+
+        // dep3 is three levels down
+        let resolver = DependencyResolver::new(&package_repo, &config).with_max_depth(Some(3));
+        assert!(resolver.resolve_dependencies("main-pkg").is_ok());
+
+        let resolver = DependencyResolver::new(&package_repo, &config).with_max_depth(Some(2));
+        let err = resolver.resolve_dependencies("main-pkg").unwrap_err();
+        assert!(matches!(
+            &err,
+            DependencyResolverError::MaxDepthExceeded(chain)
+                if chain == &["main-pkg", "dep1", "dep2", "dep3"]
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Dependency chain exceeds the maximum depth: main-pkg -> dep1 -> dep2 -> dep3"
+        );
     }
 
     #[test]
@@ -301,14 +417,111 @@ environments:
 
         assert!(result.is_err());
         match result {
-            Err(DependencyResolverError::GraphError(DependencyGraphError::CircularDependency(
-                _,
-                _,
-            ))) => {
-                // Expected error - the circular dependency was detected in the graph component
+            Err(DependencyResolverError::CircularDependency(cycle)) => {
+                assert_eq!(cycle, ["dep1", "main-pkg", "dep1"]);
             }
-            Err(DependencyResolverError::CircularDependency(_)) => {
-                // Also acceptable - the circular dependency was detected in the resolver itself
+            other => panic!("Expected circular dependency error; got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_check_dependency() {
+        let (mut package_repo, config) = setup_test_environment();
+
+        let main = Package::from_yaml(
+            r#"
+name: main-pkg
+version: 1.0.0
+environments:
+  test-env:
+    install: echo "Installing main-pkg"
+    check: jq --version
+    check_dependencies:
+      - jq
+"#,
+        )
+        .unwrap();
+        let jq = create_test_package("jq", "1.0.0", &[]);
+
+        package_repo.mock_get_package_ok("main-pkg", main);
+        package_repo.mock_get_package_ok("jq", jq);
+
+        let resolver = DependencyResolver::new(&package_repo, &config);
+        let graph = resolver.resolve_graph("main-pkg").unwrap();
+        assert!(graph.is_check_dependency("main-pkg", "jq"));
+
+        let packages = resolver.resolve_dependencies("main-pkg").unwrap();
+        let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["jq", "main-pkg"]);
+    }
+
+    #[test]
+    fn test_resolve_conditional_dependencies() {
+        let (mut package_repo, config) = setup_test_environment();
+
+        // gcc is only for another environment, and make only without cc
+        let main = create_test_package(
+            "main-pkg",
+            "1.0.0",
+            &[
+                "{ name: gcc, when_env: [other-env] }",
+                "{ name: make, when_env: [test-env], unless_command: cc }",
+                "jq",
+            ],
+        );
+        package_repo.mock_get_package_ok("main-pkg", main);
+        package_repo.mock_get_package_ok("make", create_test_package("make", "1.0.0", &[]));
+        package_repo.mock_get_package_ok("jq", create_test_package("jq", "1.0.0", &[]));
+
+        // Dependencies come before the package, in no set order of their own
+        let names = |packages: Vec<Package>| -> Vec<String> {
+            let mut names: Vec<String> = packages.into_iter().map(|package| package.name).collect();
+            let dependencies = names.len() - 1;
+            names[..dependencies].sort();
+            names
+        };
+
+        // Without cc known to be available, make is needed
+        let resolver = DependencyResolver::new(&package_repo, &config);
+        let packages = resolver.resolve_dependencies("main-pkg").unwrap();
+        assert_eq!(names(packages), ["jq", "make", "main-pkg"]);
+        assert_eq!(
+            resolver.unless_commands(),
+            BTreeSet::from(["cc".to_string()])
+        );
+
+        let resolver = DependencyResolver::new(&package_repo, &config)
+            .with_available_commands(HashSet::from(["cc".to_string()]));
+        let packages = resolver.resolve_dependencies("main-pkg").unwrap();
+        assert_eq!(names(packages), ["jq", "main-pkg"]);
+    }
+
+    #[test]
+    fn test_detect_cycle_through_check_dependency() {
+        let (mut package_repo, config) = setup_test_environment();
+
+        // main installs after dep1, whose check needs main
+        let main = create_test_package("main-pkg", "1.0.0", &["dep1"]);
+        let dep1 = Package::from_yaml(
+            r#"
+name: dep1
+version: 1.0.0
+environments:
+  test-env:
+    install: echo "Installing dep1"
+    check_dependencies:
+      - main-pkg
+"#,
+        )
+        .unwrap();
+
+        package_repo.mock_get_package_ok("main-pkg", main);
+        package_repo.mock_get_package_ok("dep1", dep1);
+
+        let resolver = DependencyResolver::new(&package_repo, &config);
+        match resolver.resolve_dependencies("main-pkg") {
+            Err(DependencyResolverError::CircularDependency(cycle)) => {
+                assert_eq!(cycle, ["dep1", "main-pkg", "dep1"]);
             }
             other => panic!("Expected circular dependency error; got {:?}", other),
         }
@@ -333,13 +546,43 @@ environments:
 
         assert!(result.is_err());
         match result {
-            Err(DependencyResolverError::PackageNotFound(name)) => {
+            Err(DependencyResolverError::PackageNotFound { name, chain }) => {
                 assert_eq!(name, "missing-dep");
+                assert_eq!(chain, vec!["main-pkg", "missing-dep"]);
             }
             _ => panic!("Expected package not found error"),
         }
     }
 
+    #[test]
+    fn test_transitive_dependency_not_found() {
+        let (mut package_repo, config) = setup_test_environment();
+
+        package_repo.mock_get_package_ok(
+            "main-pkg",
+            create_test_package("main-pkg", "1.0.0", &["dep1"]),
+        );
+        package_repo
+            .mock_get_package_ok("dep1", create_test_package("dep1", "1.0.0", &["missing"]));
+        package_repo.mock_get_package_err(
+            "missing",
+            PackageRepoError::PackageNotFound("missing".to_string()),
+        );
+
+        let resolver = DependencyResolver::new(&package_repo, &config);
+        let err = resolver.resolve_dependencies("main-pkg").unwrap_err();
+
+        assert!(matches!(
+            &err,
+            DependencyResolverError::PackageNotFound { chain, .. }
+                if chain == &["main-pkg", "dep1", "missing"]
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Package not found: missing (required via main-pkg -> dep1 -> missing)"
+        );
+    }
+
     #[test]
     fn test_environment_not_supported() {
         let (mut package_repo, config) = setup_test_environment();
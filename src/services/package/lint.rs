@@ -0,0 +1,223 @@
+// src/services/package/lint.rs
+// Implementation of the 'selfie package lint' command
+
+use thiserror::Error;
+
+use crate::{
+    domain::{
+        config::AppConfig,
+        validation::{DirectoryValidationResult, ValidationResult},
+    },
+    ports::{
+        command::CommandRunner,
+        filesystem::FileSystem,
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+    services::{
+        command_validator::CommandValidator,
+        package::validate::{common_environments, known_packages, PackageValidator},
+    },
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageLintError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+}
+
+/// One `PASS`/`FAIL` line for a package, then one line per issue. Unlike
+/// `package validate`, a warning fails it too.
+pub(crate) fn format_lint_result(result: &ValidationResult) -> String {
+    if result.issues.is_empty() {
+        return format!("PASS {}", result.package_name);
+    }
+
+    let mut output = format!(
+        "FAIL {} (errors: {}, warnings: {})",
+        result.package_name,
+        result.errors().len(),
+        result.warnings().len()
+    );
+    for issue in result.errors().into_iter().chain(result.warnings()) {
+        let level = if issue.is_warning { "warning" } else { "error" };
+        output.push_str(&format!(
+            "\n  {}: {}: {}",
+            level, issue.field, issue.message
+        ));
+    }
+
+    output
+}
+
+/// One-line count of the packages that passed and failed a lint
+pub(crate) fn format_lint_summary(results: &DirectoryValidationResult) -> String {
+    let failed = results.packages_with_issues().len();
+
+    format!(
+        "Lint: {} passed, {} failed",
+        results.results().len() - failed,
+        failed
+    )
+}
+
+/// Handles the 'package lint' command: validation with every heuristic and
+/// the strict rules on, for package authors and CI
+pub(crate) struct PackageLintService<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> {
+    fs: &'a F,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+    command_validator: &'a CommandValidator<'a, CR>,
+    run_checks: bool,
+}
+
+impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner>
+    PackageLintService<'a, F, PR, CR>
+{
+    /// Create a new lint command handler
+    pub(crate) fn new(
+        fs: &'a F,
+        config: &'a AppConfig,
+        package_repo: &'a PR,
+        command_validator: &'a CommandValidator<'a, CR>,
+    ) -> Self {
+        Self {
+            fs,
+            config,
+            package_repo,
+            command_validator,
+            run_checks: false,
+        }
+    }
+
+    /// Whether to also run each package's check command to see that it
+    /// starts
+    pub(crate) fn with_run_checks(mut self, run_checks: bool) -> Self {
+        self.run_checks = run_checks;
+        self
+    }
+
+    /// Lint `package_names`, or every package when none are given, with the
+    /// results sorted by name. A package that can't be validated at all
+    /// fails with its error as the only issue. Every package in the directory is read either way, to find the
+    /// environments most of them declare and the names install commands may
+    /// mention.
+    pub(crate) async fn lint(
+        &self,
+        package_names: &[String],
+    ) -> Result<DirectoryValidationResult, PackageLintError> {
+        let packages = self.package_repo.list_packages()?;
+        let common_environments = common_environments(&packages);
+        let known_packages = known_packages(&packages);
+
+        // One shell lookup for every command instead of one per command
+        self.command_validator.probe_packages(&packages).await;
+
+        let package_names = if package_names.is_empty() {
+            let mut names: Vec<String> = packages.into_iter().map(|package| package.name).collect();
+            names.sort();
+            names.dedup();
+            names
+        } else {
+            package_names.to_vec()
+        };
+
+        let validator = PackageValidator::new(
+            self.fs,
+            self.config,
+            self.package_repo,
+            self.command_validator,
+        )
+        .with_heuristics(true)
+        .with_strict(true)
+        .with_common_environments(Some(common_environments))
+        .with_known_packages(Some(known_packages))
+        .with_run_checks(self.run_checks);
+
+        let mut results = Vec::with_capacity(package_names.len());
+        for package_name in package_names {
+            let result = validator.validate_package_by_name(&package_name).await;
+            results.push(result.unwrap_or_else(|err| ValidationResult::failed(&package_name, err)));
+        }
+
+        Ok(DirectoryValidationResult::new(results))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{
+        domain::{config::AppConfigBuilder, package::PackageBuilder},
+        ports::{
+            command::MockCommandRunner, filesystem::MockFileSystem,
+            package_repo::MockPackageRepository,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_lint_fails_on_warnings() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_real_paths();
+        fs.mock_read_file(
+            "/test/packages/ripgrep.yaml",
+            "name: ripgrep\nversion: 1.0.0\nhomepage: https://example.com\n\
+             description: Fast grep\nenvironments:\n  test-env:\n    \
+             install: brew install ripgrep\n    check: command -v rg\n",
+        );
+        // Valid, but with a risky install and no check
+        fs.mock_read_file(
+            "/test/packages/rustup.yaml",
+            "name: rustup\nversion: 1.0.0\nhomepage: https://example.com\n\
+             description: Rust installer\nenvironments:\n  test-env:\n    \
+             install: curl -sSf https://sh.rustup.rs | sh\n",
+        );
+
+        let mut repo = MockPackageRepository::new();
+        repo.mock_list_packages(
+            ["rustup", "ripgrep"]
+                .into_iter()
+                .map(|name| {
+                    PackageBuilder::default()
+                        .name(name)
+                        .version("1.0.0")
+                        .environment("test-env", "true")
+                        .build()
+                })
+                .collect(),
+        );
+        repo.expect_find_package_files()
+            .returning(|name| Ok(vec![PathBuf::from(format!("/test/packages/{}.yaml", name))]));
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_probe_commands(&[("true", true)]);
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("command", true);
+        runner.mock_is_command_available("curl", true);
+        let command_validator = CommandValidator::new(&runner);
+
+        let results = PackageLintService::new(&fs, &config, &repo, &command_validator)
+            .lint(&[])
+            .await
+            .unwrap();
+
+        assert_eq!(results.results().len(), 2);
+        assert_eq!(format_lint_result(&results.results()[0]), "PASS ripgrep");
+
+        // Valid, but still failed
+        assert!(results.is_valid());
+        assert_eq!(results.packages_with_issues().len(), 1);
+        assert_eq!(format_lint_summary(&results), "Lint: 1 passed, 1 failed");
+
+        let output = format_lint_result(&results.results()[1]);
+        assert!(output.starts_with("FAIL rustup (errors: 0, warnings: "));
+        assert!(output.contains("\n  warning: environments.test-env.install: Command pipes"));
+        assert!(output.contains("\n  warning: environments.test-env.check: "));
+    }
+}
@@ -2,11 +2,13 @@
 // Enhanced implementation of the 'selfie package list' command with command availability checking
 
 use console::style;
+use jiff::{tz::TimeZone, Timestamp};
 
 use crate::{
     adapters::progress::ProgressManager,
     domain::{config::AppConfig, package::Package},
     ports::command::CommandRunner,
+    ports::filesystem::FileSystem,
     ports::package_repo::{PackageRepoError, PackageRepository},
     services::command_validator::CommandValidator,
 };
@@ -14,57 +16,134 @@ use crate::{
 /// Result of running the list command
 pub(crate) enum PackageListResult {
     /// Package listing successful
-    Success(String),
+    Success(PackageListing),
     /// Command failed to run
     Error(String),
 }
 
+/// What `package list` prints, in order: text, the packages as a table when
+/// they're listed without details, then a note on what was left out
+#[derive(Debug, Default)]
+pub(crate) struct PackageListing {
+    pub(crate) text: String,
+    pub(crate) table: Option<PackageTable>,
+    pub(crate) note: Option<String>,
+}
+
+/// Packages as rows beneath a header row, for [`ProgressManager::print_table`]
+#[derive(Debug)]
+pub(crate) struct PackageTable {
+    pub(crate) headers: &'static [&'static str],
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+impl PackageListing {
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+}
+
 /// Handles the 'package list' command with enhanced command availability checking
-pub(crate) struct PackageListService<'a, CR: CommandRunner, PR: PackageRepository> {
+pub(crate) struct PackageListService<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository> {
+    fs: &'a F,
     runner: &'a CR,
     config: &'a AppConfig,
     progress_manager: ProgressManager,
     package_repo: &'a PR,
+    since: Option<Timestamp>,
+    only_environment_matching: bool,
 }
 
-impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR> {
+impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
+    PackageListService<'a, F, CR, PR>
+{
     /// Create a new list command handler
     pub(crate) fn new(
+        fs: &'a F,
         runner: &'a CR,
         config: &'a AppConfig,
         progress_manager: ProgressManager,
         package_repo: &'a PR,
     ) -> Self {
         Self {
+            fs,
             runner,
             config,
             progress_manager,
             package_repo,
+            since: None,
+            only_environment_matching: false,
         }
     }
 
+    /// Only list packages whose file was modified after `since`
+    pub(crate) fn with_since(mut self, since: Option<Timestamp>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Only list packages that support the current environment, ending with
+    /// a count of the ones left out
+    pub(crate) fn with_only_environment_matching(mut self, only: bool) -> Self {
+        self.only_environment_matching = only;
+        self
+    }
+
     /// Execute the list command
     pub(crate) async fn execute(&self) -> PackageListResult {
         // Get list of packages
         match self.list_packages().await {
             Ok(output) => PackageListResult::Success(output),
             Err(err) => {
-                self.progress_manager.print_error("Failed");
+                self.progress_manager.print_error_detail("Failed");
                 PackageListResult::Error(format!("Error: {}", err))
             }
         }
     }
 
-    /// List packages with compatibility information and command availability
-    async fn list_packages(&self) -> Result<String, PackageRepoError> {
+    /// List packages, leaving out those for other environments if asked to
+    async fn list_packages(&self) -> Result<PackageListing, PackageRepoError> {
         let packages = self.package_repo.list_packages()?;
 
         if packages.is_empty() {
-            return Ok("No packages found in package directory.".to_string());
+            return Ok(PackageListing::text(
+                "No packages found in package directory.",
+            ));
+        }
+
+        if !self.only_environment_matching {
+            return Ok(self.format_packages(packages).await);
+        }
+
+        let (packages, not_applicable) = self.config.partition_by_environment(packages);
+        let mut listing = if packages.is_empty() {
+            PackageListing::text(format!(
+                "No packages support environment '{}'.",
+                self.config.environment()
+            ))
+        } else {
+            self.format_packages(packages).await
+        };
+
+        if !not_applicable.is_empty() {
+            listing.note = Some(format!(
+                "Packages left out for not supporting environment '{}': {}",
+                self.config.environment(),
+                not_applicable.len()
+            ));
         }
 
+        Ok(listing)
+    }
+
+    /// List packages with compatibility information and command availability
+    async fn format_packages(&self, packages: Vec<Package>) -> PackageListing {
         // Create command validator for checking command availability
-        let command_validator = CommandValidator::new(self.runner);
+        let command_validator =
+            CommandValidator::new(self.runner).with_progress_manager(self.progress_manager);
 
         let mut output = String::from("Available packages:\n");
 
@@ -72,38 +151,62 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
         let mut sorted_packages = packages;
         sorted_packages.sort_by(|a, b| a.name.cmp(&b.name));
 
-        for package in sorted_packages {
-            let is_compatible = package.environments.contains_key(self.config.environment());
+        // Pair each package with its file's modification time when filtering
+        // on it, since that's shown too
+        let sorted_packages: Vec<(Package, Option<Timestamp>)> = match self.since {
+            Some(since) => {
+                let changed: Vec<_> = sorted_packages
+                    .into_iter()
+                    .filter_map(|package| {
+                        let modified = self.modified(&package)?;
+                        (modified > since).then_some((package, Some(modified)))
+                    })
+                    .collect();
+
+                if changed.is_empty() {
+                    return PackageListing::text(format!(
+                        "No packages changed since {}.",
+                        format_time(since)
+                    ));
+                }
+
+                changed
+            }
+            None => sorted_packages
+                .into_iter()
+                .map(|package| (package, None))
+                .collect(),
+        };
 
-            // Style the package name and version with color
-            let package_name = if self.config.use_colors() {
-                style(&package.name).magenta().bold().to_string()
-            } else {
-                package.name.clone()
-            };
+        // Without details to show, a table keeps the columns aligned
+        if !self.config.verbose() {
+            let rows: Vec<Vec<String>> = sorted_packages
+                .iter()
+                .map(|(package, modified)| {
+                    let mut row = self.summary_cells(package).to_vec();
+                    if let Some(modified) = modified {
+                        row.push(format_time(*modified));
+                    }
+                    row
+                })
+                .collect();
 
-            let version = if self.config.use_colors() {
-                style(format!("v{}", &package.version)).dim().to_string()
+            let headers: &'static [&'static str] = if self.since.is_some() {
+                &["Name", "Version", "Status", "Modified"]
             } else {
-                format!("v{}", &package.version)
+                &["Name", "Version", "Status"]
             };
 
-            // Style the compatibility message with color
-            let compatibility = if is_compatible {
-                if self.config.use_colors() {
-                    style("Compatible with current environment")
-                        .green()
-                        .to_string()
-                } else {
-                    "Compatible with current environment".to_string()
-                }
-            } else if self.config.use_colors() {
-                style("Not compatible with current environment")
-                    .red()
-                    .to_string()
-            } else {
-                "Not compatible with current environment".to_string()
+            return PackageListing {
+                text: output,
+                table: Some(PackageTable { headers, rows }),
+                note: None,
             };
+        }
+
+        for (package, modified) in sorted_packages {
+            let is_compatible = self.config.resolve_environment(&package).is_ok();
+            let [package_name, version, compatibility] = self.summary_cells(&package);
 
             output.push_str(&format!(
                 "  {} ({}) - {}\n",
@@ -112,12 +215,12 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
 
             // Check command availability for compatible packages
             if is_compatible && self.config.verbose() {
-                if let Some(env_config) = package.environments.get(self.config.environment()) {
+                if let Ok(env_config) = self.config.resolve_environment(&package) {
                     // Extract base command
-                    if let Some(base_cmd) =
-                        CommandValidator::<CR>::extract_base_command(&env_config.install)
-                    {
-                        let cmd_available = self.runner.is_command_available(base_cmd).await;
+                    if let Some(base_cmd) = CommandValidator::<CR>::extract_base_command(
+                        env_config.install.first_command(),
+                    ) {
+                        let cmd_available = command_validator.is_command_available(base_cmd).await;
 
                         let status = if cmd_available {
                             if self.config.use_colors() {
@@ -139,7 +242,7 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
                     // Check for environment-specific recommendations
                     if let Some(recommendation) = command_validator.is_command_recommended_for_env(
                         self.config.environment(),
-                        &env_config.install,
+                        env_config.install.first_command(),
                     ) {
                         let recommendation_text = if self.config.use_colors() {
                             style(format!("    ℹ {}", recommendation))
@@ -167,7 +270,28 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
                     output.push_str(&format!("{}\n", description));
                 }
 
-                if let Some(homepage) = &package.homepage {
+                if let Some(desc) = self
+                    .config
+                    .resolve_environment(&package)
+                    .ok()
+                    .and_then(|env_config| env_config.description.as_ref())
+                {
+                    let description = if self.config.use_colors() {
+                        style(format!("    Environment description: {}", desc))
+                            .blue()
+                            .to_string()
+                    } else {
+                        format!("    Environment description: {}", desc)
+                    };
+                    output.push_str(&format!("{}\n", description));
+                }
+
+                // An environment installing a fork can have its own homepage
+                let homepage = match self.config.resolve_environment(&package) {
+                    Ok(env_config) => package.homepage_for(env_config),
+                    Err(_) => package.homepage(),
+                };
+                if let Some(homepage) = homepage {
                     let homepage_text = if self.config.use_colors() {
                         style(format!("    Homepage: {}", homepage))
                             .blue()
@@ -186,18 +310,19 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
 
                 // Check for potential issues in commands
                 if is_compatible {
-                    if let Some(env_config) = package.environments.get(self.config.environment()) {
+                    if let Ok(env_config) = self.config.resolve_environment(&package) {
                         let mut warnings = Vec::new();
 
-                        if command_validator.might_require_sudo(&env_config.install) {
+                        if command_validator.might_require_sudo(&env_config.install.to_string()) {
                             warnings.push("might require sudo privileges");
                         }
 
-                        if command_validator.uses_backticks(&env_config.install) {
+                        if command_validator.uses_backticks(&env_config.install.to_string()) {
                             warnings.push("uses backticks (consider $() instead)");
                         }
 
-                        if command_validator.might_download_content(&env_config.install) {
+                        if command_validator.might_download_content(&env_config.install.to_string())
+                        {
                             warnings.push("may download content from internet");
                         }
 
@@ -215,6 +340,10 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
                     }
                 }
 
+                if let Some(modified) = modified {
+                    output.push_str(&format!("    Modified: {}\n", format_time(modified)));
+                }
+
                 // Show file path if available
                 let path_text = if self.config.use_colors() {
                     style(format!("    Path: {}", package.path.display()))
@@ -230,7 +359,50 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
             }
         }
 
-        Ok(output)
+        PackageListing::text(output)
+    }
+
+    /// When the package's file was last modified, if that can be read
+    fn modified(&self, package: &Package) -> Option<Timestamp> {
+        let modified = self.fs.modified(&package.path).ok()?;
+        Timestamp::try_from(modified).ok()
+    }
+
+    /// Styled name, version and compatibility cells for a package
+    fn summary_cells(&self, package: &Package) -> [String; 3] {
+        let is_compatible = self.config.resolve_environment(package).is_ok();
+
+        // Style the package name and version with color
+        let package_name = if self.config.use_colors() {
+            style(&package.name).magenta().bold().to_string()
+        } else {
+            package.name.clone()
+        };
+
+        let version = if self.config.use_colors() {
+            style(format!("v{}", &package.version)).dim().to_string()
+        } else {
+            format!("v{}", &package.version)
+        };
+
+        // Style the compatibility message with color
+        let compatibility = if is_compatible {
+            if self.config.use_colors() {
+                style("Compatible with current environment")
+                    .green()
+                    .to_string()
+            } else {
+                "Compatible with current environment".to_string()
+            }
+        } else if self.config.use_colors() {
+            style("Not compatible with current environment")
+                .red()
+                .to_string()
+        } else {
+            "Not compatible with current environment".to_string()
+        };
+
+        [package_name, version, compatibility]
     }
 
     /// Filter packages by various criteria
@@ -290,13 +462,13 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
         // Compatible with current environment
         let compatible: Vec<_> = packages
             .iter()
-            .filter(|pkg| pkg.environments.contains_key(current_env))
+            .filter(|pkg| self.config.resolve_environment(pkg).is_ok())
             .collect();
 
         // Not compatible with current environment
         let incompatible: Vec<_> = packages
             .iter()
-            .filter(|pkg| !pkg.environments.contains_key(current_env))
+            .filter(|pkg| self.config.resolve_environment(pkg).is_err())
             .collect();
 
         // Format section for compatible packages
@@ -383,6 +555,14 @@ impl<'a, CR: CommandRunner, PR: PackageRepository> PackageListService<'a, CR, PR
     }
 }
 
+/// A point in time, in the local time zone, to the minute
+fn format_time(timestamp: Timestamp) -> String {
+    timestamp
+        .to_zoned(TimeZone::system())
+        .strftime("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,6 +573,24 @@ mod tests {
     };
     use std::path::Path;
 
+    /// The listing as `package list` prints it, a line at a time
+    fn rendered(listing: PackageListing, manager: ProgressManager) -> String {
+        let mut output = String::new();
+        if !listing.text.trim_end().is_empty() {
+            output.push_str(&format!("{}\n", listing.text.trim_end()));
+        }
+        if let Some(table) = &listing.table {
+            output.push_str(&format!(
+                "{}\n",
+                manager.format_table(table.headers, &table.rows)
+            ));
+        }
+        if let Some(note) = &listing.note {
+            output.push_str(&format!("{}\n", note));
+        }
+        output
+    }
+
     #[tokio::test]
     async fn test_list_empty_directory() {
         let mut fs = MockFileSystem::default();
@@ -405,6 +603,7 @@ mod tests {
             .build();
 
         fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
         fs.mock_list_directory(&package_dir, &[]);
 
         let manager = ProgressManager::from(&config);
@@ -412,11 +611,10 @@ mod tests {
         let repo = YamlPackageRepository::new(&fs, config.expanded_package_directory(), manager);
         let runner = MockCommandRunner::new();
 
-        let cmd = PackageListService::new(&runner, &config, manager, &repo);
+        let cmd = PackageListService::new(&fs, &runner, &config, manager, &repo);
 
-        let result = cmd.list_packages().await;
-        assert!(result.is_ok());
-        assert!(result.unwrap().contains("No packages found"));
+        let output = rendered(cmd.list_packages().await.unwrap(), manager);
+        assert!(output.contains("No packages found"));
     }
 
     #[tokio::test]
@@ -430,6 +628,7 @@ mod tests {
 
         let package_dir = Path::new("/test/packages");
         fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
 
         // Add test package files to the mock filesystem
         let package1_yaml = r#"
@@ -463,22 +662,86 @@ mod tests {
         runner
             .expect_is_command_available()
             .with(mockall::predicate::eq("brew"))
-            .returning(|_| true);
+            .returning(|_| Ok(true));
 
-        let cmd = PackageListService::new(&runner, &config, manager, &repo);
+        let cmd = PackageListService::new(&fs, &runner, &config, manager, &repo);
 
         // Test the list_packages function with our repo
-        let result = cmd.list_packages().await;
-        assert!(result.is_ok());
-        let output = result.unwrap();
+        let output = rendered(cmd.list_packages().await.unwrap(), manager);
+
+        // Check that both packages are listed, aligned under the header
+        assert!(output.contains("Name     Version  Status\n"));
+        assert!(output.contains("fzf      v0.1.0   Not compatible with current environment\n"));
+        assert!(output.contains("ripgrep  v1.0.0   Compatible with current environment\n"));
+
+        // Packages for other environments can be left out, leaving a count
+        let listing = cmd
+            .with_only_environment_matching(true)
+            .list_packages()
+            .await
+            .unwrap();
+        let output = rendered(listing, manager);
+        assert!(!output.contains("fzf"));
+        assert!(output.contains("ripgrep  v1.0.0   Compatible with current environment\n"));
+        assert!(
+            output.ends_with("Packages left out for not supporting environment 'test-env': 1\n")
+        );
+    }
 
-        // Check that both packages are listed
-        assert!(output.contains("ripgrep (v1.0.0)"));
-        assert!(output.contains("fzf (v0.1.0)"));
+    #[tokio::test]
+    async fn test_list_packages_since() {
+        let mut fs = MockFileSystem::default();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .use_colors(false)
+            .build();
 
-        // Check compatibility information
-        assert!(output.contains("Compatible with current environment"));
-        assert!(output.contains("Not compatible with current environment"));
+        let package_dir = Path::new("/test/packages");
+        fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
+
+        let package1_path = package_dir.join("ripgrep.yaml");
+        let package2_path = package_dir.join("fzf.yaml");
+
+        fs.mock_list_directory(package_dir, &[&package1_path, &package2_path]);
+        fs.mock_read_file(
+            &package1_path,
+            "name: ripgrep\nversion: 1.0.0\nenvironments: {}\n",
+        );
+        fs.mock_read_file(
+            &package2_path,
+            "name: fzf\nversion: 0.1.0\nenvironments: {}\n",
+        );
+
+        let since: Timestamp = "2024-06-01T00:00:00Z".parse().unwrap();
+        let recently: Timestamp = "2024-06-10T00:00:00Z".parse().unwrap();
+        let long_ago: Timestamp = "2024-01-01T00:00:00Z".parse().unwrap();
+        fs.mock_modified(&package1_path, recently.into());
+        fs.mock_modified(&package2_path, long_ago.into());
+
+        let manager = ProgressManager::from(&config);
+        let repo = YamlPackageRepository::new(&fs, config.expanded_package_directory(), manager);
+        let runner = MockCommandRunner::new();
+
+        let cmd =
+            PackageListService::new(&fs, &runner, &config, manager, &repo).with_since(Some(since));
+
+        let output = rendered(cmd.list_packages().await.unwrap(), manager);
+
+        assert!(output.contains("Name     Version  Status"));
+        assert!(output.contains("Modified"));
+        assert!(output.contains(&format!(
+            "ripgrep  v1.0.0   Not compatible with current environment  {}\n",
+            format_time(recently)
+        )));
+        assert!(!output.contains("fzf"));
+
+        // Nothing changed after the newest file
+        let cmd = PackageListService::new(&fs, &runner, &config, manager, &repo)
+            .with_since(Some(recently));
+        let output = rendered(cmd.list_packages().await.unwrap(), manager);
+        assert!(output.starts_with("No packages changed since"));
     }
 
     #[tokio::test]
@@ -493,6 +756,7 @@ mod tests {
 
         let package_dir = Path::new("/test/packages");
         fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
 
         // Add test package with detailed information
         let package_yaml = r#"
@@ -502,6 +766,7 @@ mod tests {
             homepage: https://github.com/BurntSushi/ripgrep
             environments:
               test-env:
+                description: Installed from the distro repositories
                 install: sudo apt install ripgrep
         "#;
 
@@ -518,21 +783,20 @@ mod tests {
         runner
             .expect_is_command_available()
             .with(mockall::predicate::eq("sudo"))
-            .returning(|_| true);
+            .returning(|_| Ok(true));
         runner
             .expect_is_command_available()
             .with(mockall::predicate::eq("apt"))
-            .returning(|_| true);
+            .returning(|_| Ok(true));
 
-        let cmd = PackageListService::new(&runner, &config, manager, &repo);
+        let cmd = PackageListService::new(&fs, &runner, &config, manager, &repo);
 
         // Test the list_packages function with our repo
-        let result = cmd.list_packages().await;
-        assert!(result.is_ok());
-        let output = result.unwrap();
+        let output = rendered(cmd.list_packages().await.unwrap(), manager);
 
         // Check verbose information
         assert!(output.contains("Description: Fast search tool"));
+        assert!(output.contains("Environment description: Installed from the distro repositories"));
         assert!(output.contains("Homepage: https://github.com/BurntSushi/ripgrep"));
         assert!(output.contains("Environments: test-env"));
         assert!(output.contains("Path: /test/packages/ripgrep.yaml"));
@@ -579,7 +843,7 @@ mod tests {
         let manager = ProgressManager::from(&config);
         let repo = YamlPackageRepository::new(&fs, config.expanded_package_directory(), manager);
         let runner = MockCommandRunner::new();
-        let cmd = PackageListService::new(&runner, &config, manager, &repo);
+        let cmd = PackageListService::new(&fs, &runner, &config, manager, &repo);
 
         // Filter by name
         let filtered = cmd.filter_packages(&packages, Some("rip"));
@@ -612,6 +876,7 @@ mod tests {
 
         let package_dir = Path::new("/test/packages");
         fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
 
         // Add test package files with different environments
         let package1_yaml = r#"
@@ -656,7 +921,7 @@ mod tests {
         // Create a repository with our mock filesystem
         let repo = YamlPackageRepository::new(&fs, config.expanded_package_directory(), manager);
         let runner = MockCommandRunner::new();
-        let cmd = PackageListService::new(&runner, &config, manager, &repo);
+        let cmd = PackageListService::new(&fs, &runner, &config, manager, &repo);
 
         // Test grouping by environment
         let result = cmd.list_packages_by_environment();
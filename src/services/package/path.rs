@@ -0,0 +1,73 @@
+// src/services/package/path.rs
+// Implementation of the 'selfie package path' command
+
+use std::path::PathBuf;
+
+use crate::ports::{
+    filesystem::FileSystem,
+    package_repo::{PackageRepoError, PackageRepository},
+};
+
+/// Handles the 'package path' command
+pub(crate) struct PackagePathService<'a, F: FileSystem, PR: PackageRepository> {
+    fs: &'a F,
+    package_repo: &'a PR,
+}
+
+impl<'a, F: FileSystem, PR: PackageRepository> PackagePathService<'a, F, PR> {
+    /// Create a new path command handler
+    pub(crate) fn new(fs: &'a F, package_repo: &'a PR) -> Self {
+        Self { fs, package_repo }
+    }
+
+    /// The absolute path of the file a package is defined in, found by name
+    /// or alias, with symlinks resolved
+    pub(crate) fn execute(&self, package_name: &str) -> Result<PathBuf, PackageRepoError> {
+        let package = self.package_repo.get_package(package_name)?;
+
+        Ok(self.fs.canonicalize(&package.path).unwrap_or(package.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::package::PackageBuilder,
+        ports::{filesystem::MockFileSystem, package_repo::MockPackageRepository},
+    };
+
+    #[test]
+    fn test_package_path() {
+        let mut repo = MockPackageRepository::default();
+        repo.mock_get_package_ok(
+            "rg",
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .path("/test/packages/ripgrep.yaml")
+                .build(),
+        );
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_symlink("/test/packages/ripgrep.yaml", "/dotfiles/ripgrep.yaml");
+
+        let path = PackagePathService::new(&fs, &repo).execute("rg").unwrap();
+
+        assert_eq!(path, PathBuf::from("/dotfiles/ripgrep.yaml"));
+    }
+
+    #[test]
+    fn test_package_path_not_found() {
+        let mut repo = MockPackageRepository::default();
+        repo.mock_get_package_err(
+            "missing",
+            PackageRepoError::PackageNotFound("missing".to_string()),
+        );
+
+        let fs = MockFileSystem::default();
+        let result = PackagePathService::new(&fs, &repo).execute("missing");
+
+        assert!(matches!(result, Err(PackageRepoError::PackageNotFound(_))));
+    }
+}
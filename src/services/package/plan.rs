@@ -0,0 +1,162 @@
+// src/services/package/plan.rs
+// Implementation of the 'selfie package plan' command
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    domain::{application::commands::PlanFormat, config::AppConfig, package::InstallCommand},
+    ports::package_repo::PackageRepository,
+    services::package::install::dependency::{DependencyResolver, DependencyResolverError},
+};
+
+/// One package in an install plan
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct PlanStep {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) environment: String,
+    pub(crate) install: InstallCommand,
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum PackagePlanError {
+    #[error(transparent)]
+    Resolve(#[from] DependencyResolverError),
+
+    #[error("Failed to serialize plan as YAML: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Failed to serialize plan as JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Handles the 'package plan' command
+pub(crate) struct PackagePlanService<'a, PR: PackageRepository> {
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+}
+
+impl<'a, PR: PackageRepository> PackagePlanService<'a, PR> {
+    /// Create a new plan command handler
+    pub(crate) fn new(config: &'a AppConfig, package_repo: &'a PR) -> Self {
+        Self {
+            config,
+            package_repo,
+        }
+    }
+
+    /// Resolve the packages that installing `package_name` would install, in
+    /// order, without running anything
+    pub(crate) fn steps(&self, package_name: &str) -> Result<Vec<PlanStep>, PackagePlanError> {
+        let packages = DependencyResolver::new(self.package_repo, self.config)
+            .resolve_dependencies(package_name)?;
+
+        packages
+            .into_iter()
+            .map(|package| {
+                let env_config = self.config.resolve_environment(&package).map_err(|_| {
+                    DependencyResolverError::EnvironmentNotSupported(
+                        self.config.environment().to_string(),
+                        package.name.clone(),
+                    )
+                })?;
+
+                Ok(PlanStep {
+                    install: env_config.install.clone(),
+                    name: package.name,
+                    version: package.version,
+                    environment: self.config.environment().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Render the plan for `package_name` in the given format
+    pub(crate) fn execute(
+        &self,
+        package_name: &str,
+        format: PlanFormat,
+    ) -> Result<String, PackagePlanError> {
+        let steps = self.steps(package_name)?;
+
+        Ok(match format {
+            PlanFormat::Yaml => serde_yaml::to_string(&steps)?,
+            PlanFormat::Json => serde_json::to_string_pretty(&steps)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{config::AppConfigBuilder, package::PackageBuilder},
+        ports::package_repo::MockPackageRepository,
+    };
+
+    fn create_repo() -> MockPackageRepository {
+        let mut repo = MockPackageRepository::new();
+        repo.mock_get_package_ok(
+            "ripgrep",
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "cargo install ripgrep", vec!["rust"])
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "rust",
+            PackageBuilder::default()
+                .name("rust")
+                .version("0.2.0")
+                .environment("test-env", "curl https://sh.rustup.rs | sh")
+                .build(),
+        );
+        repo
+    }
+
+    #[test]
+    fn test_plan_yaml() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let repo = create_repo();
+
+        let output = PackagePlanService::new(&config, &repo)
+            .execute("ripgrep", PlanFormat::Yaml)
+            .unwrap();
+
+        assert_eq!(
+            output,
+            "- name: rust
+  version: 0.2.0
+  environment: test-env
+  install: curl https://sh.rustup.rs | sh
+- name: ripgrep
+  version: 1.0.0
+  environment: test-env
+  install: cargo install ripgrep
+"
+        );
+    }
+
+    #[test]
+    fn test_plan_json() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let repo = create_repo();
+
+        let output = PackagePlanService::new(&config, &repo)
+            .execute("ripgrep", PlanFormat::Json)
+            .unwrap();
+        let steps: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(steps[0]["name"], "rust");
+        assert_eq!(steps[1]["name"], "ripgrep");
+        assert_eq!(steps[1]["install"], "cargo install ripgrep");
+    }
+}
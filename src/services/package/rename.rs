@@ -0,0 +1,453 @@
+// src/services/package/rename.rs
+// Implementation of the 'selfie package rename' command
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use serde_yaml::Value;
+use thiserror::Error;
+
+use crate::{
+    adapters::{package_repo::yaml::COMBINED_PACKAGES_FILE, progress::ProgressManager},
+    domain::package::Package,
+    ports::{
+        filesystem::{FileSystem, FileSystemError},
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageRenameError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error("Package not found: {0}")]
+    PackageNotFound(String),
+
+    #[error("Package '{0}' already exists")]
+    AlreadyExists(String),
+
+    #[error(
+        "Invalid package name '{0}'; use only alphanumeric characters, hyphens, and underscores"
+    )]
+    InvalidName(String),
+
+    #[error("Package '{0}' is defined in {COMBINED_PACKAGES_FILE}; rename it there by hand")]
+    InCombinedFile(String),
+
+    #[error("Multiple package files found for '{0}'")]
+    MultiplePackageFiles(String),
+
+    #[error("File system error: {0}")]
+    FileSystem(#[from] FileSystemError),
+
+    #[error("Failed to update {}: {source}", .path.display())]
+    Yaml {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+}
+
+/// A package file that depends on the renamed package, with its dependency
+/// entries updated
+#[derive(Debug)]
+pub(crate) struct DependentEdit {
+    pub(crate) path: PathBuf,
+    original: String,
+    content: String,
+
+    /// Dependency lists that mention the package, e.g.
+    /// `environments.macos.dependencies`
+    pub(crate) fields: Vec<String>,
+}
+
+/// Everything a rename changes, worked out up front so it can be shown before
+/// it's applied
+#[derive(Debug)]
+pub(crate) struct RenamePlan {
+    pub(crate) old_name: String,
+    pub(crate) new_name: String,
+    pub(crate) from: PathBuf,
+    pub(crate) to: PathBuf,
+
+    /// The renamed package's file as it is, and with its new name
+    original: String,
+    content: String,
+
+    pub(crate) dependents: Vec<DependentEdit>,
+}
+
+impl RenamePlan {
+    /// The files the rename changes, one per line
+    pub(crate) fn summary(&self) -> String {
+        let mut summary = format!(
+            "Renaming '{}' to '{}':\n  • {} -> {}",
+            self.old_name,
+            self.new_name,
+            self.from.display(),
+            self.to.display()
+        );
+
+        for dependent in &self.dependents {
+            for field in &dependent.fields {
+                summary.push_str(&format!(
+                    "\n  • {}: {}: {} -> {}",
+                    dependent.path.display(),
+                    field,
+                    self.old_name,
+                    self.new_name
+                ));
+            }
+        }
+
+        summary
+    }
+
+    /// A diff of each file the rename changes, the renamed package's first
+    pub(crate) fn diff(&self, progress_manager: &ProgressManager) -> String {
+        let mut diffs =
+            vec![progress_manager.format_diff(&self.from, &self.to, &self.original, &self.content)];
+
+        for dependent in &self.dependents {
+            diffs.push(progress_manager.format_diff(
+                &dependent.path,
+                &dependent.path,
+                &dependent.original,
+                &dependent.content,
+            ));
+        }
+
+        diffs.join("\n")
+    }
+}
+
+/// Handles the 'package rename' command
+pub(crate) struct PackageRenameService<'a, F: FileSystem, PR: PackageRepository> {
+    fs: &'a F,
+    package_repo: &'a PR,
+}
+
+impl<'a, F: FileSystem, PR: PackageRepository> PackageRenameService<'a, F, PR> {
+    /// Create a new rename command handler
+    pub(crate) fn new(fs: &'a F, package_repo: &'a PR) -> Self {
+        Self { fs, package_repo }
+    }
+
+    /// Work out the changes renaming `old_name` to `new_name` makes, without
+    /// writing anything
+    pub(crate) fn plan(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<RenamePlan, PackageRenameError> {
+        if !Package::is_valid_package_name(new_name) {
+            return Err(PackageRenameError::InvalidName(new_name.to_string()));
+        }
+
+        let mut paths = self.package_repo.find_package_files(old_name)?;
+        let from = match paths.len() {
+            0 => return Err(PackageRenameError::PackageNotFound(old_name.to_string())),
+            1 => paths.remove(0),
+            _ => {
+                return Err(PackageRenameError::MultiplePackageFiles(
+                    old_name.to_string(),
+                ))
+            }
+        };
+
+        if is_combined_file(&from) {
+            return Err(PackageRenameError::InCombinedFile(old_name.to_string()));
+        }
+
+        // Keep the whole extension, which may be more than one part
+        let extension = from
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.strip_prefix(old_name))
+            .unwrap_or(".yaml");
+        let to = from.with_file_name(format!("{}{}", new_name, extension));
+
+        if !self.package_repo.find_package_files(new_name)?.is_empty() || self.fs.path_exists(&to) {
+            return Err(PackageRenameError::AlreadyExists(new_name.to_string()));
+        }
+
+        // Edit the files' YAML rather than parsed packages, so fields selfie
+        // doesn't know about are kept
+        let (original, mut document) = self.read_yaml(&from)?;
+        if let Value::Mapping(fields) = &mut document {
+            fields.insert(Value::from("name"), Value::from(new_name));
+        }
+        let content = to_yaml(&from, &document)?;
+
+        Ok(RenamePlan {
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+            dependents: self.dependent_edits(old_name, new_name)?,
+            from,
+            to,
+            original,
+            content,
+        })
+    }
+
+    /// Write the changes in `plan`: the renamed package's file first, then
+    /// the packages that depend on it
+    pub(crate) fn apply(&self, plan: &RenamePlan) -> Result<(), PackageRenameError> {
+        self.fs.write_file(&plan.from, &plan.content)?;
+        self.fs.rename_file(&plan.from, &plan.to)?;
+
+        for dependent in &plan.dependents {
+            self.fs.write_file(&dependent.path, &dependent.content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Update every package file with a dependency on `old_name`
+    fn dependent_edits(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<Vec<DependentEdit>, PackageRenameError> {
+        // Packages in the combined file share it, so only edit each file once
+        let paths: BTreeSet<PathBuf> = self
+            .package_repo
+            .list_packages()?
+            .into_iter()
+            .filter(|package| {
+                package.name != old_name
+                    && package
+                        .environments
+                        .values()
+                        .any(|env| env.all_dependencies().any(|dep| dep == old_name))
+            })
+            .map(|package| package.path)
+            .collect();
+
+        let mut edits = Vec::new();
+        for path in paths {
+            let (original, mut document) = self.read_yaml(&path)?;
+            let mut fields = Vec::new();
+
+            if is_combined_file(&path) {
+                if let Value::Mapping(packages) = &mut document {
+                    for (name, body) in packages.iter_mut() {
+                        let prefix = format!("{}.", name.as_str().unwrap_or_default());
+                        rename_dependency(body, old_name, new_name, &prefix, &mut fields);
+                    }
+                }
+            } else {
+                rename_dependency(&mut document, old_name, new_name, "", &mut fields);
+            }
+
+            if !fields.is_empty() {
+                edits.push(DependentEdit {
+                    original,
+                    content: to_yaml(&path, &document)?,
+                    path,
+                    fields,
+                });
+            }
+        }
+
+        Ok(edits)
+    }
+
+    /// A file's contents, and the YAML document they hold
+    fn read_yaml(&self, path: &Path) -> Result<(String, Value), PackageRenameError> {
+        let content = self.fs.read_file(path)?;
+
+        let document =
+            serde_yaml::from_str(&content).map_err(|source| PackageRenameError::Yaml {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        Ok((content, document))
+    }
+}
+
+fn is_combined_file(path: &Path) -> bool {
+    path.file_name() == Some(COMBINED_PACKAGES_FILE.as_ref())
+}
+
+fn to_yaml(path: &Path, document: &Value) -> Result<String, PackageRenameError> {
+    serde_yaml::to_string(document).map_err(|source| PackageRenameError::Yaml {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Replace `old_name` with `new_name` in a package body's install and check
+/// dependency lists, recording the fields that changed
+fn rename_dependency(
+    package: &mut Value,
+    old_name: &str,
+    new_name: &str,
+    prefix: &str,
+    fields: &mut Vec<String>,
+) {
+    let Some(environments) = package
+        .get_mut("environments")
+        .and_then(Value::as_mapping_mut)
+    else {
+        return;
+    };
+
+    for (env_name, env_config) in environments.iter_mut() {
+        for key in ["dependencies", "check_dependencies"] {
+            let Some(dependencies) = env_config.get_mut(key).and_then(Value::as_sequence_mut)
+            else {
+                continue;
+            };
+
+            let mut changed = false;
+            for dependency in dependencies.iter_mut() {
+                // Either the name itself, or a conditional dependency's `name`
+                let name = match dependency.get_mut("name") {
+                    Some(name) => name,
+                    None => dependency,
+                };
+                if name.as_str() == Some(old_name) {
+                    *name = Value::from(new_name);
+                    changed = true;
+                }
+            }
+
+            if changed {
+                fields.push(format!(
+                    "{}environments.{}.{}",
+                    prefix,
+                    env_name.as_str().unwrap_or_default(),
+                    key
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::package::PackageBuilder,
+        ports::{filesystem::MockFileSystem, package_repo::MockPackageRepository},
+    };
+
+    fn create_repo() -> MockPackageRepository {
+        let mut repo = MockPackageRepository::new();
+        repo.expect_find_package_files()
+            .returning(|name| match name {
+                "rg" => Ok(vec![PathBuf::from("/test/packages/rg.yaml")]),
+                "fd" => Ok(vec![PathBuf::from("/test/packages/fd.yml")]),
+                _ => Ok(vec![]),
+            });
+        repo.mock_list_packages(vec![
+            PackageBuilder::default()
+                .name("rg")
+                .version("1.0.0")
+                .environment("test-env", "cargo install ripgrep")
+                .path("/test/packages/rg.yaml")
+                .build(),
+            PackageBuilder::default()
+                .name("search-tools")
+                .version("1.0.0")
+                .environment_with_dependencies("test-env", "true", vec!["rg", "fd"])
+                .path("/test/packages/search-tools.yaml")
+                .build(),
+            PackageBuilder::default()
+                .name("fd")
+                .version("1.0.0")
+                .environment("test-env", "cargo install fd-find")
+                .path("/test/packages/fd.yml")
+                .build(),
+        ]);
+        repo
+    }
+
+    #[test]
+    fn test_rename_package() {
+        let repo = create_repo();
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists("/test/packages/ripgrep.yaml", false);
+        fs.mock_read_file(
+            "/test/packages/rg.yaml",
+            "name: rg\nversion: 1.0.0\nx-owner: me\nenvironments:\n  test-env:\n    install: cargo install ripgrep\n",
+        );
+        fs.mock_read_file(
+            "/test/packages/search-tools.yaml",
+            "name: search-tools\nversion: 1.0.0\nenvironments:\n  test-env:\n    install: 'true'\n    dependencies:\n    - rg\n    - fd\n",
+        );
+
+        let service = PackageRenameService::new(&fs, &repo);
+        let plan = service.plan("rg", "ripgrep").unwrap();
+
+        assert_eq!(
+            plan.summary(),
+            "Renaming 'rg' to 'ripgrep':
+  • /test/packages/rg.yaml -> /test/packages/ripgrep.yaml
+  • /test/packages/search-tools.yaml: environments.test-env.dependencies: rg -> ripgrep"
+        );
+
+        let diff = plan.diff(&ProgressManager::default());
+        assert!(diff.starts_with(
+            "--- /test/packages/rg.yaml\n+++ /test/packages/ripgrep.yaml\n@@ -1,4 +1,4 @@\n-name: rg\n+name: ripgrep\n"
+        ));
+        assert!(diff.contains(
+            "--- /test/packages/search-tools.yaml\n+++ /test/packages/search-tools.yaml\n"
+        ));
+        assert!(diff.contains("\n-    - rg\n+    - ripgrep\n     - fd"));
+
+        let mut seq = mockall::Sequence::new();
+        fs.expect_write_file()
+            .withf(|path, content| {
+                path == Path::new("/test/packages/rg.yaml")
+                    && content.starts_with("name: ripgrep\n")
+                    && content.contains("x-owner: me\n")
+            })
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        fs.expect_rename_file()
+            .withf(|from, to| {
+                from == Path::new("/test/packages/rg.yaml")
+                    && to == Path::new("/test/packages/ripgrep.yaml")
+            })
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        fs.expect_write_file()
+            .withf(|path, content| {
+                path == Path::new("/test/packages/search-tools.yaml")
+                    && content.contains("    - ripgrep\n    - fd\n")
+            })
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+
+        PackageRenameService::new(&fs, &repo).apply(&plan).unwrap();
+    }
+
+    #[test]
+    fn test_rename_refuses_existing_package() {
+        let repo = create_repo();
+        let fs = MockFileSystem::default();
+        let service = PackageRenameService::new(&fs, &repo);
+
+        assert!(matches!(
+            service.plan("rg", "fd"),
+            Err(PackageRenameError::AlreadyExists(name)) if name == "fd"
+        ));
+        assert!(matches!(
+            service.plan("bat", "batcat"),
+            Err(PackageRenameError::PackageNotFound(_))
+        ));
+        assert!(matches!(
+            service.plan("rg", "rip grep"),
+            Err(PackageRenameError::InvalidName(_))
+        ));
+    }
+}
@@ -0,0 +1,330 @@
+// src/services/package/status.rs
+// Implementation of the 'selfie package status' command
+
+use console::style;
+
+use crate::{
+    domain::{
+        config::AppConfig,
+        install_state::InstalledPackage,
+        installation::{Installation, InstallationError},
+        package::Package,
+    },
+    ports::{
+        command::CommandRunner,
+        filesystem::FileSystem,
+        install_state::{InstallStateError, InstallStateStore},
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+/// How an installed package compares with what was recorded when selfie
+/// installed it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PackageDrift {
+    /// Still installed, from an unchanged package file
+    UpToDate,
+
+    /// No check configured for the current environment, so the installation
+    /// can't be verified
+    Unverified,
+
+    /// The package file's version has changed since the install
+    VersionChanged,
+
+    /// The check command no longer passes
+    Removed,
+
+    /// Running the check command failed
+    CheckFailed(String),
+
+    /// The package file can no longer be found
+    DefinitionMissing,
+
+    /// The package file can't be loaded
+    DefinitionInvalid(String),
+}
+
+impl PackageDrift {
+    /// Whether the installation no longer matches the recorded state
+    pub(crate) fn is_drift(&self) -> bool {
+        !matches!(self, Self::UpToDate | Self::Unverified)
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::UpToDate => "Up to date".to_string(),
+            Self::Unverified => "Installed (no check)".to_string(),
+            Self::VersionChanged => "Version changed since install".to_string(),
+            Self::Removed => "No longer installed".to_string(),
+            Self::CheckFailed(err) => format!("Check failed: {}", err),
+            Self::DefinitionMissing => "Package file removed".to_string(),
+            Self::DefinitionInvalid(err) => format!("Package file invalid: {}", err),
+        }
+    }
+}
+
+/// Output of the status command
+pub(crate) struct PackageStatusReport {
+    /// A row per recorded installation, beneath [`Self::HEADERS`]; empty
+    /// when selfie hasn't installed anything yet
+    pub(crate) rows: Vec<Vec<String>>,
+
+    /// Whether any installed package has drifted from the recorded state
+    pub(crate) has_drift: bool,
+}
+
+impl PackageStatusReport {
+    pub(crate) const HEADERS: &'static [&'static str] =
+        &["Name", "Installed", "Current", "Installed at", "Status"];
+}
+
+/// Handles the 'package status' command
+pub(crate) struct PackageStatusService<
+    'a,
+    F: FileSystem,
+    CR: CommandRunner,
+    PR: PackageRepository,
+    S: InstallStateStore,
+> {
+    fs: &'a F,
+    runner: &'a CR,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+    state_store: &'a S,
+}
+
+impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository, S: InstallStateStore>
+    PackageStatusService<'a, F, CR, PR, S>
+{
+    /// Create a new status command handler
+    pub(crate) fn new(
+        fs: &'a F,
+        runner: &'a CR,
+        config: &'a AppConfig,
+        package_repo: &'a PR,
+        state_store: &'a S,
+    ) -> Self {
+        Self {
+            fs,
+            runner,
+            config,
+            package_repo,
+            state_store,
+        }
+    }
+
+    /// Compare every recorded installation against the package files and the
+    /// current check command results
+    pub(crate) async fn execute(&self) -> Result<PackageStatusReport, InstallStateError> {
+        let state = self.state_store.load()?;
+
+        if state.is_empty() {
+            return Ok(PackageStatusReport {
+                rows: Vec::new(),
+                has_drift: false,
+            });
+        }
+
+        let mut rows = Vec::new();
+        let mut has_drift = false;
+
+        for (name, installed) in &state.packages {
+            let (current_version, drift) = match self.package_repo.get_package(name) {
+                Ok(package) => {
+                    let drift = self.package_drift(&package, installed).await;
+                    (format!("v{}", package.version), drift)
+                }
+                Err(PackageRepoError::PackageNotFound(_)) => {
+                    ("-".to_string(), PackageDrift::DefinitionMissing)
+                }
+                Err(err) => (
+                    "-".to_string(),
+                    PackageDrift::DefinitionInvalid(err.to_string()),
+                ),
+            };
+
+            has_drift |= drift.is_drift();
+
+            rows.push(vec![
+                name.clone(),
+                format!("v{}", installed.version),
+                current_version,
+                installed
+                    .installed_at
+                    .strftime("%Y-%m-%d %H:%M")
+                    .to_string(),
+                self.style_drift(&drift),
+            ]);
+        }
+
+        Ok(PackageStatusReport { rows, has_drift })
+    }
+
+    async fn package_drift(&self, package: &Package, installed: &InstalledPackage) -> PackageDrift {
+        let check_result = match self
+            .config
+            .resolve_environment(package)
+            .ok()
+            .filter(|env_config| env_config.has_check())
+        {
+            Some(env_config) => {
+                Installation::new(env_config.clone())
+                    .start()
+                    .execute_check(self.fs, self.runner, self.config.check_timeout(), |_| {})
+                    .await
+            }
+            None => return self.version_drift(package, installed, PackageDrift::Unverified),
+        };
+
+        match check_result {
+            Ok(Installation::AlreadyInstalled { .. }) => {
+                self.version_drift(package, installed, PackageDrift::UpToDate)
+            }
+            Ok(Installation::Failed { error_message, .. }) => {
+                PackageDrift::CheckFailed(error_message)
+            }
+            Ok(Installation::CheckTimedOut { timeout, .. }) => {
+                PackageDrift::CheckFailed(InstallationError::CheckTimedOut(timeout).to_string())
+            }
+            Ok(_) => PackageDrift::Removed,
+            Err(err) => PackageDrift::CheckFailed(err.to_string()),
+        }
+    }
+
+    /// `otherwise`, unless the package file's version has changed
+    fn version_drift(
+        &self,
+        package: &Package,
+        installed: &InstalledPackage,
+        otherwise: PackageDrift,
+    ) -> PackageDrift {
+        if package.version == installed.version {
+            otherwise
+        } else {
+            PackageDrift::VersionChanged
+        }
+    }
+
+    fn style_drift(&self, drift: &PackageDrift) -> String {
+        let text = drift.describe();
+
+        if !self.config.use_colors() {
+            return text;
+        }
+
+        match drift {
+            PackageDrift::UpToDate => style(text).green().to_string(),
+            PackageDrift::Unverified => style(text).dim().to_string(),
+            PackageDrift::VersionChanged => style(text).yellow().to_string(),
+            _ => style(text).red().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        adapters::progress::ProgressManager,
+        domain::{config::AppConfigBuilder, install_state::InstallState, package::PackageBuilder},
+        ports::{
+            command::MockCommandRunner, filesystem::MockFileSystem,
+            install_state::MockInstallStateStore, package_repo::MockPackageRepository,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_status_no_state() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let fs = MockFileSystem::new();
+        let runner = MockCommandRunner::new();
+        let repo = MockPackageRepository::new();
+        let mut store = MockInstallStateStore::new();
+        store.mock_load_ok(InstallState::default());
+
+        let report = PackageStatusService::new(&fs, &runner, &config, &repo, &store)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(!report.has_drift);
+        assert!(report.rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_drift() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .use_colors(false)
+            .build();
+
+        let mut state = InstallState::default();
+        state.record("fd", "1.0.0", "test-env");
+        state.record("gone", "1.0.0", "test-env");
+        state.record("ripgrep", "1.0.0", "test-env");
+        state.record("tokei", "1.0.0", "test-env");
+
+        let mut store = MockInstallStateStore::new();
+        store.mock_load_ok(state);
+
+        let mut repo = MockPackageRepository::new();
+        repo.mock_get_package_ok(
+            "fd",
+            PackageBuilder::default()
+                .name("fd")
+                .version("1.0.0")
+                .environment_with_check("test-env", "cargo install fd-find", "which fd")
+                .build(),
+        );
+        repo.mock_get_package_err(
+            "gone",
+            PackageRepoError::PackageNotFound("gone".to_string()),
+        );
+        repo.mock_get_package_ok(
+            "ripgrep",
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.1.0")
+                .environment_with_check("test-env", "cargo install ripgrep", "which rg")
+                .build(),
+        );
+        repo.mock_get_package_ok(
+            "tokei",
+            PackageBuilder::default()
+                .name("tokei")
+                .version("1.0.0")
+                .environment_with_check("test-env", "cargo install tokei", "which tokei")
+                .build(),
+        );
+
+        let fs = MockFileSystem::new();
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_streaming_success_0("which fd", 15, "/usr/bin/fd");
+        runner.mock_execute_streaming_success_0("which rg", 15, "/usr/bin/rg");
+        runner.mock_execute_streaming_success_1("which tokei", 15, "");
+
+        let report = PackageStatusService::new(&fs, &runner, &config, &repo, &store)
+            .execute()
+            .await
+            .unwrap();
+
+        assert!(report.has_drift);
+
+        let table =
+            ProgressManager::from(&config).format_table(PackageStatusReport::HEADERS, &report.rows);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[1].starts_with("fd       v1.0.0     v1.0.0"));
+        assert!(lines[1].ends_with("Up to date"));
+        assert!(lines[2].starts_with("gone     v1.0.0     -"));
+        assert!(lines[2].ends_with("Package file removed"));
+        assert!(lines[3].starts_with("ripgrep  v1.0.0     v1.1.0"));
+        assert!(lines[3].ends_with("Version changed since install"));
+        assert!(lines[4].ends_with("No longer installed"));
+    }
+}
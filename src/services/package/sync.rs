@@ -0,0 +1,299 @@
+// src/services/package/sync.rs
+// Implementation of the 'selfie sync' command
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::{
+    domain::{config::AppConfig, package::Package},
+    ports::package_repo::{PackageRepoError, PackageRepository},
+    services::package::plan::{PackagePlanError, PackagePlanService, PlanStep},
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageSyncError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error(transparent)]
+    Plan(#[from] PackagePlanError),
+}
+
+/// Packages a sync selected by tag and exclusion, split by whether they
+/// support the current environment. Each list is sorted by name.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SyncTargets {
+    /// Packages that support the current environment, which get synced
+    pub(crate) applicable: Vec<String>,
+
+    /// Packages left out for not supporting the current environment
+    pub(crate) not_applicable: Vec<String>,
+}
+
+/// Which packages a sync selects and how it reports on them
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SyncOptions {
+    /// Show what would be checked and installed, without installing
+    pub(crate) dry_run: bool,
+
+    /// Only sync packages with one of these tags; every package when empty
+    pub(crate) tags: Vec<String>,
+
+    /// Names of packages to leave out
+    pub(crate) exclude: Vec<String>,
+
+    /// Only count the packages left out for not supporting the current
+    /// environment, instead of naming each one
+    pub(crate) only_environment_matching: bool,
+
+    /// Write the closing summary as JSON
+    pub(crate) json: bool,
+
+    /// Only sync the packages listed in this file
+    pub(crate) packages_from: Option<PathBuf>,
+}
+
+/// Handles working out what the 'sync' command installs
+pub(crate) struct PackageSyncService<'a, PR: PackageRepository> {
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+
+    /// Real names of the only packages to consider, e.g. from a package list
+    listed: Option<Vec<String>>,
+}
+
+impl<'a, PR: PackageRepository> PackageSyncService<'a, PR> {
+    /// Create a new sync command handler
+    pub(crate) fn new(config: &'a AppConfig, package_repo: &'a PR) -> Self {
+        Self {
+            config,
+            package_repo,
+            listed: None,
+        }
+    }
+
+    /// Only consider the packages named `listed`, before tags and exclusions
+    /// narrow them down further
+    pub(crate) fn with_listed(mut self, listed: Vec<String>) -> Self {
+        self.listed = Some(listed);
+        self
+    }
+
+    /// Every package with one of `tags` (when any are given), except the
+    /// `exclude`d ones; only those supporting the current environment are
+    /// synced
+    pub(crate) fn targets(
+        &self,
+        tags: &[String],
+        exclude: &[String],
+    ) -> Result<SyncTargets, PackageSyncError> {
+        let selected: Vec<Package> = self
+            .package_repo
+            .list_packages()?
+            .into_iter()
+            .filter(|package| {
+                self.listed
+                    .as_ref()
+                    .is_none_or(|listed| listed.contains(&package.name))
+            })
+            .filter(|package| tags.is_empty() || package.tags.iter().any(|tag| tags.contains(tag)))
+            .filter(|package| !exclude.contains(&package.name))
+            .collect();
+
+        let (applicable, not_applicable) = self.config.partition_by_environment(selected);
+        let names = |packages: Vec<Package>| {
+            let mut names: Vec<String> = packages.into_iter().map(|package| package.name).collect();
+            names.sort();
+            names.dedup();
+            names
+        };
+
+        Ok(SyncTargets {
+            applicable: names(applicable),
+            not_applicable: names(not_applicable),
+        })
+    }
+
+    /// Every package syncing `targets` would check and possibly install, in
+    /// install order, with dependencies shared between targets listed once
+    pub(crate) fn plan(&self, targets: &[String]) -> Result<Vec<PlanStep>, PackageSyncError> {
+        let planner = PackagePlanService::new(self.config, self.package_repo);
+
+        let mut plan: Vec<PlanStep> = Vec::new();
+        for target in targets {
+            for step in planner.steps(target)? {
+                // Each target's own plan has its dependencies first, so
+                // keeping the first occurrence keeps the order valid
+                if !plan.iter().any(|planned| planned.name == step.name) {
+                    plan.push(step);
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Excluded packages that `targets` still depend on, each with the first
+    /// target that needs it. These are installed as dependencies anyway;
+    /// excluding a package only stops it being a target itself.
+    pub(crate) fn excluded_dependencies(
+        &self,
+        targets: &[String],
+        exclude: &[String],
+    ) -> Result<Vec<(String, String)>, PackageSyncError> {
+        let planner = PackagePlanService::new(self.config, self.package_repo);
+
+        let mut needed: Vec<(String, String)> = Vec::new();
+        for target in targets {
+            for step in planner.steps(target)? {
+                if exclude.contains(&step.name)
+                    && !needed.iter().any(|(name, _)| *name == step.name)
+                {
+                    needed.push((step.name, target.clone()));
+                }
+            }
+        }
+
+        Ok(needed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{config::AppConfigBuilder, package::PackageBuilder},
+        ports::package_repo::MockPackageRepository,
+    };
+
+    fn create_repo() -> MockPackageRepository {
+        let packages = vec![
+            PackageBuilder::default()
+                .name("ripgrep")
+                .version("1.0.0")
+                .tags(&["cli"])
+                .environment_with_dependencies("test-env", "cargo install ripgrep", vec!["rust"])
+                .build(),
+            PackageBuilder::default()
+                .name("fd")
+                .version("1.0.0")
+                .tags(&["cli"])
+                .environment_with_dependencies("test-env", "cargo install fd-find", vec!["rust"])
+                .build(),
+            PackageBuilder::default()
+                .name("rust")
+                .version("1.0.0")
+                .tags(&["lang"])
+                .environment("test-env", "rustup-init")
+                .build(),
+            PackageBuilder::default()
+                .name("mas")
+                .version("1.0.0")
+                .tags(&["cli"])
+                .environment("macos", "brew install mas")
+                .build(),
+        ];
+
+        let mut repo = MockPackageRepository::new();
+        for package in &packages {
+            repo.mock_get_package_ok(&package.name, package.clone());
+        }
+        repo.mock_list_packages(packages);
+        repo
+    }
+
+    #[test]
+    fn test_sync_targets_and_plan() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let repo = create_repo();
+        let service = PackageSyncService::new(&config, &repo);
+
+        // Packages for other environments are never synced, only counted
+        assert_eq!(
+            service.targets(&[], &[]).unwrap(),
+            SyncTargets {
+                applicable: vec!["fd".into(), "ripgrep".into(), "rust".into()],
+                not_applicable: vec!["mas".into()],
+            }
+        );
+        assert_eq!(
+            service
+                .targets(&["cli".to_string()], &["fd".to_string(), "mas".to_string()])
+                .unwrap(),
+            SyncTargets {
+                applicable: vec!["ripgrep".into()],
+                not_applicable: Vec::new(),
+            }
+        );
+
+        let targets = service
+            .targets(&["cli".to_string()], &[])
+            .unwrap()
+            .applicable;
+        let plan: Vec<String> = service
+            .plan(&targets)
+            .unwrap()
+            .into_iter()
+            .map(|step| step.name)
+            .collect();
+        assert_eq!(plan, vec!["rust", "fd", "ripgrep"]);
+    }
+
+    #[test]
+    fn test_sync_listed_targets() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let repo = create_repo();
+        let service = PackageSyncService::new(&config, &repo).with_listed(vec![
+            "ripgrep".into(),
+            "rust".into(),
+            "mas".into(),
+        ]);
+
+        // Tags and exclusions still apply to what's listed
+        assert_eq!(
+            service.targets(&[], &[]).unwrap(),
+            SyncTargets {
+                applicable: vec!["ripgrep".into(), "rust".into()],
+                not_applicable: vec!["mas".into()],
+            }
+        );
+        assert_eq!(
+            service.targets(&["cli".to_string()], &[]).unwrap(),
+            SyncTargets {
+                applicable: vec!["ripgrep".into()],
+                not_applicable: vec!["mas".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_sync_excluded_dependencies() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+        let repo = create_repo();
+        let service = PackageSyncService::new(&config, &repo);
+
+        // Excluding every user of a dependency prunes it from a tag's plan
+        let exclude = vec!["fd".to_string(), "ripgrep".to_string()];
+        let targets = service.targets(&["cli".to_string()], &exclude).unwrap();
+        assert!(targets.applicable.is_empty());
+
+        // Excluding a dependency keeps it for the packages that need it
+        let exclude = vec!["rust".to_string()];
+        let targets = service.targets(&[], &exclude).unwrap().applicable;
+        assert_eq!(targets, vec!["fd", "ripgrep"]);
+        assert_eq!(
+            service.excluded_dependencies(&targets, &exclude).unwrap(),
+            vec![("rust".to_string(), "fd".to_string())]
+        );
+    }
+}
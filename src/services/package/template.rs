@@ -0,0 +1,368 @@
+// src/services/package/template.rs
+// Implementation of the 'selfie package template' command
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::{
+    adapters::progress::ProgressManager,
+    domain::{
+        config::AppConfig,
+        package::{EnvironmentConfig, Package},
+    },
+    ports::{
+        filesystem::{FileSystem, FileSystemError},
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+/// Comments written above each field of a template, by nesting depth and
+/// key. Depth 0 is the package's own fields, 2 an environment's.
+const FIELD_COMMENTS: [(usize, &str, &str); 18] = [
+    (0, "name", "Name of the package; must match the file name"),
+    (
+        0,
+        "version",
+        "Version of this package file, not of what it installs",
+    ),
+    (0, "homepage", "Optional: the project's homepage URL"),
+    (0, "description", "Optional: what the package is"),
+    (
+        0,
+        "environments",
+        "How to install the package, per environment",
+    ),
+    (2, "description", "Optional: notes on this environment"),
+    (
+        2,
+        "install",
+        "Command that installs the package, or a list of commands run in order",
+    ),
+    (
+        2,
+        "check",
+        "Optional: command that exits 0 when the package is already installed",
+    ),
+    (
+        2,
+        "check_file",
+        "Optional: file whose existence means it's installed, without a check",
+    ),
+    (
+        2,
+        "check_path_command",
+        "Optional: binary whose presence on PATH means it's installed",
+    ),
+    (
+        2,
+        "check_timeout",
+        "Optional: seconds the check may run, instead of the configured check_timeout",
+    ),
+    (
+        2,
+        "check_inverted",
+        "Whether a passing check means it still needs installing, e.g. to remove something",
+    ),
+    (2, "dependencies", "Packages to install first"),
+    (
+        2,
+        "check_dependencies",
+        "Packages the check uses, installed before it runs",
+    ),
+    (
+        2,
+        "check_implies_dependencies",
+        "Whether a passing check means the dependencies are installed too",
+    ),
+    (2, "stdin", "Optional: input piped to the install command"),
+    (
+        2,
+        "skip_if",
+        "Optional: command that skips the package on machines where it exits 0",
+    ),
+    (
+        2,
+        "installed_version_command",
+        "Optional: command that prints the installed version, for `check-updates`",
+    ),
+];
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageTemplateError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error(
+        "Invalid package name '{0}'; use only alphanumeric characters, hyphens, and underscores"
+    )]
+    InvalidName(String),
+
+    #[error("{} already exists (pass --force to overwrite it)", .0.display())]
+    AlreadyExists(PathBuf),
+
+    #[error("Multiple package files found for '{0}'")]
+    MultiplePackageFiles(String),
+
+    #[error("Failed to write package file: {0}")]
+    FileSystem(#[from] FileSystemError),
+
+    #[error("Failed to serialize package: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// Handles the 'package template' command
+pub(crate) struct PackageTemplateService<'a, F: FileSystem, PR: PackageRepository> {
+    fs: &'a F,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+    force: bool,
+}
+
+impl<'a, F: FileSystem, PR: PackageRepository> PackageTemplateService<'a, F, PR> {
+    /// Create a new template command handler
+    pub(crate) fn new(fs: &'a F, config: &'a AppConfig, package_repo: &'a PR) -> Self {
+        Self {
+            fs,
+            config,
+            package_repo,
+            force: false,
+        }
+    }
+
+    /// Whether to overwrite the package's existing file
+    pub(crate) fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Write a template for `package_name` to the package directory,
+    /// returning the file's path
+    pub(crate) fn execute(&self, package_name: &str) -> Result<PathBuf, PackageTemplateError> {
+        let path = self.path(package_name)?;
+        let template = self.template(package_name)?;
+
+        // The package directory may not have been created yet
+        if let Some(parent) = path.parent() {
+            if !self.fs.path_exists(parent) {
+                self.fs.create_dir_all(parent)?;
+            }
+        }
+        self.fs.write_file(&path, &template)?;
+
+        Ok(path)
+    }
+
+    /// A diff of the package's file against the template that would replace
+    /// it, without writing anything
+    pub(crate) fn diff(
+        &self,
+        package_name: &str,
+        progress_manager: &ProgressManager,
+    ) -> Result<String, PackageTemplateError> {
+        let path = self.path(package_name)?;
+        let existing = if self.fs.path_exists(&path) {
+            self.fs.read_file(&path)?
+        } else {
+            String::new()
+        };
+
+        Ok(progress_manager.format_diff(&path, &path, &existing, &self.template(package_name)?))
+    }
+
+    /// Where the template for `package_name` goes: its existing file, when
+    /// overwriting it, or a new one
+    fn path(&self, package_name: &str) -> Result<PathBuf, PackageTemplateError> {
+        if !Package::is_valid_package_name(package_name) {
+            return Err(PackageTemplateError::InvalidName(package_name.to_string()));
+        }
+
+        let mut paths = self.package_repo.find_package_files(package_name)?;
+        match paths.len() {
+            0 => {
+                // Use the first extension, so the new file is found first
+                let extension = self
+                    .config
+                    .package_file_extensions()
+                    .first()
+                    .map_or("yaml", |ext| ext.trim_start_matches('.'));
+                Ok(self
+                    .config
+                    .expanded_package_directory()
+                    .join(format!("{}.{}", package_name, extension)))
+            }
+            1 if self.force => Ok(paths.remove(0)),
+            1 => Err(PackageTemplateError::AlreadyExists(paths.remove(0))),
+            _ => Err(PackageTemplateError::MultiplePackageFiles(
+                package_name.to_string(),
+            )),
+        }
+    }
+
+    /// A package for the current environment with placeholder commands,
+    /// serialized so it has every field the package schema does, with a
+    /// comment on each
+    pub(crate) fn template(&self, package_name: &str) -> Result<String, PackageTemplateError> {
+        let env_config = EnvironmentConfig::new(format!("TODO: install {}", package_name))
+            .with_check(&format!("command -v {}", package_name));
+        let package = Package::new(
+            package_name.to_string(),
+            "0.1.0".to_string(),
+            None,
+            None,
+            [(self.config.environment().to_string(), env_config)].into(),
+            PathBuf::new(),
+        );
+
+        let yaml = serde_yaml::to_string(&package)?;
+
+        let mut template = format!(
+            "# Package file for '{}'. Replace the TODO install command, then run\n\
+             # `selfie package validate {}` to check it.\n",
+            package_name, package_name
+        );
+        for line in yaml.lines() {
+            let content = line.trim_start();
+            let depth = (line.len() - content.len()) / 2;
+
+            if depth == 1 {
+                template.push_str("  # Environment name, as set in selfie's config\n");
+            } else if let Some((_, _, comment)) = FIELD_COMMENTS.iter().find(|(d, key, _)| {
+                *d == depth
+                    && content
+                        .strip_prefix(key)
+                        .is_some_and(|rest| rest.starts_with(':'))
+            }) {
+                template.push_str(&format!(
+                    "{}# {}\n",
+                    &line[..line.len() - content.len()],
+                    comment
+                ));
+            }
+
+            template.push_str(line);
+            template.push('\n');
+        }
+
+        Ok(template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        domain::config::AppConfigBuilder,
+        ports::{filesystem::MockFileSystem, package_repo::MockPackageRepository},
+    };
+
+    fn create_config() -> AppConfig {
+        AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build()
+    }
+
+    #[test]
+    fn test_template_matches_package_schema() {
+        let config = create_config();
+        let fs = MockFileSystem::default();
+        let repo = MockPackageRepository::new();
+
+        let template = PackageTemplateService::new(&fs, &config, &repo)
+            .template("ripgrep")
+            .unwrap();
+
+        assert!(
+            template.contains("# Name of the package; must match the file name\nname: ripgrep\n")
+        );
+        assert!(template.contains("  test-env:\n    # Optional: notes on this environment\n"));
+
+        let package: Package = serde_yaml::from_str(&template).unwrap();
+        assert_eq!(package.name, "ripgrep");
+        assert!(package.environments["test-env"].has_check());
+        assert!(package
+            .validate("test-env", &[], false)
+            .iter()
+            .all(|issue| issue.is_warning));
+    }
+
+    #[test]
+    fn test_template_refuses_to_overwrite() {
+        let config = create_config();
+
+        let mut repo = MockPackageRepository::new();
+        repo.expect_find_package_files()
+            .returning(|_| Ok(vec![PathBuf::from("/test/packages/ripgrep.yml")]));
+
+        let fs = MockFileSystem::default();
+        assert!(matches!(
+            PackageTemplateService::new(&fs, &config, &repo).execute("ripgrep"),
+            Err(PackageTemplateError::AlreadyExists(_))
+        ));
+
+        // --force overwrites the existing file, whatever its extension
+        let mut fs = MockFileSystem::default();
+        fs.expect_write_file()
+            .withf(|path, content| {
+                path == Path::new("/test/packages/ripgrep.yml")
+                    && content.contains("name: ripgrep\n")
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        fs.mock_path_exists("/test/packages/ripgrep.yml", true);
+        fs.mock_path_exists("/test/packages", true);
+        fs.mock_read_file(
+            "/test/packages/ripgrep.yml",
+            "name: ripgrep\nversion: 1.0.0\n",
+        );
+
+        let service = PackageTemplateService::new(&fs, &config, &repo).with_force(true);
+
+        // The diff is against the existing file, and writes nothing
+        let diff = service
+            .diff("ripgrep", &ProgressManager::default())
+            .unwrap();
+        assert!(
+            diff.starts_with("--- /test/packages/ripgrep.yml\n+++ /test/packages/ripgrep.yml\n")
+        );
+        assert!(diff.contains("\n-version: 1.0.0\n"));
+        assert!(diff.contains("\n+version: 0.1.0\n"));
+
+        let path = service.execute("ripgrep").unwrap();
+        assert_eq!(path, Path::new("/test/packages/ripgrep.yml"));
+    }
+
+    #[test]
+    fn test_template_creates_package_directory() {
+        let config = create_config();
+
+        let mut repo = MockPackageRepository::new();
+        repo.expect_find_package_files()
+            .returning(|_| Ok(Vec::new()));
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_path_exists("/test/packages", false);
+        fs.expect_create_dir_all()
+            .withf(|path| path == Path::new("/test/packages"))
+            .times(1)
+            .returning(|_| Ok(()));
+        let written = fs.mock_write_file_recording();
+
+        let path = PackageTemplateService::new(&fs, &config, &repo)
+            .execute("ripgrep")
+            .unwrap();
+
+        assert_eq!(path, Path::new("/test/packages/ripgrep.yaml"));
+        let written = written.lock().unwrap();
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].0, path);
+        assert!(written[0].1.contains(
+            "name: ripgrep
+"
+        ));
+    }
+}
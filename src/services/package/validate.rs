@@ -1,13 +1,17 @@
 // src/services/package/validate.rs
-use std::path::Path;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
 
 use thiserror::Error;
 
 use crate::{
+    adapters::package_repo::yaml::{package_name_of, COMBINED_PACKAGES_FILE},
     domain::{
-        config::AppConfig,
-        package::{Package, PackageParseError},
-        validation::{ValidationErrorCategory, ValidationIssue, ValidationResult},
+        config::{AppConfig, NameSource, NoCheckPolicy},
+        package::{EnvironmentConfig, Package, PackageParseError},
+        validation::{AutoFix, ValidationErrorCategory, ValidationIssue, ValidationResult},
     },
     ports::{
         command::CommandRunner,
@@ -18,7 +22,7 @@ use crate::{
 };
 
 #[derive(Error, Debug)]
-pub(crate) enum PackageValidatorError {
+pub enum PackageValidatorError {
     #[error("Package not found: {0}")]
     PackageNotFound(String),
 
@@ -47,6 +51,12 @@ pub(crate) struct PackageValidator<'a, F: FileSystem, PR: PackageRepository, CR:
     config: &'a AppConfig,
     package_repo: &'a PR,
     command_validator: &'a CommandValidator<'a, CR>,
+    heuristics: bool,
+    strict: bool,
+    common_environments: Option<BTreeSet<String>>,
+    known_packages: Option<HashMap<String, String>>,
+    run_checks: bool,
+    all_environments: bool,
 }
 
 impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidator<'a, F, PR, CR> {
@@ -62,9 +72,66 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
             config,
             package_repo,
             command_validator,
+            heuristics: true,
+            strict: false,
+            common_environments: None,
+            known_packages: None,
+            run_checks: false,
+            all_environments: false,
         }
     }
 
+    /// Whether to run checks that guess at mistakes and may have false
+    /// positives
+    pub(crate) fn with_heuristics(mut self, heuristics: bool) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Whether to also flag what's allowed but best avoided in a published
+    /// package, such as an environment without a check
+    pub(crate) fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Warn about packages that declare none of `common_environments`, the
+    /// environments most packages in the directory declare (see
+    /// [`common_environments`])
+    pub(crate) fn with_common_environments(
+        mut self,
+        common_environments: Option<BTreeSet<String>>,
+    ) -> Self {
+        self.common_environments = common_environments;
+        self
+    }
+
+    /// Warn about install commands that mention a package from
+    /// `known_packages` (see [`known_packages`]) that isn't declared as a
+    /// dependency. Only done with heuristics on.
+    pub(crate) fn with_known_packages(
+        mut self,
+        known_packages: Option<HashMap<String, String>>,
+    ) -> Self {
+        self.known_packages = known_packages;
+        self
+    }
+
+    /// Whether to run the current environment's check command to see that
+    /// it starts. Install commands are never run.
+    pub(crate) fn with_run_checks(mut self, run_checks: bool) -> Self {
+        self.run_checks = run_checks;
+        self
+    }
+
+    /// Whether to check command availability, recommendations and
+    /// dependencies for every environment a package declares instead of
+    /// only the current one
+    pub(crate) fn with_all_environments(mut self, all_environments: bool) -> Self {
+        self.all_environments = all_environments;
+        self
+    }
+
     /// Validate a package by name
     pub(crate) async fn validate_package_by_name(
         &self,
@@ -89,6 +156,12 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
         }
 
         let package_path = &package_files[0];
+        if package_path.file_name() == Some(COMBINED_PACKAGES_FILE.as_ref()) {
+            // The combined file holds other packages too, so validate only this one
+            let package = self.package_repo.get_package(package_name)?;
+            return Ok(self.validate_package(package_path, Ok(package)).await);
+        }
+
         self.validate_package_file(package_path).await
     }
 
@@ -104,8 +177,47 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
             .map_err(PackageValidatorError::FileSystemError)?;
 
         // Try to parse the package, but continue even if it fails
-        let package = Package::from_yaml(&file_content);
+        let mut package = Package::from_yaml(&file_content);
+
+        // With names taken from file names, the `name` field is only checked
+        let mut name_mismatch = None;
+        if let (Ok(pkg), NameSource::Filename) = (&mut package, self.config.name_source()) {
+            if let Some(file_name) =
+                package_name_of(package_path, self.config.package_file_extensions())
+            {
+                if pkg.name != file_name {
+                    name_mismatch = Some(
+                        ValidationIssue::warning(
+                            ValidationErrorCategory::InvalidValue,
+                            "name",
+                            &format!(
+                                "Name '{}' doesn't match the file name; '{}' is used instead",
+                                pkg.name, file_name
+                            ),
+                            None,
+                            Some("Rename the package or its file so the two agree."),
+                        )
+                        .with_fix(AutoFix::set("name", file_name)),
+                    );
+                    pkg.name = file_name.to_string();
+                }
+            }
+        }
+
+        let mut result = self.validate_package(package_path, package).await;
+        if let Some(issue) = name_mismatch {
+            result.add_issue(issue);
+        }
 
+        Ok(result)
+    }
+
+    /// Validate an already-parsed package, reporting a parse failure as an issue
+    async fn validate_package(
+        &self,
+        package_path: &Path,
+        package: Result<Package, PackageParseError>,
+    ) -> ValidationResult {
         // Get the package name either from the parsed package or the file name
         let package_name = match &package {
             Ok(pkg) => pkg.name.clone(),
@@ -116,13 +228,28 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
                 .to_string(),
         };
 
-        let mut result = ValidationResult::new(&package_name).with_path(package_path.to_path_buf());
+        // Report the file a symlink in the package directory points to
+        let real_path = self
+            .fs
+            .canonicalize(package_path)
+            .unwrap_or_else(|_| package_path.to_path_buf());
+        let mut result = ValidationResult::new(&package_name).with_path(real_path);
 
         // If parsing failed, add the parse error and return early
         match package {
             Ok(pkg) => {
-                // Start with domain validation
-                let domain_issues = pkg.validate(self.config.environment());
+                // Start with domain validation. With every environment selected,
+                // there's no current one to look for.
+                let current_env = if self.all_environments {
+                    ""
+                } else {
+                    self.config.environment()
+                };
+                let domain_issues = pkg.validate(
+                    current_env,
+                    self.config.environment_aliases(),
+                    self.config.strict(),
+                );
                 result.add_issues(domain_issues);
 
                 // Run the enhanced validation which now includes command validation
@@ -143,7 +270,7 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
             }
         }
 
-        Ok(result)
+        result
     }
 
     /// Enhanced validation that includes command validation
@@ -151,11 +278,188 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
         // Add command availability checks
         self.validate_command_availability(package, result).await;
 
-        // Add command syntax validation
-        self.validate_command_syntax(package, result);
+        if self.run_checks {
+            self.validate_check_runs(package, result).await;
+        }
+
+        // Add warnings for risky commands (syntax is already covered by the domain checks)
+        self.validate_command_risks(package, result);
 
         // Add environment-specific recommendations
         self.validate_environment_recommendations(package, result);
+
+        // Make sure dependencies can be found
+        self.validate_dependencies_exist(package, result);
+
+        self.validate_aliases(package, result);
+
+        self.validate_check_policy(package, result);
+
+        self.validate_common_environments(package, result);
+
+        if self.heuristics {
+            self.validate_check_matches_install(package, result);
+            self.validate_undeclared_dependencies(package, result);
+        }
+    }
+
+    /// Environments to check: every declared one with `--all-environments`,
+    /// otherwise only the current one
+    fn target_environments<'p>(
+        &self,
+        package: &'p Package,
+    ) -> Vec<(&'p str, &'p EnvironmentConfig)> {
+        if self.all_environments {
+            let mut environments: Vec<_> = package
+                .environments
+                .iter()
+                .map(|(name, env_config)| (name.as_str(), env_config))
+                .collect();
+            environments.sort_by(|a, b| a.0.cmp(b.0));
+            environments
+        } else {
+            // The current environment may go by an alias in the package
+            self.config.find_environment(package).into_iter().collect()
+        }
+    }
+
+    /// Validate that the target environments' install and check dependencies
+    /// exist in the package directory
+    fn validate_dependencies_exist(&self, package: &Package, result: &mut ValidationResult) {
+        for (env_name, env_config) in self.target_environments(package) {
+            let dependencies = env_config
+                .dependency_names()
+                .map(|dep| ("dependencies", dep))
+                .chain(
+                    env_config
+                        .check_dependencies()
+                        .iter()
+                        .map(|dep| ("check_dependencies", dep.as_str())),
+                );
+
+            for (key, dependency) in dependencies {
+                let field = format!("environments.{}.{}", env_name, key);
+
+                match self.package_repo.package_exists(dependency) {
+                    Ok(true) => {}
+                    Ok(false) => result.add_issue(
+                        ValidationIssue::warning(
+                            ValidationErrorCategory::Availability,
+                            &field,
+                            &format!("Dependency '{}' was not found", dependency),
+                            None,
+                            Some(
+                                "Create a package file for the dependency or remove it from the list.",
+                            ),
+                        )
+                        .escalated_if(self.config.strict()),
+                    ),
+                    Err(err) => result.add_issue(ValidationIssue::warning(
+                        ValidationErrorCategory::Availability,
+                        &field,
+                        &format!("Could not look up dependency '{}': {}", dependency, err),
+                        None,
+                        None,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Warn about aliases that can't be used to reach the package: one that's
+    /// another package's name always means that package instead
+    fn validate_aliases(&self, package: &Package, result: &mut ValidationResult) {
+        for alias in &package.aliases {
+            match self.package_repo.get_package(alias) {
+                Ok(other) if other.name != package.name => {
+                    result.add_issue(ValidationIssue::warning(
+                        ValidationErrorCategory::InvalidValue,
+                        "aliases",
+                        &format!("Alias '{}' is the name of another package", alias),
+                        None,
+                        Some("Remove the alias; the other package's name takes precedence."),
+                    ))
+                }
+                Err(err @ PackageRepoError::AliasCollision { .. }) => {
+                    result.add_issue(ValidationIssue::warning(
+                        ValidationErrorCategory::InvalidValue,
+                        "aliases",
+                        &err.to_string(),
+                        None,
+                        Some("Remove the alias from one of the packages."),
+                    ))
+                }
+                // Its own alias, or the package isn't in the package directory
+                _ => {}
+            }
+        }
+    }
+
+    /// With `no_check_policy: never`, every target environment needs a check;
+    /// when strict, a missing one is still worth a warning
+    fn validate_check_policy(&self, package: &Package, result: &mut ValidationResult) {
+        let required = self.config.no_check_policy() == NoCheckPolicy::Never;
+        if !required && !self.strict {
+            return;
+        }
+
+        let suggestion = Some("Add a check, check_file or check_path_command to the environment.");
+        for (env_name, env_config) in self.target_environments(package) {
+            if env_config.has_check() {
+                continue;
+            }
+
+            let field = format!("environments.{}.check", env_name);
+            result.add_issue(if required {
+                ValidationIssue::error(
+                    ValidationErrorCategory::RequiredField,
+                    &field,
+                    "A check is required when no_check_policy is 'never'",
+                    None,
+                    suggestion,
+                )
+            } else {
+                ValidationIssue::warning(
+                    ValidationErrorCategory::RequiredField,
+                    &field,
+                    "Environment has no check, so selfie can't tell whether it's installed",
+                    None,
+                    suggestion,
+                )
+            });
+        }
+    }
+
+    /// Warn when none of the package's environments are ones most packages
+    /// declare, e.g. only `freebsd` when the rest are `macos` and `ubuntu`
+    fn validate_common_environments(&self, package: &Package, result: &mut ValidationResult) {
+        let Some(common) = &self.common_environments else {
+            return;
+        };
+
+        if common.is_empty()
+            || package
+                .environments
+                .keys()
+                .any(|env_name| common.contains(&env_name.to_lowercase()))
+        {
+            return;
+        }
+
+        let mut declared: Vec<&str> = package.environments.keys().map(String::as_str).collect();
+        declared.sort();
+
+        result.add_issue(ValidationIssue::warning(
+            ValidationErrorCategory::Environment,
+            "environments",
+            &format!(
+                "None of the package's environments ({}) are ones most packages use ({})",
+                declared.join(", "),
+                common.iter().cloned().collect::<Vec<_>>().join(", ")
+            ),
+            None,
+            Some("Check the environment names for typos, or add one of the common environments."),
+        ));
     }
 
     /// Validate command availability
@@ -164,91 +468,82 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
         package: &Package,
         result: &mut ValidationResult,
     ) {
-        // We only check commands for the current environment
-        if let Some(env_config) = package.environments.get(self.config.environment()) {
-            // Extract base command from install command
-            if let Some(base_cmd) =
-                CommandValidator::<CR>::extract_base_command(&env_config.install)
-            {
+        for (env_name, env_config) in self.target_environments(package) {
+            let commands = [
+                ("install", Some(env_config.install.first_command())),
+                ("check", env_config.check()),
+                ("shell", env_config.shell().map(|shell| shell.program())),
+            ];
+
+            for (field, command) in commands {
+                let Some(base_cmd) = command.and_then(CommandValidator::<CR>::extract_base_command)
+                else {
+                    continue;
+                };
+
                 let availability_result = self
                     .command_validator
-                    .check_command_availability(self.config.environment(), base_cmd)
+                    .check_command_availability(env_name, base_cmd)
                     .await;
 
                 if !availability_result.is_available {
+                    let mut message = availability_result.error.unwrap_or_default();
+
+                    // This machine only runs one of the environments being checked
+                    if self.all_environments {
+                        message.push_str(&format!(
+                            " This is expected if this machine isn't set up as '{}'.",
+                            env_name
+                        ));
+                    }
+
                     result.add_issue(ValidationIssue::warning(
                         ValidationErrorCategory::Availability,
-                        &format!("environments.{}.install", self.config.environment()),
-                        &availability_result.error.unwrap_or_default(),
+                        &format!("environments.{}.{}", env_name, field),
+                        &message,
                         None,
                         Some("Install the command before using this package."),
                     ));
                 }
             }
-
-            // Check command if present
-            if let Some(check_cmd) = &env_config.check {
-                if let Some(base_cmd) = CommandValidator::<CR>::extract_base_command(check_cmd) {
-                    let availability_result = self
-                        .command_validator
-                        .check_command_availability(self.config.environment(), base_cmd)
-                        .await;
-
-                    if !availability_result.is_available {
-                        result.add_issue(ValidationIssue::warning(
-                            ValidationErrorCategory::Availability,
-                            &format!("environments.{}.check", self.config.environment()),
-                            &availability_result.error.unwrap_or_default(),
-                            None,
-                            Some("Install the command before using this package."),
-                        ));
-                    }
-                }
-            }
         }
     }
 
-    /// Validate command syntax
-    fn validate_command_syntax(&self, package: &Package, result: &mut ValidationResult) {
-        for (env_name, env_config) in &package.environments {
-            // Validate install command
-            let install_field = &format!("environments.{}.install", env_name);
-            let install_validation = self
-                .command_validator
-                .validate_command_syntax(env_name, &env_config.install);
+    /// Run the check command of the current environment, reporting one that
+    /// doesn't start. Other environments' checks are skipped, even with
+    /// `--all-environments`, since they're not expected to work here.
+    async fn validate_check_runs(&self, package: &Package, result: &mut ValidationResult) {
+        if self.all_environments {
+            return;
+        }
 
-            if !install_validation.is_valid {
+        for (env_name, env_config) in self.target_environments(package) {
+            let Some(check) = env_config.check() else {
+                continue;
+            };
+            let timeout = env_config
+                .check_timeout()
+                .unwrap_or_else(|| self.config.check_timeout());
+
+            if let Some(message) = self.command_validator.run_check(check, timeout).await {
                 result.add_issue(ValidationIssue::error(
                     ValidationErrorCategory::CommandSyntax,
-                    install_field,
-                    &install_validation.error.unwrap_or_default(),
+                    &format!("environments.{}.check", env_name),
+                    &message,
                     None,
-                    Some("Check the command syntax and fix any issues."),
+                    Some("Fix the check command so the shell can run it."),
                 ));
             }
+        }
+    }
 
-            // Validate check command if present
-            if let Some(check_cmd) = &env_config.check {
-                let check_field = &format!("environments.{}.check", env_name);
-                let check_validation = self
-                    .command_validator
-                    .validate_command_syntax(env_name, check_cmd);
-
-                if !check_validation.is_valid {
-                    result.add_issue(ValidationIssue::error(
-                        ValidationErrorCategory::CommandSyntax,
-                        check_field,
-                        &check_validation.error.unwrap_or_default(),
-                        None,
-                        Some("Check the command syntax and fix any issues."),
-                    ));
-                }
-            }
-
+    /// Warn about commands that need privileges or reach out to the network
+    fn validate_command_risks(&self, package: &Package, result: &mut ValidationResult) {
+        for (env_name, env_config) in &package.environments {
             // Add warnings for potential issues detected by CommandValidator
             if self
                 .command_validator
-                .might_require_sudo(&env_config.install)
+                .might_require_sudo(&env_config.install.to_string())
             {
                 result.add_issue(ValidationIssue::warning(
                     ValidationErrorCategory::CommandSyntax,
@@ -259,19 +554,22 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
                 ));
             }
 
-            if self.command_validator.uses_backticks(&env_config.install) {
+            if self
+                .command_validator
+                .pipes_download_into_shell(&env_config.install.to_string())
+            {
                 result.add_issue(ValidationIssue::warning(
                     ValidationErrorCategory::CommandSyntax,
                     &format!("environments.{}.install", env_name),
-                    "Command uses backticks for command substitution",
+                    "Command pipes downloaded content directly into a shell",
                     None,
-                    Some("Consider using $() for command substitution instead of backticks."),
+                    Some(
+                        "Download the script to a file, verify it (e.g. against a checksum), then run it.",
+                    ),
                 ));
-            }
-
-            if self
+            } else if self
                 .command_validator
-                .might_download_content(&env_config.install)
+                .might_download_content(&env_config.install.to_string())
             {
                 result.add_issue(ValidationIssue::warning(
                     ValidationErrorCategory::CommandSyntax,
@@ -286,21 +584,97 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
         }
     }
 
+    /// Warn when a check command looks for a program the install command
+    /// doesn't seem to install, which usually means it was copied from
+    /// another package
+    fn validate_check_matches_install(&self, package: &Package, result: &mut ValidationResult) {
+        for (env_name, env_config) in self.target_environments(package) {
+            let Some(check) = env_config.check() else {
+                continue;
+            };
+
+            if self
+                .command_validator
+                .check_matches_install(&env_config.install.to_string(), check)
+            {
+                continue;
+            }
+
+            let target = CommandValidator::<CR>::check_target(check).unwrap_or_default();
+            result.add_issue(ValidationIssue::warning(
+                ValidationErrorCategory::Environment,
+                &format!("environments.{}.check", env_name),
+                &format!(
+                    "Check command looks for '{}', which the install command doesn't mention",
+                    target
+                ),
+                None,
+                Some(
+                    "Make sure the check tests for what this environment installs. Pass --no-heuristics to skip this warning.",
+                ),
+            ));
+        }
+    }
+
+    /// Warn when an install command mentions another package by name or
+    /// alias without depending on it, e.g. `brew install ripgrep` when
+    /// `brew` is a package too: a dependency that should be declared so it's
+    /// installed first
+    fn validate_undeclared_dependencies(&self, package: &Package, result: &mut ValidationResult) {
+        let Some(known) = &self.known_packages else {
+            return;
+        };
+
+        for (env_name, env_config) in self.target_environments(package) {
+            // A dependency may be declared by alias, so compare real names
+            let declared: BTreeSet<&str> = env_config
+                .all_dependencies()
+                .map(|dependency| known.get(dependency).map_or(dependency, String::as_str))
+                .collect();
+
+            let mut mentioned = BTreeSet::new();
+            for word in command_words(&env_config.install.to_string()) {
+                let Some(name) = known.get(word) else {
+                    continue;
+                };
+
+                if *name == package.name || declared.contains(name.as_str()) {
+                    continue;
+                }
+
+                if mentioned.insert(name.as_str()) {
+                    result.add_issue(ValidationIssue::warning(
+                        ValidationErrorCategory::Environment,
+                        &format!("environments.{}.install", env_name),
+                        &format!(
+                            "Install command mentions package '{}', which isn't a declared dependency",
+                            name
+                        ),
+                        None,
+                        Some(&format!(
+                            "Add '{}' to the environment's dependencies so it's installed first. Pass --no-heuristics to skip this warning.",
+                            name
+                        )),
+                    ));
+                }
+            }
+        }
+    }
+
     /// Add environment-specific recommendations
     fn validate_environment_recommendations(
         &self,
         package: &Package,
         result: &mut ValidationResult,
     ) {
-        // We only check for the current environment
-        if let Some(env_config) = package.environments.get(self.config.environment()) {
+        for (env_name, env_config) in self.target_environments(package) {
             if let Some(recommendation) = self
                 .command_validator
-                .is_command_recommended_for_env(self.config.environment(), &env_config.install)
+                .is_command_recommended_for_env(env_name, env_config.install.first_command())
             {
                 result.add_issue(ValidationIssue::warning(
                     ValidationErrorCategory::Environment,
-                    &format!("environments.{}.install", self.config.environment()),
+                    &format!("environments.{}.install", env_name),
                     &recommendation,
                     None,
                     Some("Using environment-specific package managers may improve reliability."),
@@ -310,6 +684,73 @@ impl<'a, F: FileSystem, PR: PackageRepository, CR: CommandRunner> PackageValidat
     }
 }
 
+/// Map of every name and alias of `packages` to the package's name
+pub(crate) fn known_packages(packages: &[Package]) -> HashMap<String, String> {
+    let mut known = HashMap::new();
+    for package in packages {
+        for alias in &package.aliases {
+            known.insert(alias.clone(), package.name.clone());
+        }
+    }
+
+    // A package's own name wins over another's alias, as in lookups
+    for package in packages {
+        known.insert(package.name.clone(), package.name.clone());
+    }
+
+    known
+}
+
+/// The words of a shell command that could name a package: each command in
+/// it (split at `|`, `;`, `&`, parentheses and backticks) split on
+/// whitespace, leaving out flags and leading variable assignments. A
+/// command's first word is the program it runs, so a path to one counts by
+/// its file name; anywhere else a `/` is part of the word, as in a URL.
+fn command_words(command: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for simple_command in command.split(|c: char| "|;&()`\n".contains(c)) {
+        let mut program_seen = false;
+        for word in simple_command.split_whitespace() {
+            let word = word.trim_matches(['\'', '"']);
+            if word.is_empty() || word.starts_with('-') {
+                continue;
+            }
+
+            if program_seen {
+                words.push(word);
+            } else if !word.contains('=') {
+                program_seen = true;
+                words.push(word.rsplit('/').next().unwrap_or(word));
+            }
+        }
+    }
+
+    words
+}
+
+/// Environments (lowercased, since they're matched case-insensitively)
+/// declared by at least half of `packages`
+pub(crate) fn common_environments(packages: &[Package]) -> BTreeSet<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for package in packages {
+        let names: BTreeSet<String> = package
+            .environments
+            .keys()
+            .map(|env_name| env_name.to_lowercase())
+            .collect();
+
+        for env_name in names {
+            *counts.entry(env_name).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, count)| count * 2 >= packages.len())
+        .map(|(env_name, _)| env_name)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -318,8 +759,12 @@ mod tests {
 
     use crate::{
         adapters::{package_repo::yaml::YamlPackageRepository, progress::ProgressManager},
-        domain::config::AppConfigBuilder,
-        ports::{command::MockCommandRunner, filesystem::MockFileSystem},
+        domain::{config::AppConfigBuilder, package::PackageBuilder},
+        ports::{
+            command::{CommandError, CommandOutput, MockCommandRunner},
+            filesystem::MockFileSystem,
+            package_repo::MockPackageRepository,
+        },
     };
 
     // Helper function to create a test environment
@@ -332,6 +777,8 @@ mod tests {
 
         // Add the package directory to the filesystem
         fs.mock_path_exists("/test/packages", true);
+        fs.mock_path_exists("/test/packages/packages.yaml", false);
+        fs.mock_real_paths();
 
         let runner = MockCommandRunner::new();
 
@@ -433,6 +880,69 @@ environments:
         assert!(version_error.is_some());
     }
 
+    #[tokio::test]
+    async fn test_validate_run_checks() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        let yaml = r#"
+name: test-package
+version: 1.0.0
+environments:
+  test-env:
+    install: brew install test-package
+    check: tset-package --version
+  other-env:
+    install: selfie-never-run
+    check: selfie-never-run
+"#;
+        fs.mock_read_file("/test/packages/test-package.yaml", yaml);
+
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("tset-package", false);
+        runner
+            .expect_execute_with_timeout()
+            .with(
+                mockall::predicate::eq("tset-package --version"),
+                mockall::predicate::always(),
+            )
+            .times(1)
+            .returning(|command, _| {
+                Err(CommandError::NonZeroExit {
+                    command: command.to_string(),
+                    output: CommandOutput {
+                        stderr: "sh: 1: tset-package: not found".to_string(),
+                        status: 127,
+                        ..CommandOutput::default()
+                    },
+                })
+            });
+
+        let progress_manager = ProgressManager::default();
+        let package_repo =
+            YamlPackageRepository::new(&fs, config.expanded_package_directory(), progress_manager);
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator)
+            .with_run_checks(true);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        let check_errors: Vec<_> = result
+            .errors()
+            .into_iter()
+            .filter(|issue| issue.field == "environments.test-env.check")
+            .collect();
+        assert_eq!(check_errors.len(), 1);
+        assert_eq!(
+            check_errors[0].category,
+            ValidationErrorCategory::CommandSyntax
+        );
+        assert!(check_errors[0]
+            .message
+            .contains("Check command not found (exit status 127)"));
+    }
+
     #[tokio::test]
     async fn test_validate_invalid_url() {
         let (mut fs, mut runner, config) = setup_test_environment();
@@ -507,5 +1017,548 @@ environments:
             .any(|e| e.message.contains("Invalid pipe usage")));
     }
 
-    // Rest of tests...
+    #[tokio::test]
+    async fn test_validate_curl_pipe_to_shell() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        let yaml = r#"
+name: test-package
+version: 1.0.0
+environments:
+  test-env:
+    install: curl -fsSL https://example.com/install.sh | sh
+  other-env:
+    install: curl -fsSL https://example.com/tool.tar.gz | tar xz
+"#;
+        fs.mock_read_file("/test/packages/piped.yaml", yaml);
+
+        runner.mock_is_command_available("curl", true);
+
+        let progress_manager = ProgressManager::default();
+        let package_repo =
+            YamlPackageRepository::new(&fs, config.expanded_package_directory(), progress_manager);
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/piped.yaml"))
+            .await
+            .unwrap();
+
+        assert!(result.is_valid());
+
+        let piped: Vec<_> = result
+            .warnings()
+            .into_iter()
+            .filter(|w| w.message.contains("directly into a shell"))
+            .collect();
+        assert_eq!(piped.len(), 1);
+        assert_eq!(piped[0].field, "environments.test-env.install");
+        assert_eq!(piped[0].category, ValidationErrorCategory::CommandSyntax);
+
+        // The plain tarball download only gets the general notice
+        assert!(result.warnings().iter().any(|w| {
+            w.field == "environments.other-env.install"
+                && w.message.contains("may download content")
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_validate_check_mismatch() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        let yaml = r#"
+name: ripgrep
+version: 1.0.0
+environments:
+  test-env:
+    install: brew install rg
+    check: which fd
+"#;
+        fs.mock_read_file("/test/packages/ripgrep.yaml", yaml);
+
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("which", true);
+
+        let progress_manager = ProgressManager::default();
+        let package_repo =
+            YamlPackageRepository::new(&fs, config.expanded_package_directory(), progress_manager);
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/ripgrep.yaml"))
+            .await
+            .unwrap();
+
+        assert!(result.is_valid());
+
+        let mismatch = result
+            .warnings()
+            .into_iter()
+            .find(|w| w.field == "environments.test-env.check")
+            .unwrap();
+        assert_eq!(mismatch.category, ValidationErrorCategory::Environment);
+        assert!(mismatch.message.contains("'fd'"));
+
+        // Skipped when heuristics are turned off
+        let validator = validator.with_heuristics(false);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/ripgrep.yaml"))
+            .await
+            .unwrap();
+
+        assert!(!result
+            .warnings()
+            .iter()
+            .any(|w| w.field == "environments.test-env.check"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_missing_dependency() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        let yaml = r#"
+name: test-package
+version: 1.0.0
+environments:
+  test-env:
+    install: brew install test-package
+    dependencies:
+      - present-dep
+      - missing-dep
+"#;
+        fs.mock_read_file("/test/packages/test-package.yaml", yaml);
+
+        let mut package_repo = MockPackageRepository::new();
+        package_repo.mock_package_exists("present-dep", true);
+        package_repo.mock_package_exists("missing-dep", false);
+
+        runner.mock_is_command_available("brew", true);
+
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        let dependency_issues: Vec<_> = result
+            .warnings()
+            .into_iter()
+            .filter(|issue| issue.field == "environments.test-env.dependencies")
+            .collect();
+
+        assert_eq!(dependency_issues.len(), 1);
+        assert!(dependency_issues[0].message.contains("missing-dep"));
+
+        // --strict makes it an error
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .strict(true)
+            .build();
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        assert!(result
+            .errors()
+            .iter()
+            .any(|issue| issue.message == "Dependency 'missing-dep' was not found"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_alias_shadowed_by_package() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        let yaml = r#"
+name: ripgrep
+version: 1.0.0
+aliases: [rg, grep]
+environments:
+  test-env:
+    install: brew install ripgrep
+"#;
+        fs.mock_read_file("/test/packages/ripgrep.yaml", yaml);
+
+        let mut package_repo = MockPackageRepository::new();
+        package_repo.mock_get_package_ok("rg", PackageBuilder::default().name("ripgrep").build());
+        package_repo.mock_get_package_ok("grep", PackageBuilder::default().name("grep").build());
+
+        runner.mock_is_command_available("brew", true);
+
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/ripgrep.yaml"))
+            .await
+            .unwrap();
+
+        let alias_issues: Vec<_> = result
+            .warnings()
+            .into_iter()
+            .filter(|issue| issue.field == "aliases")
+            .collect();
+
+        assert_eq!(alias_issues.len(), 1);
+        assert!(alias_issues[0].message.contains("'grep'"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_check_required_by_policy() {
+        let (mut fs, mut runner, _) = setup_test_environment();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .no_check_policy(NoCheckPolicy::Never)
+            .build();
+
+        let yaml = r#"
+name: test-package
+version: 1.0.0
+environments:
+  test-env:
+    install: brew install test-package
+"#;
+        fs.mock_read_file("/test/packages/test-package.yaml", yaml);
+        runner.mock_is_command_available("brew", true);
+
+        let package_repo = MockPackageRepository::new();
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        assert!(result
+            .errors()
+            .iter()
+            .any(|issue| issue.field == "environments.test-env.check"));
+
+        // Under the default policy, only a strict validation mentions it
+        let (_, _, config) = setup_test_environment();
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let check_issues = |result: &ValidationResult| -> Vec<ValidationIssue> {
+            result
+                .issues
+                .iter()
+                .filter(|issue| issue.field == "environments.test-env.check")
+                .cloned()
+                .collect()
+        };
+
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+        assert!(check_issues(&result).is_empty());
+
+        let result = validator
+            .with_strict(true)
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+        let issues = check_issues(&result);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_warning);
+    }
+
+    #[tokio::test]
+    async fn test_validate_name_from_filename() {
+        let (mut fs, mut runner, _) = setup_test_environment();
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .name_source(NameSource::Filename)
+            .build();
+
+        let yaml = r#"
+name: rg
+version: 1.0.0
+environments:
+  test-env:
+    install: brew install ripgrep
+    check: which rg
+"#;
+        fs.mock_read_file("/test/packages/ripgrep.yaml", yaml);
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("which", true);
+
+        let package_repo = MockPackageRepository::new();
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/ripgrep.yaml"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_name, "ripgrep");
+        let name_issues: Vec<_> = result
+            .warnings()
+            .into_iter()
+            .filter(|issue| issue.field == "name")
+            .collect();
+        assert_eq!(name_issues.len(), 1);
+        assert_eq!(
+            name_issues[0]
+                .fix
+                .as_ref()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("set name to \"ripgrep\"")
+        );
+
+        // Taking names from the field, the file name doesn't matter
+        let (_, _, config) = setup_test_environment();
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/ripgrep.yaml"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_name, "rg");
+        assert!(result.issues.iter().all(|issue| issue.field != "name"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_real_path() {
+        let (_, mut runner, config) = setup_test_environment();
+
+        // Symlinks have to be set up before falling back to real paths
+        let mut fs = MockFileSystem::default();
+        fs.mock_symlink(
+            "/test/packages/test-package.yaml",
+            "/dotfiles/packages/test-package.yaml",
+        );
+        fs.mock_real_paths();
+        fs.mock_read_file(
+            "/test/packages/test-package.yaml",
+            create_valid_package_yaml(),
+        );
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("which", true);
+
+        let package_repo = MockPackageRepository::new();
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.package_path.as_deref(),
+            Some(Path::new("/dotfiles/packages/test-package.yaml"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_all_environments() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        fs.mock_read_file(
+            "/test/packages/test-package.yaml",
+            create_valid_package_yaml(),
+        );
+
+        let mut package_repo = MockPackageRepository::new();
+        package_repo.mock_package_exists("dependency1", true);
+        package_repo.mock_package_exists("dependency2", true);
+
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("which", true);
+        runner.mock_is_command_available("apt-get", false);
+
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator)
+            .with_all_environments(true);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        // Not looking for the current environment, there's no warning about it
+        assert!(!result
+            .issues
+            .iter()
+            .any(|issue| issue.field == "environments"));
+
+        // The missing command is reported against its own environment
+        let availability = result.issues_by_category(&ValidationErrorCategory::Availability);
+        assert_eq!(availability.len(), 1);
+        assert_eq!(availability[0].field, "environments.prod-env.install");
+        assert!(availability[0]
+            .message
+            .contains("expected if this machine isn't set up as 'prod-env'"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_shell_not_available() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        fs.mock_read_file(
+            "/test/packages/test-package.yaml",
+            r#"
+name: test-package
+version: 1.0.0
+environments:
+  test-env:
+    install: brew install test-package
+    check: which test-package
+    shell: pwsh
+"#,
+        );
+
+        let package_repo = MockPackageRepository::new();
+        runner.mock_is_command_available("brew", true);
+        runner.mock_is_command_available("which", true);
+        runner.mock_is_command_available("pwsh", false);
+
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        assert!(result.is_valid());
+        let availability = result.issues_by_category(&ValidationErrorCategory::Availability);
+        assert_eq!(availability.len(), 1);
+        assert_eq!(availability[0].field, "environments.test-env.shell");
+        assert!(availability[0].message.contains("'pwsh'"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_uncommon_environments() {
+        let (mut fs, runner, config) = setup_test_environment();
+
+        let yaml = r#"
+name: test-package
+version: 1.0.0
+environments:
+  freebsd:
+    install: pkg install test-package
+"#;
+        fs.mock_read_file("/test/packages/test-package.yaml", yaml);
+
+        let packages = [
+            ("fd", vec!["macos", "ubuntu"]),
+            ("ripgrep", vec!["MacOS", "ubuntu"]),
+            ("tokei", vec!["macos"]),
+            ("test-package", vec!["freebsd"]),
+        ]
+        .map(|(name, environments)| {
+            environments
+                .into_iter()
+                .fold(PackageBuilder::default().name(name), |builder, env| {
+                    builder.environment(env, "true")
+                })
+                .build()
+        });
+        let common = common_environments(&packages);
+        assert_eq!(
+            common,
+            BTreeSet::from(["macos".to_string(), "ubuntu".to_string()])
+        );
+
+        let package_repo = MockPackageRepository::new();
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator)
+            .with_common_environments(Some(common));
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/test-package.yaml"))
+            .await
+            .unwrap();
+
+        let issues: Vec<_> = result
+            .warnings()
+            .into_iter()
+            .filter(|issue| {
+                issue
+                    .message
+                    .starts_with("None of the package's environments")
+            })
+            .collect();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "environments");
+        assert!(issues[0].message.contains("(freebsd)"));
+        assert!(issues[0].message.contains("(macos, ubuntu)"));
+    }
+
+    #[test]
+    fn test_command_words() {
+        assert_eq!(
+            command_words("FOO=1 /opt/bin/jq -r . 'a.json' && curl https://x.io/fd/get | sh"),
+            vec!["jq", ".", "a.json", "curl", "https://x.io/fd/get", "sh"]
+        );
+        assert_eq!(
+            command_words("brew install owner/tap/ripgrep; (cd src && make)"),
+            vec!["brew", "install", "owner/tap/ripgrep", "cd", "src", "make"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_undeclared_dependencies() {
+        let (mut fs, mut runner, config) = setup_test_environment();
+
+        // Uses cargo, declared by its alias, and jq, which isn't declared,
+        // by its path. bat is only part of a URL.
+        let yaml = r#"
+name: cargo-tools
+version: 1.0.0
+environments:
+  test-env:
+    install: cargo install cargo-edit && /usr/local/bin/jq --version && curl -fsSL https://example.com/bat/install.sh | sh
+    check: which cargo-add
+    dependencies:
+      - rust
+"#;
+        fs.mock_read_file("/test/packages/cargo-tools.yaml", yaml);
+
+        let packages = [
+            PackageBuilder::default().name("cargo-tools").build(),
+            PackageBuilder::default()
+                .name("rustup")
+                .aliases(&["rust", "cargo"])
+                .build(),
+            PackageBuilder::default().name("jq").build(),
+            PackageBuilder::default().name("bat").build(),
+        ];
+
+        let mut package_repo = MockPackageRepository::new();
+        package_repo.mock_package_exists("rust", true);
+
+        runner.mock_is_command_available("cargo", true);
+        runner.mock_is_command_available("which", true);
+
+        let command_validator = CommandValidator::new(&runner);
+        let validator = PackageValidator::new(&fs, &config, &package_repo, &command_validator)
+            .with_known_packages(Some(known_packages(&packages)));
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/cargo-tools.yaml"))
+            .await
+            .unwrap();
+
+        let issues: Vec<_> = result
+            .warnings()
+            .into_iter()
+            .filter(|issue| issue.message.contains("isn't a declared dependency"))
+            .collect();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "environments.test-env.install");
+        assert!(issues[0].message.contains("'jq'"));
+
+        // Skipped when heuristics are turned off
+        let validator = validator.with_heuristics(false);
+        let result = validator
+            .validate_package_file(Path::new("/test/packages/cargo-tools.yaml"))
+            .await
+            .unwrap();
+        assert!(!result
+            .warnings()
+            .iter()
+            .any(|issue| issue.message.contains("isn't a declared dependency")));
+    }
 }
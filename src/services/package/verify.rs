@@ -0,0 +1,209 @@
+// src/services/package/verify.rs
+// Implementation of the 'selfie package verify' command
+
+use thiserror::Error;
+
+use crate::{
+    domain::{
+        config::{AppConfig, ConfigValidationError},
+        installation::{Installation, InstallationError},
+        package::EnvironmentConfig,
+    },
+    ports::{
+        command::CommandRunner,
+        filesystem::FileSystem,
+        package_repo::{PackageRepoError, PackageRepository},
+    },
+};
+
+#[derive(Error, Debug)]
+pub(crate) enum PackageVerifyError {
+    #[error(transparent)]
+    Repo(#[from] PackageRepoError),
+
+    #[error(transparent)]
+    Environment(#[from] ConfigValidationError),
+}
+
+/// What running a package's check again found
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Verification {
+    /// The check finds the package
+    Detected,
+
+    /// No check configured for the current environment, so there's nothing
+    /// to verify the package with
+    NoCheck,
+
+    /// The check runs, but doesn't find the package
+    NotDetected,
+
+    /// Running the check failed, or it timed out
+    CheckFailed(String),
+}
+
+impl Verification {
+    /// Why the package couldn't be confirmed as installed; `None` when it was,
+    /// or when there's no check to confirm it with
+    pub(crate) fn failure(&self) -> Option<String> {
+        match self {
+            Self::Detected | Self::NoCheck => None,
+            Self::NotDetected => Some("its check still doesn't find it".to_string()),
+            Self::CheckFailed(err) => Some(format!("its check failed: {}", err)),
+        }
+    }
+}
+
+/// Run the check of `env_config` to confirm what it installs is really there,
+/// e.g. that an install that exited 0 put the tool on PATH
+pub(crate) async fn verify_installation<F: FileSystem, CR: CommandRunner>(
+    fs: &F,
+    runner: &CR,
+    config: &AppConfig,
+    env_config: &EnvironmentConfig,
+) -> Verification {
+    if !env_config.has_check() {
+        return Verification::NoCheck;
+    }
+
+    // A package's own check timeout wins over the configured one
+    let check_timeout = env_config.check_timeout().unwrap_or(config.check_timeout());
+
+    match Installation::new(env_config.clone())
+        .start()
+        .execute_check(fs, runner, check_timeout, |_| {})
+        .await
+    {
+        Ok(Installation::AlreadyInstalled { .. }) => Verification::Detected,
+        Ok(Installation::Failed { error_message, .. }) => Verification::CheckFailed(error_message),
+        Ok(Installation::CheckTimedOut { timeout, .. }) => {
+            Verification::CheckFailed(InstallationError::CheckTimedOut(timeout).to_string())
+        }
+        Ok(_) => Verification::NotDetected,
+        Err(err) => Verification::CheckFailed(err.to_string()),
+    }
+}
+
+/// Handles the 'package verify' command
+pub(crate) struct PackageVerifyService<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
+{
+    fs: &'a F,
+    runner: &'a CR,
+    config: &'a AppConfig,
+    package_repo: &'a PR,
+}
+
+impl<'a, F: FileSystem, CR: CommandRunner, PR: PackageRepository>
+    PackageVerifyService<'a, F, CR, PR>
+{
+    /// Create a new verify command handler
+    pub(crate) fn new(
+        fs: &'a F,
+        runner: &'a CR,
+        config: &'a AppConfig,
+        package_repo: &'a PR,
+    ) -> Self {
+        Self {
+            fs,
+            runner,
+            config,
+            package_repo,
+        }
+    }
+
+    /// Run the check of `package_name` for the current environment
+    pub(crate) async fn verify(
+        &self,
+        package_name: &str,
+    ) -> Result<Verification, PackageVerifyError> {
+        let package = self.package_repo.get_package(package_name)?;
+        let env_config = self.config.resolve_environment(&package)?;
+
+        Ok(verify_installation(self.fs, self.runner, self.config, env_config).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use super::*;
+    use crate::{
+        domain::{
+            config::AppConfigBuilder,
+            package::{EnvironmentConfig, PackageBuilder},
+        },
+        ports::{
+            command::MockCommandRunner, filesystem::MockFileSystem,
+            package_repo::MockPackageRepository,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_verify() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .build();
+
+        let mut repo = MockPackageRepository::new();
+        for (name, check) in [("rg", Some("rg --version")), ("fd", Some("fd --version"))]
+            .into_iter()
+            .chain([("jq", None)])
+        {
+            let mut env_config = EnvironmentConfig::new(format!("brew install {}", name));
+            if let Some(check) = check {
+                env_config = env_config.with_check(check);
+            }
+            repo.mock_get_package_ok(
+                name,
+                PackageBuilder::default()
+                    .name(name)
+                    .version("1.0.0")
+                    .environment_config("test-env", env_config)
+                    .build(),
+            );
+        }
+
+        let fs = MockFileSystem::default();
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_streaming_success_0("rg --version", 15, "ripgrep 14.1.0");
+        runner.mock_execute_streaming_success_1("fd --version", 15, "");
+
+        let service = PackageVerifyService::new(&fs, &runner, &config, &repo);
+
+        assert_eq!(service.verify("rg").await.unwrap(), Verification::Detected);
+
+        let not_detected = service.verify("fd").await.unwrap();
+        assert_eq!(not_detected, Verification::NotDetected);
+        assert_eq!(
+            not_detected.failure().as_deref(),
+            Some("its check still doesn't find it")
+        );
+
+        let no_check = service.verify("jq").await.unwrap();
+        assert_eq!(no_check, Verification::NoCheck);
+        assert_eq!(no_check.failure(), None);
+    }
+
+    #[tokio::test]
+    async fn test_verify_uses_package_check_timeout() {
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory("/test/packages")
+            .check_timeout_unchecked(15)
+            .build();
+        let env_config = EnvironmentConfig::new("brew install rg")
+            .with_check("rg --version")
+            .with_check_timeout(NonZeroU64::new(90).unwrap());
+
+        let fs = MockFileSystem::default();
+        let mut runner = MockCommandRunner::new();
+        runner.mock_execute_streaming_success_0("rg --version", 90, "ripgrep 14.1.0");
+
+        assert_eq!(
+            verify_installation(&fs, &runner, &config, &env_config).await,
+            Verification::Detected
+        );
+    }
+}
@@ -0,0 +1,278 @@
+// src/services/sarif.rs
+// Validation results as a SARIF 2.1.0 log, for code scanning tools
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::domain::validation::{ValidationErrorCategory, ValidationIssue, ValidationResult};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// Base id that package file locations are relative to
+const PACKAGE_DIR_BASE_ID: &str = "PACKAGEDIR";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRun {
+    tool: SarifTool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    original_uri_base_ids: Option<serde_json::Map<String, serde_json::Value>>,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: &'static str,
+    short_description: SarifMessage,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    level: &'static str,
+    message: SarifMessage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+}
+
+/// Render `results` as a SARIF 2.1.0 log, with one rule per validation
+/// category and one result per issue. Files in `package_directory` are
+/// located relative to it, so the log doesn't depend on where the package
+/// directory is checked out.
+pub(crate) fn validation_results_to_sarif(
+    results: &[ValidationResult],
+    package_directory: &Path,
+) -> Result<String, serde_json::Error> {
+    let original_uri_base_ids = url::Url::from_directory_path(package_directory)
+        .ok()
+        .map(|uri| {
+            let mut base_ids = serde_json::Map::new();
+            base_ids.insert(
+                PACKAGE_DIR_BASE_ID.to_string(),
+                serde_json::json!({ "uri": uri.as_str() }),
+            );
+            base_ids
+        });
+
+    let rules = ValidationErrorCategory::ALL
+        .iter()
+        .map(|category| SarifRule {
+            id: category.rule_id(),
+            short_description: SarifMessage {
+                text: category.to_string(),
+            },
+        })
+        .collect();
+
+    let results = results
+        .iter()
+        .flat_map(|result| {
+            result
+                .issues
+                .iter()
+                .map(move |issue| sarif_result(result, issue, package_directory))
+        })
+        .collect();
+
+    let log = SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "selfie",
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules,
+                },
+            },
+            original_uri_base_ids,
+            results,
+        }],
+    };
+
+    serde_json::to_string_pretty(&log)
+}
+
+fn sarif_result(
+    result: &ValidationResult,
+    issue: &ValidationIssue,
+    package_directory: &Path,
+) -> SarifResult {
+    let mut text = format!("{}: {}", issue.field, issue.message);
+    if let Some(suggestion) = &issue.suggestion {
+        text.push_str(&format!(" {}", suggestion));
+    }
+
+    let locations = result
+        .package_path
+        .as_deref()
+        .map(|path| SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: artifact_location(path, package_directory),
+                region: issue.line.map(|start_line| SarifRegion { start_line }),
+            },
+        })
+        .into_iter()
+        .collect();
+
+    SarifResult {
+        rule_id: issue.category.rule_id(),
+        level: if issue.is_warning { "warning" } else { "error" },
+        message: SarifMessage { text },
+        locations,
+    }
+}
+
+/// `path` relative to the package directory when it's inside it, otherwise
+/// as an absolute file URI
+fn artifact_location(path: &Path, package_directory: &Path) -> SarifArtifactLocation {
+    match path.strip_prefix(package_directory) {
+        Ok(relative) => SarifArtifactLocation {
+            uri: relative.to_string_lossy().into_owned(),
+            uri_base_id: Some(PACKAGE_DIR_BASE_ID),
+        },
+        Err(_) => SarifArtifactLocation {
+            uri: url::Url::from_file_path(path)
+                .map(String::from)
+                .unwrap_or_else(|_| path.to_string_lossy().into_owned()),
+            uri_base_id: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_validation_results_to_sarif() {
+        let mut ripgrep = ValidationResult::new("ripgrep")
+            .with_path(PathBuf::from("/test/packages/ripgrep.yaml"));
+        ripgrep.add_issue(ValidationIssue::error(
+            ValidationErrorCategory::RequiredField,
+            "version",
+            "Version is required",
+            Some(2),
+            Some("Add a version."),
+        ));
+        ripgrep.add_issue(ValidationIssue::warning(
+            ValidationErrorCategory::Availability,
+            "environments.macos.install",
+            "Command 'brew' not found",
+            None,
+            None,
+        ));
+        let mut elsewhere =
+            ValidationResult::new("fd").with_path(PathBuf::from("/elsewhere/fd.yaml"));
+        elsewhere.add_issue(ValidationIssue::warning(
+            ValidationErrorCategory::UrlFormat,
+            "homepage",
+            "Invalid URL",
+            None,
+            None,
+        ));
+
+        let sarif = validation_results_to_sarif(
+            &[ripgrep, ValidationResult::new("bat"), elsewhere],
+            Path::new("/test/packages"),
+        )
+        .unwrap();
+        let log: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "selfie");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 8);
+        assert_eq!(
+            run["originalUriBaseIds"]["PACKAGEDIR"]["uri"],
+            "file:///test/packages/"
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0],
+            serde_json::json!({
+                "ruleId": "required-field",
+                "level": "error",
+                "message": { "text": "version: Version is required Add a version." },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "ripgrep.yaml", "uriBaseId": "PACKAGEDIR" },
+                        "region": { "startLine": 2 },
+                    },
+                }],
+            })
+        );
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[1]["ruleId"], "availability");
+        assert!(results[1]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"]["artifactLocation"],
+            serde_json::json!({ "uri": "file:///elsewhere/fd.yaml" })
+        );
+    }
+}
@@ -6,6 +6,9 @@ use std::path::{Path, PathBuf};
 use crate::ports::filesystem::FileSystem;
 use crate::ports::package_repo::PackageRepository;
 
+/// Most suggestions to offer for any one name
+const MAX_SUGGESTIONS: usize = 3;
+
 /// Generates suggestions for unknown names or values
 pub(crate) struct SuggestionProvider<'a> {
     fs: &'a dyn FileSystem,
@@ -20,12 +23,8 @@ impl<'a> SuggestionProvider<'a> {
 
     /// Get suggestions for a package name
     pub(crate) fn suggest_package(&self, name: &str) -> Vec<String> {
-        // Try to get packages from the repository
-        match self.package_repo.list_packages() {
-            Ok(packages) => {
-                let package_names: Vec<String> = packages.iter().map(|p| p.name.clone()).collect();
-                self.find_similar_strings(name, &package_names)
-            }
+        match self.package_repo.find_similar(name) {
+            Ok(names) => names.into_iter().take(MAX_SUGGESTIONS).collect(),
             Err(_) => Vec::new(),
         }
     }
@@ -93,8 +92,6 @@ impl<'a> SuggestionProvider<'a> {
 
     /// Find similar strings using string similarity
     fn find_similar_strings(&self, target: &str, candidates: &[String]) -> Vec<String> {
-        const MAX_SUGGESTIONS: usize = 3;
-
         // Calculate similarity for all candidates
         let mut similarities: Vec<(String, f64)> = candidates
             .iter()
@@ -126,7 +123,6 @@ impl<'a> SuggestionProvider<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::package::PackageBuilder;
     use crate::ports::filesystem::MockFileSystem;
     use crate::ports::package_repo::MockPackageRepository;
 
@@ -135,29 +131,9 @@ mod tests {
         let fs = MockFileSystem::default();
         let mut package_repo = MockPackageRepository::new();
 
-        // Create test packages
-        let packages = vec![
-            PackageBuilder::default()
-                .name("ripgrep")
-                .version("1.0.0")
-                .build(),
-            PackageBuilder::default()
-                .name("ripgrep-all")
-                .version("1.0.0")
-                .build(),
-            PackageBuilder::default()
-                .name("fzf")
-                .version("1.0.0")
-                .build(),
-            PackageBuilder::default()
-                .name("bat")
-                .version("1.0.0")
-                .build(),
-        ];
-
-        package_repo
-            .expect_list_packages()
-            .returning(move || Ok(packages.clone()));
+        package_repo.mock_find_similar("rigrep", &["ripgrep"]);
+        package_repo.mock_find_similar("rip", &["ripgrep", "ripgrep-all", "rippy", "ripper"]);
+        package_repo.mock_find_similar("completely-different", &[]);
 
         let provider = SuggestionProvider::new(&fs, &package_repo);
 
@@ -166,6 +142,10 @@ mod tests {
         assert!(!suggestions.is_empty());
         assert!(suggestions.contains(&"ripgrep".to_string()));
 
+        // Only the closest few are suggested
+        let suggestions = provider.suggest_package("rip");
+        assert_eq!(suggestions, vec!["ripgrep", "ripgrep-all", "rippy"]);
+
         // Test with unrelated name
         let suggestions = provider.suggest_package("completely-different");
         assert!(suggestions.is_empty());
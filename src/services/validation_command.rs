@@ -1,15 +1,47 @@
-use std::path::Path;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 // src/services/validation_command.rs
 use crate::{
-    adapters::{package_repo::yaml::YamlPackageRepository, progress::ProgressManager},
-    domain::config::AppConfig,
-    ports::{command::CommandRunner, filesystem::FileSystem},
-    services::package::validate::PackageValidator,
+    adapters::{
+        package_repo::yaml::{YamlPackageRepository, COMBINED_PACKAGES_FILE},
+        progress::ProgressManager,
+    },
+    domain::{
+        application::commands::ValidateFormat,
+        config::AppConfig,
+        validation::{DirectoryValidationResult, ValidationResult},
+    },
+    ports::{command::CommandRunner, filesystem::FileSystem, package_repo::PackageRepository},
+    services::{
+        package::validate::{known_packages, PackageValidator, PackageValidatorError},
+        sarif::validation_results_to_sarif,
+    },
 };
 
 use super::command_validator::CommandValidator;
 
+/// Options that change what the validate command checks and does
+#[derive(Debug, Clone)]
+pub(crate) struct ValidateOptions {
+    /// Run checks that guess at mistakes and may have false positives
+    pub(crate) heuristics: bool,
+
+    /// Apply the fixes for issues that have one to the package file
+    pub(crate) fix: bool,
+
+    /// Show a diff of the fixes, only writing them when confirmed
+    pub(crate) diff: bool,
+
+    /// Run the current environment's check command to see that it starts
+    pub(crate) run_checks: bool,
+
+    /// Check every environment a package declares, not only the current one
+    pub(crate) all_environments: bool,
+}
+
 /// Result of running the validate command
 #[derive(Debug)]
 pub(crate) enum ValidationCommandResult {
@@ -27,6 +59,11 @@ pub(crate) struct ValidationCommand<'a, F: FileSystem, CR: CommandRunner> {
     config: &'a AppConfig,
     progress_manager: ProgressManager,
     command_validator: &'a CommandValidator<'a, CR>,
+    heuristics: bool,
+    fix: bool,
+    diff: bool,
+    run_checks: bool,
+    all_environments: bool,
 }
 
 impl<'a, F: FileSystem, CR: CommandRunner> ValidationCommand<'a, F, CR> {
@@ -42,9 +79,47 @@ impl<'a, F: FileSystem, CR: CommandRunner> ValidationCommand<'a, F, CR> {
             config,
             progress_manager,
             command_validator,
+            heuristics: true,
+            fix: false,
+            diff: false,
+            run_checks: false,
+            all_environments: false,
         }
     }
 
+    /// Whether to run validation heuristics, which may have false positives
+    pub(crate) fn with_heuristics(mut self, heuristics: bool) -> Self {
+        self.heuristics = heuristics;
+        self
+    }
+
+    /// Whether to apply the fixes for issues that have one to the package
+    /// file, then validate it again
+    pub(crate) fn with_fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    /// Whether to show a diff of the fixes before writing them, and only
+    /// write them when the user said yes up front
+    pub(crate) fn with_diff(mut self, diff: bool) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    /// Whether to run each package's check command to see that it starts
+    pub(crate) fn with_run_checks(mut self, run_checks: bool) -> Self {
+        self.run_checks = run_checks;
+        self
+    }
+
+    /// Whether to check every environment a package declares instead of
+    /// only the current one
+    pub(crate) fn with_all_environments(mut self, all_environments: bool) -> Self {
+        self.all_environments = all_environments;
+        self
+    }
+
     /// Execute the validate command
     pub(crate) async fn execute(
         &self,
@@ -54,24 +129,36 @@ impl<'a, F: FileSystem, CR: CommandRunner> ValidationCommand<'a, F, CR> {
         self.progress_manager
             .print_progress(format!("Validating package '{}'", package_name));
 
-        // Create package repository
-        let package_repo = YamlPackageRepository::new(
-            self.fs,
-            self.config.expanded_package_directory(),
-            self.progress_manager,
-        );
+        let package_repo = self.package_repo();
 
         // Create the enhanced validator
         let validator =
-            PackageValidator::new(self.fs, self.config, &package_repo, self.command_validator);
+            PackageValidator::new(self.fs, self.config, &package_repo, self.command_validator)
+                .with_heuristics(self.heuristics)
+                .with_run_checks(self.run_checks)
+                .with_all_environments(self.all_environments)
+                .with_known_packages(self.known_packages(&package_repo));
 
         // Validate package
-        let result = if let Some(path) = package_path {
+        let mut result = if let Some(path) = package_path {
             validator.validate_package_file(path).await
         } else {
             validator.validate_package_by_name(package_name).await
         };
 
+        if let Ok(validation_result) = &result {
+            if self.fix {
+                match self.apply_fixes(validation_result) {
+                    Ok(Some(path)) => result = validator.validate_package_file(&path).await,
+                    Ok(None) => {}
+                    Err(err) => {
+                        self.progress_manager.print_error("Failed to apply fixes");
+                        return ValidationCommandResult::Error(format!("Error: {}", err));
+                    }
+                }
+            }
+        }
+
         match result {
             Ok(validation_result) => {
                 // Format the validation result
@@ -99,6 +186,197 @@ impl<'a, F: FileSystem, CR: CommandRunner> ValidationCommand<'a, F, CR> {
             }
         }
     }
+
+    /// Validate every package in the package directory, sorted by name,
+    /// writing the results in `format`. Package files that don't parse are
+    /// included, reported by their file name.
+    pub(crate) async fn execute_all(&self, format: ValidateFormat) -> ValidationCommandResult {
+        let package_repo = self.package_repo();
+        let package_files = match package_repo.package_files() {
+            Ok(package_files) => package_files,
+            Err(err) => return ValidationCommandResult::Error(format!("Error: {}", err)),
+        };
+        let packages = match package_repo.list_packages() {
+            Ok(packages) => packages,
+            Err(err) => return ValidationCommandResult::Error(format!("Error: {}", err)),
+        };
+        let combined_packages: Vec<&str> = packages
+            .iter()
+            .filter(|package| package.path.file_name() == Some(COMBINED_PACKAGES_FILE.as_ref()))
+            .map(|package| package.name.as_str())
+            .collect();
+
+        // One shell lookup for every command instead of one per command
+        self.command_validator.probe_packages(&packages).await;
+
+        let validator =
+            PackageValidator::new(self.fs, self.config, &package_repo, self.command_validator)
+                .with_heuristics(self.heuristics)
+                .with_run_checks(self.run_checks)
+                .with_all_environments(self.all_environments)
+                .with_known_packages(self.known_packages(&package_repo));
+
+        let mut results = Vec::with_capacity(package_files.len() + combined_packages.len());
+        for package_path in &package_files {
+            let result = validator.validate_package_file(package_path).await;
+            results.push(result.unwrap_or_else(|err| {
+                ValidationResult::failed(&package_path.to_string_lossy(), err)
+            }));
+        }
+        for package_name in &combined_packages {
+            let result = validator.validate_package_by_name(package_name).await;
+            results.push(result.unwrap_or_else(|err| ValidationResult::failed(package_name, err)));
+        }
+
+        let results = DirectoryValidationResult::new(results);
+
+        let valid = results.is_valid();
+        let output = match format {
+            ValidateFormat::Human => {
+                let mut output: Vec<String> = results
+                    .results()
+                    .iter()
+                    .map(|result| result.format_validation_result(self.progress_manager))
+                    .collect();
+                output.push(results.summary());
+                output.join("\n")
+            }
+            ValidateFormat::Json => match results.to_json() {
+                Ok(json) => json,
+                Err(err) => {
+                    return ValidationCommandResult::Error(format!(
+                        "Failed to serialize results as JSON: {}",
+                        err
+                    ))
+                }
+            },
+            ValidateFormat::Sarif => match validation_results_to_sarif(
+                results.results(),
+                &self.config.expanded_package_directory(),
+            ) {
+                Ok(sarif) => sarif,
+                Err(err) => {
+                    return ValidationCommandResult::Error(format!(
+                        "Failed to serialize results as SARIF: {}",
+                        err
+                    ))
+                }
+            },
+        };
+
+        if valid {
+            ValidationCommandResult::Valid(output)
+        } else {
+            ValidationCommandResult::Invalid(output)
+        }
+    }
+
+    fn package_repo(&self) -> YamlPackageRepository<'a, F> {
+        YamlPackageRepository::new(
+            self.fs,
+            self.config.expanded_package_directory(),
+            self.progress_manager,
+        )
+        .with_extensions(self.config.package_file_extensions())
+        .with_name_source(self.config.name_source())
+    }
+
+    /// The names install commands are checked for undeclared dependencies
+    /// against, which is only done with `--strict`, since it means reading
+    /// every package
+    fn known_packages(
+        &self,
+        package_repo: &YamlPackageRepository<'a, F>,
+    ) -> Option<HashMap<String, String>> {
+        if !self.config.strict() {
+            return None;
+        }
+
+        package_repo
+            .list_packages()
+            .ok()
+            .map(|packages| known_packages(&packages))
+    }
+
+    /// Write the fixes for `result`'s issues back to its package file,
+    /// returning the file's path if anything was fixed. Packages in the
+    /// combined file are left alone, since other packages share the file.
+    fn apply_fixes(
+        &self,
+        result: &ValidationResult,
+    ) -> Result<Option<PathBuf>, PackageValidatorError> {
+        let fixable = result.fixable();
+        let Some(path) = result.package_path.as_deref() else {
+            return Ok(None);
+        };
+        if fixable.is_empty() {
+            return Ok(None);
+        }
+
+        // The path is the real file, which the combined file may link to
+        let combined_path = self
+            .config
+            .expanded_package_directory()
+            .join(COMBINED_PACKAGES_FILE);
+        if path.file_name() == Some(COMBINED_PACKAGES_FILE.as_ref())
+            || self
+                .fs
+                .canonicalize(&combined_path)
+                .is_ok_and(|real_path| real_path == path)
+        {
+            self.progress_manager.print_warning(format!(
+                "Not applying fixes to {}; edit it by hand",
+                COMBINED_PACKAGES_FILE
+            ));
+            return Ok(None);
+        }
+
+        // Edit the file's YAML rather than the parsed package, so fields
+        // selfie doesn't know about are kept
+        let content = self.fs.read_file(path)?;
+        let mut document: serde_yaml::Value = serde_yaml::from_str(&content)
+            .map_err(|err| PackageValidatorError::InvalidPackage(err.to_string()))?;
+
+        let mut fixes = Vec::new();
+        for issue in fixable {
+            let Some(fix) = &issue.fix else { continue };
+
+            if fix.apply(&mut document) {
+                fixes.push(format!("{}: {}", issue.field, fix));
+            }
+        }
+
+        if fixes.is_empty() {
+            return Ok(None);
+        }
+
+        let fixed_content = serde_yaml::to_string(&document)
+            .map_err(|err| PackageValidatorError::InvalidPackage(err.to_string()))?;
+
+        if self.diff {
+            self.progress_manager
+                .print_progress(self.progress_manager.format_diff(
+                    path,
+                    path,
+                    &content,
+                    &fixed_content,
+                ));
+
+            if !self.config.assume_yes() {
+                self.progress_manager
+                    .print_info("Nothing changed. Pass --yes to apply the fixes.");
+                return Ok(None);
+            }
+        }
+
+        self.fs.write_file(path, &fixed_content)?;
+        for fix in fixes {
+            self.progress_manager
+                .print_success(format!("Fixed {}", fix));
+        }
+
+        Ok(Some(path.to_path_buf()))
+    }
 }
 
 #[cfg(test)]
@@ -116,7 +394,9 @@ mod tests {
         let package_dir = Path::new("/test/packages");
 
         let mut fs = MockFileSystem::default();
+        fs.mock_real_paths();
         fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
         fs.mock_path_exists(package_dir.join("test-package.yaml"), true);
         fs.mock_path_exists(package_dir.join("test-package.yml"), false);
         fs.mock_read_file(
@@ -162,6 +442,7 @@ mod tests {
     async fn test_validation_integration() {
         // Set up test environment
         let mut fs = MockFileSystem::default();
+        fs.mock_real_paths();
         let mut runner = MockCommandRunner::new();
 
         // Create config
@@ -173,6 +454,7 @@ mod tests {
         // Set up package directory
         let package_dir = Path::new("/test/packages");
         fs.mock_path_exists(&package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
 
         // Create a valid package file
         let valid_yaml = r#"
@@ -246,4 +528,148 @@ mod tests {
             _ => panic!("Expected Valid result for path validation"),
         }
     }
+
+    #[tokio::test]
+    async fn test_validation_fix() {
+        let package_dir = Path::new("/test/packages");
+        let package_path = package_dir.join("fixable.yaml");
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_real_paths();
+        fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
+        fs.mock_path_exists(&package_path, true);
+        fs.mock_path_exists(package_dir.join("fixable.yml"), false);
+
+        // Reads see what was last written
+        let content = std::sync::Arc::new(std::sync::Mutex::new(
+            "name: fixable\nversion: ''\nhomepage: http://example.com\nx-owner: me\nenvironments:\n  test-env:\n    install: echo test\n"
+                .to_string(),
+        ));
+        let read_content = content.clone();
+        fs.expect_read_file()
+            .returning(move |_| Ok(read_content.lock().unwrap().clone()));
+        let write_content = content.clone();
+        fs.expect_write_file().times(1).returning(move |_, new| {
+            *write_content.lock().unwrap() = new.to_string();
+            Ok(())
+        });
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_is_command_available("echo", true);
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory(package_dir)
+            .build();
+
+        let progress_manager = ProgressManager::default();
+        let command_validator = CommandValidator::new(&runner);
+        let command = ValidationCommand::new(&fs, &config, progress_manager, &command_validator)
+            .with_fix(true);
+
+        assert!(matches!(
+            command.execute("fixable", None).await,
+            ValidationCommandResult::Valid(_)
+        ));
+        assert_eq!(
+            *content.lock().unwrap(),
+            "name: fixable\nversion: 0.1.0\nhomepage: https://example.com\nx-owner: me\nenvironments:\n  test-env:\n    install: echo test\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validation_fix_diff_writes_nothing() {
+        let package_dir = Path::new("/test/packages");
+        let package_path = package_dir.join("fixable.yaml");
+
+        // No write_file expectation: writing would fail the test
+        let mut fs = MockFileSystem::default();
+        fs.mock_real_paths();
+        fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
+        fs.mock_path_exists(&package_path, true);
+        fs.mock_path_exists(package_dir.join("fixable.yml"), false);
+        fs.expect_read_file().returning(|_| {
+            Ok(
+                "name: fixable\nversion: ''\nenvironments:\n  test-env:\n    install: echo test\n"
+                    .to_string(),
+            )
+        });
+
+        let mut runner = MockCommandRunner::new();
+        runner.mock_is_command_available("echo", true);
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory(package_dir)
+            .build();
+
+        let progress_manager = ProgressManager::default();
+        let command_validator = CommandValidator::new(&runner);
+        let command = ValidationCommand::new(&fs, &config, progress_manager, &command_validator)
+            .with_fix(true)
+            .with_diff(true);
+
+        // The unfixed package is what gets validated
+        assert!(matches!(
+            command.execute("fixable", None).await,
+            ValidationCommandResult::Invalid(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_all_sarif() {
+        let package_dir = Path::new("/test/packages");
+
+        let mut fs = MockFileSystem::default();
+        fs.mock_real_paths();
+        fs.mock_path_exists(package_dir, true);
+        fs.mock_path_exists(package_dir.join("packages.yaml"), false);
+        fs.mock_list_directory(
+            package_dir.to_path_buf(),
+            &[
+                package_dir.join("ripgrep.yaml"),
+                package_dir.join("broken.yaml"),
+                package_dir.join("README.md"),
+            ],
+        );
+        fs.mock_read_file(
+            package_dir.join("ripgrep.yaml"),
+            "name: ripgrep\nversion: 1.0.0\nhomepage: https://example.com\n\
+             description: Fast grep\nenvironments:\n  test-env:\n    \
+             install: echo rg\n    check: which rg\n",
+        );
+        fs.mock_read_file(package_dir.join("broken.yaml"), "name: [broken");
+
+        // Every package's commands are looked up at once, up front
+        let mut runner = MockCommandRunner::new();
+        runner.mock_probe_commands(&[("echo", true), ("which", true)]);
+
+        let config = AppConfigBuilder::default()
+            .environment("test-env")
+            .package_directory(package_dir)
+            .build();
+        let command_validator = CommandValidator::new(&runner);
+        let cmd = ValidationCommand::new(
+            &fs,
+            &config,
+            ProgressManager::from(&config),
+            &command_validator,
+        );
+
+        let ValidationCommandResult::Invalid(output) = cmd.execute_all(ValidateFormat::Sarif).await
+        else {
+            panic!("Expected Invalid result for the unparseable package");
+        };
+
+        let log: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "broken.yaml"
+        );
+    }
 }